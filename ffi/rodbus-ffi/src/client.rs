@@ -1,5 +1,7 @@
+use std::time::Duration;
+
 use crate::ffi;
-use rodbus::client::{ReconnectStrategy, WriteMultiple};
+use rodbus::client::{ChannelState, ReconnectStrategy, WriteMultiple};
 use rodbus::AddressRange;
 
 pub struct Channel {
@@ -38,6 +40,50 @@ pub(crate) unsafe fn channel_destroy(channel: *mut crate::Channel) {
     };
 }
 
+pub(crate) unsafe fn channel_set_state_listener(
+    channel: *mut crate::Channel,
+    listener: crate::ffi::ChannelStateListener,
+) -> Result<(), ffi::ParamError> {
+    let channel = channel.as_ref().ok_or(ffi::ParamError::NullParameter)?;
+    let mut state = channel.inner.state();
+
+    channel.runtime.spawn(async move {
+        loop {
+            let (current, delay) = match *state.borrow() {
+                ChannelState::Disabled => (ffi::ChannelState::Disabled, Duration::ZERO),
+                ChannelState::Connecting => (ffi::ChannelState::Connecting, Duration::ZERO),
+                ChannelState::Connected => (ffi::ChannelState::Connected, Duration::ZERO),
+                ChannelState::WaitingToRetry(delay) => (ffi::ChannelState::WaitingToRetry, delay),
+            };
+            listener.on_change(current, delay);
+            // the sender is dropped when the channel task completes
+            if state.changed().await.is_err() {
+                return;
+            }
+        }
+    })?;
+
+    Ok(())
+}
+
+pub(crate) unsafe fn channel_enable(channel: *mut crate::Channel) -> Result<(), ffi::ParamError> {
+    let channel = channel.as_ref().ok_or(ffi::ParamError::NullParameter)?;
+    let mut inner = channel.inner.clone();
+    channel.runtime.spawn(async move {
+        let _ = inner.enable().await;
+    })?;
+    Ok(())
+}
+
+pub(crate) unsafe fn channel_disable(channel: *mut crate::Channel) -> Result<(), ffi::ParamError> {
+    let channel = channel.as_ref().ok_or(ffi::ParamError::NullParameter)?;
+    let mut inner = channel.inner.clone();
+    channel.runtime.spawn(async move {
+        let _ = inner.disable().await;
+    })?;
+    Ok(())
+}
+
 pub(crate) unsafe fn channel_read_coils(
     channel: *mut crate::Channel,
     param: crate::ffi::RequestParam,
@@ -42,6 +42,7 @@ impl From<rodbus::error::RequestError> for ffi::ErrorInfo {
             rodbus::error::RequestError::Exception(ex) => ex.into(),
             rodbus::error::RequestError::Io(_) => from_status(ffi::Status::IoError),
             rodbus::error::RequestError::BadResponse(_) => from_status(ffi::Status::BadResponse),
+            rodbus::error::RequestError::BadTagValue(_) => from_status(ffi::Status::BadResponse),
         }
     }
 }
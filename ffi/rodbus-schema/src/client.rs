@@ -58,6 +58,47 @@ pub(crate) fn build(
         .doc("destroy a channel instance")?
         .build()?;
 
+    let state_listener = build_state_listener(lib)?;
+    let set_state_listener_fn = lib
+        .declare_native_function("channel_set_state_listener")?
+        .param(
+            "channel",
+            Type::ClassRef(channel.clone()),
+            "channel to monitor",
+        )?
+        .param(
+            "listener",
+            Type::Interface(state_listener),
+            "listener notified of the current state and each change to it",
+        )?
+        .return_type(ReturnType::void())?
+        .fails_with(common.error_type.clone())?
+        .doc(
+            doc("set a listener that is notified when the state of the connection changes")
+                .details("The listener is called immediately with the current state. It is destroyed after the channel shuts down.")
+                .details("Setting a listener is optional, and more than one listener may be set on the same channel."),
+        )?
+        .build()?;
+
+    let enable_fn = lib
+        .declare_native_function("channel_enable")?
+        .param("channel", Type::ClassRef(channel.clone()), "channel to enable")?
+        .return_type(ReturnType::void())?
+        .fails_with(common.error_type.clone())?
+        .doc("start connecting if the channel was disabled")?
+        .build()?;
+
+    let disable_fn = lib
+        .declare_native_function("channel_disable")?
+        .param("channel", Type::ClassRef(channel.clone()), "channel to disable")?
+        .return_type(ReturnType::void())?
+        .fails_with(common.error_type.clone())?
+        .doc(
+            doc("close the connection and stop connecting until the channel is enabled again")
+                .details("Requests made while the channel is disabled fail immediately."),
+        )?
+        .build()?;
+
     let bit_read_callback = build_bit_read_callback(lib, common)?;
     let register_read_callback = build_register_read_callback(lib, common)?;
     let write_callback = build_write_callback(lib, common)?;
@@ -143,6 +184,9 @@ pub(crate) fn build(
     lib.define_class(&channel)?
         // abstract factory methods, later we'll have TLS/serial
         .static_method("create_tcp_client", &create_tcp_client_fn)?
+        .method("set_state_listener", &set_state_listener_fn)?
+        .method("enable", &enable_fn)?
+        .method("disable", &disable_fn)?
         // read methods
         .async_method("read_coils", &read_coils_fn)?
         .async_method("read_discrete_inputs", &read_discrete_inputs_fn)?
@@ -162,6 +206,45 @@ pub(crate) fn build(
     Ok(())
 }
 
+fn build_state_listener(lib: &mut LibraryBuilder) -> Result<InterfaceHandle, BindingError> {
+    let channel_state = lib
+        .define_native_enum("ChannelState")?
+        .variant(
+            "Disabled",
+            0,
+            "The channel isn't attempting to connect because it has been disabled or shut down",
+        )?
+        .variant("Connecting", 1, "Attempting to establish a connection")?
+        .variant("Connected", 2, "Connected and processing requests")?
+        .variant(
+            "WaitingToRetry",
+            3,
+            "Waiting before the next connection attempt",
+        )?
+        .doc("State of the connection maintained by a channel")?
+        .build()?;
+
+    lib.define_interface(
+        "ChannelStateListener",
+        "Callback notified when the state of the connection of a channel changes",
+    )?
+    .callback("on_change", "Called when the state changes")?
+    .param(
+        "state",
+        Type::Enum(channel_state),
+        "new state of the channel",
+    )?
+    .param(
+        "delay",
+        Type::Duration(DurationMapping::Milliseconds),
+        "delay before the next connection attempt, valid when state == WaitingToRetry",
+    )?
+    .return_type(ReturnType::void())?
+    .build()?
+    .destroy_callback("on_destroy")?
+    .build()
+}
+
 fn build_async_write_single_fn(
     name: &str,
     lib: &mut LibraryBuilder,
@@ -0,0 +1,433 @@
+use std::collections::BTreeMap;
+
+use crate::error::{BatchError, RequestError};
+use crate::types::{AddressRange, Indexed, Table};
+
+const TABLES: [Table; 4] = [
+    Table::Coils,
+    Table::DiscreteInputs,
+    Table::HoldingRegisters,
+    Table::InputRegisters,
+];
+
+/// Device specific limits used to plan the requests that read a [`ReadBatch`]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct BatchOptions {
+    /// Maximum number of addresses that weren't requested that may be read to join
+    /// two requested ranges into a single request
+    pub max_gap: u16,
+    /// Maximum count of coils or discrete inputs read by a single request
+    ///
+    /// Limited to [`MAX_READ_COILS_COUNT`](crate::constants::limits::MAX_READ_COILS_COUNT)
+    pub max_bits_per_request: u16,
+    /// Maximum count of holding or input registers read by a single request
+    ///
+    /// Limited to [`MAX_READ_REGISTERS_COUNT`](crate::constants::limits::MAX_READ_REGISTERS_COUNT)
+    pub max_registers_per_request: u16,
+}
+
+impl BatchOptions {
+    /// Create options with the specified gap tolerance and the maximum counts allowed by the specification
+    pub fn new(max_gap: u16) -> Self {
+        Self {
+            max_gap,
+            max_bits_per_request: crate::constants::limits::MAX_READ_COILS_COUNT,
+            max_registers_per_request: crate::constants::limits::MAX_READ_REGISTERS_COUNT,
+        }
+    }
+
+    fn max_count(&self, table: Table) -> u16 {
+        let max = if table.is_bits() {
+            self.max_bits_per_request
+        } else {
+            self.max_registers_per_request
+        };
+        max.clamp(1, table.max_read_count())
+    }
+}
+
+/// Request planned to read part of a [`ReadBatch`]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PlannedRead {
+    /// table that is read
+    pub table: Table,
+    /// range that is read
+    pub range: AddressRange,
+}
+
+#[derive(Clone, Debug, Default)]
+struct TableBatch {
+    requested: Vec<AddressRange>,
+    forbidden: Vec<AddressRange>,
+}
+
+/// Scattered addresses and ranges to read from the tables of a device using as few requests as possible
+///
+/// The ranges requested from each table are merged, and ranges separated by at most
+/// [`BatchOptions::max_gap`] addresses are read by the same request, as long as the request
+/// doesn't exceed the maximum count or read a forbidden address to fill the gap. Addresses that
+/// are explicitly requested are always read, even if they are also forbidden.
+#[derive(Clone, Debug)]
+pub struct ReadBatch {
+    options: BatchOptions,
+    coils: TableBatch,
+    discrete_inputs: TableBatch,
+    holding_registers: TableBatch,
+    input_registers: TableBatch,
+}
+
+/// Values read by [`Channel::read_batch`](crate::client::Channel::read_batch) keyed by address
+///
+/// Only the requested addresses are included, not the ones read to fill the gaps between them
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct BatchValues {
+    /// values of the requested coils
+    pub coils: BTreeMap<u16, bool>,
+    /// values of the requested discrete inputs
+    pub discrete_inputs: BTreeMap<u16, bool>,
+    /// values of the requested holding registers
+    pub holding_registers: BTreeMap<u16, u16>,
+    /// values of the requested input registers
+    pub input_registers: BTreeMap<u16, u16>,
+}
+
+// sorted intervals [start, end) that don't overlap or touch
+struct AddressSet {
+    intervals: Vec<(u32, u32)>,
+}
+
+impl ReadBatch {
+    /// Create an empty batch planned with the specified options
+    pub fn new(options: BatchOptions) -> Self {
+        Self {
+            options,
+            coils: TableBatch::default(),
+            discrete_inputs: TableBatch::default(),
+            holding_registers: TableBatch::default(),
+            input_registers: TableBatch::default(),
+        }
+    }
+
+    /// Request a range of a table
+    pub fn add(&mut self, table: Table, range: AddressRange) {
+        self.get_mut(table).requested.push(range);
+    }
+
+    /// Request a single address of a table
+    pub fn add_address(&mut self, table: Table, address: u16) {
+        self.add(
+            table,
+            AddressRange {
+                start: address,
+                count: 1,
+            },
+        );
+    }
+
+    /// Prevent a range of a table from being read to fill a gap between requested ranges,
+    /// e.g. because the device returns an exception when it is read
+    pub fn forbid(&mut self, table: Table, range: AddressRange) {
+        self.get_mut(table).forbidden.push(range);
+    }
+
+    /// The requests that read the batch, in the order that they are made
+    pub fn plan(&self) -> Vec<PlannedRead> {
+        let mut reads = Vec::new();
+        for table in TABLES.iter().copied() {
+            let ranges = plan_table(
+                self.get(table),
+                self.options.max_count(table),
+                self.options.max_gap,
+            );
+            reads.extend(ranges.into_iter().map(|range| PlannedRead { table, range }));
+        }
+        reads
+    }
+
+    fn requested(&self, table: Table) -> AddressSet {
+        AddressSet::new(&self.get(table).requested)
+    }
+
+    fn get(&self, table: Table) -> &TableBatch {
+        match table {
+            Table::Coils => &self.coils,
+            Table::DiscreteInputs => &self.discrete_inputs,
+            Table::HoldingRegisters => &self.holding_registers,
+            Table::InputRegisters => &self.input_registers,
+        }
+    }
+
+    fn get_mut(&mut self, table: Table) -> &mut TableBatch {
+        match table {
+            Table::Coils => &mut self.coils,
+            Table::DiscreteInputs => &mut self.discrete_inputs,
+            Table::HoldingRegisters => &mut self.holding_registers,
+            Table::InputRegisters => &mut self.input_registers,
+        }
+    }
+}
+
+impl PlannedRead {
+    pub(crate) fn error(self, error: RequestError) -> BatchError {
+        BatchError {
+            table: self.table,
+            range: self.range,
+            error,
+        }
+    }
+}
+
+impl TableBatch {
+    fn is_forbidden(&self, start: u32, end: u32) -> bool {
+        self.forbidden.iter().any(|x| {
+            let range = x.to_std_range();
+            (range.start as u32) < end && (range.end as u32) > start
+        })
+    }
+}
+
+impl BatchValues {
+    pub(crate) fn insert_bits(
+        &mut self,
+        table: Table,
+        values: Vec<Indexed<bool>>,
+        batch: &ReadBatch,
+    ) {
+        let map = match table {
+            Table::Coils => &mut self.coils,
+            _ => &mut self.discrete_inputs,
+        };
+        insert(map, values, batch.requested(table));
+    }
+
+    pub(crate) fn insert_registers(
+        &mut self,
+        table: Table,
+        values: Vec<Indexed<u16>>,
+        batch: &ReadBatch,
+    ) {
+        let map = match table {
+            Table::HoldingRegisters => &mut self.holding_registers,
+            _ => &mut self.input_registers,
+        };
+        insert(map, values, batch.requested(table));
+    }
+}
+
+fn insert<T>(map: &mut BTreeMap<u16, T>, values: Vec<Indexed<T>>, requested: AddressSet) {
+    map.extend(
+        values
+            .into_iter()
+            .filter(|x| requested.contains(x.index))
+            .map(|x| (x.index, x.value)),
+    );
+}
+
+impl AddressSet {
+    fn new(ranges: &[AddressRange]) -> Self {
+        let mut ranges: Vec<(u32, u32)> = ranges
+            .iter()
+            .map(|x| {
+                let range = x.to_std_range();
+                (range.start as u32, range.end as u32)
+            })
+            .collect();
+        ranges.sort_unstable();
+
+        let mut intervals: Vec<(u32, u32)> = Vec::with_capacity(ranges.len());
+        for (start, end) in ranges {
+            match intervals.last_mut() {
+                Some(last) if start <= last.1 => last.1 = last.1.max(end),
+                _ => intervals.push((start, end)),
+            }
+        }
+        Self { intervals }
+    }
+
+    fn contains(&self, address: u16) -> bool {
+        let address = address as u32;
+        let index = self.intervals.partition_point(|x| x.1 <= address);
+        match self.intervals.get(index) {
+            Some(x) => x.0 <= address,
+            None => false,
+        }
+    }
+}
+
+// greedily start each request at the first address that isn't read yet and extend it as far as possible
+fn plan_table(batch: &TableBatch, max_count: u16, max_gap: u16) -> Vec<AddressRange> {
+    let intervals = AddressSet::new(&batch.requested).intervals;
+    let mut requests = Vec::new();
+
+    let mut index = 0;
+    let mut next = match intervals.first() {
+        Some(x) => x.0,
+        None => return requests,
+    };
+
+    while let Some(&(_, current_end)) = intervals.get(index) {
+        let start = next;
+        let limit = start + max_count as u32;
+        let mut end = current_end.min(limit);
+
+        // join the following intervals while the request reads all of the previous ones
+        while end == intervals[index].1 {
+            match intervals.get(index + 1) {
+                Some(&(a, b))
+                    if a < limit && a - end <= max_gap as u32 && !batch.is_forbidden(end, a) =>
+                {
+                    index += 1;
+                    end = b.min(limit);
+                }
+                _ => break,
+            }
+        }
+
+        requests.push(AddressRange {
+            start: start as u16,
+            count: (end - start) as u16,
+        });
+
+        if end == intervals[index].1 {
+            index += 1;
+            if let Some(x) = intervals.get(index) {
+                next = x.0;
+            }
+        } else {
+            next = end;
+        }
+    }
+
+    requests
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn range(start: u16, count: u16) -> AddressRange {
+        AddressRange::try_from(start, count).unwrap()
+    }
+
+    fn plan(batch: &ReadBatch, table: Table) -> Vec<AddressRange> {
+        batch
+            .plan()
+            .into_iter()
+            .filter(|x| x.table == table)
+            .map(|x| x.range)
+            .collect()
+    }
+
+    #[test]
+    fn joins_ranges_separated_by_small_gaps() {
+        let mut batch = ReadBatch::new(BatchOptions::new(5));
+        batch.add_address(Table::HoldingRegisters, 10);
+        batch.add(Table::HoldingRegisters, range(12, 3));
+        batch.add(Table::HoldingRegisters, range(20, 2));
+        batch.add(Table::HoldingRegisters, range(28, 2));
+        batch.add(Table::HoldingRegisters, range(11, 2));
+        assert_eq!(
+            plan(&batch, Table::HoldingRegisters),
+            vec![range(10, 12), range(28, 2)]
+        );
+    }
+
+    #[test]
+    fn plans_each_table_separately_in_table_order() {
+        let mut batch = ReadBatch::new(BatchOptions::new(0));
+        batch.add_address(Table::InputRegisters, 3);
+        batch.add_address(Table::Coils, 3);
+        batch.add_address(Table::Coils, 4);
+        assert_eq!(
+            batch.plan(),
+            vec![
+                PlannedRead {
+                    table: Table::Coils,
+                    range: range(3, 2)
+                },
+                PlannedRead {
+                    table: Table::InputRegisters,
+                    range: range(3, 1)
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn splits_requests_at_the_maximum_count() {
+        let mut options = BatchOptions::new(10);
+        options.max_registers_per_request = 20;
+        let mut batch = ReadBatch::new(options);
+        batch.add(Table::InputRegisters, range(0, 30));
+        batch.add(Table::InputRegisters, range(35, 10));
+        batch.add(Table::InputRegisters, range(60, 5));
+        assert_eq!(
+            plan(&batch, Table::InputRegisters),
+            vec![range(0, 20), range(20, 20), range(40, 5), range(60, 5)]
+        );
+    }
+
+    #[test]
+    fn device_limits_cannot_exceed_the_specification() {
+        let mut options = BatchOptions::new(0);
+        options.max_bits_per_request = 5000;
+        options.max_registers_per_request = 0;
+        let mut batch = ReadBatch::new(options);
+        batch.add(Table::Coils, range(0, 3000));
+        batch.add(Table::HoldingRegisters, range(0, 2));
+        assert_eq!(
+            plan(&batch, Table::Coils),
+            vec![range(0, 2000), range(2000, 1000)]
+        );
+        assert_eq!(
+            plan(&batch, Table::HoldingRegisters),
+            vec![range(0, 1), range(1, 1)]
+        );
+    }
+
+    #[test]
+    fn does_not_fill_gaps_with_forbidden_addresses() {
+        let mut batch = ReadBatch::new(BatchOptions::new(10));
+        batch.add_address(Table::HoldingRegisters, 0);
+        batch.add_address(Table::HoldingRegisters, 5);
+        batch.add_address(Table::HoldingRegisters, 8);
+        batch.add_address(Table::HoldingRegisters, 9);
+        batch.forbid(Table::HoldingRegisters, range(6, 1));
+        batch.forbid(Table::HoldingRegisters, range(9, 1));
+        assert_eq!(
+            plan(&batch, Table::HoldingRegisters),
+            vec![range(0, 6), range(8, 2)]
+        );
+    }
+
+    #[test]
+    fn plans_ranges_that_end_at_the_last_address() {
+        let mut batch = ReadBatch::new(BatchOptions::new(1));
+        batch.add_address(Table::DiscreteInputs, 0xFFFF);
+        batch.add_address(Table::DiscreteInputs, 0xFFFD);
+        assert_eq!(plan(&batch, Table::DiscreteInputs), vec![range(0xFFFD, 3)]);
+    }
+
+    #[test]
+    fn empty_batch_has_no_requests() {
+        assert!(ReadBatch::new(BatchOptions::new(10)).plan().is_empty());
+    }
+
+    #[test]
+    fn only_keeps_requested_values() {
+        let mut batch = ReadBatch::new(BatchOptions::new(10));
+        batch.add_address(Table::HoldingRegisters, 1);
+        batch.add_address(Table::HoldingRegisters, 3);
+        let mut values = BatchValues::default();
+        values.insert_registers(
+            Table::HoldingRegisters,
+            (1..4).map(|x| Indexed::new(x, x * 10)).collect(),
+            &batch,
+        );
+        assert_eq!(
+            values.holding_registers.into_iter().collect::<Vec<_>>(),
+            vec![(1, 10), (3, 30)]
+        );
+        assert!(values.input_registers.is_empty());
+    }
+}
@@ -1,23 +1,67 @@
+#[cfg(feature = "profile")]
+use std::collections::BTreeMap;
 use std::net::SocketAddr;
 use std::time::Duration;
 
 use tracing::Instrument;
 
-use crate::client::message::{Promise, Request, RequestDetails};
+#[cfg(feature = "profile")]
+use crate::client::batch::BatchOptions;
+use crate::client::batch::{BatchValues, ReadBatch};
+use crate::client::endpoint::{Endpoint, EndpointList};
+use crate::client::message::{Promise, Request, RequestDetails, Setting};
+use crate::client::requests::custom::CustomFunction;
+use crate::client::requests::diagnostics::{Diagnostics, ReadStatus};
+use crate::client::requests::file_record::{ReadFileRecord, WriteFileRecord};
 use crate::client::requests::read_bits::ReadBits;
+use crate::client::requests::read_device_id::ReadDeviceId;
+use crate::client::requests::read_fifo::ReadFifoQueue;
 use crate::client::requests::read_registers::ReadRegisters;
+use crate::client::requests::read_write_multiple::{ReadWriteMultiple, ReadWriteMultipleRegisters};
 use crate::client::requests::write_multiple::{MultipleWriteRequest, WriteMultiple};
 use crate::client::requests::write_single::SingleWrite;
+use crate::client::retry::RetryPolicy;
+use crate::client::task::ClientLoop;
+use crate::codec::{decode_string, encode_string, ByteOrder, RegisterValue};
+use crate::constants::broadcast::DEFAULT_TURNAROUND_DELAY;
+use crate::constants::limits::{
+    MAX_READ_COILS_COUNT, MAX_READ_REGISTERS_COUNT, MAX_WRITE_COILS_COUNT,
+    MAX_WRITE_REGISTERS_COUNT,
+};
+use crate::custom::CustomFunctionRequest;
 use crate::decode::DecodeLevel;
+use crate::device_id::{DeviceIdPage, DeviceIdentification, ReadDeviceIdCode, ReadDeviceIdRequest};
+use crate::diagnostics::{CommEventCounter, CommEventLog, Diagnostic, DiagnosticSubFunction};
 use crate::error::*;
-use crate::tcp::client::TcpChannelTask;
+use crate::file_record::{
+    FileRecord, FileRecordData, ReadFileRecordRequest, WriteFileRecordRequest,
+};
+#[cfg(feature = "profile")]
+use crate::profile::{Access, DeviceProfile, TagValue, TagWrite};
+use crate::serial::ascii::{AsciiFormatter, AsciiParser};
+use crate::serial::client::SerialChannelTask;
+use crate::serial::frame::{RtuFormatter, RtuParser};
+use crate::serial::settings::SerialSettings;
+use crate::tcp::client::{TcpChannelTask, TcpTaskConnectionHandler};
+use crate::tcp::frame::{MbapFormatter, MbapParser};
+use crate::tcp::tls::TlsClientConfig;
 use crate::tokio;
-use crate::types::{AddressRange, BitIterator, Indexed, RegisterIterator, UnitId};
+use crate::types::{
+    AddressRange, BitIterator, Indexed, MaskWriteRegister, ReadBitsRange, ReadRegistersRange,
+    RegisterIterator, Table, UnitId,
+};
+
+type ValuesSender<T> = tokio::sync::oneshot::Sender<Result<Vec<Indexed<T>>, RequestError>>;
 
 /// Async channel used to make requests
 #[derive(Debug, Clone)]
 pub struct Channel {
     tx: tokio::sync::mpsc::Sender<Request>,
+    settings: tokio::sync::mpsc::Sender<Setting>,
+    state: tokio::sync::watch::Receiver<ChannelState>,
+    endpoint: tokio::sync::watch::Receiver<Option<Endpoint>>,
+    // false for serial channels, which can't be moved to another endpoint
+    has_endpoints: bool,
 }
 
 /// Request parameters to dispatch the request to the proper device
@@ -27,6 +71,39 @@ pub struct RequestParam {
     pub id: UnitId,
     /// Response timeout
     pub response_timeout: Duration,
+    // overrides the retry policy of the channel
+    retry_policy: Option<RetryPolicy>,
+}
+
+/// Optional settings of TCP and TLS channels
+///
+/// The default options send one request at a time
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ClientOptions {
+    max_in_flight: usize,
+}
+
+impl ClientOptions {
+    /// Send up to `max_in_flight` requests without waiting for the responses to the previous ones
+    ///
+    /// Responses are matched to requests by transaction id, and each request times out
+    /// independently. Values below 1 are treated as 1.
+    pub fn with_max_in_flight(self, max_in_flight: usize) -> Self {
+        Self {
+            max_in_flight: max_in_flight.max(1),
+        }
+    }
+
+    /// The maximum number of requests awaiting a response at the same time
+    pub fn max_in_flight(&self) -> usize {
+        self.max_in_flight
+    }
+}
+
+impl Default for ClientOptions {
+    fn default() -> Self {
+        Self { max_in_flight: 1 }
+    }
 }
 
 /// Dynamic trait that controls how the channel
@@ -38,6 +115,22 @@ pub trait ReconnectStrategy {
     fn next_delay(&mut self) -> Duration;
 }
 
+/// State of the connection maintained by the task of a [`Channel`]
+///
+/// Serial channels are `Connected` while the serial port is open
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ChannelState {
+    /// The channel isn't attempting to connect, either because the task hasn't started yet
+    /// or because it has been shut down
+    Disabled,
+    /// Attempting to establish a connection
+    Connecting,
+    /// Connected and processing requests
+    Connected,
+    /// Waiting for the specified delay before the next connection attempt
+    WaitingToRetry(Duration),
+}
+
 /// Helper functions for returning instances of `Box<dyn ReconnectStrategy>`
 pub(crate) mod strategy {
     use std::time::Duration;
@@ -92,37 +185,427 @@ impl RequestParam {
         Self {
             id,
             response_timeout,
+            retry_policy: None,
+        }
+    }
+
+    /// retry requests made with these parameters according to the specified policy
+    /// instead of the policy of the channel
+    pub fn with_retry_policy(self, policy: RetryPolicy) -> Self {
+        Self {
+            retry_policy: Some(policy),
+            ..self
         }
     }
 }
 
 impl Channel {
     pub(crate) fn new(
-        addr: SocketAddr,
+        endpoints: EndpointList,
         max_queued_requests: usize,
         connect_retry: Box<dyn ReconnectStrategy + Send>,
+        options: ClientOptions,
         decode: DecodeLevel,
     ) -> Self {
-        let (handle, task) =
-            Self::create_handle_and_task(addr, max_queued_requests, connect_retry, decode);
+        let (handle, task) = Self::create_handle_and_task(
+            endpoints,
+            max_queued_requests,
+            connect_retry,
+            options,
+            decode,
+        );
         tokio::spawn(task);
         handle
     }
 
     pub(crate) fn create_handle_and_task(
-        addr: SocketAddr,
+        endpoints: EndpointList,
         max_queued_requests: usize,
         connect_retry: Box<dyn ReconnectStrategy + Send>,
+        options: ClientOptions,
         decode: DecodeLevel,
     ) -> (Self, impl std::future::Future<Output = ()>) {
         let (tx, rx) = tokio::sync::mpsc::channel(max_queued_requests);
+        let (settings, settings_rx) = tokio::sync::mpsc::channel(max_queued_requests);
+        let (state_tx, state) = tokio::sync::watch::channel(ChannelState::Disabled);
+        let (endpoint_tx, endpoint) = tokio::sync::watch::channel(None);
         let task = async move {
-            TcpChannelTask::new(addr, rx, connect_retry, decode)
-                .run()
-                .instrument(tracing::info_span!("Modbus-Client-TCP", endpoint = ?addr))
-                .await
+            let span = tracing::info_span!("Modbus-Client-TCP", endpoint = %endpoints);
+            TcpChannelTask::new(
+                endpoints,
+                TcpTaskConnectionHandler::Tcp,
+                ClientLoop::new(
+                    rx,
+                    settings_rx,
+                    MbapFormatter::new(decode.adu),
+                    MbapParser::new(decode.adu),
+                    decode.pdu,
+                    options.max_in_flight(),
+                    connect_retry,
+                ),
+                decode,
+                state_tx,
+                endpoint_tx,
+            )
+            .run()
+            .instrument(span)
+            .await
+        };
+        (
+            Channel {
+                tx,
+                settings,
+                state,
+                endpoint,
+                has_endpoints: true,
+            },
+            task,
+        )
+    }
+
+    pub(crate) fn new_tls(
+        endpoints: EndpointList,
+        max_queued_requests: usize,
+        connect_retry: Box<dyn ReconnectStrategy + Send>,
+        tls_config: TlsClientConfig,
+        options: ClientOptions,
+        decode: DecodeLevel,
+    ) -> Self {
+        let (handle, task) = Self::create_tls_handle_and_task(
+            endpoints,
+            max_queued_requests,
+            connect_retry,
+            tls_config,
+            options,
+            decode,
+        );
+        tokio::spawn(task);
+        handle
+    }
+
+    pub(crate) fn create_tls_handle_and_task(
+        endpoints: EndpointList,
+        max_queued_requests: usize,
+        connect_retry: Box<dyn ReconnectStrategy + Send>,
+        tls_config: TlsClientConfig,
+        options: ClientOptions,
+        decode: DecodeLevel,
+    ) -> (Self, impl std::future::Future<Output = ()>) {
+        let (tx, rx) = tokio::sync::mpsc::channel(max_queued_requests);
+        let (settings, settings_rx) = tokio::sync::mpsc::channel(max_queued_requests);
+        let (state_tx, state) = tokio::sync::watch::channel(ChannelState::Disabled);
+        let (endpoint_tx, endpoint) = tokio::sync::watch::channel(None);
+        let task = async move {
+            let span = tracing::info_span!("Modbus-Client-TLS", endpoint = %endpoints);
+            TcpChannelTask::new(
+                endpoints,
+                TcpTaskConnectionHandler::Tls(tls_config),
+                ClientLoop::new(
+                    rx,
+                    settings_rx,
+                    MbapFormatter::new(decode.adu),
+                    MbapParser::new(decode.adu),
+                    decode.pdu,
+                    options.max_in_flight(),
+                    connect_retry,
+                ),
+                decode,
+                state_tx,
+                endpoint_tx,
+            )
+            .run()
+            .instrument(span)
+            .await
+        };
+        (
+            Channel {
+                tx,
+                settings,
+                state,
+                endpoint,
+                has_endpoints: true,
+            },
+            task,
+        )
+    }
+
+    pub(crate) fn new_rtu_over_tcp(
+        endpoints: EndpointList,
+        max_queued_requests: usize,
+        connect_retry: Box<dyn ReconnectStrategy + Send>,
+        decode: DecodeLevel,
+    ) -> Self {
+        let (handle, task) = Self::create_rtu_over_tcp_handle_and_task(
+            endpoints,
+            max_queued_requests,
+            connect_retry,
+            decode,
+        );
+        tokio::spawn(task);
+        handle
+    }
+
+    pub(crate) fn create_rtu_over_tcp_handle_and_task(
+        endpoints: EndpointList,
+        max_queued_requests: usize,
+        connect_retry: Box<dyn ReconnectStrategy + Send>,
+        decode: DecodeLevel,
+    ) -> (Self, impl std::future::Future<Output = ()>) {
+        let (tx, rx) = tokio::sync::mpsc::channel(max_queued_requests);
+        let (settings, settings_rx) = tokio::sync::mpsc::channel(max_queued_requests);
+        let (state_tx, state) = tokio::sync::watch::channel(ChannelState::Disabled);
+        let (endpoint_tx, endpoint) = tokio::sync::watch::channel(None);
+        let task = async move {
+            let span = tracing::info_span!("Modbus-Client-RTU-over-TCP", endpoint = %endpoints);
+            // frames are delimited by the TCP stream, so there is no inter-frame silence, and
+            // without a transaction id only a single request may be outstanding
+            TcpChannelTask::new(
+                endpoints,
+                TcpTaskConnectionHandler::Tcp,
+                ClientLoop::new(
+                    rx,
+                    settings_rx,
+                    RtuFormatter::new(decode.adu),
+                    RtuParser::new_response_parser(None, decode.adu),
+                    decode.pdu,
+                    1,
+                    connect_retry,
+                )
+                .with_broadcast(DEFAULT_TURNAROUND_DELAY),
+                decode,
+                state_tx,
+                endpoint_tx,
+            )
+            .run()
+            .instrument(span)
+            .await
         };
-        (Channel { tx }, task)
+        (
+            Channel {
+                tx,
+                settings,
+                state,
+                endpoint,
+                has_endpoints: true,
+            },
+            task,
+        )
+    }
+
+    pub(crate) fn new_rtu(
+        path: &str,
+        serial_settings: SerialSettings,
+        max_queued_requests: usize,
+        retry: Box<dyn ReconnectStrategy + Send>,
+        decode: DecodeLevel,
+    ) -> Self {
+        let (handle, task) = Self::create_rtu_handle_and_task(
+            path,
+            serial_settings,
+            max_queued_requests,
+            retry,
+            decode,
+        );
+        tokio::spawn(task);
+        handle
+    }
+
+    pub(crate) fn create_rtu_handle_and_task(
+        path: &str,
+        serial_settings: SerialSettings,
+        max_queued_requests: usize,
+        retry: Box<dyn ReconnectStrategy + Send>,
+        decode: DecodeLevel,
+    ) -> (Self, impl std::future::Future<Output = ()>) {
+        let (tx, rx) = tokio::sync::mpsc::channel(max_queued_requests);
+        let (settings, settings_rx) = tokio::sync::mpsc::channel(max_queued_requests);
+        let (state_tx, state) = tokio::sync::watch::channel(ChannelState::Disabled);
+        // serial channels don't have an endpoint
+        let (_, endpoint) = tokio::sync::watch::channel(None);
+        let path = path.to_string();
+        let task = async move {
+            SerialChannelTask::new(
+                &path,
+                serial_settings,
+                // only a single request may be outstanding on a serial line
+                ClientLoop::new(
+                    rx,
+                    settings_rx,
+                    RtuFormatter::new(decode.adu),
+                    RtuParser::new_response_parser(
+                        Some(serial_settings.effective_inter_frame_delay()),
+                        decode.adu,
+                    ),
+                    decode.pdu,
+                    1,
+                    retry,
+                )
+                .with_broadcast(DEFAULT_TURNAROUND_DELAY),
+                decode,
+                state_tx,
+            )
+            .run()
+            .instrument(tracing::info_span!("Modbus-Client-RTU", port = ?path))
+            .await
+        };
+        (
+            Channel {
+                tx,
+                settings,
+                state,
+                endpoint,
+                has_endpoints: false,
+            },
+            task,
+        )
+    }
+
+    pub(crate) fn new_ascii(
+        path: &str,
+        serial_settings: SerialSettings,
+        max_queued_requests: usize,
+        retry: Box<dyn ReconnectStrategy + Send>,
+        decode: DecodeLevel,
+    ) -> Self {
+        let (handle, task) = Self::create_ascii_handle_and_task(
+            path,
+            serial_settings,
+            max_queued_requests,
+            retry,
+            decode,
+        );
+        tokio::spawn(task);
+        handle
+    }
+
+    pub(crate) fn create_ascii_handle_and_task(
+        path: &str,
+        serial_settings: SerialSettings,
+        max_queued_requests: usize,
+        retry: Box<dyn ReconnectStrategy + Send>,
+        decode: DecodeLevel,
+    ) -> (Self, impl std::future::Future<Output = ()>) {
+        let (tx, rx) = tokio::sync::mpsc::channel(max_queued_requests);
+        let (settings, settings_rx) = tokio::sync::mpsc::channel(max_queued_requests);
+        let (state_tx, state) = tokio::sync::watch::channel(ChannelState::Disabled);
+        // serial channels don't have an endpoint
+        let (_, endpoint) = tokio::sync::watch::channel(None);
+        let path = path.to_string();
+        let task = async move {
+            SerialChannelTask::new(
+                &path,
+                serial_settings,
+                // only a single request may be outstanding on a serial line
+                ClientLoop::new(
+                    rx,
+                    settings_rx,
+                    AsciiFormatter::new(decode.adu),
+                    AsciiParser::new(decode.adu),
+                    decode.pdu,
+                    1,
+                    retry,
+                )
+                .with_broadcast(DEFAULT_TURNAROUND_DELAY),
+                decode,
+                state_tx,
+            )
+            .run()
+            .instrument(tracing::info_span!("Modbus-Client-ASCII", port = ?path))
+            .await
+        };
+        (
+            Channel {
+                tx,
+                settings,
+                state,
+                endpoint,
+                has_endpoints: false,
+            },
+            task,
+        )
+    }
+
+    /// Receiver that is notified each time the state of the connection changes
+    ///
+    /// The current state is available immediately from `borrow()`. The state becomes
+    /// [`ChannelState::Disabled`] once the task has shut down.
+    pub fn state(&self) -> tokio::sync::watch::Receiver<ChannelState> {
+        self.state.clone()
+    }
+
+    /// Receiver that is notified each time the channel connects to one of its endpoints or
+    /// loses its connection
+    ///
+    /// The value is `None` while the channel isn't connected, and always `None` for serial channels.
+    pub fn active_endpoint(&self) -> tokio::sync::watch::Receiver<Option<Endpoint>> {
+        self.endpoint.clone()
+    }
+
+    /// Enable the channel so that its task connects to the server and processes requests
+    ///
+    /// Channels are enabled when they are created
+    pub async fn enable(&mut self) -> Result<(), RequestError> {
+        self.settings.send(Setting::Enable).await?;
+        Ok(())
+    }
+
+    /// Disable the channel, closing the connection until the channel is enabled again
+    ///
+    /// Requests in flight or waiting to be retried, and those made while the channel is disabled,
+    /// fail with [`RequestError::NoConnection`]
+    pub async fn disable(&mut self) -> Result<(), RequestError> {
+        self.settings.send(Setting::Disable).await?;
+        Ok(())
+    }
+
+    /// Replace the strategy that controls the delay between failed connection attempts
+    ///
+    /// The current connection isn't affected
+    pub async fn set_reconnect_strategy(
+        &mut self,
+        strategy: Box<dyn ReconnectStrategy + Send>,
+    ) -> Result<(), RequestError> {
+        self.settings
+            .send(Setting::ReconnectStrategy(strategy))
+            .await?;
+        Ok(())
+    }
+
+    /// Change the address of the server, closing the current connection
+    ///
+    /// Requests in flight fail with [`RequestError::NoConnection`], and the task connects to the
+    /// new address right away if the channel is enabled. Serial channels don't have an address
+    /// and fail with [`InvalidRequest::UnsupportedSetting`].
+    pub async fn set_endpoint(&mut self, addr: SocketAddr) -> Result<(), RequestError> {
+        self.set_endpoints(EndpointList::from(addr)).await
+    }
+
+    /// Same as [`Channel::set_endpoint`], replacing the address with a list of redundant endpoints
+    pub async fn set_endpoints(&mut self, endpoints: EndpointList) -> Result<(), RequestError> {
+        if !self.has_endpoints {
+            return Err(InvalidRequest::UnsupportedSetting.into());
+        }
+        self.settings.send(Setting::Endpoint(endpoints)).await?;
+        Ok(())
+    }
+
+    /// Replace the policy used to retry the requests that fail with a transient error
+    ///
+    /// Channels don't retry requests until a policy is set. Requests made with a
+    /// [`RequestParam::with_retry_policy`] use their own policy instead.
+    pub async fn set_retry_policy(&mut self, policy: RetryPolicy) -> Result<(), RequestError> {
+        self.settings.send(Setting::RetryPolicy(policy)).await?;
+        Ok(())
+    }
+
+    /// Change the time waited after a write to the broadcast unit id before sending the next request
+    ///
+    /// Only serial and RTU over TCP channels broadcast writes, which default to
+    /// [`DEFAULT_TURNAROUND_DELAY`](crate::constants::broadcast::DEFAULT_TURNAROUND_DELAY).
+    /// Other channels ignore the delay.
+    pub async fn set_turnaround_delay(&mut self, delay: Duration) -> Result<(), RequestError> {
+        self.settings.send(Setting::TurnaroundDelay(delay)).await?;
+        Ok(())
     }
 
     /// Read coils from the server
@@ -262,6 +745,673 @@ impl Channel {
         self.tx.send(request).await?;
         rx.await?
     }
+
+    /// Read coils from the server, splitting a range larger than [`MAX_READ_COILS_COUNT`]
+    /// into several requests and joining the values
+    ///
+    /// All of the requests are queued at once, so they are pipelined on channels that allow more
+    /// than one request in flight. If any of them fails, the error identifies its range.
+    pub async fn read_coils_chunked(
+        &mut self,
+        param: RequestParam,
+        range: AddressRange,
+    ) -> Result<Vec<Indexed<bool>>, ChunkError> {
+        self.read_chunked(param, range, MAX_READ_COILS_COUNT, |range, tx| {
+            RequestDetails::ReadCoils(ReadBits::new(
+                ReadBitsRange { inner: range },
+                crate::client::requests::read_bits::Promise::Channel(tx),
+            ))
+        })
+        .await
+    }
+
+    /// Read discrete inputs from the server, splitting a range larger than
+    /// [`MAX_READ_COILS_COUNT`] into several requests and joining the values
+    ///
+    /// All of the requests are queued at once, so they are pipelined on channels that allow more
+    /// than one request in flight. If any of them fails, the error identifies its range.
+    pub async fn read_discrete_inputs_chunked(
+        &mut self,
+        param: RequestParam,
+        range: AddressRange,
+    ) -> Result<Vec<Indexed<bool>>, ChunkError> {
+        self.read_chunked(param, range, MAX_READ_COILS_COUNT, |range, tx| {
+            RequestDetails::ReadDiscreteInputs(ReadBits::new(
+                ReadBitsRange { inner: range },
+                crate::client::requests::read_bits::Promise::Channel(tx),
+            ))
+        })
+        .await
+    }
+
+    /// Read holding registers from the server, splitting a range larger than
+    /// [`MAX_READ_REGISTERS_COUNT`] into several requests and joining the values
+    ///
+    /// All of the requests are queued at once, so they are pipelined on channels that allow more
+    /// than one request in flight. If any of them fails, the error identifies its range.
+    pub async fn read_holding_registers_chunked(
+        &mut self,
+        param: RequestParam,
+        range: AddressRange,
+    ) -> Result<Vec<Indexed<u16>>, ChunkError> {
+        self.read_chunked(param, range, MAX_READ_REGISTERS_COUNT, |range, tx| {
+            RequestDetails::ReadHoldingRegisters(ReadRegisters::new(
+                ReadRegistersRange { inner: range },
+                crate::client::requests::read_registers::Promise::Channel(tx),
+            ))
+        })
+        .await
+    }
+
+    /// Read input registers from the server, splitting a range larger than
+    /// [`MAX_READ_REGISTERS_COUNT`] into several requests and joining the values
+    ///
+    /// All of the requests are queued at once, so they are pipelined on channels that allow more
+    /// than one request in flight. If any of them fails, the error identifies its range.
+    pub async fn read_input_registers_chunked(
+        &mut self,
+        param: RequestParam,
+        range: AddressRange,
+    ) -> Result<Vec<Indexed<u16>>, ChunkError> {
+        self.read_chunked(param, range, MAX_READ_REGISTERS_COUNT, |range, tx| {
+            RequestDetails::ReadInputRegisters(ReadRegisters::new(
+                ReadRegistersRange { inner: range },
+                crate::client::requests::read_registers::Promise::Channel(tx),
+            ))
+        })
+        .await
+    }
+
+    /// Write multiple contiguous coils on the server, splitting more than
+    /// [`MAX_WRITE_COILS_COUNT`] values into several requests
+    ///
+    /// The requests are made one after another in order of address. If one of them fails, no
+    /// further requests are made and the error identifies the range that wasn't written. All
+    /// of the values before that range have been written.
+    pub async fn write_multiple_coils_chunked(
+        &mut self,
+        param: RequestParam,
+        request: WriteMultiple<bool>,
+    ) -> Result<AddressRange, ChunkError> {
+        for chunk in request.chunks(MAX_WRITE_COILS_COUNT) {
+            let range = chunk.range;
+            self.write_multiple_coils(param, chunk)
+                .await
+                .map_err(|error| ChunkError { range, error })?;
+        }
+        Ok(request.range)
+    }
+
+    /// Write multiple contiguous registers on the server, splitting more than
+    /// [`MAX_WRITE_REGISTERS_COUNT`] values into several requests
+    ///
+    /// The requests are made one after another in order of address. If one of them fails, no
+    /// further requests are made and the error identifies the range that wasn't written. All
+    /// of the values before that range have been written.
+    pub async fn write_multiple_registers_chunked(
+        &mut self,
+        param: RequestParam,
+        request: WriteMultiple<u16>,
+    ) -> Result<AddressRange, ChunkError> {
+        for chunk in request.chunks(MAX_WRITE_REGISTERS_COUNT) {
+            let range = chunk.range;
+            self.write_multiple_registers(param, chunk)
+                .await
+                .map_err(|error| ChunkError { range, error })?;
+        }
+        Ok(request.range)
+    }
+
+    /// Read a value stored in consecutive holding registers starting at `address`
+    ///
+    /// Values stored in input registers can be decoded from the result of
+    /// [`Channel::read_input_registers`] with [`decode_at`](crate::codec::decode_at)
+    pub async fn read_value<T: RegisterValue>(
+        &mut self,
+        param: RequestParam,
+        address: u16,
+        order: ByteOrder,
+    ) -> Result<T, RequestError> {
+        let range = AddressRange::try_from(address, T::COUNT)?;
+        let registers: Vec<u16> = self
+            .read_holding_registers(param, range)
+            .await?
+            .iter()
+            .map(|x| x.value)
+            .collect();
+        // the length of the response is validated when it is parsed
+        T::from_registers(&registers, order)
+            .map_err(|_| RequestError::BadResponse(AduParseError::InsufficientBytes))
+    }
+
+    /// Read a `u32` stored in two holding registers
+    pub async fn read_u32(
+        &mut self,
+        param: RequestParam,
+        address: u16,
+        order: ByteOrder,
+    ) -> Result<u32, RequestError> {
+        self.read_value(param, address, order).await
+    }
+
+    /// Read an `i32` stored in two holding registers
+    pub async fn read_i32(
+        &mut self,
+        param: RequestParam,
+        address: u16,
+        order: ByteOrder,
+    ) -> Result<i32, RequestError> {
+        self.read_value(param, address, order).await
+    }
+
+    /// Read an `f32` stored in two holding registers
+    pub async fn read_f32(
+        &mut self,
+        param: RequestParam,
+        address: u16,
+        order: ByteOrder,
+    ) -> Result<f32, RequestError> {
+        self.read_value(param, address, order).await
+    }
+
+    /// Read a `u64` stored in four holding registers
+    pub async fn read_u64(
+        &mut self,
+        param: RequestParam,
+        address: u16,
+        order: ByteOrder,
+    ) -> Result<u64, RequestError> {
+        self.read_value(param, address, order).await
+    }
+
+    /// Read an `i64` stored in four holding registers
+    pub async fn read_i64(
+        &mut self,
+        param: RequestParam,
+        address: u16,
+        order: ByteOrder,
+    ) -> Result<i64, RequestError> {
+        self.read_value(param, address, order).await
+    }
+
+    /// Read an `f64` stored in four holding registers
+    pub async fn read_f64(
+        &mut self,
+        param: RequestParam,
+        address: u16,
+        order: ByteOrder,
+    ) -> Result<f64, RequestError> {
+        self.read_value(param, address, order).await
+    }
+
+    /// Read an ASCII string stored in the holding registers of `range`, as decoded by
+    /// [`decode_string`](crate::codec::decode_string)
+    pub async fn read_string(
+        &mut self,
+        param: RequestParam,
+        range: AddressRange,
+        order: ByteOrder,
+    ) -> Result<String, RequestError> {
+        let registers: Vec<u16> = self
+            .read_holding_registers(param, range)
+            .await?
+            .iter()
+            .map(|x| x.value)
+            .collect();
+        Ok(decode_string(&registers, order))
+    }
+
+    /// Write a value into consecutive holding registers starting at `address`
+    pub async fn write_value<T: RegisterValue>(
+        &mut self,
+        param: RequestParam,
+        address: u16,
+        value: T,
+        order: ByteOrder,
+    ) -> Result<AddressRange, RequestError> {
+        let request = WriteMultiple::from(address, value.to_registers(order))?;
+        self.write_multiple_registers(param, request).await
+    }
+
+    /// Write an ASCII string into a range of holding registers, as encoded by
+    /// [`encode_string`](crate::codec::encode_string)
+    pub async fn write_string(
+        &mut self,
+        param: RequestParam,
+        range: AddressRange,
+        value: &str,
+        order: ByteOrder,
+    ) -> Result<AddressRange, RequestError> {
+        let registers = encode_string(value, range.count, order)?;
+        let request = WriteMultiple::from(range.start, registers)?;
+        self.write_multiple_registers(param, request).await
+    }
+
+    /// Read the scattered addresses of a [`ReadBatch`] using the requests planned by [`ReadBatch::plan`]
+    ///
+    /// All of the requests are queued at once, so they are pipelined on channels that allow more
+    /// than one request in flight. If any of them fails, the error identifies its table and range.
+    pub async fn read_batch(
+        &mut self,
+        param: RequestParam,
+        batch: &ReadBatch,
+    ) -> Result<BatchValues, BatchError> {
+        let mut bits = Vec::new();
+        let mut registers = Vec::new();
+        for read in batch.plan() {
+            let details = match read.table {
+                Table::Coils | Table::DiscreteInputs => {
+                    let (tx, rx) = tokio::sync::oneshot::channel();
+                    bits.push((read, rx));
+                    let request = ReadBits::new(
+                        ReadBitsRange { inner: read.range },
+                        crate::client::requests::read_bits::Promise::Channel(tx),
+                    );
+                    if read.table == Table::Coils {
+                        RequestDetails::ReadCoils(request)
+                    } else {
+                        RequestDetails::ReadDiscreteInputs(request)
+                    }
+                }
+                Table::HoldingRegisters | Table::InputRegisters => {
+                    let (tx, rx) = tokio::sync::oneshot::channel();
+                    registers.push((read, rx));
+                    let request = ReadRegisters::new(
+                        ReadRegistersRange { inner: read.range },
+                        crate::client::requests::read_registers::Promise::Channel(tx),
+                    );
+                    if read.table == Table::HoldingRegisters {
+                        RequestDetails::ReadHoldingRegisters(request)
+                    } else {
+                        RequestDetails::ReadInputRegisters(request)
+                    }
+                }
+            };
+            self.tx
+                .send(wrap(param, details))
+                .await
+                .map_err(|err| read.error(err.into()))?;
+        }
+
+        let mut values = BatchValues::default();
+        for (read, rx) in bits {
+            let result = rx.await.unwrap_or_else(|err| Err(err.into()));
+            values.insert_bits(
+                read.table,
+                result.map_err(|error| read.error(error))?,
+                batch,
+            );
+        }
+        for (read, rx) in registers {
+            let result = rx.await.unwrap_or_else(|err| Err(err.into()));
+            values.insert_registers(
+                read.table,
+                result.map_err(|error| read.error(error))?,
+                batch,
+            );
+        }
+        Ok(values)
+    }
+
+    /// Read the value of a tag of a [`DeviceProfile`] in engineering units
+    #[cfg(feature = "profile")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "profile")))]
+    pub async fn read_tag(
+        &mut self,
+        param: RequestParam,
+        profile: &DeviceProfile,
+        name: &str,
+    ) -> Result<TagValue, RequestError> {
+        let tag = profile.get(name).ok_or(InvalidRequest::UnknownTag)?;
+        let mut batch = ReadBatch::new(BatchOptions::new(0));
+        batch.add(tag.table, tag.range());
+        let values = self.read_batch(param, &batch).await?;
+        DeviceProfile::decode(tag, &values).map_err(RequestError::BadTagValue)
+    }
+
+    /// Read every tag of a [`DeviceProfile`] using the fewest requests allowed by the options
+    ///
+    /// The values are returned in engineering units by tag name
+    #[cfg(feature = "profile")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "profile")))]
+    pub async fn read_tags(
+        &mut self,
+        param: RequestParam,
+        profile: &DeviceProfile,
+        options: BatchOptions,
+    ) -> Result<BTreeMap<String, TagValue>, BatchError> {
+        let values = self.read_batch(param, &profile.read_batch(options)).await?;
+        profile
+            .tags()
+            .map(|tag| {
+                let value = DeviceProfile::decode(tag, &values).map_err(|err| BatchError {
+                    table: tag.table,
+                    range: tag.range(),
+                    error: RequestError::BadTagValue(err),
+                })?;
+                Ok((tag.name.clone(), value))
+            })
+            .collect()
+    }
+
+    /// Write the value of a tag of a [`DeviceProfile`] in engineering units
+    ///
+    /// Bits of registers are written with [`Channel::mask_write_register`] so that the other bits
+    /// of the register are left unchanged
+    #[cfg(feature = "profile")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "profile")))]
+    pub async fn write_tag(
+        &mut self,
+        param: RequestParam,
+        profile: &DeviceProfile,
+        name: &str,
+        value: &TagValue,
+    ) -> Result<(), RequestError> {
+        let tag = profile.get(name).ok_or(InvalidRequest::UnknownTag)?;
+        if tag.access != Access::ReadWrite {
+            return Err(InvalidRequest::TagNotWritable.into());
+        }
+        match tag.encode(value)? {
+            TagWrite::Coil(x) => {
+                self.write_single_coil(param, Indexed::new(tag.address, x))
+                    .await?;
+            }
+            TagWrite::RegisterBit(bit, x) => {
+                self.mask_write_register(param, tag.address, !(1 << bit), (x as u16) << bit)
+                    .await?;
+            }
+            TagWrite::Registers(registers) => {
+                if let [x] = registers.as_slice() {
+                    self.write_single_register(param, Indexed::new(tag.address, *x))
+                        .await?;
+                } else {
+                    let request = WriteMultiple::from(tag.address, registers)?;
+                    self.write_multiple_registers(param, request).await?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Read the eight exception status outputs of a serial line server
+    ///
+    /// The meaning of each bit is device specific
+    pub async fn read_exception_status(&mut self, param: RequestParam) -> Result<u8, RequestError> {
+        let (tx, rx) = tokio::sync::oneshot::channel::<Result<u8, RequestError>>();
+        let request = wrap(
+            param,
+            RequestDetails::ReadExceptionStatus(ReadStatus::new(Promise::Channel(tx), |x| x.value)),
+        );
+        self.tx.send(request).await?;
+        rx.await?
+    }
+
+    /// Perform a diagnostics sub-function on a serial line server, returning the data of the response
+    ///
+    /// The counters are returned by the `Return...Count` sub-functions with `data` set to `0x0000`.
+    /// The server doesn't respond to [`DiagnosticSubFunction::ForceListenOnlyMode`], so the
+    /// request always fails with [`RequestError::ResponseTimeout`].
+    pub async fn diagnostics(
+        &mut self,
+        param: RequestParam,
+        sub_function: DiagnosticSubFunction,
+        data: u16,
+    ) -> Result<u16, RequestError> {
+        let (tx, rx) = tokio::sync::oneshot::channel::<Result<u16, RequestError>>();
+        let request = wrap(
+            param,
+            RequestDetails::Diagnostics(Diagnostics::new(
+                Diagnostic::new(sub_function.get_value(), data),
+                Promise::Channel(tx),
+            )),
+        );
+        self.tx.send(request).await?;
+        rx.await?
+    }
+
+    /// Read the status word and the count of events (requests completed without an exception)
+    /// of a serial line server
+    pub async fn get_comm_event_counter(
+        &mut self,
+        param: RequestParam,
+    ) -> Result<CommEventCounter, RequestError> {
+        let (tx, rx) = tokio::sync::oneshot::channel::<Result<CommEventCounter, RequestError>>();
+        let request = wrap(
+            param,
+            RequestDetails::GetCommEventCounter(ReadStatus::new(Promise::Channel(tx), |x| x)),
+        );
+        self.tx.send(request).await?;
+        rx.await?
+    }
+
+    /// Read the event counter, message counter, and the most recent communication events
+    /// of a serial line server
+    pub async fn get_comm_event_log(
+        &mut self,
+        param: RequestParam,
+    ) -> Result<CommEventLog, RequestError> {
+        let (tx, rx) = tokio::sync::oneshot::channel::<Result<CommEventLog, RequestError>>();
+        let request = wrap(
+            param,
+            RequestDetails::GetCommEventLog(ReadStatus::new(Promise::Channel(tx), |x| x)),
+        );
+        self.tx.send(request).await?;
+        rx.await?
+    }
+
+    /// Read the device specific server id of a serial line server
+    ///
+    /// The data usually consists of the server id, the run indicator status (`0x00` = OFF,
+    /// `0xFF` = ON), and any additional data, but the layout is defined by the device.
+    pub async fn report_server_id(&mut self, param: RequestParam) -> Result<Vec<u8>, RequestError> {
+        let (tx, rx) = tokio::sync::oneshot::channel::<Result<Vec<u8>, RequestError>>();
+        let request = wrap(
+            param,
+            RequestDetails::ReportServerId(ReadStatus::new(Promise::Channel(tx), |x| x.data)),
+        );
+        self.tx.send(request).await?;
+        rx.await?
+    }
+
+    /// Read groups of records from files on the server
+    ///
+    /// All of the groups are read with a single request, so the sub-requests and the records
+    /// they return must fit in a single PDU. The data is returned in the order it was requested.
+    pub async fn read_file_record(
+        &mut self,
+        param: RequestParam,
+        records: Vec<FileRecord>,
+    ) -> Result<Vec<FileRecordData>, RequestError> {
+        let request = ReadFileRecordRequest::new(records)?;
+        let (tx, rx) = tokio::sync::oneshot::channel::<Result<Vec<FileRecordData>, RequestError>>();
+        let request = wrap(
+            param,
+            RequestDetails::ReadFileRecord(ReadFileRecord::new(request, Promise::Channel(tx))),
+        );
+        self.tx.send(request).await?;
+        rx.await?
+    }
+
+    /// Write groups of records to files on the server
+    ///
+    /// All of the groups are written with a single request, so they must fit in a single PDU
+    pub async fn write_file_record(
+        &mut self,
+        param: RequestParam,
+        records: Vec<FileRecordData>,
+    ) -> Result<(), RequestError> {
+        let request = WriteFileRecordRequest::new(records)?;
+        let (tx, rx) = tokio::sync::oneshot::channel::<Result<(), RequestError>>();
+        let request = wrap(
+            param,
+            RequestDetails::WriteFileRecord(WriteFileRecord::new(request, Promise::Channel(tx))),
+        );
+        self.tx.send(request).await?;
+        rx.await?
+    }
+
+    /// Modify individual bits of a holding register on the server
+    ///
+    /// The new value of the register is `(current AND and_mask) OR (or_mask AND NOT and_mask)`
+    pub async fn mask_write_register(
+        &mut self,
+        param: RequestParam,
+        address: u16,
+        and_mask: u16,
+        or_mask: u16,
+    ) -> Result<MaskWriteRegister, RequestError> {
+        let (tx, rx) = tokio::sync::oneshot::channel::<Result<MaskWriteRegister, RequestError>>();
+        let request = wrap(
+            param,
+            RequestDetails::MaskWriteRegister(SingleWrite::new(
+                MaskWriteRegister::new(address, and_mask, or_mask),
+                Promise::Channel(tx),
+            )),
+        );
+        self.tx.send(request).await?;
+        rx.await?
+    }
+
+    /// Write multiple contiguous registers and then read holding registers in a single request
+    pub async fn read_write_multiple_registers(
+        &mut self,
+        param: RequestParam,
+        request: ReadWriteMultiple,
+    ) -> Result<Vec<Indexed<u16>>, RequestError> {
+        let (tx, rx) = tokio::sync::oneshot::channel::<Result<Vec<Indexed<u16>>, RequestError>>();
+        let request = wrap(
+            param,
+            RequestDetails::ReadWriteMultipleRegisters(ReadWriteMultipleRegisters::new(
+                request,
+                crate::client::requests::read_registers::Promise::Channel(tx),
+            )),
+        );
+        self.tx.send(request).await?;
+        rx.await?
+    }
+
+    /// Read the contents of the FIFO queue at the pointer address
+    ///
+    /// The queue contains at most [`MAX_FIFO_COUNT`](crate::constants::limits::MAX_FIFO_COUNT) values
+    pub async fn read_fifo_queue(
+        &mut self,
+        param: RequestParam,
+        pointer_address: u16,
+    ) -> Result<Vec<u16>, RequestError> {
+        let (tx, rx) = tokio::sync::oneshot::channel::<Result<Vec<u16>, RequestError>>();
+        let request = wrap(
+            param,
+            RequestDetails::ReadFifoQueue(ReadFifoQueue::new(
+                pointer_address,
+                Promise::Channel(tx),
+            )),
+        );
+        self.tx.send(request).await?;
+        rx.await?
+    }
+
+    /// Read the identification objects of a device
+    ///
+    /// Stream access (`Basic`, `Regular`, and `Extended`) starts at `object_id` and issues
+    /// follow-up requests until the server has returned all of the objects in the category.
+    /// `Individual` access reads the single object with `object_id`.
+    pub async fn read_device_identification(
+        &mut self,
+        param: RequestParam,
+        code: ReadDeviceIdCode,
+        object_id: u8,
+    ) -> Result<DeviceIdentification, RequestError> {
+        // there are only 256 object ids, so a server that needs more requests isn't making progress
+        const MAX_REQUESTS: usize = 256;
+
+        let mut identification = DeviceIdentification::default();
+        let mut request = ReadDeviceIdRequest::new(code, object_id);
+        for _ in 0..MAX_REQUESTS {
+            let (tx, rx) = tokio::sync::oneshot::channel::<Result<DeviceIdPage, RequestError>>();
+            self.tx
+                .send(wrap(
+                    param,
+                    RequestDetails::ReadDeviceIdentification(ReadDeviceId::new(
+                        request,
+                        Promise::Channel(tx),
+                    )),
+                ))
+                .await?;
+            let page = rx.await??;
+            let next_object_id = page.next_object_id;
+            identification.extend(page);
+            match next_object_id {
+                // each response is checked to continue after the object that was requested
+                Some(id) if id > request.object_id => request.object_id = id,
+                Some(id) => return Err(AduParseError::DeviceIdNoProgress(id).into()),
+                None => return Ok(identification),
+            }
+        }
+
+        Err(AduParseError::DeviceIdNoProgress(request.object_id).into())
+    }
+
+    /// Send a request with raw data for a standard or user-defined function code, returning the
+    /// raw data that follows the function code in the response
+    ///
+    /// Only the standard function codes supported by the library and the user-defined function
+    /// codes (65-72 and 100-110) are accepted, other codes fail with
+    /// [`InvalidRequest::UnsupportedFunctionCode`]. Exception responses are returned as
+    /// [`RequestError::Exception`].
+    ///
+    /// On RTU serial channels the end of a response to a user-defined function is detected by
+    /// inter-frame silence. RTU over TCP channels have no way to find the end of such responses,
+    /// so user-defined function codes fail with [`InvalidRequest::UnsupportedByChannel`].
+    pub async fn send_custom_function(
+        &mut self,
+        param: RequestParam,
+        function: u8,
+        data: Vec<u8>,
+    ) -> Result<Vec<u8>, RequestError> {
+        let request = CustomFunctionRequest::new(function, data)?;
+        let (tx, rx) = tokio::sync::oneshot::channel::<Result<Vec<u8>, RequestError>>();
+        let request = wrap(
+            param,
+            RequestDetails::CustomFunction(CustomFunction::new(request, Promise::Channel(tx))),
+        );
+        self.tx.send(request).await?;
+        rx.await?
+    }
+
+    async fn read_chunked<T>(
+        &mut self,
+        param: RequestParam,
+        range: AddressRange,
+        max: u16,
+        details: fn(AddressRange, ValuesSender<T>) -> RequestDetails,
+    ) -> Result<Vec<Indexed<T>>, ChunkError> {
+        let mut responses = Vec::new();
+        for chunk in range.chunks(max) {
+            let (tx, rx) = tokio::sync::oneshot::channel();
+            self.tx
+                .send(wrap(param, details(chunk, tx)))
+                .await
+                .map_err(|err| ChunkError {
+                    range: chunk,
+                    error: err.into(),
+                })?;
+            responses.push((chunk, rx));
+        }
+
+        let mut values = Vec::with_capacity(range.count as usize);
+        for (chunk, rx) in responses {
+            match rx.await.unwrap_or_else(|err| Err(err.into())) {
+                Ok(x) => values.extend(x),
+                Err(error) => {
+                    return Err(ChunkError {
+                        range: chunk,
+                        error,
+                    })
+                }
+            }
+        }
+        Ok(values)
+    }
 }
 
 /// Callback-based session
@@ -380,6 +1530,56 @@ impl CallbackSession {
         .await;
     }
 
+    /// Modify individual bits of a holding register on the server
+    pub async fn mask_write_register<C>(
+        &mut self,
+        address: u16,
+        and_mask: u16,
+        or_mask: u16,
+        callback: C,
+    ) where
+        C: FnOnce(Result<MaskWriteRegister, RequestError>) + Send + Sync + 'static,
+    {
+        self.send(wrap(
+            self.param,
+            RequestDetails::MaskWriteRegister(SingleWrite::new(
+                MaskWriteRegister::new(address, and_mask, or_mask),
+                Promise::Callback(Box::new(callback)),
+            )),
+        ))
+        .await;
+    }
+
+    /// Write multiple contiguous registers and then read holding registers in a single request
+    pub async fn read_write_multiple_registers<C>(&mut self, value: ReadWriteMultiple, callback: C)
+    where
+        C: FnOnce(Result<RegisterIterator, RequestError>) + Send + Sync + 'static,
+    {
+        self.send(wrap(
+            self.param,
+            RequestDetails::ReadWriteMultipleRegisters(ReadWriteMultipleRegisters::new(
+                value,
+                crate::client::requests::read_registers::Promise::Callback(Box::new(callback)),
+            )),
+        ))
+        .await;
+    }
+
+    /// Read the contents of the FIFO queue at the pointer address
+    pub async fn read_fifo_queue<C>(&mut self, pointer_address: u16, callback: C)
+    where
+        C: FnOnce(Result<Vec<u16>, RequestError>) + Send + Sync + 'static,
+    {
+        self.send(wrap(
+            self.param,
+            RequestDetails::ReadFifoQueue(ReadFifoQueue::new(
+                pointer_address,
+                Promise::Callback(Box::new(callback)),
+            )),
+        ))
+        .await;
+    }
+
     async fn read_bits<C, W>(&mut self, range: AddressRange, callback: C, wrap_req: W)
     where
         C: FnOnce(Result<BitIterator, RequestError>) + Send + Sync + 'static,
@@ -420,5 +1620,5 @@ impl CallbackSession {
 }
 
 fn wrap(param: RequestParam, details: RequestDetails) -> Request {
-    Request::new(param.id, param.response_timeout, details)
+    Request::new(param.id, param.response_timeout, details).with_retry_policy(param.retry_policy)
 }
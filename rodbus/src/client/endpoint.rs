@@ -0,0 +1,229 @@
+use std::net::SocketAddr;
+use std::time::Duration;
+
+use crate::tokio::net::{TcpSocket, TcpStream};
+
+/// Address of a server that a TCP client channel connects to
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Endpoint {
+    /// IP address and port
+    Addr(SocketAddr),
+    /// Host name and port in the form `host:port`, resolved before each connection attempt
+    Host(String),
+}
+
+/// Ordered list of the endpoints of a server, starting with the primary
+///
+/// When a connection attempt fails, the channel waits for the delay returned by its
+/// [`ReconnectStrategy`](crate::client::ReconnectStrategy) and then tries the next endpoint,
+/// wrapping around to the primary after the last backup.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct EndpointList {
+    endpoints: Vec<Endpoint>,
+    fail_back: Option<Duration>,
+    local: Option<SocketAddr>,
+}
+
+impl Endpoint {
+    pub(crate) async fn connect(&self, local: Option<SocketAddr>) -> std::io::Result<TcpStream> {
+        let local = match local {
+            Some(local) => local,
+            None => {
+                return match self {
+                    Self::Addr(addr) => TcpStream::connect(addr).await,
+                    Self::Host(host) => TcpStream::connect(host.as_str()).await,
+                }
+            }
+        };
+
+        let remotes: Vec<SocketAddr> = match self {
+            Self::Addr(addr) => vec![*addr],
+            Self::Host(host) => crate::tokio::net::lookup_host(host.as_str())
+                .await?
+                .collect(),
+        };
+
+        // only the addresses of the same family as the local address can be reached from it
+        let mut last_error = None;
+        for remote in remotes
+            .into_iter()
+            .filter(|x| x.is_ipv4() == local.is_ipv4())
+        {
+            match connect_from(local, remote).await {
+                Ok(socket) => return Ok(socket),
+                Err(err) => last_error = Some(err),
+            }
+        }
+
+        Err(last_error.unwrap_or_else(|| {
+            std::io::Error::new(
+                std::io::ErrorKind::AddrNotAvailable,
+                format!("{} has no address of the same family as {}", self, local),
+            )
+        }))
+    }
+}
+
+async fn connect_from(local: SocketAddr, remote: SocketAddr) -> std::io::Result<TcpStream> {
+    let socket = if remote.is_ipv4() {
+        TcpSocket::new_v4()?
+    } else {
+        TcpSocket::new_v6()?
+    };
+    // a fixed local port must be reusable while the previous connection is in TIME_WAIT
+    socket.set_reuseaddr(true)?;
+    socket.bind(local)?;
+    socket.connect(remote).await
+}
+
+impl EndpointList {
+    /// Create a list with a single endpoint
+    pub fn new(primary: impl Into<Endpoint>) -> Self {
+        Self {
+            endpoints: vec![primary.into()],
+            fail_back: None,
+            local: None,
+        }
+    }
+
+    /// Add a backup endpoint to the end of the list
+    pub fn with_backup(mut self, backup: impl Into<Endpoint>) -> Self {
+        self.endpoints.push(backup.into());
+        self
+    }
+
+    /// Close a connection to a backup endpoint after it has been open for the specified
+    /// period, so that the channel tries to connect to the primary again
+    pub fn with_fail_back(mut self, period: Duration) -> Self {
+        self.fail_back = Some(period);
+        self
+    }
+
+    /// Bind the socket to a local address before connecting, so that the connection is made
+    /// through the network interface that has this address
+    ///
+    /// Use port 0 to let the operating system choose the local port. Only the addresses of the
+    /// endpoints that belong to the same family (IPv4 or IPv6) as the local address are used.
+    pub fn with_local_address(mut self, local: SocketAddr) -> Self {
+        self.local = Some(local);
+        self
+    }
+
+    /// The endpoints in the order they are tried
+    pub fn endpoints(&self) -> &[Endpoint] {
+        self.endpoints.as_slice()
+    }
+
+    /// The period after which a connection to a backup is closed, if any
+    pub fn fail_back(&self) -> Option<Duration> {
+        self.fail_back
+    }
+
+    /// The local address that the socket is bound to before connecting, if any
+    pub fn local_address(&self) -> Option<SocketAddr> {
+        self.local
+    }
+
+    pub(crate) fn get(&self, index: usize) -> &Endpoint {
+        &self.endpoints[index]
+    }
+
+    pub(crate) fn next_index(&self, index: usize) -> usize {
+        (index + 1) % self.endpoints.len()
+    }
+}
+
+impl From<SocketAddr> for Endpoint {
+    fn from(addr: SocketAddr) -> Self {
+        Self::Addr(addr)
+    }
+}
+
+impl From<&str> for Endpoint {
+    fn from(host: &str) -> Self {
+        Self::Host(host.to_string())
+    }
+}
+
+impl From<String> for Endpoint {
+    fn from(host: String) -> Self {
+        Self::Host(host)
+    }
+}
+
+impl From<SocketAddr> for EndpointList {
+    fn from(addr: SocketAddr) -> Self {
+        Self::new(addr)
+    }
+}
+
+impl From<&str> for EndpointList {
+    fn from(host: &str) -> Self {
+        Self::new(host)
+    }
+}
+
+impl From<String> for EndpointList {
+    fn from(host: String) -> Self {
+        Self::new(host)
+    }
+}
+
+impl From<Endpoint> for EndpointList {
+    fn from(endpoint: Endpoint) -> Self {
+        Self::new(endpoint)
+    }
+}
+
+impl std::fmt::Display for Endpoint {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Self::Addr(addr) => write!(f, "{}", addr),
+            Self::Host(host) => f.write_str(host),
+        }
+    }
+}
+
+impl std::fmt::Display for EndpointList {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        for (i, endpoint) in self.endpoints.iter().enumerate() {
+            if i > 0 {
+                f.write_str(", ")?;
+            }
+            write!(f, "{}", endpoint)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn list() -> EndpointList {
+        EndpointList::new("127.0.0.1:502".parse::<SocketAddr>().unwrap())
+            .with_backup("backup.local:502")
+    }
+
+    #[test]
+    fn rotates_through_the_endpoints_starting_with_the_primary() {
+        let list = list();
+        assert_eq!(list.next_index(0), 1);
+        assert_eq!(list.next_index(1), 0);
+        assert_eq!(list.get(1), &Endpoint::Host("backup.local:502".to_string()));
+        assert_eq!(list.fail_back(), None);
+    }
+
+    #[test]
+    fn strings_are_host_names() {
+        assert_eq!(
+            EndpointList::from("plc.local:502").endpoints(),
+            &[Endpoint::Host("plc.local:502".to_string())]
+        );
+    }
+
+    #[test]
+    fn formats_the_endpoints_in_order() {
+        assert_eq!(list().to_string(), "127.0.0.1:502, backup.local:502");
+    }
+}
@@ -1,24 +1,40 @@
 use crate::common::function::FunctionCode;
 use crate::common::traits::Loggable;
+use crate::custom::CustomFunctionDataDisplay;
 use crate::decode::PduDecodeLevel;
+use crate::diagnostics::{CommEventCounter, CommEventLog, ExceptionStatus, ServerId};
 use crate::error::AduParseError;
 use crate::error::*;
 use crate::exception::ExceptionCode;
+use crate::file_record::{FileRecordDataDisplay, FileRecordDisplay};
 use crate::tokio;
 
+use crate::client::channel::ReconnectStrategy;
+use crate::client::endpoint::EndpointList;
+use crate::client::requests::custom::CustomFunction;
+use crate::client::requests::diagnostics::{Diagnostics, ReadStatus};
+use crate::client::requests::file_record::{ReadFileRecord, WriteFileRecord};
 use crate::client::requests::read_bits::ReadBits;
+use crate::client::requests::read_device_id::ReadDeviceId;
+use crate::client::requests::read_fifo::ReadFifoQueue;
 use crate::client::requests::read_registers::ReadRegisters;
+use crate::client::requests::read_write_multiple::ReadWriteMultipleRegisters;
 use crate::client::requests::write_multiple::MultipleWriteRequest;
 use crate::client::requests::write_single::SingleWrite;
+use crate::client::retry::RetryPolicy;
 use crate::common::cursor::{ReadCursor, WriteCursor};
 use crate::common::traits::Serialize;
-use crate::types::{Indexed, UnitId};
+use crate::types::{Indexed, MaskWriteRegister, UnitId};
 use std::time::Duration;
 
 pub(crate) struct Request {
     pub(crate) id: UnitId,
     pub(crate) timeout: Duration,
     pub(crate) details: RequestDetails,
+    // overrides the retry policy of the channel
+    pub(crate) retry_policy: Option<RetryPolicy>,
+    // number of times the request has already been sent again
+    pub(crate) retries: u8,
 }
 
 // possible requests that can be sent through the channel
@@ -29,8 +45,30 @@ pub(crate) enum RequestDetails {
     ReadInputRegisters(ReadRegisters),
     WriteSingleCoil(SingleWrite<Indexed<bool>>),
     WriteSingleRegister(SingleWrite<Indexed<u16>>),
+    ReadExceptionStatus(ReadStatus<ExceptionStatus, u8>),
+    Diagnostics(Diagnostics),
+    GetCommEventCounter(ReadStatus<CommEventCounter, CommEventCounter>),
+    GetCommEventLog(ReadStatus<CommEventLog, CommEventLog>),
     WriteMultipleCoils(MultipleWriteRequest<bool>),
     WriteMultipleRegisters(MultipleWriteRequest<u16>),
+    ReportServerId(ReadStatus<ServerId, Vec<u8>>),
+    ReadFileRecord(ReadFileRecord),
+    WriteFileRecord(WriteFileRecord),
+    MaskWriteRegister(SingleWrite<MaskWriteRegister>),
+    ReadWriteMultipleRegisters(ReadWriteMultipleRegisters),
+    ReadFifoQueue(ReadFifoQueue),
+    ReadDeviceIdentification(ReadDeviceId),
+    CustomFunction(CustomFunction),
+}
+
+// changes to the settings of a channel that are applied by its task
+pub(crate) enum Setting {
+    Enable,
+    Disable,
+    ReconnectStrategy(Box<dyn ReconnectStrategy + Send>),
+    Endpoint(EndpointList),
+    RetryPolicy(RetryPolicy),
+    TurnaroundDelay(Duration),
 }
 
 impl Request {
@@ -39,6 +77,25 @@ impl Request {
             id,
             timeout,
             details,
+            retry_policy: None,
+            retries: 0,
+        }
+    }
+
+    pub(crate) fn with_retry_policy(self, retry_policy: Option<RetryPolicy>) -> Self {
+        Self {
+            retry_policy,
+            ..self
+        }
+    }
+
+    // the exception code if the payload is an exception response to this request
+    pub(crate) fn exception(&self, payload: &[u8]) -> Option<ExceptionCode> {
+        match payload {
+            [function, code] if *function == self.details.function().as_error() => {
+                Some(ExceptionCode::from(*code))
+            }
+            _ => None,
         }
     }
 
@@ -111,8 +168,22 @@ impl RequestDetails {
             RequestDetails::ReadInputRegisters(_) => FunctionCode::ReadInputRegisters,
             RequestDetails::WriteSingleCoil(_) => FunctionCode::WriteSingleCoil,
             RequestDetails::WriteSingleRegister(_) => FunctionCode::WriteSingleRegister,
+            RequestDetails::ReadExceptionStatus(_) => FunctionCode::ReadExceptionStatus,
+            RequestDetails::Diagnostics(_) => FunctionCode::Diagnostics,
+            RequestDetails::GetCommEventCounter(_) => FunctionCode::GetCommEventCounter,
+            RequestDetails::GetCommEventLog(_) => FunctionCode::GetCommEventLog,
             RequestDetails::WriteMultipleCoils(_) => FunctionCode::WriteMultipleCoils,
             RequestDetails::WriteMultipleRegisters(_) => FunctionCode::WriteMultipleRegisters,
+            RequestDetails::ReportServerId(_) => FunctionCode::ReportServerId,
+            RequestDetails::ReadFileRecord(_) => FunctionCode::ReadFileRecord,
+            RequestDetails::WriteFileRecord(_) => FunctionCode::WriteFileRecord,
+            RequestDetails::MaskWriteRegister(_) => FunctionCode::MaskWriteRegister,
+            RequestDetails::ReadWriteMultipleRegisters(_) => {
+                FunctionCode::ReadWriteMultipleRegisters
+            }
+            RequestDetails::ReadFifoQueue(_) => FunctionCode::ReadFifoQueue,
+            RequestDetails::ReadDeviceIdentification(_) => FunctionCode::ReadDeviceIdentification,
+            RequestDetails::CustomFunction(x) => x.request.function,
         }
     }
 
@@ -124,8 +195,62 @@ impl RequestDetails {
             RequestDetails::ReadInputRegisters(x) => x.failure(err),
             RequestDetails::WriteSingleCoil(x) => x.failure(err),
             RequestDetails::WriteSingleRegister(x) => x.failure(err),
+            RequestDetails::ReadExceptionStatus(x) => x.failure(err),
+            RequestDetails::Diagnostics(x) => x.failure(err),
+            RequestDetails::GetCommEventCounter(x) => x.failure(err),
+            RequestDetails::GetCommEventLog(x) => x.failure(err),
             RequestDetails::WriteMultipleCoils(x) => x.failure(err),
             RequestDetails::WriteMultipleRegisters(x) => x.failure(err),
+            RequestDetails::ReportServerId(x) => x.failure(err),
+            RequestDetails::ReadFileRecord(x) => x.failure(err),
+            RequestDetails::WriteFileRecord(x) => x.failure(err),
+            RequestDetails::MaskWriteRegister(x) => x.failure(err),
+            RequestDetails::ReadWriteMultipleRegisters(x) => x.failure(err),
+            RequestDetails::ReadFifoQueue(x) => x.failure(err),
+            RequestDetails::ReadDeviceIdentification(x) => x.failure(err),
+            RequestDetails::CustomFunction(x) => x.failure(err),
+        }
+    }
+
+    // the requests that servers process without a response when sent to the broadcast unit id
+    pub(crate) fn is_broadcast_write(&self) -> bool {
+        matches!(
+            self,
+            RequestDetails::WriteSingleCoil(_)
+                | RequestDetails::WriteSingleRegister(_)
+                | RequestDetails::WriteMultipleCoils(_)
+                | RequestDetails::WriteMultipleRegisters(_)
+                | RequestDetails::MaskWriteRegister(_)
+        )
+    }
+
+    pub(crate) fn complete_broadcast(self) {
+        let function = self.function();
+        match self {
+            RequestDetails::WriteSingleCoil(x) => x.complete_broadcast(),
+            RequestDetails::WriteSingleRegister(x) => x.complete_broadcast(),
+            RequestDetails::WriteMultipleCoils(x) => x.complete_broadcast(),
+            RequestDetails::WriteMultipleRegisters(x) => x.complete_broadcast(),
+            RequestDetails::MaskWriteRegister(x) => x.complete_broadcast(),
+            // the task only completes requests for which is_broadcast_write() is true this way,
+            // the others are sent normally and wait for a response
+            RequestDetails::ReadCoils(_)
+            | RequestDetails::ReadDiscreteInputs(_)
+            | RequestDetails::ReadHoldingRegisters(_)
+            | RequestDetails::ReadInputRegisters(_)
+            | RequestDetails::ReadExceptionStatus(_)
+            | RequestDetails::Diagnostics(_)
+            | RequestDetails::GetCommEventCounter(_)
+            | RequestDetails::GetCommEventLog(_)
+            | RequestDetails::ReportServerId(_)
+            | RequestDetails::ReadFileRecord(_)
+            | RequestDetails::WriteFileRecord(_)
+            | RequestDetails::ReadWriteMultipleRegisters(_)
+            | RequestDetails::ReadFifoQueue(_)
+            | RequestDetails::ReadDeviceIdentification(_)
+            | RequestDetails::CustomFunction(_) => {
+                self.fail(InternalError::NotBroadcastWrite(function.get_value()).into())
+            }
         }
     }
 
@@ -138,10 +263,26 @@ impl RequestDetails {
             RequestDetails::ReadInputRegisters(x) => x.handle_response(cursor, function, decode),
             RequestDetails::WriteSingleCoil(x) => x.handle_response(cursor, function, decode),
             RequestDetails::WriteSingleRegister(x) => x.handle_response(cursor, function, decode),
+            RequestDetails::ReadExceptionStatus(x) => x.handle_response(cursor, function, decode),
+            RequestDetails::Diagnostics(x) => x.handle_response(cursor, function, decode),
+            RequestDetails::GetCommEventCounter(x) => x.handle_response(cursor, function, decode),
+            RequestDetails::GetCommEventLog(x) => x.handle_response(cursor, function, decode),
             RequestDetails::WriteMultipleCoils(x) => x.handle_response(cursor, function, decode),
             RequestDetails::WriteMultipleRegisters(x) => {
                 x.handle_response(cursor, function, decode)
             }
+            RequestDetails::ReportServerId(x) => x.handle_response(cursor, function, decode),
+            RequestDetails::ReadFileRecord(x) => x.handle_response(cursor, function, decode),
+            RequestDetails::WriteFileRecord(x) => x.handle_response(cursor, function, decode),
+            RequestDetails::MaskWriteRegister(x) => x.handle_response(cursor, function, decode),
+            RequestDetails::ReadWriteMultipleRegisters(x) => {
+                x.handle_response(cursor, function, decode)
+            }
+            RequestDetails::ReadFifoQueue(x) => x.handle_response(cursor, function, decode),
+            RequestDetails::ReadDeviceIdentification(x) => {
+                x.handle_response(cursor, function, decode)
+            }
+            RequestDetails::CustomFunction(x) => x.handle_response(cursor, function, decode),
         }
     }
 }
@@ -155,8 +296,20 @@ impl Serialize for RequestDetails {
             RequestDetails::ReadInputRegisters(x) => x.serialize(cursor),
             RequestDetails::WriteSingleCoil(x) => x.serialize(cursor),
             RequestDetails::WriteSingleRegister(x) => x.serialize(cursor),
+            RequestDetails::ReadExceptionStatus(x) => x.serialize(cursor),
+            RequestDetails::Diagnostics(x) => x.serialize(cursor),
+            RequestDetails::GetCommEventCounter(x) => x.serialize(cursor),
+            RequestDetails::GetCommEventLog(x) => x.serialize(cursor),
             RequestDetails::WriteMultipleCoils(x) => x.serialize(cursor),
             RequestDetails::WriteMultipleRegisters(x) => x.serialize(cursor),
+            RequestDetails::ReportServerId(x) => x.serialize(cursor),
+            RequestDetails::ReadFileRecord(x) => x.serialize(cursor),
+            RequestDetails::WriteFileRecord(x) => x.serialize(cursor),
+            RequestDetails::MaskWriteRegister(x) => x.serialize(cursor),
+            RequestDetails::ReadWriteMultipleRegisters(x) => x.serialize(cursor),
+            RequestDetails::ReadFifoQueue(x) => x.serialize(cursor),
+            RequestDetails::ReadDeviceIdentification(x) => x.serialize(cursor),
+            RequestDetails::CustomFunction(x) => x.serialize(cursor),
         }
     }
 }
@@ -205,6 +358,13 @@ impl std::fmt::Display for RequestDetailsDisplay<'_> {
                 RequestDetails::WriteSingleRegister(details) => {
                     write!(f, "{}", details.request)?;
                 }
+                RequestDetails::ReadExceptionStatus(_)
+                | RequestDetails::GetCommEventCounter(_)
+                | RequestDetails::GetCommEventLog(_)
+                | RequestDetails::ReportServerId(_) => {}
+                RequestDetails::Diagnostics(details) => {
+                    write!(f, "{}", details.request)?;
+                }
                 RequestDetails::WriteMultipleCoils(details) => {
                     write!(f, "{}", details.request.range)?;
                     if self.level.data_values() {
@@ -221,6 +381,45 @@ impl std::fmt::Display for RequestDetailsDisplay<'_> {
                         }
                     }
                 }
+                RequestDetails::ReadFileRecord(details) => {
+                    write!(f, "{}", FileRecordDisplay::new(&details.request.records))?;
+                }
+                RequestDetails::WriteFileRecord(details) => {
+                    write!(
+                        f,
+                        "{}",
+                        FileRecordDataDisplay::new(self.level, &details.request.records)
+                    )?;
+                }
+                RequestDetails::MaskWriteRegister(details) => {
+                    write!(f, "{}", details.request)?;
+                }
+                RequestDetails::ReadWriteMultipleRegisters(details) => {
+                    write!(
+                        f,
+                        "read: {} write: {}",
+                        details.request.read_range.get(),
+                        details.request.write.range
+                    )?;
+                    if self.level.data_values() {
+                        for x in details.request.write.iter() {
+                            write!(f, "\n{}", x)?;
+                        }
+                    }
+                }
+                RequestDetails::ReadFifoQueue(details) => {
+                    write!(f, "pointer: {:#06X}", details.pointer_address)?;
+                }
+                RequestDetails::ReadDeviceIdentification(details) => {
+                    write!(f, "{}", details.request)?;
+                }
+                RequestDetails::CustomFunction(details) => {
+                    write!(
+                        f,
+                        "{}",
+                        CustomFunctionDataDisplay::new(self.level, &details.request.data.data)
+                    )?;
+                }
             }
         }
 
@@ -253,3 +452,31 @@ impl<T> Promise<T> {
 trait Callback<U> {
     fn complete(self, result: Result<U, RequestError>);
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tokio::test::*;
+    use crate::types::AddressRange;
+
+    #[test]
+    fn fails_requests_other_than_writes_completed_as_broadcasts() {
+        let (tx, rx) = tokio::sync::oneshot::channel();
+        let details = RequestDetails::ReadCoils(ReadBits::new(
+            AddressRange::try_from(0, 2)
+                .unwrap()
+                .of_read_bits()
+                .unwrap(),
+            crate::client::requests::read_bits::Promise::Channel(tx),
+        ));
+
+        details.complete_broadcast();
+
+        assert_ready_eq!(
+            spawn(rx).poll(),
+            Ok(Err(RequestError::Internal(
+                InternalError::NotBroadcastWrite(0x01)
+            )))
+        );
+    }
+}
@@ -1,16 +1,27 @@
 use std::net::SocketAddr;
 
 use crate::decode::DecodeLevel;
+use crate::serial::settings::SerialSettings;
+use crate::tcp::tls::TlsClientConfig;
 
+pub(crate) mod batch;
 /// persistent communication channel such as a TCP connection
 pub(crate) mod channel;
+pub(crate) mod endpoint;
 pub(crate) mod message;
+pub(crate) mod poll;
 pub(crate) mod requests;
+pub(crate) mod retry;
 pub(crate) mod task;
 
+pub use crate::client::batch::{BatchOptions, BatchValues, PlannedRead, ReadBatch};
 pub use crate::client::channel::strategy::*;
 pub use crate::client::channel::*;
+pub use crate::client::endpoint::{Endpoint, EndpointList};
+pub use crate::client::poll::{PollDefinition, PollId, PollUpdate, PollValues, Poller};
+pub use crate::client::requests::read_write_multiple::ReadWriteMultiple;
 pub use crate::client::requests::write_multiple::WriteMultiple;
+pub use crate::client::retry::{RetryPolicy, RetryableErrors};
 
 /// Spawns a channel task onto the runtime that maintains a TCP connection and processes
 /// requests from an mpsc request queue. The task completes when the returned channel handle
@@ -28,7 +39,55 @@ pub fn spawn_tcp_client_task(
     retry: Box<dyn ReconnectStrategy + Send>,
     decode: DecodeLevel,
 ) -> Channel {
-    Channel::new(addr, max_queued_requests, retry, decode)
+    spawn_tcp_client_task_with_options(
+        addr,
+        max_queued_requests,
+        retry,
+        ClientOptions::default(),
+        decode,
+    )
+}
+
+/// Same as [`spawn_tcp_client_task`], with [`ClientOptions`] such as the maximum number of
+/// requests sent without waiting for the responses to the previous ones
+///
+/// * `addr` - Socket address of the remote server
+/// * `max_queued_requests` - The maximum size of the request queue
+/// * `retry` - A boxed trait object that controls when the connection is retried on failure
+/// * `options` - Optional settings of the channel
+/// * `decode` - Decode log level
+pub fn spawn_tcp_client_task_with_options(
+    addr: SocketAddr,
+    max_queued_requests: usize,
+    retry: Box<dyn ReconnectStrategy + Send>,
+    options: ClientOptions,
+    decode: DecodeLevel,
+) -> Channel {
+    spawn_tcp_client_task_with_endpoints(
+        EndpointList::from(addr),
+        max_queued_requests,
+        retry,
+        options,
+        decode,
+    )
+}
+
+/// Same as [`spawn_tcp_client_task_with_options`], connecting to a list of redundant endpoints
+/// instead of a single address
+///
+/// * `endpoints` - Primary endpoint of the remote server followed by its backups
+/// * `max_queued_requests` - The maximum size of the request queue
+/// * `retry` - A boxed trait object that controls when the connection is retried on failure
+/// * `options` - Optional settings of the channel
+/// * `decode` - Decode log level
+pub fn spawn_tcp_client_task_with_endpoints(
+    endpoints: EndpointList,
+    max_queued_requests: usize,
+    retry: Box<dyn ReconnectStrategy + Send>,
+    options: ClientOptions,
+    decode: DecodeLevel,
+) -> Channel {
+    Channel::new(endpoints, max_queued_requests, retry, options, decode)
 }
 
 /// Creates a channel task, but does not spawn it. Most users will prefer
@@ -47,5 +106,388 @@ pub fn create_handle_and_task(
     retry: Box<dyn ReconnectStrategy + Send>,
     decode: DecodeLevel,
 ) -> (Channel, impl std::future::Future<Output = ()>) {
-    Channel::create_handle_and_task(addr, max_queued_requests, retry, decode)
+    create_handle_and_task_with_options(
+        addr,
+        max_queued_requests,
+        retry,
+        ClientOptions::default(),
+        decode,
+    )
+}
+
+/// Same as [`create_handle_and_task`], with [`ClientOptions`] such as the maximum number of
+/// requests sent without waiting for the responses to the previous ones
+///
+/// * `addr` - Socket address of the remote server
+/// * `max_queued_requests` - The maximum size of the request queue
+/// * `retry` - A boxed trait object that controls when the connection is retried on failure
+/// * `options` - Optional settings of the channel
+/// * `decode` - Decode log level
+pub fn create_handle_and_task_with_options(
+    addr: SocketAddr,
+    max_queued_requests: usize,
+    retry: Box<dyn ReconnectStrategy + Send>,
+    options: ClientOptions,
+    decode: DecodeLevel,
+) -> (Channel, impl std::future::Future<Output = ()>) {
+    create_handle_and_task_with_endpoints(
+        EndpointList::from(addr),
+        max_queued_requests,
+        retry,
+        options,
+        decode,
+    )
+}
+
+/// Same as [`create_handle_and_task_with_options`], connecting to a list of redundant endpoints
+/// instead of a single address
+///
+/// * `endpoints` - Primary endpoint of the remote server followed by its backups
+/// * `max_queued_requests` - The maximum size of the request queue
+/// * `retry` - A boxed trait object that controls when the connection is retried on failure
+/// * `options` - Optional settings of the channel
+/// * `decode` - Decode log level
+pub fn create_handle_and_task_with_endpoints(
+    endpoints: EndpointList,
+    max_queued_requests: usize,
+    retry: Box<dyn ReconnectStrategy + Send>,
+    options: ClientOptions,
+    decode: DecodeLevel,
+) -> (Channel, impl std::future::Future<Output = ()>) {
+    Channel::create_handle_and_task(endpoints, max_queued_requests, retry, options, decode)
+}
+
+/// Spawns a channel task onto the runtime that maintains a TLS connection (Modbus/TCP Security)
+/// and processes requests from an mpsc request queue. The task completes when the returned
+/// channel handle and all derived session handles are dropped.
+///
+/// The channel uses the provided [`ReconnectStrategy`] to pause between failed connection
+/// attempts, including failed TLS handshakes
+///
+/// * `addr` - Socket address of the remote server, usually on port 802
+/// * `max_queued_requests` - The maximum size of the request queue
+/// * `retry` - A boxed trait object that controls when the connection is retried on failure
+/// * `tls_config` - TLS configuration used to authenticate the server and the client
+/// * `decode` - Decode log level
+pub fn spawn_tls_client_task(
+    addr: SocketAddr,
+    max_queued_requests: usize,
+    retry: Box<dyn ReconnectStrategy + Send>,
+    tls_config: TlsClientConfig,
+    decode: DecodeLevel,
+) -> Channel {
+    spawn_tls_client_task_with_options(
+        addr,
+        max_queued_requests,
+        retry,
+        tls_config,
+        ClientOptions::default(),
+        decode,
+    )
+}
+
+/// Same as [`spawn_tls_client_task`], with [`ClientOptions`] such as the maximum number of
+/// requests sent without waiting for the responses to the previous ones
+///
+/// * `addr` - Socket address of the remote server, usually on port 802
+/// * `max_queued_requests` - The maximum size of the request queue
+/// * `retry` - A boxed trait object that controls when the connection is retried on failure
+/// * `tls_config` - TLS configuration used to authenticate the server and the client
+/// * `options` - Optional settings of the channel
+/// * `decode` - Decode log level
+pub fn spawn_tls_client_task_with_options(
+    addr: SocketAddr,
+    max_queued_requests: usize,
+    retry: Box<dyn ReconnectStrategy + Send>,
+    tls_config: TlsClientConfig,
+    options: ClientOptions,
+    decode: DecodeLevel,
+) -> Channel {
+    spawn_tls_client_task_with_endpoints(
+        EndpointList::from(addr),
+        max_queued_requests,
+        retry,
+        tls_config,
+        options,
+        decode,
+    )
+}
+
+/// Same as [`spawn_tls_client_task_with_options`], connecting to a list of redundant endpoints
+/// instead of a single address
+///
+/// * `endpoints` - Primary endpoint of the remote server followed by its backups
+/// * `max_queued_requests` - The maximum size of the request queue
+/// * `retry` - A boxed trait object that controls when the connection is retried on failure
+/// * `tls_config` - TLS configuration used to authenticate the server and the client
+/// * `options` - Optional settings of the channel
+/// * `decode` - Decode log level
+pub fn spawn_tls_client_task_with_endpoints(
+    endpoints: EndpointList,
+    max_queued_requests: usize,
+    retry: Box<dyn ReconnectStrategy + Send>,
+    tls_config: TlsClientConfig,
+    options: ClientOptions,
+    decode: DecodeLevel,
+) -> Channel {
+    Channel::new_tls(
+        endpoints,
+        max_queued_requests,
+        retry,
+        tls_config,
+        options,
+        decode,
+    )
+}
+
+/// Creates a TLS channel task, but does not spawn it. Most users will prefer
+/// [`spawn_tls_client_task`], unless they are using the library from outside the Tokio runtime
+/// and need to spawn it using a Runtime handle instead of the `tokio::spawn` function.
+///
+/// The channel uses the provided [`ReconnectStrategy`] to pause between failed connection
+/// attempts, including failed TLS handshakes
+///
+/// * `addr` - Socket address of the remote server, usually on port 802
+/// * `max_queued_requests` - The maximum size of the request queue
+/// * `retry` - A boxed trait object that controls when the connection is retried on failure
+/// * `tls_config` - TLS configuration used to authenticate the server and the client
+/// * `decode` - Decode log level
+pub fn create_tls_handle_and_task(
+    addr: SocketAddr,
+    max_queued_requests: usize,
+    retry: Box<dyn ReconnectStrategy + Send>,
+    tls_config: TlsClientConfig,
+    decode: DecodeLevel,
+) -> (Channel, impl std::future::Future<Output = ()>) {
+    create_tls_handle_and_task_with_options(
+        addr,
+        max_queued_requests,
+        retry,
+        tls_config,
+        ClientOptions::default(),
+        decode,
+    )
+}
+
+/// Same as [`create_tls_handle_and_task`], with [`ClientOptions`] such as the maximum number
+/// of requests sent without waiting for the responses to the previous ones
+///
+/// * `addr` - Socket address of the remote server, usually on port 802
+/// * `max_queued_requests` - The maximum size of the request queue
+/// * `retry` - A boxed trait object that controls when the connection is retried on failure
+/// * `tls_config` - TLS configuration used to authenticate the server and the client
+/// * `options` - Optional settings of the channel
+/// * `decode` - Decode log level
+pub fn create_tls_handle_and_task_with_options(
+    addr: SocketAddr,
+    max_queued_requests: usize,
+    retry: Box<dyn ReconnectStrategy + Send>,
+    tls_config: TlsClientConfig,
+    options: ClientOptions,
+    decode: DecodeLevel,
+) -> (Channel, impl std::future::Future<Output = ()>) {
+    create_tls_handle_and_task_with_endpoints(
+        EndpointList::from(addr),
+        max_queued_requests,
+        retry,
+        tls_config,
+        options,
+        decode,
+    )
+}
+
+/// Same as [`create_tls_handle_and_task_with_options`], connecting to a list of redundant
+/// endpoints instead of a single address
+///
+/// * `endpoints` - Primary endpoint of the remote server followed by its backups
+/// * `max_queued_requests` - The maximum size of the request queue
+/// * `retry` - A boxed trait object that controls when the connection is retried on failure
+/// * `tls_config` - TLS configuration used to authenticate the server and the client
+/// * `options` - Optional settings of the channel
+/// * `decode` - Decode log level
+pub fn create_tls_handle_and_task_with_endpoints(
+    endpoints: EndpointList,
+    max_queued_requests: usize,
+    retry: Box<dyn ReconnectStrategy + Send>,
+    tls_config: TlsClientConfig,
+    options: ClientOptions,
+    decode: DecodeLevel,
+) -> (Channel, impl std::future::Future<Output = ()>) {
+    Channel::create_tls_handle_and_task(
+        endpoints,
+        max_queued_requests,
+        retry,
+        tls_config,
+        options,
+        decode,
+    )
+}
+
+/// Spawns a channel task onto the runtime that maintains a TCP connection to a server that
+/// expects raw Modbus RTU frames (unit id, PDU, and CRC without an MBAP header), such as a
+/// serial-to-Ethernet converter. Requests are processed from an mpsc request queue. The task
+/// completes when the returned channel handle and all derived session handles are dropped.
+///
+/// The channel uses the provided [`ReconnectStrategy`] to pause between failed connection attempts
+///
+/// * `addr` - Socket address of the remote server
+/// * `max_queued_requests` - The maximum size of the request queue
+/// * `retry` - A boxed trait object that controls when the connection is retried on failure
+/// * `decode` - Decode log level
+pub fn spawn_rtu_over_tcp_client_task(
+    addr: SocketAddr,
+    max_queued_requests: usize,
+    retry: Box<dyn ReconnectStrategy + Send>,
+    decode: DecodeLevel,
+) -> Channel {
+    spawn_rtu_over_tcp_client_task_with_endpoints(
+        EndpointList::from(addr),
+        max_queued_requests,
+        retry,
+        decode,
+    )
+}
+
+/// Same as [`spawn_rtu_over_tcp_client_task`], connecting to a list of redundant endpoints
+/// instead of a single address
+///
+/// * `endpoints` - Primary endpoint of the remote server followed by its backups
+/// * `max_queued_requests` - The maximum size of the request queue
+/// * `retry` - A boxed trait object that controls when the connection is retried on failure
+/// * `decode` - Decode log level
+pub fn spawn_rtu_over_tcp_client_task_with_endpoints(
+    endpoints: EndpointList,
+    max_queued_requests: usize,
+    retry: Box<dyn ReconnectStrategy + Send>,
+    decode: DecodeLevel,
+) -> Channel {
+    Channel::new_rtu_over_tcp(endpoints, max_queued_requests, retry, decode)
+}
+
+/// Creates an RTU over TCP channel task, but does not spawn it. Most users will prefer
+/// [`spawn_rtu_over_tcp_client_task`], unless they are using the library from outside the Tokio
+/// runtime and need to spawn it using a Runtime handle instead of the `tokio::spawn` function.
+///
+/// The channel uses the provided [`ReconnectStrategy`] to pause between failed connection attempts
+///
+/// * `addr` - Socket address of the remote server
+/// * `max_queued_requests` - The maximum size of the request queue
+/// * `retry` - A boxed trait object that controls when the connection is retried on failure
+/// * `decode` - Decode log level
+pub fn create_rtu_over_tcp_handle_and_task(
+    addr: SocketAddr,
+    max_queued_requests: usize,
+    retry: Box<dyn ReconnectStrategy + Send>,
+    decode: DecodeLevel,
+) -> (Channel, impl std::future::Future<Output = ()>) {
+    create_rtu_over_tcp_handle_and_task_with_endpoints(
+        EndpointList::from(addr),
+        max_queued_requests,
+        retry,
+        decode,
+    )
+}
+
+/// Same as [`create_rtu_over_tcp_handle_and_task`], connecting to a list of redundant endpoints
+/// instead of a single address
+///
+/// * `endpoints` - Primary endpoint of the remote server followed by its backups
+/// * `max_queued_requests` - The maximum size of the request queue
+/// * `retry` - A boxed trait object that controls when the connection is retried on failure
+/// * `decode` - Decode log level
+pub fn create_rtu_over_tcp_handle_and_task_with_endpoints(
+    endpoints: EndpointList,
+    max_queued_requests: usize,
+    retry: Box<dyn ReconnectStrategy + Send>,
+    decode: DecodeLevel,
+) -> (Channel, impl std::future::Future<Output = ()>) {
+    Channel::create_rtu_over_tcp_handle_and_task(endpoints, max_queued_requests, retry, decode)
+}
+
+/// Spawns a channel task onto the runtime that communicates with servers using Modbus RTU
+/// over a serial port, processing requests from an mpsc request queue. The task completes
+/// when the returned channel handle and all derived session handles are dropped.
+///
+/// The channel uses the provided [`ReconnectStrategy`] to pause between failed attempts
+/// to open the serial port
+///
+/// * `path` - Path to the serial device, e.g. `/dev/ttyUSB0` or `COM1`
+/// * `serial_settings` - Serial port settings
+/// * `max_queued_requests` - The maximum size of the request queue
+/// * `retry` - A boxed trait object that controls when opening the port is retried on failure
+/// * `decode` - Decode log level
+pub fn spawn_rtu_client_task(
+    path: &str,
+    serial_settings: SerialSettings,
+    max_queued_requests: usize,
+    retry: Box<dyn ReconnectStrategy + Send>,
+    decode: DecodeLevel,
+) -> Channel {
+    Channel::new_rtu(path, serial_settings, max_queued_requests, retry, decode)
+}
+
+/// Creates a Modbus RTU channel task, but does not spawn it. Most users will prefer
+/// [`spawn_rtu_client_task`], unless they are using the library from outside the Tokio runtime
+/// and need to spawn it using a Runtime handle instead of the `tokio::spawn` function.
+///
+/// The channel uses the provided [`ReconnectStrategy`] to pause between failed attempts
+/// to open the serial port
+///
+/// * `path` - Path to the serial device, e.g. `/dev/ttyUSB0` or `COM1`
+/// * `serial_settings` - Serial port settings
+/// * `max_queued_requests` - The maximum size of the request queue
+/// * `retry` - A boxed trait object that controls when opening the port is retried on failure
+/// * `decode` - Decode log level
+pub fn create_rtu_handle_and_task(
+    path: &str,
+    serial_settings: SerialSettings,
+    max_queued_requests: usize,
+    retry: Box<dyn ReconnectStrategy + Send>,
+    decode: DecodeLevel,
+) -> (Channel, impl std::future::Future<Output = ()>) {
+    Channel::create_rtu_handle_and_task(path, serial_settings, max_queued_requests, retry, decode)
+}
+
+/// Spawns a channel task onto the runtime that communicates with servers using Modbus ASCII
+/// over a serial port, processing requests from an mpsc request queue. The task completes
+/// when the returned channel handle and all derived session handles are dropped.
+///
+/// The channel uses the provided [`ReconnectStrategy`] to pause between failed attempts
+/// to open the serial port
+///
+/// * `path` - Path to the serial device, e.g. `/dev/ttyUSB0` or `COM1`
+/// * `serial_settings` - Serial port settings
+/// * `max_queued_requests` - The maximum size of the request queue
+/// * `retry` - A boxed trait object that controls when opening the port is retried on failure
+/// * `decode` - Decode log level
+pub fn spawn_ascii_client_task(
+    path: &str,
+    serial_settings: SerialSettings,
+    max_queued_requests: usize,
+    retry: Box<dyn ReconnectStrategy + Send>,
+    decode: DecodeLevel,
+) -> Channel {
+    Channel::new_ascii(path, serial_settings, max_queued_requests, retry, decode)
+}
+
+/// Creates a Modbus ASCII channel task, but does not spawn it. Most users will prefer
+/// [`spawn_ascii_client_task`], unless they are using the library from outside the Tokio runtime
+/// and need to spawn it using a Runtime handle instead of the `tokio::spawn` function.
+///
+/// The channel uses the provided [`ReconnectStrategy`] to pause between failed attempts
+/// to open the serial port
+///
+/// * `path` - Path to the serial device, e.g. `/dev/ttyUSB0` or `COM1`
+/// * `serial_settings` - Serial port settings
+/// * `max_queued_requests` - The maximum size of the request queue
+/// * `retry` - A boxed trait object that controls when opening the port is retried on failure
+/// * `decode` - Decode log level
+pub fn create_ascii_handle_and_task(
+    path: &str,
+    serial_settings: SerialSettings,
+    max_queued_requests: usize,
+    retry: Box<dyn ReconnectStrategy + Send>,
+    decode: DecodeLevel,
+) -> (Channel, impl std::future::Future<Output = ()>) {
+    Channel::create_ascii_handle_and_task(path, serial_settings, max_queued_requests, retry, decode)
 }
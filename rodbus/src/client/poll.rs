@@ -0,0 +1,424 @@
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+
+use crate::client::channel::{Channel, RequestParam};
+use crate::error::RequestError;
+use crate::tokio;
+use crate::tokio::time::{Duration, Instant};
+use crate::types::{AddressRange, Indexed, Table, UnitId};
+
+/// Identifies a poll added to a [`Poller`]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct PollId {
+    value: usize,
+}
+
+/// Periodic read of a range of one of the tables of a device
+#[derive(Clone, Copy, Debug)]
+pub struct PollDefinition {
+    /// unit id and response timeout of the requests
+    pub param: RequestParam,
+    /// table that is read
+    pub table: Table,
+    /// range that is read, split into several requests if it is too large for one
+    pub range: AddressRange,
+    /// period between the reads
+    pub period: Duration,
+    /// polls with a higher priority are read first when several of them are due
+    pub priority: u8,
+}
+
+/// Values read by a poll
+#[derive(Clone, Debug, PartialEq)]
+pub enum PollValues {
+    /// values of coils or discrete inputs
+    Bits(Vec<Indexed<bool>>),
+    /// values of holding or input registers
+    Registers(Vec<Indexed<u16>>),
+}
+
+/// Change published by a [`Poller`]
+#[derive(Clone, Debug, PartialEq)]
+pub struct PollUpdate {
+    /// poll that produced the update
+    pub poll: PollId,
+    /// unit id of the device that was read
+    pub unit_id: UnitId,
+    /// table that was read
+    pub table: Table,
+    /// values that changed since the last update, or the error that made the read fail
+    pub result: Result<PollValues, RequestError>,
+}
+
+/// Reads a list of [`PollDefinition`] periodically on a [`Channel`] and publishes the values that change
+///
+/// The first successful read of a poll publishes all of its values, and subsequent reads only publish
+/// the values that are different from the previous read. A failed read is published when the poll
+/// starts to fail or the error changes. The next successful read after a failure publishes all of the
+/// values again.
+///
+/// Each read is delayed by a random amount between zero and the configured jitter so that polls with
+/// the same period don't always make their requests at the same time.
+#[derive(Debug)]
+pub struct Poller {
+    channel: Channel,
+    schedule: Schedule,
+    tx: tokio::sync::broadcast::Sender<PollUpdate>,
+}
+
+#[derive(Debug)]
+struct Schedule {
+    jitter: Duration,
+    rng: u64,
+    polls: Vec<Poll>,
+}
+
+#[derive(Debug)]
+struct Poll {
+    id: PollId,
+    definition: PollDefinition,
+    // time at which the poll is scheduled, without the jitter
+    next: Instant,
+    due: Instant,
+    last: Option<Result<PollValues, RequestError>>,
+}
+
+impl PollDefinition {
+    /// Create a poll definition with the lowest priority
+    pub fn new(param: RequestParam, table: Table, range: AddressRange, period: Duration) -> Self {
+        Self {
+            param,
+            table,
+            range,
+            period,
+            priority: 0,
+        }
+    }
+
+    /// Set the priority of the poll
+    pub fn with_priority(self, priority: u8) -> Self {
+        Self { priority, ..self }
+    }
+}
+
+impl Poller {
+    /// Create a poller without any polls
+    ///
+    /// * `channel` - Channel used to make the requests
+    /// * `jitter` - Maximum random delay added to each read
+    /// * `capacity` - Number of updates kept for subscribers that haven't received them yet
+    pub fn new(channel: Channel, jitter: Duration, capacity: usize) -> Self {
+        let (tx, _) = tokio::sync::broadcast::channel(capacity.max(1));
+        Self {
+            channel,
+            schedule: Schedule::new(jitter, RandomState::new().build_hasher().finish()),
+            tx,
+        }
+    }
+
+    /// Add a poll that is first read as soon as the poller runs
+    pub fn add(&mut self, definition: PollDefinition) -> PollId {
+        self.schedule.add(definition, Instant::now())
+    }
+
+    /// Subscribe to the updates published after this call
+    pub fn subscribe(&self) -> tokio::sync::broadcast::Receiver<PollUpdate> {
+        self.tx.subscribe()
+    }
+
+    /// Read the polls until the channel shuts down
+    pub async fn run(mut self) {
+        loop {
+            match self.schedule.next_due(Instant::now()) {
+                Some(index) => {
+                    let definition = self.schedule.polls[index].definition;
+                    let result = self.read(definition).await;
+                    let shutdown = result == Err(RequestError::Shutdown);
+                    if let Some(update) = self.schedule.complete(index, result, Instant::now()) {
+                        // there may not be any subscribers
+                        self.tx.send(update).ok();
+                    }
+                    if shutdown {
+                        return;
+                    }
+                }
+                None => match self.schedule.earliest_due() {
+                    Some(due) => tokio::time::sleep_until(due).await,
+                    None => return,
+                },
+            }
+        }
+    }
+
+    async fn read(&mut self, definition: PollDefinition) -> Result<PollValues, RequestError> {
+        let PollDefinition {
+            param,
+            table,
+            range,
+            ..
+        } = definition;
+        let values = match table {
+            Table::Coils => PollValues::Bits(self.channel.read_coils_chunked(param, range).await?),
+            Table::DiscreteInputs => PollValues::Bits(
+                self.channel
+                    .read_discrete_inputs_chunked(param, range)
+                    .await?,
+            ),
+            Table::HoldingRegisters => PollValues::Registers(
+                self.channel
+                    .read_holding_registers_chunked(param, range)
+                    .await?,
+            ),
+            Table::InputRegisters => PollValues::Registers(
+                self.channel
+                    .read_input_registers_chunked(param, range)
+                    .await?,
+            ),
+        };
+        Ok(values)
+    }
+}
+
+impl Schedule {
+    fn new(jitter: Duration, seed: u64) -> Self {
+        Self {
+            jitter,
+            // xorshift gets stuck at zero
+            rng: seed | 1,
+            polls: Vec::new(),
+        }
+    }
+
+    fn add(&mut self, definition: PollDefinition, now: Instant) -> PollId {
+        let id = PollId {
+            value: self.polls.len(),
+        };
+        let due = now + self.random_jitter();
+        self.polls.push(Poll {
+            id,
+            definition,
+            next: now,
+            due,
+            last: None,
+        });
+        id
+    }
+
+    // the due poll with the highest priority, and the earliest due time among those
+    fn next_due(&self, now: Instant) -> Option<usize> {
+        self.polls
+            .iter()
+            .enumerate()
+            .filter(|(_, x)| x.due <= now)
+            .max_by(|(_, a), (_, b)| {
+                a.definition
+                    .priority
+                    .cmp(&b.definition.priority)
+                    .then(b.due.cmp(&a.due))
+            })
+            .map(|(index, _)| index)
+    }
+
+    fn earliest_due(&self) -> Option<Instant> {
+        self.polls.iter().map(|x| x.due).min()
+    }
+
+    // reschedule the poll and return the update to publish, if any
+    fn complete(
+        &mut self,
+        index: usize,
+        result: Result<PollValues, RequestError>,
+        now: Instant,
+    ) -> Option<PollUpdate> {
+        let jitter = self.random_jitter();
+        let poll = &mut self.polls[index];
+
+        poll.next += poll.definition.period;
+        // skip the reads that were missed instead of making them all at once
+        if poll.next < now {
+            poll.next = now;
+        }
+        poll.due = poll.next + jitter;
+
+        let changes = match (&result, &poll.last) {
+            (Ok(values), Some(Ok(last))) => values.changes(last),
+            (Err(err), Some(Err(last))) if err == last => None,
+            (result, _) => Some(result.clone()),
+        };
+        poll.last = Some(result);
+
+        changes.map(|result| PollUpdate {
+            poll: poll.id,
+            unit_id: poll.definition.param.id,
+            table: poll.definition.table,
+            result,
+        })
+    }
+
+    // xorshift64 is more than random enough to spread out the requests
+    fn random_jitter(&mut self) -> Duration {
+        let max = self.jitter.as_nanos() as u64;
+        if max == 0 {
+            return Duration::from_secs(0);
+        }
+        self.rng ^= self.rng << 13;
+        self.rng ^= self.rng >> 7;
+        self.rng ^= self.rng << 17;
+        Duration::from_nanos(self.rng % (max + 1))
+    }
+}
+
+impl PollValues {
+    // values that are different from the last read, or None if nothing changed
+    fn changes(&self, last: &PollValues) -> Option<Result<PollValues, RequestError>> {
+        let values = match (self, last) {
+            (PollValues::Bits(values), PollValues::Bits(last)) => {
+                PollValues::Bits(changed_values(values, last))
+            }
+            (PollValues::Registers(values), PollValues::Registers(last)) => {
+                PollValues::Registers(changed_values(values, last))
+            }
+            _ => self.clone(),
+        };
+        if values.is_empty() {
+            None
+        } else {
+            Some(Ok(values))
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        match self {
+            PollValues::Bits(x) => x.is_empty(),
+            PollValues::Registers(x) => x.is_empty(),
+        }
+    }
+}
+
+fn changed_values<T>(values: &[Indexed<T>], last: &[Indexed<T>]) -> Vec<Indexed<T>>
+where
+    T: Copy + PartialEq,
+{
+    values
+        .iter()
+        .zip(last.iter())
+        .filter(|(x, y)| x.value != y.value)
+        .map(|(x, _)| *x)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn definition(period: u64, priority: u8) -> PollDefinition {
+        PollDefinition::new(
+            RequestParam::new(UnitId::new(1), Duration::from_secs(1)),
+            Table::HoldingRegisters,
+            AddressRange::try_from(0, 2).unwrap(),
+            Duration::from_secs(period),
+        )
+        .with_priority(priority)
+    }
+
+    fn registers(values: &[u16]) -> Result<PollValues, RequestError> {
+        Ok(PollValues::Registers(
+            values
+                .iter()
+                .enumerate()
+                .map(|(i, x)| Indexed::new(i as u16, *x))
+                .collect(),
+        ))
+    }
+
+    #[test]
+    fn reads_due_polls_by_priority() {
+        let now = Instant::now();
+        let mut schedule = Schedule::new(Duration::from_secs(0), 1);
+        schedule.add(definition(5, 0), now);
+        schedule.add(definition(5, 2), now);
+        schedule.add(definition(5, 1), now);
+
+        assert_eq!(schedule.next_due(now), Some(1));
+        schedule.complete(1, registers(&[0, 0]), now);
+        assert_eq!(schedule.next_due(now), Some(2));
+        schedule.complete(2, registers(&[0, 0]), now);
+        assert_eq!(schedule.next_due(now), Some(0));
+        schedule.complete(0, registers(&[0, 0]), now);
+        assert_eq!(schedule.next_due(now), None);
+        assert_eq!(schedule.earliest_due(), Some(now + Duration::from_secs(5)));
+    }
+
+    #[test]
+    fn skips_missed_reads() {
+        let now = Instant::now();
+        let mut schedule = Schedule::new(Duration::from_secs(0), 1);
+        schedule.add(definition(2, 0), now);
+        schedule.complete(0, registers(&[0, 0]), now + Duration::from_secs(7));
+        assert_eq!(schedule.earliest_due(), Some(now + Duration::from_secs(7)));
+        schedule.complete(0, registers(&[0, 0]), now + Duration::from_secs(7));
+        assert_eq!(schedule.earliest_due(), Some(now + Duration::from_secs(9)));
+    }
+
+    #[test]
+    fn jitter_delays_reads_by_at_most_the_maximum() {
+        let now = Instant::now();
+        let mut schedule = Schedule::new(Duration::from_millis(100), 0x1234_5678);
+        for _ in 0..20 {
+            schedule.add(definition(1, 0), now);
+        }
+        assert!(schedule
+            .polls
+            .iter()
+            .all(|x| x.due >= now && x.due <= now + Duration::from_millis(100)));
+        assert!(schedule.polls.iter().any(|x| x.due != now));
+    }
+
+    #[test]
+    fn publishes_only_changed_values() {
+        let now = Instant::now();
+        let mut schedule = Schedule::new(Duration::from_secs(0), 1);
+        schedule.add(definition(1, 0), now);
+
+        let update = schedule.complete(0, registers(&[1, 2]), now).unwrap();
+        assert_eq!(update.result, registers(&[1, 2]));
+        assert_eq!(update.table, Table::HoldingRegisters);
+        assert_eq!(update.unit_id, UnitId::new(1));
+
+        assert_eq!(schedule.complete(0, registers(&[1, 2]), now), None);
+        assert_eq!(
+            schedule
+                .complete(0, registers(&[1, 3]), now)
+                .unwrap()
+                .result,
+            Ok(PollValues::Registers(vec![Indexed::new(1, 3)]))
+        );
+    }
+
+    #[test]
+    fn publishes_errors_once_and_all_values_after_recovering() {
+        let now = Instant::now();
+        let mut schedule = Schedule::new(Duration::from_secs(0), 1);
+        schedule.add(definition(1, 0), now);
+        schedule.complete(0, registers(&[1, 2]), now);
+
+        assert_eq!(
+            schedule
+                .complete(0, Err(RequestError::ResponseTimeout), now)
+                .unwrap()
+                .result,
+            Err(RequestError::ResponseTimeout)
+        );
+        assert_eq!(
+            schedule.complete(0, Err(RequestError::ResponseTimeout), now),
+            None
+        );
+        assert_eq!(
+            schedule
+                .complete(0, registers(&[1, 2]), now)
+                .unwrap()
+                .result,
+            registers(&[1, 2])
+        );
+    }
+}
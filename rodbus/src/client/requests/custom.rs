@@ -0,0 +1,54 @@
+use crate::client::message::Promise;
+use crate::common::cursor::{ReadCursor, WriteCursor};
+use crate::common::function::FunctionCode;
+use crate::common::traits::{Parse, Serialize};
+use crate::custom::{CustomFunctionData, CustomFunctionDataDisplay, CustomFunctionRequest};
+use crate::decode::PduDecodeLevel;
+use crate::error::RequestError;
+
+pub(crate) struct CustomFunction {
+    pub(crate) request: CustomFunctionRequest,
+    promise: Promise<Vec<u8>>,
+}
+
+impl CustomFunction {
+    pub(crate) fn new(request: CustomFunctionRequest, promise: Promise<Vec<u8>>) -> Self {
+        Self { request, promise }
+    }
+
+    pub(crate) fn serialize(&self, cursor: &mut WriteCursor) -> Result<(), RequestError> {
+        self.request.data.serialize(cursor)
+    }
+
+    pub(crate) fn failure(self, err: RequestError) {
+        self.promise.failure(err)
+    }
+
+    pub(crate) fn handle_response(
+        self,
+        mut cursor: ReadCursor,
+        function: FunctionCode,
+        decode: PduDecodeLevel,
+    ) {
+        let result = CustomFunctionData::parse(&mut cursor);
+
+        match &result {
+            Ok(response) => {
+                if decode.data_headers() {
+                    tracing::info!(
+                        "PDU RX - {} {}",
+                        function,
+                        CustomFunctionDataDisplay::new(decode, &response.data)
+                    );
+                } else if decode.header() {
+                    tracing::info!("PDU RX - {}", function);
+                }
+            }
+            Err(err) => {
+                tracing::warn!("{}", err);
+            }
+        }
+
+        self.promise.complete(result.map(|x| x.data))
+    }
+}
@@ -0,0 +1,169 @@
+use crate::client::message::Promise;
+use crate::common::cursor::{ReadCursor, WriteCursor};
+use crate::common::function::FunctionCode;
+use crate::common::traits::{Loggable, LoggableDisplay, Parse, Serialize};
+use crate::decode::PduDecodeLevel;
+use crate::diagnostics::{Diagnostic, DiagnosticSubFunction};
+use crate::error::{AduParseError, RequestError};
+
+// request without any data, e.g. `get comm event counter`, whose response
+// is parsed as `T` and converted to the value returned to the user
+pub(crate) struct ReadStatus<T, U> {
+    promise: Promise<U>,
+    convert: fn(T) -> U,
+}
+
+pub(crate) struct Diagnostics {
+    pub(crate) request: Diagnostic,
+    promise: Promise<u16>,
+}
+
+impl<T, U> ReadStatus<T, U>
+where
+    T: Parse + Loggable,
+{
+    pub(crate) fn new(promise: Promise<U>, convert: fn(T) -> U) -> Self {
+        Self { promise, convert }
+    }
+
+    pub(crate) fn serialize(&self, _cursor: &mut WriteCursor) -> Result<(), RequestError> {
+        Ok(())
+    }
+
+    pub(crate) fn failure(self, err: RequestError) {
+        self.promise.failure(err)
+    }
+
+    pub(crate) fn handle_response(
+        self,
+        mut cursor: ReadCursor,
+        function: FunctionCode,
+        decode: PduDecodeLevel,
+    ) {
+        let result = T::parse(&mut cursor);
+
+        match &result {
+            Ok(response) => {
+                if decode.data_headers() {
+                    tracing::info!(
+                        "PDU RX - {} {}",
+                        function,
+                        LoggableDisplay::new(response, &[], decode)
+                    );
+                } else if decode.header() {
+                    tracing::info!("PDU RX - {}", function);
+                }
+            }
+            Err(err) => {
+                tracing::warn!("{}", err);
+            }
+        }
+
+        self.promise.complete(result.map(self.convert))
+    }
+}
+
+impl Diagnostics {
+    pub(crate) fn new(request: Diagnostic, promise: Promise<u16>) -> Self {
+        Self { request, promise }
+    }
+
+    pub(crate) fn serialize(&self, cursor: &mut WriteCursor) -> Result<(), RequestError> {
+        self.request.serialize(cursor)
+    }
+
+    pub(crate) fn failure(self, err: RequestError) {
+        self.promise.failure(err)
+    }
+
+    pub(crate) fn handle_response(
+        self,
+        mut cursor: ReadCursor,
+        function: FunctionCode,
+        decode: PduDecodeLevel,
+    ) {
+        let result = self.parse_all(&mut cursor);
+
+        match &result {
+            Ok(response) => {
+                if decode.data_headers() {
+                    tracing::info!("PDU RX - {} {}", function, response);
+                } else if decode.header() {
+                    tracing::info!("PDU RX - {}", function);
+                }
+            }
+            Err(err) => {
+                tracing::warn!("{}", err);
+            }
+        }
+
+        self.promise.complete(result.map(|x| x.data))
+    }
+
+    fn parse_all(&self, cursor: &mut ReadCursor) -> Result<Diagnostic, RequestError> {
+        let response = Diagnostic::parse(cursor)?;
+        if response.sub_function != self.request.sub_function {
+            return Err(AduParseError::DiagnosticSubFunctionMismatch(
+                response.sub_function,
+                self.request.sub_function,
+            )
+            .into());
+        }
+
+        // these sub-functions echo the data of the request
+        let echo = [
+            DiagnosticSubFunction::ReturnQueryData.get_value(),
+            DiagnosticSubFunction::RestartCommunications.get_value(),
+        ];
+        if echo.contains(&response.sub_function) && response.data != self.request.data {
+            return Err(AduParseError::ReplyEchoMismatch.into());
+        }
+
+        Ok(response)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tokio;
+
+    fn parse(request: Diagnostic, response: &[u8]) -> Result<Diagnostic, RequestError> {
+        let (tx, _rx) = tokio::sync::oneshot::channel();
+        let request = Diagnostics::new(request, Promise::Channel(tx));
+        request.parse_all(&mut ReadCursor::new(response))
+    }
+
+    #[test]
+    fn accepts_counter_response() {
+        let request = Diagnostic::new(
+            DiagnosticSubFunction::ReturnBusMessageCount.get_value(),
+            0x0000,
+        );
+        assert_eq!(
+            parse(request, &[0x00, 0x0B, 0x01, 0x2C]),
+            Ok(Diagnostic::new(0x0B, 0x012C))
+        );
+    }
+
+    #[test]
+    fn rejects_different_sub_function() {
+        let request = Diagnostic::new(
+            DiagnosticSubFunction::ReturnBusMessageCount.get_value(),
+            0x0000,
+        );
+        assert_eq!(
+            parse(request, &[0x00, 0x0C, 0x00, 0x00]),
+            Err(AduParseError::DiagnosticSubFunctionMismatch(0x0C, 0x0B).into())
+        );
+    }
+
+    #[test]
+    fn rejects_query_data_that_is_not_echoed() {
+        let request = Diagnostic::new(DiagnosticSubFunction::ReturnQueryData.get_value(), 0xA537);
+        assert_eq!(
+            parse(request, &[0x00, 0x00, 0xA5, 0x38]),
+            Err(AduParseError::ReplyEchoMismatch.into())
+        );
+    }
+}
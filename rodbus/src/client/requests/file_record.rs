@@ -0,0 +1,116 @@
+use crate::client::message::Promise;
+use crate::common::cursor::{ReadCursor, WriteCursor};
+use crate::common::function::FunctionCode;
+use crate::common::traits::Serialize;
+use crate::decode::PduDecodeLevel;
+use crate::error::{AduParseError, RequestError};
+use crate::file_record::{
+    FileRecordData, FileRecordDataDisplay, ReadFileRecordRequest, WriteFileRecordRequest,
+    WriteFileRecordSubRequests,
+};
+
+pub(crate) struct ReadFileRecord {
+    pub(crate) request: ReadFileRecordRequest,
+    promise: Promise<Vec<FileRecordData>>,
+}
+
+pub(crate) struct WriteFileRecord {
+    pub(crate) request: WriteFileRecordRequest,
+    promise: Promise<()>,
+}
+
+impl ReadFileRecord {
+    pub(crate) fn new(
+        request: ReadFileRecordRequest,
+        promise: Promise<Vec<FileRecordData>>,
+    ) -> Self {
+        Self { request, promise }
+    }
+
+    pub(crate) fn serialize(&self, cursor: &mut WriteCursor) -> Result<(), RequestError> {
+        self.request.serialize(cursor)
+    }
+
+    pub(crate) fn failure(self, err: RequestError) {
+        self.promise.failure(err)
+    }
+
+    pub(crate) fn handle_response(
+        self,
+        mut cursor: ReadCursor,
+        function: FunctionCode,
+        decode: PduDecodeLevel,
+    ) {
+        let result = self.request.parse_response(&mut cursor);
+
+        match &result {
+            Ok(records) => {
+                if decode.data_headers() {
+                    tracing::info!(
+                        "PDU RX - {} {}",
+                        function,
+                        FileRecordDataDisplay::new(decode, records)
+                    );
+                } else if decode.header() {
+                    tracing::info!("PDU RX - {}", function);
+                }
+            }
+            Err(err) => {
+                tracing::warn!("{}", err);
+            }
+        }
+
+        self.promise.complete(result)
+    }
+}
+
+impl WriteFileRecord {
+    pub(crate) fn new(request: WriteFileRecordRequest, promise: Promise<()>) -> Self {
+        Self { request, promise }
+    }
+
+    pub(crate) fn serialize(&self, cursor: &mut WriteCursor) -> Result<(), RequestError> {
+        self.request.serialize(cursor)
+    }
+
+    pub(crate) fn failure(self, err: RequestError) {
+        self.promise.failure(err)
+    }
+
+    pub(crate) fn handle_response(
+        self,
+        mut cursor: ReadCursor,
+        function: FunctionCode,
+        decode: PduDecodeLevel,
+    ) {
+        let result = self.parse_all(&mut cursor);
+
+        match &result {
+            Ok(()) => {
+                if decode.data_headers() {
+                    tracing::info!(
+                        "PDU RX - {} {}",
+                        function,
+                        FileRecordDataDisplay::new(decode, &self.request.records)
+                    );
+                } else if decode.header() {
+                    tracing::info!("PDU RX - {}", function);
+                }
+            }
+            Err(err) => {
+                tracing::warn!("{}", err);
+            }
+        }
+
+        self.promise.complete(result)
+    }
+
+    // the response is an echo of the request
+    fn parse_all(&self, cursor: &mut ReadCursor) -> Result<(), RequestError> {
+        let response = WriteFileRecordSubRequests::parse(cursor)?;
+        if response.request != self.request {
+            return Err(AduParseError::ReplyEchoMismatch.into());
+        }
+        Ok(())
+    }
+}
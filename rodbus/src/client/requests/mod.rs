@@ -1,4 +1,10 @@
+pub(crate) mod custom;
+pub(crate) mod diagnostics;
+pub(crate) mod file_record;
 pub(crate) mod read_bits;
+pub(crate) mod read_device_id;
+pub(crate) mod read_fifo;
 pub(crate) mod read_registers;
+pub(crate) mod read_write_multiple;
 pub(crate) mod write_multiple;
 pub(crate) mod write_single;
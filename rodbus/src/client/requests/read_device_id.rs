@@ -0,0 +1,154 @@
+use crate::client::message::Promise;
+use crate::common::cursor::{ReadCursor, WriteCursor};
+use crate::common::function::FunctionCode;
+use crate::common::traits::Serialize;
+use crate::decode::PduDecodeLevel;
+use crate::device_id::{
+    DeviceIdPage, DeviceIdResponse, DeviceIdResponseDisplay, ReadDeviceIdCode, ReadDeviceIdRequest,
+};
+use crate::error::{AduParseError, RequestError};
+
+pub(crate) struct ReadDeviceId {
+    pub(crate) request: ReadDeviceIdRequest,
+    promise: Promise<DeviceIdPage>,
+}
+
+impl ReadDeviceId {
+    pub(crate) fn new(request: ReadDeviceIdRequest, promise: Promise<DeviceIdPage>) -> Self {
+        Self { request, promise }
+    }
+
+    pub(crate) fn serialize(&self, cursor: &mut WriteCursor) -> Result<(), RequestError> {
+        self.request.serialize(cursor)
+    }
+
+    pub(crate) fn failure(self, err: RequestError) {
+        self.promise.failure(err)
+    }
+
+    pub(crate) fn handle_response(
+        self,
+        mut cursor: ReadCursor,
+        function: FunctionCode,
+        decode: PduDecodeLevel,
+    ) {
+        let result = self.parse_all(&mut cursor, function, decode);
+
+        if let Err(err) = &result {
+            tracing::warn!("{}", err);
+        }
+
+        self.promise.complete(result)
+    }
+
+    fn parse_all(
+        &self,
+        cursor: &mut ReadCursor,
+        function: FunctionCode,
+        decode: PduDecodeLevel,
+    ) -> Result<DeviceIdPage, RequestError> {
+        let mut response = DeviceIdResponse::parse(cursor)?;
+        if response.code != self.request.code {
+            return Err(AduParseError::ReplyEchoMismatch.into());
+        }
+
+        if decode.data_headers() {
+            tracing::info!(
+                "PDU RX - {} {}",
+                function,
+                DeviceIdResponseDisplay::new(decode, &response)
+            );
+        } else if decode.header() {
+            tracing::info!("PDU RX - {}", function);
+        }
+
+        if self.request.code == ReadDeviceIdCode::Individual {
+            // individual access always returns a single object
+            response.next_object_id = None;
+        }
+
+        // the next object must follow both the requested object and the objects in this response,
+        // otherwise a server could make the client request the same objects forever
+        if let Some(next) = response.next_object_id {
+            match response.objects.last() {
+                Some((last, _)) if next > *last && next > self.request.object_id => {}
+                _ => return Err(AduParseError::DeviceIdNoProgress(next).into()),
+            }
+        }
+
+        Ok(response.into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tokio;
+
+    fn parse(code: ReadDeviceIdCode, bytes: &[u8]) -> Result<DeviceIdPage, RequestError> {
+        parse_from(code, 0, bytes)
+    }
+
+    fn parse_from(
+        code: ReadDeviceIdCode,
+        object_id: u8,
+        bytes: &[u8],
+    ) -> Result<DeviceIdPage, RequestError> {
+        let (tx, _rx) = tokio::sync::oneshot::channel();
+        let request = ReadDeviceId::new(
+            ReadDeviceIdRequest::new(code, object_id),
+            Promise::Channel(tx),
+        );
+        request.parse_all(
+            &mut ReadCursor::new(bytes),
+            FunctionCode::ReadDeviceIdentification,
+            PduDecodeLevel::Nothing,
+        )
+    }
+
+    #[test]
+    fn serializes_request() {
+        let mut buffer = [0u8; 3];
+        let mut cursor = WriteCursor::new(&mut buffer);
+        ReadDeviceIdRequest::new(ReadDeviceIdCode::Extended, 0x80)
+            .serialize(&mut cursor)
+            .unwrap();
+        assert_eq!(buffer, [0x0E, 0x03, 0x80]);
+    }
+
+    #[test]
+    fn rejects_response_with_different_code() {
+        let bytes = [0x0E, 0x02, 0x81, 0x00, 0x00, 0x00];
+        assert_eq!(
+            parse(ReadDeviceIdCode::Basic, &bytes).err(),
+            Some(AduParseError::ReplyEchoMismatch.into())
+        );
+    }
+
+    #[test]
+    fn rejects_continuation_that_does_not_advance() {
+        let bytes = [0x0E, 0x01, 0x81, 0xFF, 0x01, 0x01, 0x01, 0x01, b'A'];
+        assert_eq!(
+            parse(ReadDeviceIdCode::Basic, &bytes).err(),
+            Some(AduParseError::DeviceIdNoProgress(0x01).into())
+        );
+    }
+
+    #[test]
+    fn rejects_continuation_that_points_back_before_the_requested_object() {
+        // the objects were requested from 0x02, but the server restarts from 0x00 and continues at 0x01
+        let bytes = [0x0E, 0x01, 0x81, 0xFF, 0x01, 0x01, 0x00, 0x01, b'A'];
+        assert_eq!(
+            parse_from(ReadDeviceIdCode::Basic, 0x02, &bytes).err(),
+            Some(AduParseError::DeviceIdNoProgress(0x01).into())
+        );
+    }
+
+    #[test]
+    fn returns_next_object_of_continuation() {
+        let bytes = [0x0E, 0x01, 0x81, 0xFF, 0x01, 0x01, 0x00, 0x01, b'A'];
+        let page = parse(ReadDeviceIdCode::Basic, &bytes).unwrap();
+        assert_eq!(page.next_object_id, Some(0x01));
+        assert_eq!(page.objects, vec![(0x00, vec![b'A'])]);
+    }
+}
@@ -0,0 +1,58 @@
+use crate::client::message::Promise;
+use crate::common::cursor::{ReadCursor, WriteCursor};
+use crate::common::function::FunctionCode;
+use crate::common::traits::Parse;
+use crate::decode::PduDecodeLevel;
+use crate::error::RequestError;
+use crate::types::{FifoQueue, FifoQueueDisplay};
+
+pub(crate) struct ReadFifoQueue {
+    pub(crate) pointer_address: u16,
+    promise: Promise<Vec<u16>>,
+}
+
+impl ReadFifoQueue {
+    pub(crate) fn new(pointer_address: u16, promise: Promise<Vec<u16>>) -> Self {
+        Self {
+            pointer_address,
+            promise,
+        }
+    }
+
+    pub(crate) fn serialize(&self, cursor: &mut WriteCursor) -> Result<(), RequestError> {
+        cursor.write_u16_be(self.pointer_address)?;
+        Ok(())
+    }
+
+    pub(crate) fn failure(self, err: RequestError) {
+        self.promise.failure(err)
+    }
+
+    pub(crate) fn handle_response(
+        self,
+        mut cursor: ReadCursor,
+        function: FunctionCode,
+        decode: PduDecodeLevel,
+    ) {
+        let result = FifoQueue::parse(&mut cursor);
+
+        match &result {
+            Ok(queue) => {
+                if decode.data_headers() {
+                    tracing::info!(
+                        "PDU RX - {} {}",
+                        function,
+                        FifoQueueDisplay::new(decode, queue)
+                    );
+                } else if decode.header() {
+                    tracing::info!("PDU RX - {}", function);
+                }
+            }
+            Err(err) => {
+                tracing::warn!("{}", err);
+            }
+        }
+
+        self.promise.complete(result.map(|queue| queue.values))
+    }
+}
@@ -0,0 +1,138 @@
+use crate::client::requests::read_registers::Promise;
+use crate::client::requests::write_multiple::WriteMultiple;
+use crate::common::cursor::{ReadCursor, WriteCursor};
+use crate::common::function::FunctionCode;
+use crate::common::traits::Serialize;
+use crate::decode::PduDecodeLevel;
+use crate::error::{InvalidRequest, RequestError};
+use crate::types::{AddressRange, ReadRegistersRange, RegisterIterator, RegisterIteratorDisplay};
+
+/// Registers to write and the range of holding registers to read back in a single request
+///
+/// Used when making `read/write multiple registers` requests. The server performs the
+/// write before the read.
+#[derive(Debug, Clone)]
+pub struct ReadWriteMultiple {
+    /// range of holding registers to read
+    pub(crate) read_range: ReadRegistersRange,
+    /// registers to write
+    pub(crate) write: WriteMultiple<u16>,
+}
+
+impl ReadWriteMultiple {
+    /// Create a new request from the range to read, and the starting address and values to write
+    pub fn new(
+        read_range: AddressRange,
+        write_start: u16,
+        values: Vec<u16>,
+    ) -> Result<Self, InvalidRequest> {
+        let read_range = read_range.of_read_registers()?;
+        let write = WriteMultiple::from(write_start, values)?;
+        write
+            .range
+            .limited_count(crate::constants::limits::MAX_READ_WRITE_WRITE_COUNT)?;
+        Ok(Self { read_range, write })
+    }
+}
+
+impl Serialize for ReadWriteMultiple {
+    fn serialize(&self, cursor: &mut WriteCursor) -> Result<(), RequestError> {
+        self.read_range.get().serialize(cursor)?;
+        self.write.serialize(cursor)
+    }
+}
+
+pub(crate) struct ReadWriteMultipleRegisters {
+    pub(crate) request: ReadWriteMultiple,
+    promise: Promise,
+}
+
+impl ReadWriteMultipleRegisters {
+    pub(crate) fn new(request: ReadWriteMultiple, promise: Promise) -> Self {
+        Self { request, promise }
+    }
+
+    pub(crate) fn serialize(&self, cursor: &mut WriteCursor) -> Result<(), RequestError> {
+        self.request.serialize(cursor)
+    }
+
+    pub(crate) fn failure(self, err: RequestError) {
+        self.promise.failure(err)
+    }
+
+    pub(crate) fn handle_response(
+        self,
+        mut cursor: ReadCursor,
+        function: FunctionCode,
+        decode: PduDecodeLevel,
+    ) {
+        let result = Self::parse_registers_response(self.request.read_range.get(), &mut cursor);
+
+        match &result {
+            Ok(response) => {
+                if decode.enabled() {
+                    tracing::info!(
+                        "PDU RX - {} {}",
+                        function,
+                        RegisterIteratorDisplay::new(decode, response)
+                    );
+                }
+            }
+            Err(err) => {
+                tracing::warn!("{}", err);
+            }
+        }
+
+        self.promise.complete(result)
+    }
+
+    fn parse_registers_response<'a>(
+        range: AddressRange,
+        cursor: &'a mut ReadCursor,
+    ) -> Result<RegisterIterator<'a>, RequestError> {
+        // the byte count is validated because all of the bytes must be consumed
+        cursor.read_u8()?;
+        RegisterIterator::parse_all(range, cursor)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::InvalidRange;
+
+    #[test]
+    fn serializes_request() {
+        let request =
+            ReadWriteMultiple::new(AddressRange::try_from(3, 6).unwrap(), 14, vec![0x00FF; 3])
+                .unwrap();
+        let mut buffer = [0u8; 15];
+        let mut cursor = WriteCursor::new(&mut buffer);
+        request.serialize(&mut cursor).unwrap();
+        assert_eq!(
+            buffer,
+            [
+                0x00, 0x03, 0x00, 0x06, 0x00, 0x0E, 0x00, 0x03, 0x06, 0x00, 0xFF, 0x00, 0xFF, 0x00,
+                0xFF
+            ]
+        );
+    }
+
+    #[test]
+    fn rejects_read_count_above_limit() {
+        assert_eq!(
+            ReadWriteMultiple::new(AddressRange::try_from(0, 126).unwrap(), 0, vec![0])
+                .unwrap_err(),
+            InvalidRequest::BadRange(InvalidRange::CountTooLargeForType(126, 125))
+        );
+    }
+
+    #[test]
+    fn rejects_write_count_above_limit() {
+        assert_eq!(
+            ReadWriteMultiple::new(AddressRange::try_from(0, 1).unwrap(), 0, vec![0; 122])
+                .unwrap_err(),
+            InvalidRequest::BadRange(InvalidRange::CountTooLargeForType(122, 121))
+        );
+    }
+}
@@ -37,6 +37,21 @@ impl<T> WriteMultiple<T> {
         Ok(Self { range, values })
     }
 
+    /// split into consecutive writes that each contain at most `max` values
+    pub(crate) fn chunks(&self, max: u16) -> Vec<WriteMultiple<T>>
+    where
+        T: Clone,
+    {
+        self.range
+            .chunks(max)
+            .zip(self.values.chunks(max as usize))
+            .map(|(range, values)| Self {
+                range,
+                values: values.to_vec(),
+            })
+            .collect()
+    }
+
     pub(crate) fn iter(&self) -> WriteMultipleIterator<'_, T> {
         WriteMultipleIterator::new(self.range, self.values.iter())
     }
@@ -102,6 +117,11 @@ where
         self.promise.failure(err)
     }
 
+    // broadcasts aren't answered, so the range is returned as if the server had echoed it
+    pub(crate) fn complete_broadcast(self) {
+        self.promise.complete(Ok(self.request.range))
+    }
+
     pub(crate) fn handle_response(
         self,
         cursor: ReadCursor,
@@ -3,10 +3,11 @@ use std::fmt::Display;
 use crate::client::message::Promise;
 use crate::common::cursor::{ReadCursor, WriteCursor};
 use crate::common::function::FunctionCode;
+use crate::common::traits::{Parse, Serialize};
 use crate::decode::PduDecodeLevel;
 use crate::error::AduParseError;
 use crate::error::RequestError;
-use crate::types::{coil_from_u16, coil_to_u16, Indexed};
+use crate::types::{coil_from_u16, coil_to_u16, Indexed, MaskWriteRegister};
 
 pub(crate) trait SingleWriteOperation: Sized + PartialEq {
     fn serialize(&self, cursor: &mut WriteCursor) -> Result<(), RequestError>;
@@ -37,6 +38,11 @@ where
         self.promise.failure(err)
     }
 
+    // broadcasts aren't answered, so the request is returned as if the server had echoed it
+    pub(crate) fn complete_broadcast(self) {
+        self.promise.complete(Ok(self.request))
+    }
+
     pub(crate) fn handle_response(
         self,
         cursor: ReadCursor,
@@ -98,3 +104,13 @@ impl SingleWriteOperation for Indexed<u16> {
         Ok(Indexed::new(cursor.read_u16_be()?, cursor.read_u16_be()?))
     }
 }
+
+impl SingleWriteOperation for MaskWriteRegister {
+    fn serialize(&self, cursor: &mut WriteCursor) -> Result<(), RequestError> {
+        Serialize::serialize(self, cursor)
+    }
+
+    fn parse(cursor: &mut ReadCursor) -> Result<Self, RequestError> {
+        Parse::parse(cursor)
+    }
+}
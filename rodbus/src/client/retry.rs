@@ -0,0 +1,135 @@
+use std::time::Duration;
+
+use crate::error::RequestError;
+use crate::exception::ExceptionCode;
+
+/// Errors after which a request may be sent again
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RetryableErrors {
+    /// No response was received before the response timeout
+    pub response_timeout: bool,
+    /// An I/O error occurred while sending the request or waiting for the response
+    pub io: bool,
+    /// The server returned a [`ExceptionCode::ServerDeviceBusy`] exception
+    pub server_device_busy: bool,
+    /// The server returned a [`ExceptionCode::Acknowledge`] exception
+    pub acknowledge: bool,
+}
+
+/// Controls how many times, and after which errors, the task of a channel sends a request
+/// again before completing it with the error
+///
+/// The delay before the first retry is `min_delay`, and it doubles before each of the
+/// following retries up to `max_delay`. Each attempt waits for the full response timeout.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RetryPolicy {
+    /// Maximum number of times the request is sent again after the first attempt
+    pub max_retries: u8,
+    /// Delay before the first retry
+    pub min_delay: Duration,
+    /// Maximum delay before a retry
+    pub max_delay: Duration,
+    /// Errors after which the request is retried
+    pub retry_on: RetryableErrors,
+}
+
+impl RetryableErrors {
+    /// Every error that may be transient
+    pub fn all() -> Self {
+        Self {
+            response_timeout: true,
+            io: true,
+            server_device_busy: true,
+            acknowledge: true,
+        }
+    }
+
+    pub(crate) fn contains(&self, err: &RequestError) -> bool {
+        match err {
+            RequestError::ResponseTimeout => self.response_timeout,
+            RequestError::Io(_) => self.io,
+            RequestError::Exception(ExceptionCode::ServerDeviceBusy) => self.server_device_busy,
+            RequestError::Exception(ExceptionCode::Acknowledge) => self.acknowledge,
+            _ => false,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Retry a request up to `max_retries` times after any of the [`RetryableErrors::all`]
+    pub fn new(max_retries: u8, min_delay: Duration, max_delay: Duration) -> Self {
+        Self {
+            max_retries,
+            min_delay,
+            max_delay,
+            retry_on: RetryableErrors::all(),
+        }
+    }
+
+    /// Never retry requests, which is the policy of a channel when it is created
+    pub fn none() -> Self {
+        Self::new(0, Duration::from_secs(0), Duration::from_secs(0))
+    }
+
+    /// Delay before sending the request again, or `None` if a request that has already been
+    /// retried `retries` times must be completed with the error
+    pub(crate) fn next_delay(&self, err: &RequestError, retries: u8) -> Option<Duration> {
+        if retries >= self.max_retries || !self.retry_on.contains(err) {
+            return None;
+        }
+
+        let delay = self
+            .min_delay
+            .checked_mul(1 << retries.min(31))
+            .unwrap_or(self.max_delay);
+        Some(delay.min(self.max_delay))
+    }
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self::none()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn delay_doubles_up_to_the_maximum() {
+        let policy = RetryPolicy::new(4, Duration::from_millis(100), Duration::from_millis(300));
+        let err = RequestError::ResponseTimeout;
+        assert_eq!(policy.next_delay(&err, 0), Some(Duration::from_millis(100)));
+        assert_eq!(policy.next_delay(&err, 1), Some(Duration::from_millis(200)));
+        assert_eq!(policy.next_delay(&err, 2), Some(Duration::from_millis(300)));
+        assert_eq!(policy.next_delay(&err, 3), Some(Duration::from_millis(300)));
+        assert_eq!(policy.next_delay(&err, 4), None);
+    }
+
+    #[test]
+    fn only_transient_errors_are_retried() {
+        let mut policy =
+            RetryPolicy::new(1, Duration::from_millis(100), Duration::from_millis(100));
+        policy.retry_on.acknowledge = false;
+        assert!(policy
+            .next_delay(&RequestError::Exception(ExceptionCode::ServerDeviceBusy), 0)
+            .is_some());
+        assert!(policy
+            .next_delay(&RequestError::Io(std::io::ErrorKind::BrokenPipe), 0)
+            .is_some());
+        assert!(policy
+            .next_delay(&RequestError::Exception(ExceptionCode::Acknowledge), 0)
+            .is_none());
+        assert!(policy
+            .next_delay(
+                &RequestError::Exception(ExceptionCode::IllegalDataAddress),
+                0
+            )
+            .is_none());
+        assert!(policy.next_delay(&RequestError::NoConnection, 0).is_none());
+        assert!(RetryPolicy::none()
+            .next_delay(&RequestError::ResponseTimeout, 0)
+            .is_none());
+    }
+}
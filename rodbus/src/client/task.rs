@@ -7,8 +7,11 @@ use crate::decode::PduDecodeLevel;
 use crate::tokio;
 use crate::tokio::time::Instant;
 
-use crate::client::message::Request;
-use crate::common::frame::{FrameFormatter, FrameHeader, FrameParser, FramedReader, TxId};
+use crate::client::channel::ReconnectStrategy;
+use crate::client::endpoint::EndpointList;
+use crate::client::message::{Request, Setting};
+use crate::client::retry::RetryPolicy;
+use crate::common::frame::{Frame, FrameFormatter, FrameHeader, FrameParser, FramedReader, TxId};
 use crate::error::*;
 
 /**
@@ -22,6 +25,12 @@ pub(crate) enum SessionError {
     BadFrame,
     // the mpsc is closed (dropped)  on the sender side
     Shutdown,
+    // the channel was disabled
+    Disabled,
+    // the channel must connect to a different server
+    EndpointChanged(EndpointList),
+    // the session reached the end of the time it was allowed to run
+    Expired,
 }
 
 impl SessionError {
@@ -41,10 +50,50 @@ where
     P: FrameParser,
 {
     rx: tokio::sync::mpsc::Receiver<Request>,
+    settings: tokio::sync::mpsc::Receiver<Setting>,
     formatter: F,
     reader: FramedReader<P>,
     tx_id: TxId,
     decode: PduDecodeLevel,
+    // maximum number of requests that may be awaiting a response at the same time
+    max_in_flight: usize,
+    // requests that have been sent and are awaiting a response, oldest first
+    in_flight: Vec<InFlight>,
+    // requests that failed with a transient error and are waiting to be sent again
+    retries: Vec<Retry>,
+    // settings that may be changed through the channel
+    enabled: bool,
+    retry: Box<dyn ReconnectStrategy + Send>,
+    retry_policy: RetryPolicy,
+    // writes to the broadcast unit id are only supported on links without transaction ids
+    turnaround_delay: Option<Duration>,
+    // no request is sent before this instant after a broadcast write
+    turnaround: Option<Instant>,
+}
+
+struct Retry {
+    due: Instant,
+    request: Request,
+    // error that caused the request to be retried
+    error: RequestError,
+}
+
+struct InFlight {
+    tx_id: TxId,
+    deadline: Instant,
+    request: Request,
+    span: tracing::Span,
+}
+
+// the first thing that happens while running the session
+enum Event {
+    Request(Option<Request>),
+    Setting(Setting),
+    Frame(Result<Frame, RequestError>),
+    Timeout,
+    Retry,
+    Turnaround,
+    Expired,
 }
 
 impl<F, P> ClientLoop<F, P>
@@ -54,50 +103,239 @@ where
 {
     pub(crate) fn new(
         rx: tokio::sync::mpsc::Receiver<Request>,
+        settings: tokio::sync::mpsc::Receiver<Setting>,
         formatter: F,
         parser: P,
         decode: PduDecodeLevel,
+        max_in_flight: usize,
+        retry: Box<dyn ReconnectStrategy + Send>,
     ) -> Self {
         Self {
             rx,
+            settings,
             formatter,
             reader: FramedReader::new(parser),
             tx_id: TxId::default(),
             decode,
+            max_in_flight: max_in_flight.max(1),
+            in_flight: Vec::new(),
+            retries: Vec::new(),
+            enabled: true,
+            retry,
+            retry_policy: RetryPolicy::none(),
+            turnaround_delay: None,
+            turnaround: None,
         }
     }
 
+    // send writes to the broadcast unit id without waiting for a response
+    pub(crate) fn with_broadcast(mut self, turnaround_delay: Duration) -> Self {
+        self.turnaround_delay = Some(turnaround_delay);
+        self
+    }
+
+    pub(crate) fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    pub(crate) fn next_delay(&mut self) -> Duration {
+        self.retry.next_delay()
+    }
+
+    pub(crate) fn reset_delay(&mut self) {
+        self.retry.reset()
+    }
+
     pub(crate) async fn run(&mut self, io: &mut PhysLayer) -> SessionError {
-        while let Some(request) = self.rx.recv().await {
-            if let Some(err) = self.run_one_request(io, request).await {
-                return err;
+        self.run_until(io, None).await
+    }
+
+    // run the session until it fails, or until the optional end of the session is reached
+    pub(crate) async fn run_until(
+        &mut self,
+        io: &mut PhysLayer,
+        end: Option<Instant>,
+    ) -> SessionError {
+        let mut shutdown = false;
+
+        loop {
+            // requests already sent are still answered after the channel is dropped
+            if shutdown && self.in_flight.is_empty() && self.retries.is_empty() {
+                return SessionError::Shutdown;
+            }
+
+            // servers are given time to process a broadcast before anything else is sent
+            let turnaround = self.turnaround.filter(|x| *x > Instant::now());
+            let has_room = self.in_flight.len() < self.max_in_flight && turnaround.is_none();
+            let can_send = !shutdown && has_room;
+            let next_retry = self.next_retry();
+            let awaiting_response = !self.in_flight.is_empty();
+            let deadline = self
+                .in_flight
+                .iter()
+                .map(|x| x.deadline)
+                .min()
+                .unwrap_or_else(Instant::now);
+
+            let event = tokio::select! {
+                x = self.rx.recv(), if can_send => Event::Request(x),
+                x = next_setting(&mut self.settings) => Event::Setting(x),
+                x = self.reader.next_frame(io), if awaiting_response => Event::Frame(x),
+                _ = tokio::time::sleep_until(deadline), if awaiting_response => Event::Timeout,
+                _ = tokio::time::sleep_until(next_retry.unwrap_or_else(Instant::now)), if has_room && next_retry.is_some() => Event::Retry,
+                _ = tokio::time::sleep_until(turnaround.unwrap_or_else(Instant::now)), if turnaround.is_some() => Event::Turnaround,
+                _ = tokio::time::sleep_until(end.unwrap_or_else(Instant::now)), if end.is_some() => Event::Expired,
+            };
+
+            let result = match event {
+                Event::Request(Some(request)) => self.send_request(io, request).await,
+                Event::Request(None) => {
+                    shutdown = true;
+                    Ok(())
+                }
+                Event::Setting(setting) => match self.apply(setting) {
+                    Some(err) => return self.close(err),
+                    None => Ok(()),
+                },
+                Event::Frame(Ok(frame)) => {
+                    self.handle_frame(frame);
+                    Ok(())
+                }
+                Event::Frame(Err(err)) => Err(err),
+                Event::Timeout => {
+                    self.fail_expired_requests();
+                    Ok(())
+                }
+                Event::Retry => {
+                    let retry = self.take_next_retry();
+                    self.send_request(io, retry.request).await
+                }
+                Event::Turnaround => {
+                    self.turnaround = None;
+                    Ok(())
+                }
+                Event::Expired => return self.close(SessionError::Expired),
+            };
+
+            if let Err(err) = result {
+                tracing::warn!("error occurred making request: {}", err);
+                if let Some(session_err) = SessionError::from(&err) {
+                    for x in std::mem::take(&mut self.in_flight) {
+                        self.fail_or_retry(x.request, err);
+                    }
+                    return session_err;
+                }
+            }
+        }
+    }
+
+    fn is_broadcast_write(&self, request: &Request) -> bool {
+        self.turnaround_delay.is_some()
+            && request.id.is_broadcast()
+            && request.details.is_broadcast_write()
+    }
+
+    // the session is closed, so the responses to the requests in flight will never be received
+    fn close(&mut self, err: SessionError) -> SessionError {
+        for x in self.in_flight.drain(..) {
+            x.request.details.fail(RequestError::NoConnection);
+        }
+        err
+    }
+
+    fn next_retry(&self) -> Option<Instant> {
+        self.retries.iter().map(|x| x.due).min()
+    }
+
+    fn take_next_retry(&mut self) -> Retry {
+        let position = self
+            .retries
+            .iter()
+            .enumerate()
+            .min_by_key(|(_, x)| x.due)
+            .map(|(i, _)| i)
+            .unwrap_or_default();
+        self.retries.remove(position)
+    }
+
+    // queue the request to be sent again if its retry policy allows it, otherwise return it
+    fn retry(&mut self, mut request: Request, err: RequestError) -> Option<Request> {
+        let policy = request.retry_policy.unwrap_or(self.retry_policy);
+        match policy.next_delay(&err, request.retries) {
+            None => Some(request),
+            Some(delay) => {
+                request.retries += 1;
+                tracing::warn!(
+                    "{} - sending the request again in {:?} (retry {} of {})",
+                    err,
+                    delay,
+                    request.retries,
+                    policy.max_retries
+                );
+                self.retries.push(Retry {
+                    due: Instant::now() + delay,
+                    request,
+                    error: err,
+                });
+                None
             }
         }
-        SessionError::Shutdown
     }
 
-    async fn run_one_request(
+    // complete the request with the error, unless it can be retried
+    fn fail_or_retry(&mut self, request: Request, err: RequestError) {
+        if let Some(request) = self.retry(request, err) {
+            request.details.fail(err);
+        }
+    }
+
+    async fn send_request(
         &mut self,
         io: &mut PhysLayer,
         request: Request,
-    ) -> Option<SessionError> {
+    ) -> Result<(), RequestError> {
+        let function = request.details.function();
+        if !self.reader.can_frame_response(function) {
+            let err = InvalidRequest::UnsupportedByChannel(function.get_value()).into();
+            request.details.fail(err);
+            return Err(err);
+        }
+
         let tx_id = self.tx_id.next();
+        let span = tracing::info_span!("Transaction", tx_id = %tx_id);
+
         let result = self
-            .execute_request(io, request, tx_id)
-            .instrument(tracing::info_span!("Transaction", tx_id = %tx_id))
+            .write_request(io, &request, tx_id)
+            .instrument(span.clone())
             .await;
 
-        if let Err(e) = &result {
-            tracing::warn!("error occurred making request: {}", e);
+        match result {
+            // broadcast writes are never answered, so they complete as soon as they are sent
+            Ok(()) if self.is_broadcast_write(&request) => {
+                self.turnaround = self.turnaround_delay.map(|x| Instant::now() + x);
+                span.in_scope(|| request.details.complete_broadcast());
+                Ok(())
+            }
+            Ok(()) => {
+                self.in_flight.push(InFlight {
+                    tx_id,
+                    deadline: Instant::now() + request.timeout,
+                    request,
+                    span,
+                });
+                Ok(())
+            }
+            Err(err) => {
+                self.fail_or_retry(request, err);
+                Err(err)
+            }
         }
-
-        result.as_ref().err().and_then(SessionError::from)
     }
 
-    async fn execute_request(
+    async fn write_request(
         &mut self,
         io: &mut PhysLayer,
-        request: Request,
+        request: &Request,
         tx_id: TxId,
     ) -> Result<(), RequestError> {
         let bytes = self.formatter.format(
@@ -108,50 +346,94 @@ where
         )?;
 
         io.write(bytes).await?;
+        Ok(())
+    }
 
-        let deadline = Instant::now() + request.timeout;
-
-        // loop until we get a response with the correct tx id or we timeout
-        let response = loop {
-            let frame = tokio::select! {
-                _ = tokio::time::sleep_until(deadline) => {
-                    request.details.fail(RequestError::ResponseTimeout);
-                    return Ok(());
+    fn handle_frame(&mut self, frame: Frame) {
+        let position = match frame.header.tx_id {
+            Some(rx_id) => {
+                let position = self.in_flight.iter().position(|x| x.tx_id == rx_id);
+                if position.is_none() {
+                    tracing::warn!(
+                        "received {:?} which doesn't match any pending request",
+                        rx_id
+                    );
                 }
-                x = self.reader.next_frame(io) => match x {
-                    Ok(frame) => frame,
-                    Err(err) => {
-                        request.details.fail(err);
-                        return Err(err);
-                    }
+                position
+            }
+            // without a transaction id (serial), the best we can do is to check the unit id
+            None => {
+                let position = self
+                    .in_flight
+                    .iter()
+                    .position(|x| x.request.id == frame.header.unit_id);
+                if position.is_none() {
+                    tracing::warn!(
+                        "received response from unit {} which doesn't match any pending request",
+                        frame.header.unit_id
+                    );
                 }
-            };
-
-            if frame.header.tx_id != tx_id {
-                tracing::warn!(
-                    "received {:?} while expecting {:?}",
-                    frame.header.tx_id,
-                    tx_id
-                );
-                continue; // next iteration of loop
+                position
             }
-
-            break frame;
         };
 
-        request.handle_response(response.payload(), self.decode);
-        Ok(())
+        if let Some(position) = position {
+            let InFlight { request, span, .. } = self.in_flight.remove(position);
+            // transient exceptions are retried without completing the request
+            let request = match request.exception(frame.payload()) {
+                Some(exception) => match self.retry(request, RequestError::Exception(exception)) {
+                    Some(request) => request,
+                    None => return,
+                },
+                None => request,
+            };
+            let decode = self.decode;
+            span.in_scope(|| request.handle_response(frame.payload(), decode));
+        }
     }
 
-    pub(crate) async fn fail_requests_for(&mut self, duration: Duration) -> Result<(), ()> {
+    // each request times out independently of the others
+    fn fail_expired_requests(&mut self) {
+        let now = Instant::now();
+        let (expired, pending) = self
+            .in_flight
+            .drain(..)
+            .partition::<Vec<_>, _>(|x| x.deadline <= now);
+        self.in_flight = pending;
+        for x in expired {
+            self.fail_or_retry(x.request, RequestError::ResponseTimeout);
+        }
+    }
+
+    pub(crate) fn reset(&mut self) {
+        self.reader.reset();
+    }
+
+    pub(crate) async fn fail_requests_for(
+        &mut self,
+        duration: Duration,
+    ) -> Result<(), SessionError> {
         let deadline = Instant::now() + duration;
 
         loop {
+            // queued retries are kept for the next connection, but each one that comes due while
+            // disconnected counts as a failed attempt
+            let next_retry = self.next_retry();
+
             tokio::select! {
                 _ = tokio::time::sleep_until(deadline) => {
                     // Timeout occured
                     return Ok(())
                 }
+                _ = tokio::time::sleep_until(next_retry.unwrap_or_else(Instant::now)), if next_retry.is_some() => {
+                    let retry = self.take_next_retry();
+                    self.fail_or_retry(retry.request, retry.error);
+                }
+                x = next_setting(&mut self.settings) => {
+                    if let Some(err) = self.apply(x) {
+                        return Err(err);
+                    }
+                }
                 x = self.rx.recv() => match x {
                     Some(request) => {
                         // fail request, do another iteration
@@ -159,12 +441,72 @@ where
                     }
                     None => {
                         // channel was closed
-                        return Err(())
+                        return Err(SessionError::Shutdown)
                     }
                 }
             }
         }
     }
+
+    // fail requests until the channel is enabled, returning early if the endpoint changes
+    pub(crate) async fn wait_until_enabled(&mut self) -> Result<(), SessionError> {
+        // retries aren't kept while the channel is disabled
+        for x in self.retries.drain(..) {
+            x.request.details.fail(RequestError::NoConnection);
+        }
+        while !self.enabled {
+            tokio::select! {
+                x = next_setting(&mut self.settings) => {
+                    if let Some(SessionError::EndpointChanged(endpoints)) = self.apply(x) {
+                        return Err(SessionError::EndpointChanged(endpoints));
+                    }
+                }
+                x = self.rx.recv() => match x {
+                    Some(request) => request.details.fail(RequestError::NoConnection),
+                    None => return Err(SessionError::Shutdown),
+                }
+            }
+        }
+        Ok(())
+    }
+
+    // returns the reason the current session must end, if any
+    fn apply(&mut self, setting: Setting) -> Option<SessionError> {
+        match setting {
+            Setting::Enable => {
+                self.enabled = true;
+                None
+            }
+            Setting::Disable => {
+                self.enabled = false;
+                Some(SessionError::Disabled)
+            }
+            Setting::ReconnectStrategy(retry) => {
+                self.retry = retry;
+                None
+            }
+            Setting::Endpoint(endpoints) => Some(SessionError::EndpointChanged(endpoints)),
+            Setting::RetryPolicy(policy) => {
+                self.retry_policy = policy;
+                None
+            }
+            Setting::TurnaroundDelay(delay) => {
+                if self.turnaround_delay.is_some() {
+                    self.turnaround_delay = Some(delay);
+                }
+                None
+            }
+        }
+    }
+}
+
+// settings are closed when every channel handle is dropped, but requests may still
+// arrive through callback sessions, so closed settings are simply never ready
+async fn next_setting(settings: &mut tokio::sync::mpsc::Receiver<Setting>) -> Setting {
+    match settings.recv().await {
+        Some(x) => x,
+        None => std::future::pending().await,
+    }
 }
 
 #[cfg(test)]
@@ -172,8 +514,11 @@ mod tests {
     use std::task::Poll;
 
     use super::*;
+    use crate::client::default_reconnect_strategy;
+    use crate::client::message::Promise;
     use crate::client::message::RequestDetails;
     use crate::client::requests::read_bits::ReadBits;
+    use crate::client::requests::write_single::SingleWrite;
     use crate::common::function::FunctionCode;
     use crate::common::traits::{Loggable, Serialize};
     use crate::decode::*;
@@ -190,22 +535,32 @@ mod tests {
         client: ClientLoop<MbapFormatter, MbapParser>,
         io: PhysLayer,
         io_handle: io::Handle,
+        settings: tokio::sync::mpsc::Sender<Setting>,
     }
 
     impl ClientFixture {
         fn new() -> (Self, tokio::sync::mpsc::Sender<Request>) {
+            Self::with_max_in_flight(1)
+        }
+
+        fn with_max_in_flight(max_in_flight: usize) -> (Self, tokio::sync::mpsc::Sender<Request>) {
             let (tx, rx) = tokio::sync::mpsc::channel(10);
+            let (settings, settings_rx) = tokio::sync::mpsc::channel(10);
             let (io, io_handle) = io::mock();
             (
                 Self {
                     client: ClientLoop::new(
                         rx,
+                        settings_rx,
                         MbapFormatter::new(AduDecodeLevel::Nothing),
                         MbapParser::new(AduDecodeLevel::Nothing),
                         PduDecodeLevel::Nothing,
+                        max_in_flight,
+                        default_reconnect_strategy(),
                     ),
                     io: PhysLayer::new_mock(io, PhysDecodeLevel::Nothing),
                     io_handle,
+                    settings,
                 },
                 tx,
             )
@@ -238,6 +593,12 @@ mod tests {
             }
         }
 
+        fn change_setting(&mut self, setting: Setting) {
+            if self.settings.try_send(setting).is_err() {
+                panic!("can't change setting");
+            }
+        }
+
         fn assert_pending(&mut self) {
             let mut task = spawn(self.client.run(&mut self.io));
             assert_pending!(task.poll());
@@ -250,11 +611,18 @@ mod tests {
     }
 
     fn get_framed_adu<T>(function: FunctionCode, payload: &T) -> Vec<u8>
+    where
+        T: Serialize + Loggable + Sized,
+    {
+        get_framed_adu_with_tx_id(function, payload, 0)
+    }
+
+    fn get_framed_adu_with_tx_id<T>(function: FunctionCode, payload: &T, tx_id: u16) -> Vec<u8>
     where
         T: Serialize + Loggable + Sized,
     {
         let mut fmt = MbapFormatter::new(AduDecodeLevel::Nothing);
-        let header = FrameHeader::new(UnitId::new(1), TxId::new(0));
+        let header = FrameHeader::new(UnitId::new(1), TxId::new(tx_id));
         let bytes = fmt
             .format(header, function, payload, PduDecodeLevel::Nothing)
             .unwrap();
@@ -317,6 +685,71 @@ mod tests {
         );
     }
 
+    #[test]
+    fn disabling_closes_the_session_and_fails_requests_in_flight() {
+        let (mut fixture, mut tx) = ClientFixture::new();
+
+        let range = AddressRange::try_from(7, 2).unwrap();
+        fixture
+            .io_handle
+            .write(&get_framed_adu(FunctionCode::ReadCoils, &range));
+
+        let rx = fixture.read_coils(&mut tx, range, Duration::from_secs(5));
+        fixture.assert_pending();
+
+        fixture.change_setting(Setting::Disable);
+        fixture.assert_run(SessionError::Disabled);
+        assert!(!fixture.client.is_enabled());
+
+        assert_ready_eq!(spawn(rx).poll(), Ok(Err(RequestError::NoConnection)));
+    }
+
+    #[test]
+    fn session_expires_at_the_end_of_its_period() {
+        let (mut fixture, mut tx) = ClientFixture::new();
+
+        let range = AddressRange::try_from(7, 2).unwrap();
+        fixture
+            .io_handle
+            .write(&get_framed_adu(FunctionCode::ReadCoils, &range));
+
+        let rx = fixture.read_coils(&mut tx, range, Duration::from_secs(10));
+        let end = Some(Instant::now() + Duration::from_secs(5));
+        assert_pending!(spawn(fixture.client.run_until(&mut fixture.io, end)).poll());
+
+        tokio::time::advance(Duration::from_secs(5));
+        assert_ready_eq!(
+            spawn(fixture.client.run_until(&mut fixture.io, end)).poll(),
+            SessionError::Expired
+        );
+
+        assert_ready_eq!(spawn(rx).poll(), Ok(Err(RequestError::NoConnection)));
+    }
+
+    #[test]
+    fn requests_fail_until_the_channel_is_enabled() {
+        let (mut fixture, mut tx) = ClientFixture::new();
+
+        fixture.change_setting(Setting::Disable);
+        fixture.assert_run(SessionError::Disabled);
+
+        let range = AddressRange::try_from(7, 2).unwrap();
+        let rx = fixture.read_coils(&mut tx, range, Duration::from_secs(5));
+        assert_pending!(spawn(fixture.client.wait_until_enabled()).poll());
+        assert_ready_eq!(spawn(rx).poll(), Ok(Err(RequestError::NoConnection)));
+
+        let endpoints =
+            EndpointList::from("127.0.0.1:502".parse::<std::net::SocketAddr>().unwrap());
+        fixture.change_setting(Setting::Endpoint(endpoints.clone()));
+        assert_ready_eq!(
+            spawn(fixture.client.wait_until_enabled()).poll(),
+            Err(SessionError::EndpointChanged(endpoints))
+        );
+
+        fixture.change_setting(Setting::Enable);
+        assert_ready_eq!(spawn(fixture.client.wait_until_enabled()).poll(), Ok(()));
+    }
+
     #[test]
     fn transmit_read_coils_when_requested() {
         let (mut fixture, mut tx) = ClientFixture::new();
@@ -346,4 +779,304 @@ mod tests {
             Ok(Ok(vec![Indexed::new(7, true), Indexed::new(8, false)]))
         );
     }
+
+    fn read_coils_response(range: AddressRange, tx_id: u16) -> Vec<u8> {
+        get_framed_adu_with_tx_id(
+            FunctionCode::ReadCoils,
+            &BitWriter::new(ReadBitsRange { inner: range }, |idx| Ok(idx % 2 == 0)),
+            tx_id,
+        )
+    }
+
+    #[test]
+    fn retries_request_after_server_device_busy_exception() {
+        let (mut fixture, mut tx) = ClientFixture::new();
+        fixture.change_setting(Setting::RetryPolicy(RetryPolicy::new(
+            1,
+            Duration::from_secs(0),
+            Duration::from_secs(0),
+        )));
+        // the policy is applied before the request is made
+        fixture.assert_pending();
+
+        let range = AddressRange::try_from(0, 2).unwrap();
+        fixture.io_handle.write(&get_framed_adu_with_tx_id(
+            FunctionCode::ReadCoils,
+            &range,
+            0,
+        ));
+        fixture
+            .io_handle
+            .read(&[0x00, 0x00, 0x00, 0x00, 0x00, 0x03, 0x01, 0x81, 0x06]);
+        fixture.io_handle.write(&get_framed_adu_with_tx_id(
+            FunctionCode::ReadCoils,
+            &range,
+            1,
+        ));
+        fixture.io_handle.read(&read_coils_response(range, 1));
+
+        let rx = fixture.read_coils(&mut tx, range, Duration::from_secs(1));
+        drop(tx);
+
+        fixture.assert_run(SessionError::Shutdown);
+        assert!(fixture.io_handle.all_done());
+        assert_ready_eq!(
+            spawn(rx).poll(),
+            Ok(Ok(vec![Indexed::new(0, true), Indexed::new(1, false)]))
+        );
+    }
+
+    #[test]
+    fn fails_with_the_last_error_once_retries_are_exhausted() {
+        let (mut fixture, mut tx) = ClientFixture::new();
+        fixture.change_setting(Setting::RetryPolicy(RetryPolicy::new(
+            1,
+            Duration::from_secs(1),
+            Duration::from_secs(1),
+        )));
+        fixture.assert_pending();
+
+        let range = AddressRange::try_from(0, 2).unwrap();
+        fixture.io_handle.write(&get_framed_adu_with_tx_id(
+            FunctionCode::ReadCoils,
+            &range,
+            0,
+        ));
+        fixture.io_handle.write(&get_framed_adu_with_tx_id(
+            FunctionCode::ReadCoils,
+            &range,
+            1,
+        ));
+
+        let rx = fixture.read_coils(&mut tx, range, Duration::from_secs(1));
+        fixture.assert_pending();
+
+        // the first attempt times out and the retry is sent after the delay
+        tokio::time::advance(Duration::from_secs(1));
+        fixture.assert_pending();
+        tokio::time::advance(Duration::from_secs(1));
+        fixture.assert_pending();
+        assert!(fixture.io_handle.all_done());
+
+        tokio::time::advance(Duration::from_secs(1));
+        fixture.assert_pending();
+        assert_ready_eq!(spawn(rx).poll(), Ok(Err(RequestError::ResponseTimeout)));
+    }
+
+    #[test]
+    fn keeps_queued_retries_for_the_next_connection() {
+        let (mut fixture, mut tx) = ClientFixture::new();
+        fixture.change_setting(Setting::RetryPolicy(RetryPolicy::new(
+            1,
+            Duration::from_secs(1),
+            Duration::from_secs(1),
+        )));
+        fixture.assert_pending();
+
+        let range = AddressRange::try_from(0, 2).unwrap();
+        fixture.io_handle.write(&get_framed_adu_with_tx_id(
+            FunctionCode::ReadCoils,
+            &range,
+            0,
+        ));
+
+        let mut rx = spawn(fixture.read_coils(&mut tx, range, Duration::from_secs(1)));
+        fixture.assert_pending();
+
+        // the first attempt times out, then the connection is lost before the retry is due
+        tokio::time::advance(Duration::from_secs(1));
+        fixture.assert_pending();
+        {
+            let mut wait = spawn(fixture.client.fail_requests_for(Duration::from_millis(500)));
+            assert_pending!(wait.poll());
+            tokio::time::advance(Duration::from_millis(500));
+            assert_ready_eq!(wait.poll(), Ok(()));
+        }
+        assert_pending!(rx.poll());
+
+        // the retry is sent once reconnected
+        fixture.io_handle.write(&get_framed_adu_with_tx_id(
+            FunctionCode::ReadCoils,
+            &range,
+            1,
+        ));
+        fixture.io_handle.read(&read_coils_response(range, 1));
+        tokio::time::advance(Duration::from_millis(500));
+        fixture.assert_pending();
+        assert!(fixture.io_handle.all_done());
+        assert_ready_eq!(
+            rx.poll(),
+            Ok(Ok(vec![Indexed::new(0, true), Indexed::new(1, false)]))
+        );
+    }
+
+    #[test]
+    fn retries_that_come_due_while_disconnected_count_as_attempts() {
+        let (mut fixture, mut tx) = ClientFixture::new();
+        fixture.change_setting(Setting::RetryPolicy(RetryPolicy::new(
+            1,
+            Duration::from_secs(1),
+            Duration::from_secs(1),
+        )));
+        fixture.assert_pending();
+
+        let range = AddressRange::try_from(0, 2).unwrap();
+        fixture.io_handle.write(&get_framed_adu_with_tx_id(
+            FunctionCode::ReadCoils,
+            &range,
+            0,
+        ));
+
+        let mut rx = spawn(fixture.read_coils(&mut tx, range, Duration::from_secs(1)));
+        fixture.assert_pending();
+        tokio::time::advance(Duration::from_secs(1));
+        fixture.assert_pending();
+
+        // the only retry comes due while waiting to reconnect
+        let mut wait = spawn(fixture.client.fail_requests_for(Duration::from_secs(5)));
+        assert_pending!(wait.poll());
+        tokio::time::advance(Duration::from_secs(1));
+        assert_pending!(wait.poll());
+        assert_ready_eq!(rx.poll(), Ok(Err(RequestError::ResponseTimeout)));
+    }
+
+    #[test]
+    fn pipelines_requests_up_to_the_maximum_in_flight() {
+        let (mut fixture, mut tx) = ClientFixture::with_max_in_flight(2);
+
+        let ranges = [
+            AddressRange::try_from(0, 2).unwrap(),
+            AddressRange::try_from(2, 2).unwrap(),
+            AddressRange::try_from(4, 2).unwrap(),
+        ];
+
+        // the third request is only sent once a response frees up a slot
+        fixture.io_handle.write(&get_framed_adu_with_tx_id(
+            FunctionCode::ReadCoils,
+            &ranges[0],
+            0,
+        ));
+        fixture.io_handle.write(&get_framed_adu_with_tx_id(
+            FunctionCode::ReadCoils,
+            &ranges[1],
+            1,
+        ));
+        fixture.io_handle.read(&read_coils_response(ranges[1], 1));
+        fixture.io_handle.write(&get_framed_adu_with_tx_id(
+            FunctionCode::ReadCoils,
+            &ranges[2],
+            2,
+        ));
+        fixture.io_handle.read(&read_coils_response(ranges[0], 0));
+        fixture.io_handle.read(&read_coils_response(ranges[2], 2));
+
+        let receivers: Vec<_> = ranges
+            .iter()
+            .map(|range| fixture.read_coils(&mut tx, *range, Duration::from_secs(1)))
+            .collect();
+        drop(tx);
+
+        fixture.assert_run(SessionError::Shutdown);
+        assert!(fixture.io_handle.all_done());
+
+        for (rx, range) in receivers.into_iter().zip(ranges.iter()) {
+            assert_ready_eq!(
+                spawn(rx).poll(),
+                Ok(Ok(vec![
+                    Indexed::new(range.start, true),
+                    Indexed::new(range.start + 1, false)
+                ]))
+            );
+        }
+    }
+
+    #[test]
+    fn requests_in_flight_time_out_independently() {
+        let (mut fixture, mut tx) = ClientFixture::with_max_in_flight(2);
+
+        let first = AddressRange::try_from(0, 2).unwrap();
+        let second = AddressRange::try_from(2, 2).unwrap();
+
+        fixture.io_handle.write(&get_framed_adu_with_tx_id(
+            FunctionCode::ReadCoils,
+            &first,
+            0,
+        ));
+        fixture.io_handle.write(&get_framed_adu_with_tx_id(
+            FunctionCode::ReadCoils,
+            &second,
+            1,
+        ));
+
+        let first_rx = fixture.read_coils(&mut tx, first, Duration::from_secs(1));
+        let second_rx = fixture.read_coils(&mut tx, second, Duration::from_secs(5));
+        fixture.assert_pending();
+
+        tokio::time::advance(Duration::from_secs(2));
+        fixture.assert_pending();
+        assert_ready_eq!(
+            spawn(first_rx).poll(),
+            Ok(Err(RequestError::ResponseTimeout))
+        );
+
+        // a late response to the first request is discarded
+        fixture.io_handle.read(&read_coils_response(first, 0));
+        fixture.io_handle.read(&read_coils_response(second, 1));
+        drop(tx);
+
+        fixture.assert_run(SessionError::Shutdown);
+        assert_ready_eq!(
+            spawn(second_rx).poll(),
+            Ok(Ok(vec![Indexed::new(2, true), Indexed::new(3, false)]))
+        );
+    }
+
+    #[test]
+    fn broadcast_writes_complete_without_a_response_and_delay_the_next_request() {
+        let (mut fixture, mut tx) = ClientFixture::new();
+        fixture.client = fixture.client.with_broadcast(Duration::from_millis(100));
+
+        let value = Indexed::new(1, 0xCAFE);
+        let mut fmt = MbapFormatter::new(AduDecodeLevel::Nothing);
+        let broadcast = Vec::from(
+            fmt.format(
+                FrameHeader::new(UnitId::broadcast(), TxId::new(0)),
+                FunctionCode::WriteSingleRegister,
+                &value,
+                PduDecodeLevel::Nothing,
+            )
+            .unwrap(),
+        );
+        fixture.io_handle.write(&broadcast);
+
+        let (response_tx, response_rx) = tokio::sync::oneshot::channel();
+        let details = RequestDetails::WriteSingleRegister(SingleWrite::new(
+            value,
+            Promise::Channel(response_tx),
+        ));
+        let request = Request::new(UnitId::broadcast(), Duration::from_secs(1), details);
+        assert_ready!(spawn(tx.send(request)).poll()).unwrap();
+
+        let range = AddressRange::try_from(0, 2).unwrap();
+        let read_rx = fixture.read_coils(&mut tx, range, Duration::from_secs(1));
+        fixture.assert_pending();
+        assert_ready_eq!(spawn(response_rx).poll(), Ok(Ok(value)));
+
+        // the read is only sent once the turnaround delay has elapsed
+        fixture.io_handle.write(&get_framed_adu_with_tx_id(
+            FunctionCode::ReadCoils,
+            &range,
+            1,
+        ));
+        fixture.io_handle.read(&read_coils_response(range, 1));
+        tokio::time::advance(Duration::from_millis(100));
+        drop(tx);
+
+        fixture.assert_run(SessionError::Shutdown);
+        assert!(fixture.io_handle.all_done());
+        assert_ready_eq!(
+            spawn(read_rx).poll(),
+            Ok(Ok(vec![Indexed::new(0, true), Indexed::new(1, false)]))
+        );
+    }
 }
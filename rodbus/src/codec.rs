@@ -0,0 +1,432 @@
+use crate::error::CodecError;
+use crate::types::Indexed;
+
+/// Order of the bytes of a value that spans one or more registers
+///
+/// Each variant is named after the order of the bytes on the wire for a 32-bit value
+/// whose big-endian representation is `A B C D`. Values of other sizes follow the same
+/// pattern: the registers are either in order or reversed, and the two bytes of each
+/// register are either in order or swapped.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ByteOrder {
+    /// big-endian registers with the most significant register first
+    Abcd,
+    /// big-endian registers with the least significant register first
+    Cdab,
+    /// byte-swapped registers with the most significant register first
+    Badc,
+    /// byte-swapped registers with the least significant register first, i.e. little-endian
+    Dcba,
+}
+
+/// Value stored in a fixed number of consecutive registers
+pub trait RegisterValue: Sized {
+    /// Number of registers used to store the value
+    const COUNT: u16;
+
+    /// Decode the value from exactly [`Self::COUNT`] registers
+    fn from_registers(registers: &[u16], order: ByteOrder) -> Result<Self, CodecError>;
+
+    /// Encode the value into [`Self::COUNT`] registers
+    fn to_registers(&self, order: ByteOrder) -> Vec<u16>;
+}
+
+/// Decode a value from the registers that start at `address` in a collection of values
+/// such as the one returned by a read
+///
+/// Fails if any of the registers is missing
+pub fn decode_at<T: RegisterValue>(
+    values: &[Indexed<u16>],
+    address: u16,
+    order: ByteOrder,
+) -> Result<T, CodecError> {
+    let registers: Vec<u16> = (0..T::COUNT)
+        .map_while(|offset| {
+            let address = address.checked_add(offset)?;
+            values.iter().find(|x| x.index == address).map(|x| x.value)
+        })
+        .collect();
+    T::from_registers(&registers, order)
+}
+
+/// Decode an ASCII string stored two characters per register
+///
+/// The string ends at the first NUL character. Bytes that aren't ASCII are decoded as the
+/// Unicode code point with the same value. Only the order of the bytes within the registers
+/// applies to strings: [`ByteOrder::Badc`] and [`ByteOrder::Dcba`] store the first character
+/// in the low byte of each register.
+pub fn decode_string(registers: &[u16], order: ByteOrder) -> String {
+    registers
+        .iter()
+        .flat_map(|x| register_bytes(*x, order))
+        .take_while(|x| *x != 0)
+        .map(char::from)
+        .collect()
+}
+
+/// Encode an ASCII string into `count` registers, two characters per register,
+/// padding the remaining space with NUL characters
+///
+/// See [`decode_string`] for the meaning of the byte order.
+pub fn encode_string(value: &str, count: u16, order: ByteOrder) -> Result<Vec<u16>, CodecError> {
+    if let Some(x) = value.chars().find(|x| !x.is_ascii()) {
+        return Err(CodecError::NotAscii(x));
+    }
+    let capacity = 2 * count as usize;
+    if value.len() > capacity {
+        return Err(CodecError::StringTooLong(value.len(), capacity));
+    }
+
+    let mut bytes = value.as_bytes().to_vec();
+    bytes.resize(capacity, 0);
+    Ok(bytes
+        .chunks(2)
+        .map(|x| {
+            let register = u16::from_be_bytes([x[0], x[1]]);
+            if swaps_bytes(order) {
+                register.swap_bytes()
+            } else {
+                register
+            }
+        })
+        .collect())
+}
+
+/// Decode a binary-coded decimal value with four digits per register
+///
+/// Up to four registers (16 digits) may be decoded.
+pub fn decode_bcd(registers: &[u16], order: ByteOrder) -> Result<u64, CodecError> {
+    if registers.len() > 4 {
+        return Err(CodecError::BcdTooLong(registers.len()));
+    }
+    let mut value: u64 = 0;
+    for byte in to_bytes(registers, order) {
+        for digit in [byte >> 4, byte & 0x0F].iter().copied() {
+            if digit > 9 {
+                return Err(CodecError::InvalidBcdDigit(byte));
+            }
+            value = value * 10 + digit as u64;
+        }
+    }
+    Ok(value)
+}
+
+/// Encode a value as binary-coded decimal in `count` registers with four digits per register
+pub fn encode_bcd(value: u64, count: u16, order: ByteOrder) -> Result<Vec<u16>, CodecError> {
+    if count > 4 {
+        return Err(CodecError::BcdTooLong(count as usize));
+    }
+    let mut remaining = value;
+    let mut bytes = vec![0u8; 2 * count as usize];
+    for byte in bytes.iter_mut().rev() {
+        let low = (remaining % 10) as u8;
+        let high = ((remaining / 10) % 10) as u8;
+        *byte = (high << 4) | low;
+        remaining /= 100;
+    }
+    if remaining != 0 {
+        return Err(CodecError::BcdValueTooLarge(value, count));
+    }
+    Ok(from_bytes(&bytes, order))
+}
+
+/// Range of bits within a register
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BitField {
+    start: u8,
+    count: u8,
+}
+
+impl BitField {
+    /// Create a bit field of `count` bits starting at bit `start`, where bit 0 is the least significant
+    pub fn new(start: u8, count: u8) -> Result<Self, CodecError> {
+        if count == 0 || start as u16 + count as u16 > 16 {
+            return Err(CodecError::BadBitField(start, count));
+        }
+        Ok(Self { start, count })
+    }
+
+    /// Extract the value of the field from a register
+    pub fn get(self, register: u16) -> u16 {
+        (register >> self.start) & self.mask()
+    }
+
+    /// Replace the value of the field in a register, ignoring the bits of `value` that don't fit
+    pub fn set(self, register: u16, value: u16) -> u16 {
+        let mask = self.mask() << self.start;
+        (register & !mask) | ((value << self.start) & mask)
+    }
+
+    fn mask(self) -> u16 {
+        (((1u32 << self.count) - 1) & 0xFFFF) as u16
+    }
+}
+
+fn swaps_bytes(order: ByteOrder) -> bool {
+    matches!(order, ByteOrder::Badc | ByteOrder::Dcba)
+}
+
+fn swaps_registers(order: ByteOrder) -> bool {
+    matches!(order, ByteOrder::Cdab | ByteOrder::Dcba)
+}
+
+fn register_bytes(register: u16, order: ByteOrder) -> [u8; 2] {
+    if swaps_bytes(order) {
+        register.to_le_bytes()
+    } else {
+        register.to_be_bytes()
+    }
+}
+
+// big-endian bytes of a value stored in the registers with the specified order
+fn to_bytes(registers: &[u16], order: ByteOrder) -> Vec<u8> {
+    let mut bytes: Vec<u8> = Vec::with_capacity(2 * registers.len());
+    if swaps_registers(order) {
+        for x in registers.iter().rev() {
+            bytes.extend(register_bytes(*x, order).iter());
+        }
+    } else {
+        for x in registers.iter() {
+            bytes.extend(register_bytes(*x, order).iter());
+        }
+    }
+    bytes
+}
+
+// registers that store a value with the specified big-endian bytes
+fn from_bytes(bytes: &[u8], order: ByteOrder) -> Vec<u16> {
+    let mut registers: Vec<u16> = bytes
+        .chunks(2)
+        .map(|x| {
+            let register = u16::from_be_bytes([x[0], x.get(1).copied().unwrap_or(0)]);
+            if swaps_bytes(order) {
+                register.swap_bytes()
+            } else {
+                register
+            }
+        })
+        .collect();
+    if swaps_registers(order) {
+        registers.reverse();
+    }
+    registers
+}
+
+fn check_count(registers: &[u16], count: u16) -> Result<(), CodecError> {
+    if registers.len() != count as usize {
+        return Err(CodecError::WrongRegisterCount(registers.len(), count));
+    }
+    Ok(())
+}
+
+macro_rules! impl_register_value {
+    ($type:ty, $count:expr) => {
+        impl RegisterValue for $type {
+            const COUNT: u16 = $count;
+
+            fn from_registers(registers: &[u16], order: ByteOrder) -> Result<Self, CodecError> {
+                check_count(registers, Self::COUNT)?;
+                let mut bytes = [0u8; 2 * $count];
+                bytes.copy_from_slice(&to_bytes(registers, order));
+                Ok(<$type>::from_be_bytes(bytes))
+            }
+
+            fn to_registers(&self, order: ByteOrder) -> Vec<u16> {
+                from_bytes(&self.to_be_bytes(), order)
+            }
+        }
+    };
+}
+
+impl_register_value!(u16, 1);
+impl_register_value!(i16, 1);
+impl_register_value!(u32, 2);
+impl_register_value!(i32, 2);
+impl_register_value!(f32, 2);
+impl_register_value!(u64, 4);
+impl_register_value!(i64, 4);
+impl_register_value!(f64, 4);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ORDERS: [ByteOrder; 4] = [
+        ByteOrder::Abcd,
+        ByteOrder::Cdab,
+        ByteOrder::Badc,
+        ByteOrder::Dcba,
+    ];
+
+    #[test]
+    fn decodes_u32_in_all_orders() {
+        let expected = 0x1122_3344u32;
+        assert_eq!(
+            u32::from_registers(&[0x1122, 0x3344], ByteOrder::Abcd),
+            Ok(expected)
+        );
+        assert_eq!(
+            u32::from_registers(&[0x3344, 0x1122], ByteOrder::Cdab),
+            Ok(expected)
+        );
+        assert_eq!(
+            u32::from_registers(&[0x2211, 0x4433], ByteOrder::Badc),
+            Ok(expected)
+        );
+        assert_eq!(
+            u32::from_registers(&[0x4433, 0x2211], ByteOrder::Dcba),
+            Ok(expected)
+        );
+    }
+
+    #[test]
+    fn decodes_f32_and_signed_values() {
+        // 123.456 is 0x42F6E979
+        assert_eq!(
+            f32::from_registers(&[0xE979, 0x42F6], ByteOrder::Cdab),
+            Ok(123.456)
+        );
+        assert_eq!(i16::from_registers(&[0xFFFE], ByteOrder::Abcd), Ok(-2));
+        assert_eq!(
+            i32::from_registers(&[0xFFFF, 0xFFFD], ByteOrder::Abcd),
+            Ok(-3)
+        );
+    }
+
+    #[test]
+    fn decodes_64_bit_values_in_all_orders() {
+        let expected = 0x1122_3344_5566_7788u64;
+        assert_eq!(
+            u64::from_registers(&[0x1122, 0x3344, 0x5566, 0x7788], ByteOrder::Abcd),
+            Ok(expected)
+        );
+        assert_eq!(
+            u64::from_registers(&[0x7788, 0x5566, 0x3344, 0x1122], ByteOrder::Cdab),
+            Ok(expected)
+        );
+        assert_eq!(
+            u64::from_registers(&[0x2211, 0x4433, 0x6655, 0x8877], ByteOrder::Badc),
+            Ok(expected)
+        );
+        assert_eq!(
+            u64::from_registers(&[0x8877, 0x6655, 0x4433, 0x2211], ByteOrder::Dcba),
+            Ok(expected)
+        );
+    }
+
+    #[test]
+    fn encoding_round_trips_in_all_orders() {
+        for order in ORDERS.iter().copied() {
+            let registers = (-1.5e300f64).to_registers(order);
+            assert_eq!(registers.len(), 4);
+            assert_eq!(f64::from_registers(&registers, order), Ok(-1.5e300));
+            let registers = (-7i64).to_registers(order);
+            assert_eq!(i64::from_registers(&registers, order), Ok(-7));
+            let registers = 0xCAFEu16.to_registers(order);
+            assert_eq!(u16::from_registers(&registers, order), Ok(0xCAFE));
+        }
+    }
+
+    #[test]
+    fn rejects_wrong_number_of_registers() {
+        assert_eq!(
+            f32::from_registers(&[0x0000], ByteOrder::Abcd),
+            Err(CodecError::WrongRegisterCount(1, 2))
+        );
+        assert_eq!(
+            u64::from_registers(&[0; 5], ByteOrder::Abcd),
+            Err(CodecError::WrongRegisterCount(5, 4))
+        );
+    }
+
+    #[test]
+    fn decodes_values_by_address() {
+        let values = vec![
+            Indexed::new(9, 0x0000),
+            Indexed::new(10, 0x3344),
+            Indexed::new(11, 0x1122),
+        ];
+        assert_eq!(
+            decode_at::<u32>(&values, 10, ByteOrder::Cdab),
+            Ok(0x1122_3344)
+        );
+        assert_eq!(
+            decode_at::<u32>(&values, 11, ByteOrder::Cdab),
+            Err(CodecError::WrongRegisterCount(1, 2))
+        );
+        assert_eq!(
+            decode_at::<u16>(&values, 0xFFFF, ByteOrder::Abcd),
+            Err(CodecError::WrongRegisterCount(0, 1))
+        );
+    }
+
+    #[test]
+    fn decodes_and_encodes_strings() {
+        assert_eq!(
+            decode_string(&[0x4142, 0x4300, 0x4445], ByteOrder::Abcd),
+            "ABC"
+        );
+        assert_eq!(decode_string(&[0x4241, 0x0043], ByteOrder::Badc), "ABC");
+        assert_eq!(
+            encode_string("ABC", 3, ByteOrder::Abcd),
+            Ok(vec![0x4142, 0x4300, 0x0000])
+        );
+        assert_eq!(
+            encode_string("ABC", 2, ByteOrder::Dcba),
+            Ok(vec![0x4241, 0x0043])
+        );
+        assert_eq!(
+            encode_string("ABCDE", 2, ByteOrder::Abcd),
+            Err(CodecError::StringTooLong(5, 4))
+        );
+        assert_eq!(
+            encode_string("A°", 2, ByteOrder::Abcd),
+            Err(CodecError::NotAscii('°'))
+        );
+    }
+
+    #[test]
+    fn decodes_and_encodes_bcd() {
+        assert_eq!(decode_bcd(&[0x1234], ByteOrder::Abcd), Ok(1234));
+        assert_eq!(
+            decode_bcd(&[0x5678, 0x1234], ByteOrder::Cdab),
+            Ok(12_345_678)
+        );
+        assert_eq!(
+            decode_bcd(&[0x12A4], ByteOrder::Abcd),
+            Err(CodecError::InvalidBcdDigit(0xA4))
+        );
+        assert_eq!(
+            decode_bcd(&[0; 5], ByteOrder::Abcd),
+            Err(CodecError::BcdTooLong(5))
+        );
+        assert_eq!(
+            encode_bcd(12_345_678, 2, ByteOrder::Badc),
+            Ok(vec![0x3412, 0x7856])
+        );
+        assert_eq!(encode_bcd(42, 1, ByteOrder::Abcd), Ok(vec![0x0042]));
+        assert_eq!(
+            encode_bcd(10_000, 1, ByteOrder::Abcd),
+            Err(CodecError::BcdValueTooLarge(10_000, 1))
+        );
+        assert_eq!(
+            decode_bcd(
+                &encode_bcd(9_999_999_999_999_999, 4, ByteOrder::Dcba).unwrap(),
+                ByteOrder::Dcba
+            ),
+            Ok(9_999_999_999_999_999)
+        );
+    }
+
+    #[test]
+    fn gets_and_sets_bit_fields() {
+        let field = BitField::new(4, 3).unwrap();
+        assert_eq!(field.get(0b1111_0101_0000), 0b101);
+        assert_eq!(field.set(0xFFFF, 0b010), 0b1111_1111_1010_1111);
+        assert_eq!(field.set(0x0000, 0xFF), 0b0111_0000);
+        assert_eq!(BitField::new(0, 16).unwrap().get(0xABCD), 0xABCD);
+        assert_eq!(BitField::new(15, 1).unwrap().get(0x8000), 1);
+        assert_eq!(BitField::new(9, 8), Err(CodecError::BadBitField(9, 8)));
+        assert_eq!(BitField::new(0, 0), Err(CodecError::BadBitField(0, 0)));
+    }
+}
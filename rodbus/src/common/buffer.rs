@@ -30,6 +30,12 @@ impl ReadBuffer {
         self.begin == self.end
     }
 
+    #[cfg_attr(feature = "no-panic", no_panic)]
+    pub(crate) fn reset(&mut self) {
+        self.begin = 0;
+        self.end = 0;
+    }
+
     #[cfg_attr(feature = "no-panic", no_panic)]
     pub(crate) fn read(&mut self, count: usize) -> Result<&[u8], InternalError> {
         if self.len() < count {
@@ -45,6 +51,18 @@ impl ReadBuffer {
         }
     }
 
+    #[cfg_attr(feature = "no-panic", no_panic)]
+    pub(crate) fn peek(&self, count: usize) -> Result<&[u8], InternalError> {
+        if self.len() < count {
+            return Err(InternalError::InsufficientBytesForRead(count, self.len()));
+        }
+
+        match self.buffer.get(self.begin..(self.begin + count)) {
+            Some(ret) => Ok(ret),
+            None => Err(InternalError::InsufficientBytesForRead(count, self.len())),
+        }
+    }
+
     #[cfg_attr(feature = "no-panic", no_panic)]
     pub(crate) fn read_u8(&mut self) -> Result<u8, InternalError> {
         if self.is_empty() {
@@ -111,6 +129,28 @@ mod tests {
         );
     }
 
+    #[test]
+    fn peek_does_not_consume_bytes() {
+        let mut buffer = ReadBuffer::new(3);
+
+        let (io, mut io_handle) = io::mock();
+        let mut phys = PhysLayer::new_mock(io, PhysDecodeLevel::Nothing);
+
+        {
+            let buf_ref = &mut buffer;
+            let mut task = spawn(async { buf_ref.read_some(&mut phys).await.unwrap() });
+            io_handle.read(&[0x01, 0x02]);
+            assert_ready_eq!(task.poll(), 2);
+        }
+
+        assert_eq!(buffer.peek(2).unwrap(), &[0x01, 0x02]);
+        assert_eq!(
+            buffer.peek(3),
+            Err(InternalError::InsufficientBytesForRead(3, 2))
+        );
+        assert_eq!(buffer.read(2).unwrap(), &[0x01, 0x02]);
+    }
+
     #[test]
     fn shifts_contents_when_buffer_at_capacity() {
         let mut buffer = ReadBuffer::new(3);
@@ -126,4 +126,19 @@ impl<'a> WriteCursor<'a> {
         self.write_u8(upper)?;
         self.write_u8(lower)
     }
+
+    #[cfg_attr(feature = "no-panic", no_panic)]
+    pub(crate) fn write_bytes(&mut self, bytes: &[u8]) -> Result<(), InternalError> {
+        match self.dest.get_mut(self.pos..self.pos + bytes.len()) {
+            Some(x) => {
+                x.copy_from_slice(bytes);
+                self.pos += bytes.len();
+                Ok(())
+            }
+            None => Err(InternalError::InsufficientWriteSpace(
+                bytes.len(),
+                self.remaining(),
+            )),
+        }
+    }
 }
@@ -1,3 +1,5 @@
+use std::time::Duration;
+
 use crate::common::phys::PhysLayer;
 
 use crate::common::buffer::ReadBuffer;
@@ -55,12 +57,23 @@ impl std::fmt::Display for TxId {
 #[derive(Copy, Clone)]
 pub(crate) struct FrameHeader {
     pub(crate) unit_id: UnitId,
-    pub(crate) tx_id: TxId,
+    // serial framings (RTU) have no transaction id
+    pub(crate) tx_id: Option<TxId>,
 }
 
 impl FrameHeader {
     pub(crate) fn new(unit_id: UnitId, tx_id: TxId) -> Self {
-        FrameHeader { unit_id, tx_id }
+        FrameHeader {
+            unit_id,
+            tx_id: Some(tx_id),
+        }
+    }
+
+    pub(crate) fn new_without_tx_id(unit_id: UnitId) -> Self {
+        FrameHeader {
+            unit_id,
+            tx_id: None,
+        }
     }
 }
 
@@ -109,6 +122,55 @@ pub(crate) trait FrameParser {
      * Ok(Some(..)) will contain a fully parsed frame and will advance the Cursor appropriately
      */
     fn parse(&mut self, cursor: &mut ReadBuffer) -> Result<Option<Frame>, RequestError>;
+
+    /**
+     * Discard any partially parsed state so that parsing starts over with the next byte
+     */
+    fn reset(&mut self);
+
+    /**
+     * Framings that are delimited by silence on the line (serial) return the minimum silent
+     * interval between frames. Stream based framings (TCP) return None.
+     */
+    fn silence_timeout(&self) -> Option<Duration> {
+        None
+    }
+
+    /**
+     * Returns false if the end of a request to the function can't be found, e.g. requests to
+     * user-defined functions in RTU framing without inter-frame silence. The parser then returns
+     * a frame that only contains the function code so that the request can be rejected.
+     */
+    fn can_frame_request(&self, _function: FunctionCode) -> bool {
+        true
+    }
+
+    /**
+     * Returns false if the end of a response to the function can't be found, e.g. responses to
+     * user-defined functions in RTU framing without inter-frame silence
+     */
+    fn can_frame_response(&self, _function: FunctionCode) -> bool {
+        true
+    }
+
+    /**
+     * Returns true if the partially received frame is completed or discarded when the line goes
+     * silent. Otherwise, the remaining bytes of a frame whose length is known are waited for
+     * regardless of the gaps between them.
+     */
+    fn is_delimited_by_silence(&self) -> bool {
+        self.silence_timeout().is_some()
+    }
+
+    /**
+     * Called when the line goes silent while a frame is partially received
+     *
+     * Ok(None) implies that the partially received data must be discarded
+     * Ok(Some(..)) will contain a frame that was completed by the silence
+     */
+    fn on_silence(&mut self, _cursor: &mut ReadBuffer) -> Result<Option<Frame>, RequestError> {
+        Ok(None)
+    }
 }
 
 pub(crate) trait FrameFormatter {
@@ -216,6 +278,8 @@ where
 {
     parser: T,
     buffer: ReadBuffer,
+    // after an error on a serial line, bytes are discarded until the line goes silent
+    discard_until_silence: bool,
 }
 
 impl<T: FrameParser> FramedReader<T> {
@@ -224,17 +288,82 @@ impl<T: FrameParser> FramedReader<T> {
         Self {
             parser,
             buffer: ReadBuffer::new(size),
+            discard_until_silence: false,
         }
     }
 
+    pub(crate) fn can_frame_request(&self, function: FunctionCode) -> bool {
+        self.parser.can_frame_request(function)
+    }
+
+    pub(crate) fn can_frame_response(&self, function: FunctionCode) -> bool {
+        self.parser.can_frame_response(function)
+    }
+
+    pub(crate) fn reset(&mut self) {
+        self.parser.reset();
+        self.buffer.reset();
+        self.discard_until_silence = self.parser.silence_timeout().is_some();
+    }
+
     pub(crate) async fn next_frame(&mut self, io: &mut PhysLayer) -> Result<Frame, RequestError> {
+        let silence = match self.parser.silence_timeout() {
+            Some(x) => x,
+            None => loop {
+                match self.parser.parse(&mut self.buffer)? {
+                    Some(frame) => return Ok(frame),
+                    None => {
+                        self.buffer.read_some(io).await?;
+                    }
+                }
+            },
+        };
+
         loop {
+            if self.discard_until_silence {
+                if self.read_before_silence(io, silence).await? {
+                    self.buffer.reset();
+                    continue;
+                }
+                self.discard_until_silence = false;
+            }
+
             match self.parser.parse(&mut self.buffer)? {
                 Some(frame) => return Ok(frame),
                 None => {
-                    self.buffer.read_some(io).await?;
+                    if self.buffer.is_empty() || !self.parser.is_delimited_by_silence() {
+                        // wait indefinitely for the start of the next frame, or for the rest of
+                        // a frame of known length
+                        self.buffer.read_some(io).await?;
+                    } else if !self.read_before_silence(io, silence).await? {
+                        if let Some(frame) = self.parser.on_silence(&mut self.buffer)? {
+                            return Ok(frame);
+                        }
+                        tracing::warn!(
+                            "discarding {} bytes of incomplete frame after inter-frame silence",
+                            self.buffer.len()
+                        );
+                        self.parser.reset();
+                        self.buffer.reset();
+                    }
                 }
             }
         }
     }
+
+    // returns false if the line was silent for the specified duration
+    async fn read_before_silence(
+        &mut self,
+        io: &mut PhysLayer,
+        silence: Duration,
+    ) -> Result<bool, RequestError> {
+        let deadline = crate::tokio::time::Instant::now() + silence;
+        crate::tokio::select! {
+            _ = crate::tokio::time::sleep_until(deadline) => Ok(false),
+            x = self.buffer.read_some(io) => {
+                x?;
+                Ok(true)
+            }
+        }
+    }
 }
@@ -7,21 +7,68 @@ mod constants {
     pub(crate) const READ_INPUT_REGISTERS: u8 = 4;
     pub(crate) const WRITE_SINGLE_COIL: u8 = 5;
     pub(crate) const WRITE_SINGLE_REGISTER: u8 = 6;
+    pub(crate) const READ_EXCEPTION_STATUS: u8 = 7;
+    pub(crate) const DIAGNOSTICS: u8 = 8;
+    pub(crate) const GET_COMM_EVENT_COUNTER: u8 = 11;
+    pub(crate) const GET_COMM_EVENT_LOG: u8 = 12;
     pub(crate) const WRITE_MULTIPLE_COILS: u8 = 15;
     pub(crate) const WRITE_MULTIPLE_REGISTERS: u8 = 16;
+    pub(crate) const REPORT_SERVER_ID: u8 = 17;
+    pub(crate) const READ_FILE_RECORD: u8 = 20;
+    pub(crate) const WRITE_FILE_RECORD: u8 = 21;
+    pub(crate) const MASK_WRITE_REGISTER: u8 = 22;
+    pub(crate) const READ_WRITE_MULTIPLE_REGISTERS: u8 = 23;
+    pub(crate) const READ_FIFO_QUEUE: u8 = 24;
+    pub(crate) const READ_DEVICE_IDENTIFICATION: u8 = 43;
+
+    // function codes reserved by the specification for user-defined functions
+    pub(crate) const USER_DEFINED_1: std::ops::RangeInclusive<u8> = 65..=72;
+    pub(crate) const USER_DEFINED_2: std::ops::RangeInclusive<u8> = 100..=110;
 }
 
-#[derive(Debug, Copy, Clone, PartialEq)]
-#[repr(u8)]
-pub(crate) enum FunctionCode {
-    ReadCoils = constants::READ_COILS,
-    ReadDiscreteInputs = constants::READ_DISCRETE_INPUTS,
-    ReadHoldingRegisters = constants::READ_HOLDING_REGISTERS,
-    ReadInputRegisters = constants::READ_INPUT_REGISTERS,
-    WriteSingleCoil = constants::WRITE_SINGLE_COIL,
-    WriteSingleRegister = constants::WRITE_SINGLE_REGISTER,
-    WriteMultipleCoils = constants::WRITE_MULTIPLE_COILS,
-    WriteMultipleRegisters = constants::WRITE_MULTIPLE_REGISTERS,
+/// Modbus function code of a request
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum FunctionCode {
+    /// Read Coils (0x01)
+    ReadCoils,
+    /// Read Discrete Inputs (0x02)
+    ReadDiscreteInputs,
+    /// Read Holding Registers (0x03)
+    ReadHoldingRegisters,
+    /// Read Input Registers (0x04)
+    ReadInputRegisters,
+    /// Write Single Coil (0x05)
+    WriteSingleCoil,
+    /// Write Single Register (0x06)
+    WriteSingleRegister,
+    /// Read Exception Status (0x07)
+    ReadExceptionStatus,
+    /// Diagnostics (0x08)
+    Diagnostics,
+    /// Get Comm Event Counter (0x0B)
+    GetCommEventCounter,
+    /// Get Comm Event Log (0x0C)
+    GetCommEventLog,
+    /// Write Multiple Coils (0x0F)
+    WriteMultipleCoils,
+    /// Write Multiple Registers (0x10)
+    WriteMultipleRegisters,
+    /// Report Server ID (0x11)
+    ReportServerId,
+    /// Read File Record (0x14)
+    ReadFileRecord,
+    /// Write File Record (0x15)
+    WriteFileRecord,
+    /// Mask Write Register (0x16)
+    MaskWriteRegister,
+    /// Read/Write Multiple Registers (0x17)
+    ReadWriteMultipleRegisters,
+    /// Read FIFO Queue (0x18)
+    ReadFifoQueue,
+    /// Read Device Identification (0x2B / MEI type 0x0E)
+    ReadDeviceIdentification,
+    /// User-defined function code (65-72 or 100-110)
+    Custom(u8),
 }
 
 impl Display for FunctionCode {
@@ -43,19 +90,86 @@ impl Display for FunctionCode {
             FunctionCode::WriteSingleRegister => {
                 write!(f, "WRITE SINGLE REGISTER ({:#04X})", self.get_value())
             }
+            FunctionCode::ReadExceptionStatus => {
+                write!(f, "READ EXCEPTION STATUS ({:#04X})", self.get_value())
+            }
+            FunctionCode::Diagnostics => write!(f, "DIAGNOSTICS ({:#04X})", self.get_value()),
+            FunctionCode::GetCommEventCounter => {
+                write!(f, "GET COMM EVENT COUNTER ({:#04X})", self.get_value())
+            }
+            FunctionCode::GetCommEventLog => {
+                write!(f, "GET COMM EVENT LOG ({:#04X})", self.get_value())
+            }
             FunctionCode::WriteMultipleCoils => {
                 write!(f, "WRITE MULTIPLE COILS ({:#04X})", self.get_value())
             }
             FunctionCode::WriteMultipleRegisters => {
                 write!(f, "WRITE MULTIPLE REGISTERS ({:#04X})", self.get_value())
             }
+            FunctionCode::ReportServerId => {
+                write!(f, "REPORT SERVER ID ({:#04X})", self.get_value())
+            }
+            FunctionCode::ReadFileRecord => {
+                write!(f, "READ FILE RECORD ({:#04X})", self.get_value())
+            }
+            FunctionCode::WriteFileRecord => {
+                write!(f, "WRITE FILE RECORD ({:#04X})", self.get_value())
+            }
+            FunctionCode::MaskWriteRegister => {
+                write!(f, "MASK WRITE REGISTER ({:#04X})", self.get_value())
+            }
+            FunctionCode::ReadWriteMultipleRegisters => {
+                write!(
+                    f,
+                    "READ/WRITE MULTIPLE REGISTERS ({:#04X})",
+                    self.get_value()
+                )
+            }
+            FunctionCode::ReadFifoQueue => write!(f, "READ FIFO QUEUE ({:#04X})", self.get_value()),
+            FunctionCode::ReadDeviceIdentification => {
+                write!(f, "READ DEVICE IDENTIFICATION ({:#04X})", self.get_value())
+            }
+            FunctionCode::Custom(_) => write!(f, "CUSTOM ({:#04X})", self.get_value()),
         }
     }
 }
 
 impl FunctionCode {
-    pub(crate) const fn get_value(self) -> u8 {
-        self as u8
+    /// Get the raw value of the function code
+    pub const fn get_value(self) -> u8 {
+        match self {
+            FunctionCode::ReadCoils => constants::READ_COILS,
+            FunctionCode::ReadDiscreteInputs => constants::READ_DISCRETE_INPUTS,
+            FunctionCode::ReadHoldingRegisters => constants::READ_HOLDING_REGISTERS,
+            FunctionCode::ReadInputRegisters => constants::READ_INPUT_REGISTERS,
+            FunctionCode::WriteSingleCoil => constants::WRITE_SINGLE_COIL,
+            FunctionCode::WriteSingleRegister => constants::WRITE_SINGLE_REGISTER,
+            FunctionCode::ReadExceptionStatus => constants::READ_EXCEPTION_STATUS,
+            FunctionCode::Diagnostics => constants::DIAGNOSTICS,
+            FunctionCode::GetCommEventCounter => constants::GET_COMM_EVENT_COUNTER,
+            FunctionCode::GetCommEventLog => constants::GET_COMM_EVENT_LOG,
+            FunctionCode::WriteMultipleCoils => constants::WRITE_MULTIPLE_COILS,
+            FunctionCode::WriteMultipleRegisters => constants::WRITE_MULTIPLE_REGISTERS,
+            FunctionCode::ReportServerId => constants::REPORT_SERVER_ID,
+            FunctionCode::ReadFileRecord => constants::READ_FILE_RECORD,
+            FunctionCode::WriteFileRecord => constants::WRITE_FILE_RECORD,
+            FunctionCode::MaskWriteRegister => constants::MASK_WRITE_REGISTER,
+            FunctionCode::ReadWriteMultipleRegisters => constants::READ_WRITE_MULTIPLE_REGISTERS,
+            FunctionCode::ReadFifoQueue => constants::READ_FIFO_QUEUE,
+            FunctionCode::ReadDeviceIdentification => constants::READ_DEVICE_IDENTIFICATION,
+            FunctionCode::Custom(value) => value,
+        }
+    }
+
+    /// Get the user-defined function code with the given value, if the value
+    /// lies in one of the ranges (65-72 or 100-110) reserved for user-defined functions
+    pub fn custom(value: u8) -> Option<Self> {
+        if constants::USER_DEFINED_1.contains(&value) || constants::USER_DEFINED_2.contains(&value)
+        {
+            Some(FunctionCode::Custom(value))
+        } else {
+            None
+        }
     }
 
     pub(crate) const fn as_error(self) -> u8 {
@@ -70,9 +184,22 @@ impl FunctionCode {
             constants::READ_INPUT_REGISTERS => Some(FunctionCode::ReadInputRegisters),
             constants::WRITE_SINGLE_COIL => Some(FunctionCode::WriteSingleCoil),
             constants::WRITE_SINGLE_REGISTER => Some(FunctionCode::WriteSingleRegister),
+            constants::READ_EXCEPTION_STATUS => Some(FunctionCode::ReadExceptionStatus),
+            constants::DIAGNOSTICS => Some(FunctionCode::Diagnostics),
+            constants::GET_COMM_EVENT_COUNTER => Some(FunctionCode::GetCommEventCounter),
+            constants::GET_COMM_EVENT_LOG => Some(FunctionCode::GetCommEventLog),
             constants::WRITE_MULTIPLE_COILS => Some(FunctionCode::WriteMultipleCoils),
             constants::WRITE_MULTIPLE_REGISTERS => Some(FunctionCode::WriteMultipleRegisters),
-            _ => None,
+            constants::REPORT_SERVER_ID => Some(FunctionCode::ReportServerId),
+            constants::READ_FILE_RECORD => Some(FunctionCode::ReadFileRecord),
+            constants::WRITE_FILE_RECORD => Some(FunctionCode::WriteFileRecord),
+            constants::MASK_WRITE_REGISTER => Some(FunctionCode::MaskWriteRegister),
+            constants::READ_WRITE_MULTIPLE_REGISTERS => {
+                Some(FunctionCode::ReadWriteMultipleRegisters)
+            }
+            constants::READ_FIFO_QUEUE => Some(FunctionCode::ReadFifoQueue),
+            constants::READ_DEVICE_IDENTIFICATION => Some(FunctionCode::ReadDeviceIdentification),
+            _ => Self::custom(value),
         }
     }
 }
@@ -1,7 +1,8 @@
 use crate::common::cursor::ReadCursor;
 use crate::common::traits::Parse;
+use crate::constants::limits::MAX_FIFO_COUNT;
 use crate::error::*;
-use crate::types::{coil_from_u16, AddressRange, Indexed};
+use crate::types::{coil_from_u16, AddressRange, FifoQueue, Indexed, MaskWriteRegister};
 
 impl Parse for AddressRange {
     fn parse(cursor: &mut ReadCursor) -> Result<Self, RequestError> {
@@ -27,6 +28,77 @@ impl Parse for Indexed<u16> {
     }
 }
 
+impl Parse for MaskWriteRegister {
+    fn parse(cursor: &mut ReadCursor) -> Result<Self, RequestError> {
+        Ok(MaskWriteRegister::new(
+            cursor.read_u16_be()?,
+            cursor.read_u16_be()?,
+            cursor.read_u16_be()?,
+        ))
+    }
+}
+
+impl Parse for FifoQueue {
+    fn parse(cursor: &mut ReadCursor) -> Result<Self, RequestError> {
+        // the byte count includes the FIFO count
+        let byte_count = cursor.read_u16_be()? as usize;
+        if byte_count != cursor.len() {
+            return Err(
+                AduParseError::InsufficientBytesForByteCount(byte_count, cursor.len()).into(),
+            );
+        }
+
+        let count = cursor.read_u16_be()?;
+        if count > MAX_FIFO_COUNT {
+            return Err(AduParseError::FifoCountTooLarge(count).into());
+        }
+
+        let values = cursor.read_bytes(2 * count as usize)?;
+        cursor.expect_empty()?;
+
+        Ok(FifoQueue::new(
+            values
+                .chunks_exact(2)
+                .map(|x| u16::from_be_bytes([x[0], x[1]]))
+                .collect(),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod fifo_queue {
+    use crate::common::cursor::ReadCursor;
+    use crate::common::traits::Parse;
+    use crate::error::AduParseError;
+    use crate::types::FifoQueue;
+
+    #[test]
+    fn parse_succeeds_for_valid_fifo_queue() {
+        let mut cursor = ReadCursor::new(&[0x00, 0x06, 0x00, 0x02, 0x01, 0xB8, 0x12, 0x84]);
+        let result = FifoQueue::parse(&mut cursor);
+        assert_eq!(result, Ok(FifoQueue::new(vec![0x01B8, 0x1284])));
+    }
+
+    #[test]
+    fn parse_fails_when_byte_count_does_not_match() {
+        let mut cursor = ReadCursor::new(&[0x00, 0x06, 0x00, 0x02, 0x01, 0xB8]);
+        let result = FifoQueue::parse(&mut cursor);
+        assert_eq!(
+            result,
+            Err(AduParseError::InsufficientBytesForByteCount(6, 4).into())
+        );
+    }
+
+    #[test]
+    fn parse_fails_for_fifo_count_above_maximum() {
+        let mut bytes = vec![0x00, 0x42, 0x00, 0x20];
+        bytes.extend_from_slice(&[0x00; 64]);
+        let mut cursor = ReadCursor::new(&bytes);
+        let result = FifoQueue::parse(&mut cursor);
+        assert_eq!(result, Err(AduParseError::FifoCountTooLarge(0x20).into()));
+    }
+}
+
 #[cfg(test)]
 mod coils {
     use crate::common::cursor::ReadCursor;
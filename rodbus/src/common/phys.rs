@@ -10,6 +10,8 @@ pub(crate) struct PhysLayer {
 // encapsulates all possible physical layers as an enum
 pub(crate) enum PhysLayerImpl {
     Tcp(crate::tokio::net::TcpStream),
+    Serial(tokio_serial::SerialStream),
+    Tls(Box<tokio_rustls::TlsStream<crate::tokio::net::TcpStream>>),
     #[cfg(test)]
     Mock(tokio_mock::mock::test::io::MockIO),
 }
@@ -18,6 +20,8 @@ impl std::fmt::Debug for PhysLayer {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
         match self.layer {
             PhysLayerImpl::Tcp(_) => f.write_str("Tcp"),
+            PhysLayerImpl::Serial(_) => f.write_str("Serial"),
+            PhysLayerImpl::Tls(_) => f.write_str("Tls"),
             #[cfg(test)]
             PhysLayerImpl::Mock(_) => f.write_str("Mock"),
         }
@@ -32,6 +36,23 @@ impl PhysLayer {
         }
     }
 
+    pub(crate) fn new_serial(stream: tokio_serial::SerialStream, level: PhysDecodeLevel) -> Self {
+        Self {
+            layer: PhysLayerImpl::Serial(stream),
+            level,
+        }
+    }
+
+    pub(crate) fn new_tls(
+        stream: tokio_rustls::TlsStream<crate::tokio::net::TcpStream>,
+        level: PhysDecodeLevel,
+    ) -> Self {
+        Self {
+            layer: PhysLayerImpl::Tls(Box::new(stream)),
+            level,
+        }
+    }
+
     #[cfg(test)]
     pub(crate) fn new_mock(
         mock: tokio_mock::mock::test::io::MockIO,
@@ -46,6 +67,8 @@ impl PhysLayer {
     pub(crate) async fn read(&mut self, buffer: &mut [u8]) -> Result<usize, std::io::Error> {
         let length = match &mut self.layer {
             PhysLayerImpl::Tcp(x) => x.read(buffer).await?,
+            PhysLayerImpl::Serial(x) => x.read(buffer).await?,
+            PhysLayerImpl::Tls(x) => x.read(buffer).await?,
             #[cfg(test)]
             PhysLayerImpl::Mock(x) => x.read(buffer).await?,
         };
@@ -66,6 +89,12 @@ impl PhysLayer {
 
         match &mut self.layer {
             PhysLayerImpl::Tcp(x) => x.write_all(data).await,
+            PhysLayerImpl::Serial(x) => x.write_all(data).await,
+            PhysLayerImpl::Tls(x) => {
+                // encrypted records may remain buffered in the session until flushed
+                x.write_all(data).await?;
+                x.flush().await
+            }
             #[cfg(test)]
             PhysLayerImpl::Mock(x) => x.write_all(data).await,
         }
@@ -9,8 +9,8 @@ use crate::common::traits::Serialize;
 use crate::error::{InternalError, RequestError};
 use crate::server::response::{BitWriter, RegisterWriter};
 use crate::types::{
-    coil_from_u16, coil_to_u16, AddressRange, BitIterator, BitIteratorDisplay, Indexed,
-    RegisterIterator, RegisterIteratorDisplay,
+    coil_from_u16, coil_to_u16, AddressRange, BitIterator, BitIteratorDisplay, FifoQueue,
+    FifoQueueDisplay, Indexed, MaskWriteRegister, RegisterIterator, RegisterIteratorDisplay,
 };
 
 pub(crate) fn calc_bytes_for_bits(num_bits: usize) -> Result<u8, InternalError> {
@@ -134,6 +134,34 @@ impl Loggable for Indexed<u16> {
     }
 }
 
+impl Serialize for MaskWriteRegister {
+    fn serialize(&self, cursor: &mut WriteCursor) -> Result<(), RequestError> {
+        cursor.write_u16_be(self.address)?;
+        cursor.write_u16_be(self.and_mask)?;
+        cursor.write_u16_be(self.or_mask)?;
+        Ok(())
+    }
+}
+
+impl Loggable for MaskWriteRegister {
+    fn log(
+        &self,
+        payload: &[u8],
+        level: crate::decode::PduDecodeLevel,
+        f: &mut std::fmt::Formatter,
+    ) -> std::fmt::Result {
+        if level.data_headers() {
+            let mut cursor = ReadCursor::new(payload);
+
+            if let Ok(value) = MaskWriteRegister::parse(&mut cursor) {
+                write!(f, "{}", value)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
 impl Serialize for &[bool] {
     fn serialize(&self, cursor: &mut WriteCursor) -> Result<(), RequestError> {
         // how many bytes should we have?
@@ -263,6 +291,42 @@ where
     }
 }
 
+impl Serialize for FifoQueue {
+    fn serialize(&self, cursor: &mut WriteCursor) -> Result<(), RequestError> {
+        // the byte count includes the FIFO count
+        let count = self.values.len();
+        let byte_count =
+            u16::try_from(2 + 2 * count).map_err(|_| InternalError::BadByteCount(count))?;
+        cursor.write_u16_be(byte_count)?;
+        cursor.write_u16_be(count as u16)?;
+
+        for value in self.values.iter() {
+            cursor.write_u16_be(*value)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Loggable for FifoQueue {
+    fn log(
+        &self,
+        payload: &[u8],
+        level: crate::decode::PduDecodeLevel,
+        f: &mut std::fmt::Formatter,
+    ) -> std::fmt::Result {
+        if level.data_headers() {
+            let mut cursor = ReadCursor::new(payload);
+
+            if let Ok(queue) = FifoQueue::parse(&mut cursor) {
+                write!(f, "{}", FifoQueueDisplay::new(level, &queue))?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
 impl Serialize for &[u16] {
     fn serialize(&self, cursor: &mut WriteCursor) -> Result<(), RequestError> {
         let num_bytes = calc_bytes_for_registers(self.len())?;
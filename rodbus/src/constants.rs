@@ -16,6 +16,44 @@ pub mod limits {
     pub const MAX_WRITE_COILS_COUNT: u16 = 0x07B0;
     /// Maximum count allowed in a `write multiple registers` request
     pub const MAX_WRITE_REGISTERS_COUNT: u16 = 0x007B;
+    /// Maximum count of registers written by a `read/write multiple registers` request
+    pub const MAX_READ_WRITE_WRITE_COUNT: u16 = 0x0079;
+    /// Maximum length of a single object returned by `read device identification`
+    pub const MAX_DEVICE_ID_OBJECT_LENGTH: usize = 0xF4;
+    /// Maximum count of values returned by a `read FIFO queue` request
+    pub const MAX_FIFO_COUNT: u16 = 0x001F;
+    /// Maximum record number in a `read/write file record` request
+    pub const MAX_FILE_RECORD_NUMBER: u16 = 0x270F;
+    /// Maximum count of events returned by a `get comm event log` request
+    pub const MAX_COMM_EVENTS: usize = 64;
+    /// Maximum length of the device specific data returned by a `report server id` request
+    pub const MAX_SERVER_ID_LENGTH: usize = 0xFB;
+    /// Maximum length of the data following the function code in a custom function request or response
+    pub const MAX_CUSTOM_FUNCTION_DATA_LENGTH: usize = 0xFC;
+}
+
+/// Object ids used by `read device identification`
+pub mod device_id {
+    /// Vendor name (basic, mandatory)
+    pub const VENDOR_NAME: u8 = 0x00;
+    /// Product code (basic, mandatory)
+    pub const PRODUCT_CODE: u8 = 0x01;
+    /// Major and minor revision (basic, mandatory)
+    pub const MAJOR_MINOR_REVISION: u8 = 0x02;
+    /// Vendor URL (regular, optional)
+    pub const VENDOR_URL: u8 = 0x03;
+    /// Product name (regular, optional)
+    pub const PRODUCT_NAME: u8 = 0x04;
+    /// Model name (regular, optional)
+    pub const MODEL_NAME: u8 = 0x05;
+    /// User application name (regular, optional)
+    pub const USER_APPLICATION_NAME: u8 = 0x06;
+    /// Last object id of the basic category
+    pub const LAST_BASIC: u8 = 0x02;
+    /// Last object id of the regular category
+    pub const LAST_REGULAR: u8 = 0x7F;
+    /// First object id of the extended (vendor specific) category
+    pub const FIRST_EXTENDED: u8 = 0x80;
 }
 
 /// Modbus exception codes
@@ -39,3 +77,11 @@ pub mod exceptions {
     /// Specialized use in conjunction with gateways, indicates that no response was obtained from the target device. Usually means that the device is not present on the network.
     pub const GATEWAY_TARGET_DEVICE_FAILED_TO_RESPOND: u8 = 0x0B;
 }
+
+/// Broadcast requests on serial and RTU over TCP links
+pub mod broadcast {
+    /// Unit id of the requests that are processed by every server without a response
+    pub const UNIT_ID: u8 = 0x00;
+    /// Default time that a client waits after sending a broadcast write before sending the next request
+    pub const DEFAULT_TURNAROUND_DELAY: std::time::Duration = std::time::Duration::from_millis(100);
+}
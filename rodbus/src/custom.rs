@@ -0,0 +1,122 @@
+use crate::common::cursor::{ReadCursor, WriteCursor};
+use crate::common::function::FunctionCode;
+use crate::common::traits::{Loggable, Parse, Serialize};
+use crate::constants::limits::MAX_CUSTOM_FUNCTION_DATA_LENGTH;
+use crate::decode::PduDecodeLevel;
+use crate::error::{InternalError, InvalidRequest, RequestError};
+
+/// Raw data following the function code of a custom function request or response
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct CustomFunctionData {
+    pub(crate) data: Vec<u8>,
+}
+
+/// Validated custom function request sent by the client
+pub(crate) struct CustomFunctionRequest {
+    pub(crate) function: FunctionCode,
+    pub(crate) data: CustomFunctionData,
+}
+
+impl CustomFunctionRequest {
+    pub(crate) fn new(function: u8, data: Vec<u8>) -> Result<Self, InvalidRequest> {
+        let function =
+            FunctionCode::get(function).ok_or(InvalidRequest::UnsupportedFunctionCode(function))?;
+        if data.len() > MAX_CUSTOM_FUNCTION_DATA_LENGTH {
+            return Err(InvalidRequest::CustomFunctionDataTooLong(data.len()));
+        }
+        Ok(Self {
+            function,
+            data: CustomFunctionData { data },
+        })
+    }
+}
+
+impl Parse for CustomFunctionData {
+    fn parse(cursor: &mut ReadCursor) -> Result<Self, RequestError> {
+        Ok(Self {
+            data: cursor.read_bytes(cursor.len())?.to_vec(),
+        })
+    }
+}
+
+impl Serialize for CustomFunctionData {
+    fn serialize(&self, cursor: &mut WriteCursor) -> Result<(), RequestError> {
+        if self.data.len() > MAX_CUSTOM_FUNCTION_DATA_LENGTH {
+            return Err(InternalError::BadByteCount(self.data.len()).into());
+        }
+        cursor.write_bytes(&self.data)?;
+        Ok(())
+    }
+}
+
+impl Loggable for CustomFunctionData {
+    fn log(
+        &self,
+        _payload: &[u8],
+        level: PduDecodeLevel,
+        f: &mut std::fmt::Formatter,
+    ) -> std::fmt::Result {
+        if level.data_headers() {
+            write!(f, "{}", CustomFunctionDataDisplay::new(level, &self.data))?;
+        }
+        Ok(())
+    }
+}
+
+pub(crate) struct CustomFunctionDataDisplay<'a> {
+    level: PduDecodeLevel,
+    data: &'a [u8],
+}
+
+impl<'a> CustomFunctionDataDisplay<'a> {
+    pub(crate) fn new(level: PduDecodeLevel, data: &'a [u8]) -> Self {
+        Self { level, data }
+    }
+}
+
+impl std::fmt::Display for CustomFunctionDataDisplay<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "length: {}", self.data.len())?;
+        if self.level.data_values() {
+            for x in self.data.iter() {
+                write!(f, " {:02X}", x)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn accepts_standard_and_user_defined_function_codes() {
+        let request = CustomFunctionRequest::new(0x41, vec![0xCA, 0xFE]).unwrap();
+        assert_eq!(request.function, FunctionCode::Custom(0x41));
+        assert_eq!(request.data.data, vec![0xCA, 0xFE]);
+        let request = CustomFunctionRequest::new(0x03, vec![0x00, 0x00, 0x00, 0x01]).unwrap();
+        assert_eq!(request.function, FunctionCode::ReadHoldingRegisters);
+    }
+
+    #[test]
+    fn rejects_unassigned_and_exception_function_codes() {
+        for code in [0x09, 0x49, 0x63, 0x6F, 0xC1] {
+            assert_eq!(
+                CustomFunctionRequest::new(code, Vec::new()).err(),
+                Some(InvalidRequest::UnsupportedFunctionCode(code))
+            );
+        }
+    }
+
+    #[test]
+    fn rejects_data_that_does_not_fit_in_a_request() {
+        assert!(CustomFunctionRequest::new(0x64, vec![0; MAX_CUSTOM_FUNCTION_DATA_LENGTH]).is_ok());
+        assert_eq!(
+            CustomFunctionRequest::new(0x64, vec![0; MAX_CUSTOM_FUNCTION_DATA_LENGTH + 1]).err(),
+            Some(InvalidRequest::CustomFunctionDataTooLong(
+                MAX_CUSTOM_FUNCTION_DATA_LENGTH + 1
+            ))
+        );
+    }
+}
@@ -0,0 +1,560 @@
+use std::collections::BTreeMap;
+
+use crate::common::cursor::{ReadCursor, WriteCursor};
+use crate::common::traits::{Loggable, Parse, Serialize};
+use crate::constants::device_id::*;
+use crate::constants::limits::MAX_DEVICE_ID_OBJECT_LENGTH;
+use crate::decode::PduDecodeLevel;
+use crate::error::{AduParseError, InvalidRequest, RequestError};
+use crate::exception::ExceptionCode;
+
+// MEI type of read device identification within function code 43
+pub(crate) const MEI_TYPE_READ_DEVICE_ID: u8 = 0x0E;
+
+// bytes available for objects in a response: 253 byte PDU minus function code,
+// MEI type, device id code, conformity level, more follows, next object id, and count
+const MAX_OBJECTS_LENGTH: usize = 246;
+
+/// Access type of a `read device identification` request
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ReadDeviceIdCode {
+    /// Stream access to the basic objects (vendor name, product code, and revision)
+    Basic,
+    /// Stream access to the basic and regular objects
+    Regular,
+    /// Stream access to the basic, regular, and extended objects
+    Extended,
+    /// Access to one specific object
+    Individual,
+}
+
+impl ReadDeviceIdCode {
+    pub(crate) fn get_value(self) -> u8 {
+        match self {
+            ReadDeviceIdCode::Basic => 0x01,
+            ReadDeviceIdCode::Regular => 0x02,
+            ReadDeviceIdCode::Extended => 0x03,
+            ReadDeviceIdCode::Individual => 0x04,
+        }
+    }
+
+    pub(crate) fn get(value: u8) -> Option<Self> {
+        match value {
+            0x01 => Some(ReadDeviceIdCode::Basic),
+            0x02 => Some(ReadDeviceIdCode::Regular),
+            0x03 => Some(ReadDeviceIdCode::Extended),
+            0x04 => Some(ReadDeviceIdCode::Individual),
+            _ => None,
+        }
+    }
+
+    // last object id returned by a stream access
+    fn last_object_id(self) -> u8 {
+        match self {
+            ReadDeviceIdCode::Basic => LAST_BASIC,
+            ReadDeviceIdCode::Regular => LAST_REGULAR,
+            ReadDeviceIdCode::Extended | ReadDeviceIdCode::Individual => u8::MAX,
+        }
+    }
+}
+
+/// Identification objects of a device
+///
+/// Returned by the client when reading device identification, and registered on the
+/// server per unit id with [`ServerHandlerMap::set_device_identification`](crate::server::ServerHandlerMap::set_device_identification).
+/// Object ids are defined in [`constants::device_id`](crate::constants::device_id).
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct DeviceIdentification {
+    conformity_level: Option<u8>,
+    objects: BTreeMap<u8, Vec<u8>>,
+}
+
+impl DeviceIdentification {
+    /// Create an identification from the mandatory basic objects
+    pub fn new(
+        vendor_name: &str,
+        product_code: &str,
+        revision: &str,
+    ) -> Result<Self, InvalidRequest> {
+        Self::default()
+            .with_object(VENDOR_NAME, vendor_name.as_bytes())?
+            .with_object(PRODUCT_CODE, product_code.as_bytes())?
+            .with_object(MAJOR_MINOR_REVISION, revision.as_bytes())
+    }
+
+    /// Add an object, replacing any existing object with the same id
+    ///
+    /// Each object must fit in a single response, i.e. be no longer than
+    /// [`MAX_DEVICE_ID_OBJECT_LENGTH`](crate::constants::limits::MAX_DEVICE_ID_OBJECT_LENGTH)
+    pub fn with_object(mut self, id: u8, value: &[u8]) -> Result<Self, InvalidRequest> {
+        if value.len() > MAX_DEVICE_ID_OBJECT_LENGTH {
+            return Err(InvalidRequest::DeviceIdObjectTooLong(id, value.len()));
+        }
+        self.objects.insert(id, value.to_vec());
+        Ok(self)
+    }
+
+    /// Conformity level reported by the server, `None` if not read from a device
+    pub fn conformity_level(&self) -> Option<u8> {
+        self.conformity_level
+    }
+
+    /// Vendor name object, if present and valid UTF-8
+    pub fn vendor_name(&self) -> Option<&str> {
+        self.get_str(VENDOR_NAME)
+    }
+
+    /// Product code object, if present and valid UTF-8
+    pub fn product_code(&self) -> Option<&str> {
+        self.get_str(PRODUCT_CODE)
+    }
+
+    /// Major and minor revision object, if present and valid UTF-8
+    pub fn revision(&self) -> Option<&str> {
+        self.get_str(MAJOR_MINOR_REVISION)
+    }
+
+    /// Vendor URL object, if present and valid UTF-8
+    pub fn vendor_url(&self) -> Option<&str> {
+        self.get_str(VENDOR_URL)
+    }
+
+    /// Product name object, if present and valid UTF-8
+    pub fn product_name(&self) -> Option<&str> {
+        self.get_str(PRODUCT_NAME)
+    }
+
+    /// Model name object, if present and valid UTF-8
+    pub fn model_name(&self) -> Option<&str> {
+        self.get_str(MODEL_NAME)
+    }
+
+    /// User application name object, if present and valid UTF-8
+    pub fn user_application_name(&self) -> Option<&str> {
+        self.get_str(USER_APPLICATION_NAME)
+    }
+
+    /// Raw value of any object
+    pub fn get(&self, id: u8) -> Option<&[u8]> {
+        self.objects.get(&id).map(|x| x.as_slice())
+    }
+
+    /// Iterate over the objects that are not defined by the specification (ids `0x07` and above)
+    pub fn custom_objects(&self) -> impl Iterator<Item = (u8, &[u8])> {
+        self.objects
+            .range(USER_APPLICATION_NAME + 1..)
+            .map(|(id, value)| (*id, value.as_slice()))
+    }
+
+    fn get_str(&self, id: u8) -> Option<&str> {
+        self.get(id).and_then(|x| std::str::from_utf8(x).ok())
+    }
+
+    // merge the objects of a response into an identification read from a device
+    pub(crate) fn extend(&mut self, page: DeviceIdPage) {
+        self.conformity_level = Some(page.conformity_level);
+        self.objects.extend(page.objects);
+    }
+
+    // conformity level implied by the categories of the objects that are present
+    fn implied_conformity_level(&self) -> u8 {
+        let category = match self.objects.keys().next_back() {
+            Some(id) if *id >= FIRST_EXTENDED => ReadDeviceIdCode::Extended,
+            Some(id) if *id > LAST_BASIC => ReadDeviceIdCode::Regular,
+            _ => ReadDeviceIdCode::Basic,
+        };
+        // individual access is always supported
+        category.get_value() | 0x80
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub(crate) struct ReadDeviceIdRequest {
+    pub(crate) code: ReadDeviceIdCode,
+    pub(crate) object_id: u8,
+}
+
+impl ReadDeviceIdRequest {
+    pub(crate) fn new(code: ReadDeviceIdCode, object_id: u8) -> Self {
+        Self { code, object_id }
+    }
+}
+
+impl Parse for ReadDeviceIdRequest {
+    fn parse(cursor: &mut ReadCursor) -> Result<Self, RequestError> {
+        let mei_type = cursor.read_u8()?;
+        if mei_type != MEI_TYPE_READ_DEVICE_ID {
+            return Err(AduParseError::UnknownMeiType(mei_type).into());
+        }
+        let code = cursor.read_u8()?;
+        let code =
+            ReadDeviceIdCode::get(code).ok_or(AduParseError::UnknownReadDeviceIdCode(code))?;
+        let object_id = cursor.read_u8()?;
+        Ok(Self::new(code, object_id))
+    }
+}
+
+impl Serialize for ReadDeviceIdRequest {
+    fn serialize(&self, cursor: &mut WriteCursor) -> Result<(), RequestError> {
+        cursor.write_u8(MEI_TYPE_READ_DEVICE_ID)?;
+        cursor.write_u8(self.code.get_value())?;
+        cursor.write_u8(self.object_id)?;
+        Ok(())
+    }
+}
+
+impl std::fmt::Display for ReadDeviceIdRequest {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "code: {:?} object: {:#04X}", self.code, self.object_id)
+    }
+}
+
+pub(crate) struct DeviceIdResponse<'a> {
+    pub(crate) code: ReadDeviceIdCode,
+    pub(crate) conformity_level: u8,
+    pub(crate) next_object_id: Option<u8>,
+    pub(crate) objects: Vec<(u8, &'a [u8])>,
+}
+
+impl<'a> DeviceIdResponse<'a> {
+    // select the objects returned by the server for a request
+    pub(crate) fn build(
+        identification: &'a DeviceIdentification,
+        request: ReadDeviceIdRequest,
+    ) -> Result<Self, ExceptionCode> {
+        let conformity_level = identification.implied_conformity_level();
+
+        if request.code == ReadDeviceIdCode::Individual {
+            let value = identification
+                .objects
+                .get(&request.object_id)
+                .ok_or(ExceptionCode::IllegalDataAddress)?;
+            return Ok(Self {
+                code: request.code,
+                conformity_level,
+                next_object_id: None,
+                objects: vec![(request.object_id, value.as_slice())],
+            });
+        }
+
+        let last = request.code.last_object_id();
+        // the stream restarts at the first object if the requested object doesn't exist
+        let first = if request.object_id <= last
+            && identification.objects.contains_key(&request.object_id)
+        {
+            request.object_id
+        } else {
+            VENDOR_NAME
+        };
+
+        let mut length = 0;
+        let mut objects = Vec::new();
+        for (id, value) in identification.objects.range(first..=last) {
+            length += 2 + value.len();
+            if length > MAX_OBJECTS_LENGTH {
+                return Ok(Self {
+                    code: request.code,
+                    conformity_level,
+                    next_object_id: Some(*id),
+                    objects,
+                });
+            }
+            objects.push((*id, value.as_slice()));
+        }
+
+        Ok(Self {
+            code: request.code,
+            conformity_level,
+            next_object_id: None,
+            objects,
+        })
+    }
+
+    pub(crate) fn parse(cursor: &mut ReadCursor<'a>) -> Result<Self, RequestError> {
+        let mei_type = cursor.read_u8()?;
+        if mei_type != MEI_TYPE_READ_DEVICE_ID {
+            return Err(AduParseError::UnknownMeiType(mei_type).into());
+        }
+        let code = cursor.read_u8()?;
+        let code =
+            ReadDeviceIdCode::get(code).ok_or(AduParseError::UnknownReadDeviceIdCode(code))?;
+        let conformity_level = cursor.read_u8()?;
+        let more_follows = match cursor.read_u8()? {
+            0x00 => false,
+            0xFF => true,
+            x => return Err(AduParseError::UnknownMoreFollows(x).into()),
+        };
+        let next_object_id = cursor.read_u8()?;
+        let count = cursor.read_u8()?;
+
+        let mut objects = Vec::with_capacity(count as usize);
+        for _ in 0..count {
+            let id = cursor.read_u8()?;
+            let length = cursor.read_u8()?;
+            objects.push((id, cursor.read_bytes(length as usize)?));
+        }
+        cursor.expect_empty()?;
+
+        Ok(Self {
+            code,
+            conformity_level,
+            next_object_id: if more_follows {
+                Some(next_object_id)
+            } else {
+                None
+            },
+            objects,
+        })
+    }
+}
+
+// owned copy of a response that is returned to the channel
+pub(crate) struct DeviceIdPage {
+    pub(crate) conformity_level: u8,
+    pub(crate) next_object_id: Option<u8>,
+    pub(crate) objects: Vec<(u8, Vec<u8>)>,
+}
+
+impl From<DeviceIdResponse<'_>> for DeviceIdPage {
+    fn from(response: DeviceIdResponse) -> Self {
+        Self {
+            conformity_level: response.conformity_level,
+            next_object_id: response.next_object_id,
+            objects: response
+                .objects
+                .into_iter()
+                .map(|(id, value)| (id, value.to_vec()))
+                .collect(),
+        }
+    }
+}
+
+impl Serialize for DeviceIdResponse<'_> {
+    fn serialize(&self, cursor: &mut WriteCursor) -> Result<(), RequestError> {
+        cursor.write_u8(MEI_TYPE_READ_DEVICE_ID)?;
+        cursor.write_u8(self.code.get_value())?;
+        cursor.write_u8(self.conformity_level)?;
+        match self.next_object_id {
+            Some(id) => {
+                cursor.write_u8(0xFF)?;
+                cursor.write_u8(id)?;
+            }
+            None => {
+                cursor.write_u8(0x00)?;
+                cursor.write_u8(0x00)?;
+            }
+        }
+        // the count can't overflow because the objects fit in a single PDU
+        cursor.write_u8(self.objects.len() as u8)?;
+        for (id, value) in self.objects.iter() {
+            cursor.write_u8(*id)?;
+            cursor.write_u8(value.len() as u8)?;
+            cursor.write_bytes(value)?;
+        }
+        Ok(())
+    }
+}
+
+impl Loggable for DeviceIdResponse<'_> {
+    fn log(
+        &self,
+        _payload: &[u8],
+        level: PduDecodeLevel,
+        f: &mut std::fmt::Formatter,
+    ) -> std::fmt::Result {
+        if level.data_headers() {
+            write!(f, "{}", DeviceIdResponseDisplay::new(level, self))?;
+        }
+        Ok(())
+    }
+}
+
+pub(crate) struct DeviceIdResponseDisplay<'a, 'b> {
+    level: PduDecodeLevel,
+    response: &'a DeviceIdResponse<'b>,
+}
+
+impl<'a, 'b> DeviceIdResponseDisplay<'a, 'b> {
+    pub(crate) fn new(level: PduDecodeLevel, response: &'a DeviceIdResponse<'b>) -> Self {
+        Self { level, response }
+    }
+}
+
+impl std::fmt::Display for DeviceIdResponseDisplay<'_, '_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(
+            f,
+            "code: {:?} conformity: {:#04X} count: {}",
+            self.response.code,
+            self.response.conformity_level,
+            self.response.objects.len()
+        )?;
+        if let Some(next) = self.response.next_object_id {
+            write!(f, " next: {:#04X}", next)?;
+        }
+        if self.level.data_values() {
+            for (id, value) in self.response.objects.iter() {
+                write!(
+                    f,
+                    "\nobject: {:#04X} value: {}",
+                    id,
+                    String::from_utf8_lossy(value)
+                )?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn identification() -> DeviceIdentification {
+        DeviceIdentification::new("ACME", "X-100", "V1.2")
+            .unwrap()
+            .with_object(PRODUCT_NAME, b"widget")
+            .unwrap()
+            .with_object(0x80, &[0xCA, 0xFE])
+            .unwrap()
+    }
+
+    fn serialize(response: &DeviceIdResponse) -> Vec<u8> {
+        let mut buffer = [0u8; 256];
+        let mut cursor = WriteCursor::new(&mut buffer);
+        response.serialize(&mut cursor).unwrap();
+        let length = cursor.position();
+        buffer[..length].to_vec()
+    }
+
+    #[test]
+    fn rejects_object_that_does_not_fit_in_a_response() {
+        assert_eq!(
+            DeviceIdentification::default()
+                .with_object(0x80, &[0; MAX_DEVICE_ID_OBJECT_LENGTH + 1])
+                .unwrap_err(),
+            InvalidRequest::DeviceIdObjectTooLong(0x80, MAX_DEVICE_ID_OBJECT_LENGTH + 1)
+        );
+    }
+
+    #[test]
+    fn provides_standard_and_custom_objects() {
+        let identification = identification();
+        assert_eq!(identification.vendor_name(), Some("ACME"));
+        assert_eq!(identification.product_code(), Some("X-100"));
+        assert_eq!(identification.revision(), Some("V1.2"));
+        assert_eq!(identification.product_name(), Some("widget"));
+        assert_eq!(identification.model_name(), None);
+        assert_eq!(
+            identification.custom_objects().collect::<Vec<_>>(),
+            vec![(0x80, [0xCA, 0xFE].as_ref())]
+        );
+    }
+
+    #[test]
+    fn serializes_basic_stream_response() {
+        let identification = identification();
+        let response = DeviceIdResponse::build(
+            &identification,
+            ReadDeviceIdRequest::new(ReadDeviceIdCode::Basic, 0),
+        )
+        .unwrap();
+        assert_eq!(
+            serialize(&response),
+            vec![
+                0x0E, 0x01, 0x83, 0x00, 0x00, 0x03, 0x00, 0x04, b'A', b'C', b'M', b'E', 0x01, 0x05,
+                b'X', b'-', b'1', b'0', b'0', 0x02, 0x04, b'V', b'1', b'.', b'2'
+            ]
+        );
+    }
+
+    #[test]
+    fn restarts_stream_at_first_object_when_object_is_unknown() {
+        let identification = identification();
+        let response = DeviceIdResponse::build(
+            &identification,
+            ReadDeviceIdRequest::new(ReadDeviceIdCode::Regular, 0x05),
+        )
+        .unwrap();
+        assert_eq!(
+            response.objects.iter().map(|x| x.0).collect::<Vec<_>>(),
+            vec![0x00, 0x01, 0x02, 0x04]
+        );
+    }
+
+    #[test]
+    fn splits_stream_that_does_not_fit_in_a_response() {
+        let identification = DeviceIdentification::new("ACME", "X-100", "V1.2")
+            .unwrap()
+            .with_object(0x80, &[0; 200])
+            .unwrap()
+            .with_object(0x81, &[0; 200])
+            .unwrap();
+        let request = ReadDeviceIdRequest::new(ReadDeviceIdCode::Extended, 0);
+        let response = DeviceIdResponse::build(&identification, request).unwrap();
+        assert_eq!(response.next_object_id, Some(0x81));
+        assert_eq!(
+            response.objects.iter().map(|x| x.0).collect::<Vec<_>>(),
+            vec![0x00, 0x01, 0x02, 0x80]
+        );
+
+        let response = DeviceIdResponse::build(
+            &identification,
+            ReadDeviceIdRequest::new(ReadDeviceIdCode::Extended, 0x81),
+        )
+        .unwrap();
+        assert_eq!(response.next_object_id, None);
+        assert_eq!(response.objects.len(), 1);
+    }
+
+    #[test]
+    fn individual_access_to_missing_object_is_illegal_data_address() {
+        assert_eq!(
+            DeviceIdResponse::build(
+                &identification(),
+                ReadDeviceIdRequest::new(ReadDeviceIdCode::Individual, 0x03),
+            )
+            .err(),
+            Some(ExceptionCode::IllegalDataAddress)
+        );
+    }
+
+    #[test]
+    fn parses_response_with_more_follows() {
+        let bytes = [
+            0x0E, 0x03, 0x83, 0xFF, 0x81, 0x02, 0x00, 0x01, b'A', 0x80, 0x02, 0xCA, 0xFE,
+        ];
+        let mut cursor = ReadCursor::new(&bytes);
+        let response = DeviceIdResponse::parse(&mut cursor).unwrap();
+        assert_eq!(response.code, ReadDeviceIdCode::Extended);
+        assert_eq!(response.conformity_level, 0x83);
+        assert_eq!(response.next_object_id, Some(0x81));
+        assert_eq!(
+            response.objects,
+            vec![(0x00, [b'A'].as_ref()), (0x80, [0xCA, 0xFE].as_ref())]
+        );
+    }
+
+    #[test]
+    fn rejects_response_with_bad_more_follows() {
+        let bytes = [0x0E, 0x01, 0x81, 0x01, 0x00, 0x00];
+        let mut cursor = ReadCursor::new(&bytes);
+        assert_eq!(
+            DeviceIdResponse::parse(&mut cursor).err(),
+            Some(RequestError::BadResponse(
+                AduParseError::UnknownMoreFollows(0x01)
+            ))
+        );
+    }
+
+    #[test]
+    fn rejects_request_with_unknown_mei_type() {
+        let mut cursor = ReadCursor::new(&[0x0D, 0x01, 0x00]);
+        assert_eq!(
+            ReadDeviceIdRequest::parse(&mut cursor).err(),
+            Some(RequestError::BadResponse(AduParseError::UnknownMeiType(
+                0x0D
+            )))
+        );
+    }
+}
@@ -0,0 +1,613 @@
+use crate::common::cursor::{ReadCursor, WriteCursor};
+use crate::common::traits::{Loggable, Parse, Serialize};
+use crate::constants::limits::{MAX_COMM_EVENTS, MAX_SERVER_ID_LENGTH};
+use crate::decode::PduDecodeLevel;
+use crate::error::{AduParseError, InternalError, RequestError};
+
+// status word reported when the server is not busy processing a program command
+pub(crate) const STATUS_NOT_BUSY: u16 = 0x0000;
+
+// status word, event count, and message count preceding the events of a comm event log
+const COMM_EVENT_LOG_HEADER_LENGTH: usize = 6;
+
+/// Sub-function of a `diagnostics` request (function code 0x08)
+///
+/// All sub-functions except [`DiagnosticSubFunction::ReturnQueryData`] and
+/// [`DiagnosticSubFunction::RestartCommunications`] expect a data field of `0x0000`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum DiagnosticSubFunction {
+    /// Echo the data field back to the client
+    ReturnQueryData,
+    /// Restart the serial line port and clear the counters, `0xFF00` also clears the event log
+    RestartCommunications,
+    /// Return the contents of the diagnostic register
+    ReturnDiagnosticRegister,
+    /// Change the character that terminates ASCII messages
+    ChangeAsciiInputDelimiter,
+    /// Stop responding to requests until [`DiagnosticSubFunction::RestartCommunications`] is received
+    ///
+    /// The server does not respond to this sub-function
+    ForceListenOnlyMode,
+    /// Clear all of the counters and the diagnostic register
+    ClearCountersAndDiagnosticRegister,
+    /// Return the number of messages detected on the bus
+    ReturnBusMessageCount,
+    /// Return the number of CRC (or LRC) errors detected on the bus
+    ReturnBusCommunicationErrorCount,
+    /// Return the number of exception responses returned by the server
+    ReturnBusExceptionErrorCount,
+    /// Return the number of messages addressed to the server
+    ReturnServerMessageCount,
+    /// Return the number of messages addressed to the server that were not answered
+    ReturnServerNoResponseCount,
+    /// Return the number of negative acknowledge exceptions returned by the server
+    ReturnServerNakCount,
+    /// Return the number of server device busy exceptions returned by the server
+    ReturnServerBusyCount,
+    /// Return the number of messages that could not be handled due to a character overrun
+    ReturnBusCharacterOverrunCount,
+    /// Clear the character overrun counter and error flag
+    ClearOverrunCounterAndFlag,
+}
+
+impl DiagnosticSubFunction {
+    pub(crate) fn get_value(self) -> u16 {
+        match self {
+            DiagnosticSubFunction::ReturnQueryData => 0x00,
+            DiagnosticSubFunction::RestartCommunications => 0x01,
+            DiagnosticSubFunction::ReturnDiagnosticRegister => 0x02,
+            DiagnosticSubFunction::ChangeAsciiInputDelimiter => 0x03,
+            DiagnosticSubFunction::ForceListenOnlyMode => 0x04,
+            DiagnosticSubFunction::ClearCountersAndDiagnosticRegister => 0x0A,
+            DiagnosticSubFunction::ReturnBusMessageCount => 0x0B,
+            DiagnosticSubFunction::ReturnBusCommunicationErrorCount => 0x0C,
+            DiagnosticSubFunction::ReturnBusExceptionErrorCount => 0x0D,
+            DiagnosticSubFunction::ReturnServerMessageCount => 0x0E,
+            DiagnosticSubFunction::ReturnServerNoResponseCount => 0x0F,
+            DiagnosticSubFunction::ReturnServerNakCount => 0x10,
+            DiagnosticSubFunction::ReturnServerBusyCount => 0x11,
+            DiagnosticSubFunction::ReturnBusCharacterOverrunCount => 0x12,
+            DiagnosticSubFunction::ClearOverrunCounterAndFlag => 0x14,
+        }
+    }
+
+    pub(crate) fn get(value: u16) -> Option<Self> {
+        match value {
+            0x00 => Some(DiagnosticSubFunction::ReturnQueryData),
+            0x01 => Some(DiagnosticSubFunction::RestartCommunications),
+            0x02 => Some(DiagnosticSubFunction::ReturnDiagnosticRegister),
+            0x03 => Some(DiagnosticSubFunction::ChangeAsciiInputDelimiter),
+            0x04 => Some(DiagnosticSubFunction::ForceListenOnlyMode),
+            0x0A => Some(DiagnosticSubFunction::ClearCountersAndDiagnosticRegister),
+            0x0B => Some(DiagnosticSubFunction::ReturnBusMessageCount),
+            0x0C => Some(DiagnosticSubFunction::ReturnBusCommunicationErrorCount),
+            0x0D => Some(DiagnosticSubFunction::ReturnBusExceptionErrorCount),
+            0x0E => Some(DiagnosticSubFunction::ReturnServerMessageCount),
+            0x0F => Some(DiagnosticSubFunction::ReturnServerNoResponseCount),
+            0x10 => Some(DiagnosticSubFunction::ReturnServerNakCount),
+            0x11 => Some(DiagnosticSubFunction::ReturnServerBusyCount),
+            0x12 => Some(DiagnosticSubFunction::ReturnBusCharacterOverrunCount),
+            0x14 => Some(DiagnosticSubFunction::ClearOverrunCounterAndFlag),
+            _ => None,
+        }
+    }
+}
+
+/// Event recorded in the communication event log of a server
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum CommEvent {
+    /// The server received a message
+    Receive {
+        /// A communication (CRC or LRC) error was detected
+        communication_error: bool,
+        /// A character overrun was detected
+        character_overrun: bool,
+        /// The server is in listen only mode
+        listen_only: bool,
+        /// The message was a broadcast
+        broadcast: bool,
+    },
+    /// The server completed the processing of a message
+    Send {
+        /// An illegal function, data address, or data value exception was sent
+        read_exception: bool,
+        /// A server device failure exception was sent
+        abort_exception: bool,
+        /// An acknowledge or server device busy exception was sent
+        busy_exception: bool,
+        /// A negative acknowledge exception was sent
+        nak_exception: bool,
+        /// A write timeout occurred
+        write_timeout: bool,
+        /// The server is in listen only mode
+        listen_only: bool,
+    },
+    /// The server entered listen only mode
+    EnteredListenOnlyMode,
+    /// The server restarted its communications
+    CommunicationRestart,
+    /// The event is not defined in the standard
+    Unknown(u8),
+}
+
+impl CommEvent {
+    // send event for a response with an optional exception code
+    pub(crate) fn send(exception: Option<u8>, listen_only: bool) -> Self {
+        let exception = exception.unwrap_or(0);
+        CommEvent::Send {
+            read_exception: (0x01..=0x03).contains(&exception),
+            abort_exception: exception == 0x04,
+            busy_exception: exception == 0x05 || exception == 0x06,
+            nak_exception: exception == 0x07,
+            write_timeout: false,
+            listen_only,
+        }
+    }
+}
+
+impl From<u8> for CommEvent {
+    fn from(value: u8) -> Self {
+        let bit = |n: u8| value & (1 << n) != 0;
+        match value {
+            0x00 => CommEvent::CommunicationRestart,
+            0x04 => CommEvent::EnteredListenOnlyMode,
+            // bits 0, 2, and 3 of a receive event are not used
+            x if x & 0x80 != 0 && x & 0x0D == 0 => CommEvent::Receive {
+                communication_error: bit(1),
+                character_overrun: bit(4),
+                listen_only: bit(5),
+                broadcast: bit(6),
+            },
+            x if x & 0xC0 == 0x40 => CommEvent::Send {
+                read_exception: bit(0),
+                abort_exception: bit(1),
+                busy_exception: bit(2),
+                nak_exception: bit(3),
+                write_timeout: bit(4),
+                listen_only: bit(5),
+            },
+            x => CommEvent::Unknown(x),
+        }
+    }
+}
+
+impl From<CommEvent> for u8 {
+    fn from(event: CommEvent) -> Self {
+        let bit = |set: bool, n: u8| if set { 1 << n } else { 0 };
+        match event {
+            CommEvent::Receive {
+                communication_error,
+                character_overrun,
+                listen_only,
+                broadcast,
+            } => {
+                0x80 | bit(communication_error, 1)
+                    | bit(character_overrun, 4)
+                    | bit(listen_only, 5)
+                    | bit(broadcast, 6)
+            }
+            CommEvent::Send {
+                read_exception,
+                abort_exception,
+                busy_exception,
+                nak_exception,
+                write_timeout,
+                listen_only,
+            } => {
+                0x40 | bit(read_exception, 0)
+                    | bit(abort_exception, 1)
+                    | bit(busy_exception, 2)
+                    | bit(nak_exception, 3)
+                    | bit(write_timeout, 4)
+                    | bit(listen_only, 5)
+            }
+            CommEvent::EnteredListenOnlyMode => 0x04,
+            CommEvent::CommunicationRestart => 0x00,
+            CommEvent::Unknown(x) => x,
+        }
+    }
+}
+
+/// Response to a `get comm event counter` request
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct CommEventCounter {
+    /// `0xFFFF` if the server is busy processing a program command, `0x0000` otherwise
+    pub status: u16,
+    /// Number of requests completed without an exception
+    pub event_count: u16,
+}
+
+/// Response to a `get comm event log` request
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CommEventLog {
+    /// `0xFFFF` if the server is busy processing a program command, `0x0000` otherwise
+    pub status: u16,
+    /// Number of requests completed without an exception
+    pub event_count: u16,
+    /// Number of messages detected on the bus
+    pub message_count: u16,
+    /// Events recorded by the server, most recent first
+    pub events: Vec<CommEvent>,
+}
+
+/// Sub-function and data of a `diagnostics` request or response
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub(crate) struct Diagnostic {
+    pub(crate) sub_function: u16,
+    pub(crate) data: u16,
+}
+
+impl Diagnostic {
+    pub(crate) fn new(sub_function: u16, data: u16) -> Self {
+        Self { sub_function, data }
+    }
+}
+
+impl Parse for Diagnostic {
+    fn parse(cursor: &mut ReadCursor) -> Result<Self, RequestError> {
+        let sub_function = cursor.read_u16_be()?;
+        let data = cursor.read_u16_be()?;
+        cursor.expect_empty()?;
+        Ok(Self::new(sub_function, data))
+    }
+}
+
+impl Serialize for Diagnostic {
+    fn serialize(&self, cursor: &mut WriteCursor) -> Result<(), RequestError> {
+        cursor.write_u16_be(self.sub_function)?;
+        cursor.write_u16_be(self.data)?;
+        Ok(())
+    }
+}
+
+impl Loggable for Diagnostic {
+    fn log(
+        &self,
+        _payload: &[u8],
+        level: PduDecodeLevel,
+        f: &mut std::fmt::Formatter,
+    ) -> std::fmt::Result {
+        if level.data_headers() {
+            write!(f, "{}", self)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match DiagnosticSubFunction::get(self.sub_function) {
+            Some(x) => write!(f, "sub-function: {:?}", x)?,
+            None => write!(f, "sub-function: {:#06X}", self.sub_function)?,
+        }
+        write!(f, " data: {:#06X}", self.data)
+    }
+}
+
+/// Output data of a `read exception status` response
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub(crate) struct ExceptionStatus {
+    pub(crate) value: u8,
+}
+
+impl Parse for ExceptionStatus {
+    fn parse(cursor: &mut ReadCursor) -> Result<Self, RequestError> {
+        let value = cursor.read_u8()?;
+        cursor.expect_empty()?;
+        Ok(Self { value })
+    }
+}
+
+impl Serialize for ExceptionStatus {
+    fn serialize(&self, cursor: &mut WriteCursor) -> Result<(), RequestError> {
+        cursor.write_u8(self.value)?;
+        Ok(())
+    }
+}
+
+impl Loggable for ExceptionStatus {
+    fn log(
+        &self,
+        _payload: &[u8],
+        level: PduDecodeLevel,
+        f: &mut std::fmt::Formatter,
+    ) -> std::fmt::Result {
+        if level.data_headers() {
+            write!(f, "status: {:#04X}", self.value)?;
+        }
+        Ok(())
+    }
+}
+
+impl Parse for CommEventCounter {
+    fn parse(cursor: &mut ReadCursor) -> Result<Self, RequestError> {
+        let status = cursor.read_u16_be()?;
+        let event_count = cursor.read_u16_be()?;
+        cursor.expect_empty()?;
+        Ok(Self {
+            status,
+            event_count,
+        })
+    }
+}
+
+impl Serialize for CommEventCounter {
+    fn serialize(&self, cursor: &mut WriteCursor) -> Result<(), RequestError> {
+        cursor.write_u16_be(self.status)?;
+        cursor.write_u16_be(self.event_count)?;
+        Ok(())
+    }
+}
+
+impl Loggable for CommEventCounter {
+    fn log(
+        &self,
+        _payload: &[u8],
+        level: PduDecodeLevel,
+        f: &mut std::fmt::Formatter,
+    ) -> std::fmt::Result {
+        if level.data_headers() {
+            write!(f, "{}", self)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::fmt::Display for CommEventCounter {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(
+            f,
+            "status: {:#06X} event count: {}",
+            self.status, self.event_count
+        )
+    }
+}
+
+impl Parse for CommEventLog {
+    fn parse(cursor: &mut ReadCursor) -> Result<Self, RequestError> {
+        let byte_count = cursor.read_u8()? as usize;
+        if byte_count != cursor.len() {
+            return Err(
+                AduParseError::InsufficientBytesForByteCount(byte_count, cursor.len()).into(),
+            );
+        }
+        let events = match byte_count.checked_sub(COMM_EVENT_LOG_HEADER_LENGTH) {
+            Some(x) if x <= MAX_COMM_EVENTS => x,
+            Some(x) => return Err(AduParseError::TooManyCommEvents(x).into()),
+            None => return Err(AduParseError::InsufficientBytes.into()),
+        };
+
+        let status = cursor.read_u16_be()?;
+        let event_count = cursor.read_u16_be()?;
+        let message_count = cursor.read_u16_be()?;
+        let events = cursor
+            .read_bytes(events)?
+            .iter()
+            .map(|x| CommEvent::from(*x))
+            .collect();
+
+        Ok(Self {
+            status,
+            event_count,
+            message_count,
+            events,
+        })
+    }
+}
+
+impl Serialize for CommEventLog {
+    fn serialize(&self, cursor: &mut WriteCursor) -> Result<(), RequestError> {
+        if self.events.len() > MAX_COMM_EVENTS {
+            return Err(InternalError::BadByteCount(self.events.len()).into());
+        }
+        cursor.write_u8((COMM_EVENT_LOG_HEADER_LENGTH + self.events.len()) as u8)?;
+        cursor.write_u16_be(self.status)?;
+        cursor.write_u16_be(self.event_count)?;
+        cursor.write_u16_be(self.message_count)?;
+        for event in self.events.iter() {
+            cursor.write_u8((*event).into())?;
+        }
+        Ok(())
+    }
+}
+
+impl Loggable for CommEventLog {
+    fn log(
+        &self,
+        _payload: &[u8],
+        level: PduDecodeLevel,
+        f: &mut std::fmt::Formatter,
+    ) -> std::fmt::Result {
+        if level.data_headers() {
+            write!(f, "{}", CommEventLogDisplay::new(level, self))?;
+        }
+        Ok(())
+    }
+}
+
+pub(crate) struct CommEventLogDisplay<'a> {
+    level: PduDecodeLevel,
+    log: &'a CommEventLog,
+}
+
+impl<'a> CommEventLogDisplay<'a> {
+    pub(crate) fn new(level: PduDecodeLevel, log: &'a CommEventLog) -> Self {
+        Self { level, log }
+    }
+}
+
+impl std::fmt::Display for CommEventLogDisplay<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(
+            f,
+            "status: {:#06X} event count: {} message count: {} events: {}",
+            self.log.status,
+            self.log.event_count,
+            self.log.message_count,
+            self.log.events.len()
+        )?;
+        if self.level.data_values() {
+            for event in self.log.events.iter() {
+                write!(f, "\n{:?}", event)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Device specific data of a `report server id` response
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct ServerId {
+    pub(crate) data: Vec<u8>,
+}
+
+impl Parse for ServerId {
+    fn parse(cursor: &mut ReadCursor) -> Result<Self, RequestError> {
+        let byte_count = cursor.read_u8()? as usize;
+        if byte_count != cursor.len() {
+            return Err(
+                AduParseError::InsufficientBytesForByteCount(byte_count, cursor.len()).into(),
+            );
+        }
+        Ok(Self {
+            data: cursor.read_bytes(byte_count)?.to_vec(),
+        })
+    }
+}
+
+impl Serialize for ServerId {
+    fn serialize(&self, cursor: &mut WriteCursor) -> Result<(), RequestError> {
+        if self.data.len() > MAX_SERVER_ID_LENGTH {
+            return Err(InternalError::BadByteCount(self.data.len()).into());
+        }
+        cursor.write_u8(self.data.len() as u8)?;
+        cursor.write_bytes(&self.data)?;
+        Ok(())
+    }
+}
+
+impl Loggable for ServerId {
+    fn log(
+        &self,
+        _payload: &[u8],
+        level: PduDecodeLevel,
+        f: &mut std::fmt::Formatter,
+    ) -> std::fmt::Result {
+        if level.data_headers() {
+            write!(f, "{}", ServerIdDisplay::new(level, &self.data))?;
+        }
+        Ok(())
+    }
+}
+
+pub(crate) struct ServerIdDisplay<'a> {
+    level: PduDecodeLevel,
+    data: &'a [u8],
+}
+
+impl<'a> ServerIdDisplay<'a> {
+    pub(crate) fn new(level: PduDecodeLevel, data: &'a [u8]) -> Self {
+        Self { level, data }
+    }
+}
+
+impl std::fmt::Display for ServerIdDisplay<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "length: {}", self.data.len())?;
+        if self.level.data_values() {
+            for x in self.data.iter() {
+                write!(f, " {:02X}", x)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn serialize<T: Serialize>(value: &T) -> Vec<u8> {
+        let mut buffer = [0u8; 256];
+        let mut cursor = WriteCursor::new(&mut buffer);
+        value.serialize(&mut cursor).unwrap();
+        let length = cursor.position();
+        buffer[..length].to_vec()
+    }
+
+    #[test]
+    fn sub_function_codes_round_trip() {
+        for value in 0..=0x20 {
+            if let Some(x) = DiagnosticSubFunction::get(value) {
+                assert_eq!(x.get_value(), value);
+            }
+        }
+        assert_eq!(DiagnosticSubFunction::get(0x13), None);
+    }
+
+    #[test]
+    fn comm_events_round_trip() {
+        for value in 0..=u8::MAX {
+            assert_eq!(u8::from(CommEvent::from(value)), value);
+        }
+    }
+
+    #[test]
+    fn decodes_comm_events() {
+        assert_eq!(
+            CommEvent::from(0xC2),
+            CommEvent::Receive {
+                communication_error: true,
+                character_overrun: false,
+                listen_only: false,
+                broadcast: true,
+            }
+        );
+        assert_eq!(CommEvent::send(Some(0x06), true), CommEvent::from(0x64));
+        assert_eq!(CommEvent::from(0x04), CommEvent::EnteredListenOnlyMode);
+        assert_eq!(CommEvent::from(0x00), CommEvent::CommunicationRestart);
+    }
+
+    #[test]
+    fn comm_event_log_round_trips() {
+        let log = CommEventLog {
+            status: 0x0000,
+            event_count: 0x0108,
+            message_count: 0x0121,
+            events: vec![CommEvent::from(0x20), CommEvent::from(0x00)],
+        };
+        let bytes = serialize(&log);
+        assert_eq!(
+            bytes,
+            [0x08, 0x00, 0x00, 0x01, 0x08, 0x01, 0x21, 0x20, 0x00]
+        );
+        assert_eq!(
+            CommEventLog::parse(&mut ReadCursor::new(&bytes)).unwrap(),
+            log
+        );
+    }
+
+    #[test]
+    fn rejects_comm_event_log_with_bad_byte_count() {
+        let mut cursor = ReadCursor::new(&[0x07, 0x00, 0x00, 0x01, 0x08, 0x01, 0x21]);
+        assert_eq!(
+            CommEventLog::parse(&mut cursor).unwrap_err(),
+            AduParseError::InsufficientBytesForByteCount(7, 6).into()
+        );
+        let mut cursor = ReadCursor::new(&[0x04, 0x00, 0x00, 0x01, 0x08]);
+        assert_eq!(
+            CommEventLog::parse(&mut cursor).unwrap_err(),
+            AduParseError::InsufficientBytes.into()
+        );
+    }
+
+    #[test]
+    fn server_id_round_trips() {
+        let id = ServerId {
+            data: vec![0x2A, 0xFF, 0x01],
+        };
+        let bytes = serialize(&id);
+        assert_eq!(bytes, [0x03, 0x2A, 0xFF, 0x01]);
+        assert_eq!(ServerId::parse(&mut ReadCursor::new(&bytes)).unwrap(), id);
+    }
+}
@@ -13,6 +13,8 @@ pub enum RequestError {
     BadFrame(FrameParseError),
     /// Response ADU was invalid
     BadResponse(AduParseError),
+    /// Value of a tag couldn't be decoded from the registers in the response
+    BadTagValue(CodecError),
     /// An internal error occurred in the library itself
     ///
     /// These errors should never happen, but are trapped here for reporting purposes in case they ever do occur
@@ -35,6 +37,7 @@ impl std::fmt::Display for RequestError {
             RequestError::BadRequest(err) => err.fmt(f),
             RequestError::BadFrame(err) => err.fmt(f),
             RequestError::BadResponse(err) => err.fmt(f),
+            RequestError::BadTagValue(err) => write!(f, "unable to decode tag value: {}", err),
             RequestError::Internal(err) => err.fmt(f),
             RequestError::ResponseTimeout => f.write_str("response timeout"),
             RequestError::NoConnection => f.write_str("no connection to server"),
@@ -103,6 +106,155 @@ impl From<InvalidRange> for RequestError {
     }
 }
 
+/// Error returned when one of the requests that a large read or write was split into fails
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ChunkError {
+    /// range of the request that failed
+    pub range: crate::types::AddressRange,
+    /// error that caused the request to fail
+    pub error: RequestError,
+}
+
+impl std::error::Error for ChunkError {}
+
+impl std::fmt::Display for ChunkError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> Result<(), std::fmt::Error> {
+        write!(f, "request for ({}) failed: {}", self.range, self.error)
+    }
+}
+
+impl From<ChunkError> for RequestError {
+    fn from(err: ChunkError) -> Self {
+        err.error
+    }
+}
+
+/// Error returned when one of the requests made to read a [`ReadBatch`](crate::client::ReadBatch) fails
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct BatchError {
+    /// table read by the request that failed
+    pub table: crate::types::Table,
+    /// range of the request that failed
+    pub range: crate::types::AddressRange,
+    /// error that caused the request to fail
+    pub error: RequestError,
+}
+
+impl std::error::Error for BatchError {}
+
+impl std::fmt::Display for BatchError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> Result<(), std::fmt::Error> {
+        write!(
+            f,
+            "request for {} ({}) failed: {}",
+            self.table, self.range, self.error
+        )
+    }
+}
+
+impl From<BatchError> for RequestError {
+    fn from(err: BatchError) -> Self {
+        err.error
+    }
+}
+
+/// errors that occur while encoding or decoding values stored in registers
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum CodecError {
+    /// Number of registers doesn't match the size of the value
+    WrongRegisterCount(usize, u16), // actual and expected
+    /// String contains a character that isn't ASCII
+    NotAscii(char),
+    /// String doesn't fit in the registers
+    StringTooLong(usize, usize), // length and capacity
+    /// Byte that isn't a pair of BCD digits
+    InvalidBcdDigit(u8),
+    /// BCD values are limited to four registers
+    BcdTooLong(usize),
+    /// Value has more digits than fit in the registers
+    BcdValueTooLarge(u64, u16), // value and count of registers
+    /// Bit field is empty or extends past the end of the register
+    BadBitField(u8, u8), // start and count
+    /// Value can't be represented by the type of a tag
+    ValueOutOfRange(f64),
+}
+
+impl std::error::Error for CodecError {}
+
+impl std::fmt::Display for CodecError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> Result<(), std::fmt::Error> {
+        match self {
+            CodecError::WrongRegisterCount(actual, expected) => write!(
+                f,
+                "value requires {} registers but {} were provided",
+                expected, actual
+            ),
+            CodecError::NotAscii(x) => write!(f, "character {:?} is not ASCII", x),
+            CodecError::StringTooLong(length, capacity) => write!(
+                f,
+                "string length of {} exceeds the capacity of {} characters",
+                length, capacity
+            ),
+            CodecError::InvalidBcdDigit(x) => write!(f, "{:#04X} is not a pair of BCD digits", x),
+            CodecError::BcdTooLong(count) => write!(
+                f,
+                "BCD value of {} registers exceeds the maximum of 4",
+                count
+            ),
+            CodecError::BcdValueTooLarge(value, count) => {
+                write!(f, "{} doesn't fit in {} BCD registers", value, count)
+            }
+            CodecError::BadBitField(start, count) => write!(
+                f,
+                "bit field of {} bits starting at bit {} doesn't fit in a register",
+                count, start
+            ),
+            CodecError::ValueOutOfRange(value) => {
+                write!(f, "{} is outside the range of the tag", value)
+            }
+        }
+    }
+}
+
+impl From<CodecError> for InvalidRequest {
+    fn from(x: CodecError) -> Self {
+        InvalidRequest::BadValue(x)
+    }
+}
+
+impl From<CodecError> for RequestError {
+    fn from(x: CodecError) -> Self {
+        RequestError::BadRequest(x.into())
+    }
+}
+
+/// errors that occur while loading a device profile
+#[cfg(feature = "profile")]
+#[cfg_attr(docsrs, doc(cfg(feature = "profile")))]
+#[derive(Clone, Debug, PartialEq)]
+pub enum ProfileError {
+    /// Profile text isn't valid TOML or CSV
+    Parse(String),
+    /// More than one tag has the same name
+    DuplicateTag(String),
+    /// Tag definition is invalid
+    InvalidTag(String, String), // name and reason
+}
+
+#[cfg(feature = "profile")]
+impl std::error::Error for ProfileError {}
+
+#[cfg(feature = "profile")]
+impl std::fmt::Display for ProfileError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> Result<(), std::fmt::Error> {
+        match self {
+            ProfileError::Parse(err) => write!(f, "unable to parse profile: {}", err),
+            ProfileError::DuplicateTag(name) => write!(f, "duplicate tag: {}", name),
+            ProfileError::InvalidTag(name, reason) => write!(f, "invalid tag {}: {}", name, reason),
+        }
+    }
+}
+
 /// errors that can be produced when validating start/count
 #[derive(Copy, Clone, Debug, PartialEq)]
 pub enum InvalidRange {
@@ -129,6 +281,8 @@ pub enum InternalError {
     BadSeekOperation,
     /// Byte count would exceed maximum allowed size in the ADU of u8
     BadByteCount(usize),
+    /// Request other than a write was completed as a broadcast
+    NotBroadcastWrite(u8),
 }
 
 impl std::error::Error for InternalError {}
@@ -164,6 +318,11 @@ impl std::fmt::Display for InternalError {
                 "Byte count of in ADU {} exceeds maximum size of u8",
                 size
             ),
+            InternalError::NotBroadcastWrite(function) => write!(
+                f,
+                "function code {:#04X} isn't a write that can be broadcast",
+                function
+            ),
         }
     }
 }
@@ -177,6 +336,18 @@ pub enum FrameParseError {
     MbapLengthTooBig(usize, usize), // actual size and the maximum size
     /// Received TCP frame within non-Modbus protocol id
     UnknownProtocolId(u16),
+    /// Received serial frame with a length that exceeds max allowed size
+    FrameLengthTooBig(usize, usize), // actual size and the maximum size
+    /// Received serial frame with a function code for which the length cannot be determined
+    UnknownFunctionCode(u8),
+    /// Received serial frame that failed CRC validation
+    CrcValidationFailure(u16, u16), // received and expected
+    /// Received ASCII frame containing a character that is not valid at its position
+    InvalidAsciiCharacter(u8),
+    /// Received ASCII frame with a number of encoded characters that cannot be a valid frame
+    InvalidAsciiLength(usize),
+    /// Received ASCII frame that failed LRC validation
+    LrcValidationFailure(u8, u8), // received and expected
 }
 
 impl std::error::Error for FrameParseError {}
@@ -195,6 +366,36 @@ impl std::fmt::Display for FrameParseError {
             FrameParseError::UnknownProtocolId(id) => {
                 write!(f, "Received TCP frame with non-Modbus protocol id: {}", id)
             }
+            FrameParseError::FrameLengthTooBig(size, max) => write!(
+                f,
+                "Received serial frame with length ({}) that exceeds max allowed size ({})",
+                size, max
+            ),
+            FrameParseError::UnknownFunctionCode(code) => write!(
+                f,
+                "Received serial frame with unknown function code: {:#04X}",
+                code
+            ),
+            FrameParseError::CrcValidationFailure(received, expected) => write!(
+                f,
+                "Received serial frame with CRC {:#06X} while expecting {:#06X}",
+                received, expected
+            ),
+            FrameParseError::InvalidAsciiCharacter(value) => write!(
+                f,
+                "Received ASCII frame with invalid character: {:#04X}",
+                value
+            ),
+            FrameParseError::InvalidAsciiLength(length) => write!(
+                f,
+                "Received ASCII frame with invalid number of characters: {}",
+                length
+            ),
+            FrameParseError::LrcValidationFailure(received, expected) => write!(
+                f,
+                "Received ASCII frame with LRC {:#04X} while expecting {:#04X}",
+                received, expected
+            ),
         }
     }
 }
@@ -214,6 +415,22 @@ pub enum AduParseError {
     UnknownResponseFunction(u8, u8, u8), // actual, expected, expected error
     /// Bad value for the coil state
     UnknownCoilState(u16),
+    /// MEI type other than read device identification (0x0E)
+    UnknownMeiType(u8),
+    /// Bad value for the read device id code
+    UnknownReadDeviceIdCode(u8),
+    /// Bad value for the `more follows` field of a read device identification response
+    UnknownMoreFollows(u8),
+    /// Read device identification response asks to continue from an object that was already received
+    DeviceIdNoProgress(u8),
+    /// FIFO count exceeds the maximum allowed by the specification
+    FifoCountTooLarge(u16),
+    /// File record reference type other than 0x06
+    UnknownReferenceType(u8),
+    /// Comm event log contains more events than allowed by the specification
+    TooManyCommEvents(usize),
+    /// Diagnostics response echoes a different sub-function than requested
+    DiagnosticSubFunctionMismatch(u16, u16), // received / expected
 }
 
 impl std::error::Error for AduParseError {}
@@ -243,6 +460,46 @@ impl std::fmt::Display for AduParseError {
                 "received coil state with unspecified value: 0x{:04X}",
                 value
             ),
+            AduParseError::UnknownMeiType(value) => {
+                write!(f, "received unsupported MEI type: 0x{:02X}", value)
+            }
+            AduParseError::UnknownReadDeviceIdCode(value) => write!(
+                f,
+                "received read device id code with unspecified value: 0x{:02X}",
+                value
+            ),
+            AduParseError::UnknownMoreFollows(value) => write!(
+                f,
+                "received 'more follows' with unspecified value: 0x{:02X}",
+                value
+            ),
+            AduParseError::DeviceIdNoProgress(next) => write!(
+                f,
+                "device identification response continues from object 0x{:02X} which does not follow the objects received",
+                next
+            ),
+            AduParseError::FifoCountTooLarge(count) => write!(
+                f,
+                "FIFO count of {} exceeds the maximum of {}",
+                count,
+                crate::constants::limits::MAX_FIFO_COUNT
+            ),
+            AduParseError::UnknownReferenceType(value) => write!(
+                f,
+                "received file record with unknown reference type: 0x{:02X}",
+                value
+            ),
+            AduParseError::TooManyCommEvents(count) => write!(
+                f,
+                "comm event log contains {} events which exceeds the maximum of {}",
+                count,
+                crate::constants::limits::MAX_COMM_EVENTS
+            ),
+            AduParseError::DiagnosticSubFunctionMismatch(received, expected) => write!(
+                f,
+                "received diagnostics sub-function 0x{:04X} while expecting 0x{:04X}",
+                received, expected
+            ),
         }
     }
 }
@@ -256,6 +513,30 @@ pub enum InvalidRequest {
     CountTooBigForU16(usize),
     /// Count too big for specific request
     CountTooBigForType(u16, u16),
+    /// Device identification object is too long to fit in a response
+    DeviceIdObjectTooLong(u8, usize), // object id / length
+    /// File record request without any sub-requests
+    NoFileRecords,
+    /// File record number exceeds the maximum allowed by the specification
+    FileRecordNumberTooLarge(u16),
+    /// File record sub-requests don't fit in a single request or response
+    FileRecordsTooLarge(usize, usize), // length / max
+    /// Function code is not a standard or user-defined function code
+    UnsupportedFunctionCode(u8),
+    /// Custom function data doesn't fit in a single request
+    CustomFunctionDataTooLong(usize),
+    /// The channel can't find the end of the response to the function code
+    UnsupportedByChannel(u8),
+    /// Setting doesn't apply to the type of the channel
+    UnsupportedSetting,
+    /// Value can't be encoded into registers
+    BadValue(CodecError),
+    /// Profile doesn't contain a tag with the requested name
+    UnknownTag,
+    /// Tag can only be read
+    TagNotWritable,
+    /// Value doesn't match the type of the tag
+    WrongTagValueType,
 }
 
 impl std::error::Error for InvalidRequest {}
@@ -275,6 +556,52 @@ impl std::fmt::Display for InvalidRequest {
                 "the request count of {} exceeds maximum allowed count of {} for this type",
                 count, max
             ),
+            InvalidRequest::DeviceIdObjectTooLong(id, length) => write!(
+                f,
+                "device identification object 0x{:02X} has a length of {} which exceeds the maximum of {}",
+                id,
+                length,
+                crate::constants::limits::MAX_DEVICE_ID_OBJECT_LENGTH
+            ),
+            InvalidRequest::NoFileRecords => {
+                f.write_str("file record request must contain at least one sub-request")
+            }
+            InvalidRequest::FileRecordNumberTooLarge(number) => write!(
+                f,
+                "file record number {:#06X} exceeds the maximum of {:#06X}",
+                number,
+                crate::constants::limits::MAX_FILE_RECORD_NUMBER
+            ),
+            InvalidRequest::FileRecordsTooLarge(length, max) => write!(
+                f,
+                "file record sub-requests require {} bytes which exceeds the maximum of {}",
+                length, max
+            ),
+            InvalidRequest::UnsupportedFunctionCode(code) => write!(
+                f,
+                "function code {:#04X} is not a standard or user-defined function code",
+                code
+            ),
+            InvalidRequest::UnsupportedByChannel(code) => write!(
+                f,
+                "the channel can't determine the length of responses to function code {:#04X}",
+                code
+            ),
+            InvalidRequest::UnsupportedSetting => {
+                f.write_str("the setting doesn't apply to this type of channel")
+            }
+            InvalidRequest::CustomFunctionDataTooLong(length) => write!(
+                f,
+                "custom function data has a length of {} which exceeds the maximum of {}",
+                length,
+                crate::constants::limits::MAX_CUSTOM_FUNCTION_DATA_LENGTH
+            ),
+            InvalidRequest::BadValue(err) => write!(f, "{}", err),
+            InvalidRequest::UnknownTag => f.write_str("profile doesn't contain the requested tag"),
+            InvalidRequest::TagNotWritable => f.write_str("tag can only be read"),
+            InvalidRequest::WrongTagValueType => {
+                f.write_str("value doesn't match the type of the tag")
+            }
         }
     }
 }
@@ -0,0 +1,545 @@
+use crate::common::cursor::{ReadCursor, WriteCursor};
+use crate::common::traits::{Loggable, Serialize};
+use crate::constants::limits::MAX_FILE_RECORD_NUMBER;
+use crate::decode::PduDecodeLevel;
+use crate::error::{AduParseError, InvalidRequest, RequestError};
+use crate::exception::ExceptionCode;
+
+// the only reference type defined by the specification
+const REFERENCE_TYPE: u8 = 0x06;
+
+// maximum value of the byte count in a `read file record` request
+const MAX_READ_REQUEST_LENGTH: usize = 0xF5;
+// maximum value of the byte count in a `read file record` response or `write file record` request/response
+const MAX_DATA_LENGTH: usize = 0xFB;
+
+// reference type, file number, record number, and record length
+const SUB_REQUEST_HEADER_LENGTH: usize = 7;
+// length and reference type
+const SUB_RESPONSE_HEADER_LENGTH: usize = 2;
+
+/// Group of registers to read from a file
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FileRecord {
+    /// file number
+    pub file_number: u16,
+    /// number of the first record (register) within the file
+    pub record_number: u16,
+    /// count of records (registers) to read
+    pub record_length: u16,
+}
+
+/// Registers written to, or read from, a file
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FileRecordData {
+    /// file number
+    pub file_number: u16,
+    /// number of the first record (register) within the file
+    pub record_number: u16,
+    /// value of each record (register)
+    pub values: Vec<u16>,
+}
+
+impl FileRecord {
+    /// Create a new file record reference
+    pub fn new(file_number: u16, record_number: u16, record_length: u16) -> Self {
+        Self {
+            file_number,
+            record_number,
+            record_length,
+        }
+    }
+}
+
+impl FileRecordData {
+    /// Create a new group of records
+    pub fn new(file_number: u16, record_number: u16, values: Vec<u16>) -> Self {
+        Self {
+            file_number,
+            record_number,
+            values,
+        }
+    }
+
+    fn data_length(&self) -> usize {
+        SUB_REQUEST_HEADER_LENGTH + 2 * self.values.len()
+    }
+}
+
+impl std::fmt::Display for FileRecord {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "file: {:#06X} record: {:#06X} qty: {}",
+            self.file_number, self.record_number, self.record_length
+        )
+    }
+}
+
+impl std::fmt::Display for FileRecordData {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "file: {:#06X} record: {:#06X} qty: {}",
+            self.file_number,
+            self.record_number,
+            self.values.len()
+        )
+    }
+}
+
+fn validate_record_number(record_number: u16) -> Result<(), InvalidRequest> {
+    if record_number > MAX_FILE_RECORD_NUMBER {
+        return Err(InvalidRequest::FileRecordNumberTooLarge(record_number));
+    }
+    Ok(())
+}
+
+fn validate_length(length: usize, max: usize) -> Result<(), InvalidRequest> {
+    if length > max {
+        return Err(InvalidRequest::FileRecordsTooLarge(length, max));
+    }
+    Ok(())
+}
+
+// response length of a read sub-request
+fn read_response_length(record: &FileRecord) -> usize {
+    SUB_RESPONSE_HEADER_LENGTH + 2 * record.record_length as usize
+}
+
+fn write_values(cursor: &mut WriteCursor, values: &[u16]) -> Result<(), RequestError> {
+    for value in values {
+        cursor.write_u16_be(*value)?;
+    }
+    Ok(())
+}
+
+fn read_values(cursor: &mut ReadCursor, count: usize) -> Result<Vec<u16>, RequestError> {
+    let bytes = cursor.read_bytes(2 * count)?;
+    Ok(bytes
+        .chunks_exact(2)
+        .map(|x| u16::from_be_bytes([x[0], x[1]]))
+        .collect())
+}
+
+// read the byte count and verify that it matches the rest of the PDU
+fn read_byte_count(cursor: &mut ReadCursor) -> Result<(), RequestError> {
+    let byte_count = cursor.read_u8()? as usize;
+    if byte_count != cursor.len() {
+        return Err(AduParseError::InsufficientBytesForByteCount(byte_count, cursor.len()).into());
+    }
+    Ok(())
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct ReadFileRecordRequest {
+    pub(crate) records: Vec<FileRecord>,
+}
+
+impl ReadFileRecordRequest {
+    pub(crate) fn new(records: Vec<FileRecord>) -> Result<Self, InvalidRequest> {
+        if records.is_empty() {
+            return Err(InvalidRequest::NoFileRecords);
+        }
+        for record in records.iter() {
+            validate_record_number(record.record_number)?;
+        }
+        validate_length(
+            records.len() * SUB_REQUEST_HEADER_LENGTH,
+            MAX_READ_REQUEST_LENGTH,
+        )?;
+        // the records must also fit in the response
+        validate_length(
+            records.iter().map(read_response_length).sum(),
+            MAX_DATA_LENGTH,
+        )?;
+        Ok(Self { records })
+    }
+
+    pub(crate) fn parse_response(
+        &self,
+        cursor: &mut ReadCursor,
+    ) -> Result<Vec<FileRecordData>, RequestError> {
+        read_byte_count(cursor)?;
+        let mut records = Vec::with_capacity(self.records.len());
+        for record in self.records.iter() {
+            let length = cursor.read_u8()? as usize;
+            if length != 1 + 2 * record.record_length as usize {
+                return Err(AduParseError::ReplyEchoMismatch.into());
+            }
+            let reference_type = cursor.read_u8()?;
+            if reference_type != REFERENCE_TYPE {
+                return Err(AduParseError::UnknownReferenceType(reference_type).into());
+            }
+            records.push(FileRecordData::new(
+                record.file_number,
+                record.record_number,
+                read_values(cursor, record.record_length as usize)?,
+            ));
+        }
+        cursor.expect_empty()?;
+        Ok(records)
+    }
+}
+
+impl Serialize for ReadFileRecordRequest {
+    fn serialize(&self, cursor: &mut WriteCursor) -> Result<(), RequestError> {
+        // validated when the request was created
+        cursor.write_u8((self.records.len() * SUB_REQUEST_HEADER_LENGTH) as u8)?;
+        for record in self.records.iter() {
+            cursor.write_u8(REFERENCE_TYPE)?;
+            cursor.write_u16_be(record.file_number)?;
+            cursor.write_u16_be(record.record_number)?;
+            cursor.write_u16_be(record.record_length)?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct WriteFileRecordRequest {
+    pub(crate) records: Vec<FileRecordData>,
+}
+
+impl WriteFileRecordRequest {
+    pub(crate) fn new(records: Vec<FileRecordData>) -> Result<Self, InvalidRequest> {
+        if records.is_empty() {
+            return Err(InvalidRequest::NoFileRecords);
+        }
+        for record in records.iter() {
+            validate_record_number(record.record_number)?;
+        }
+        validate_length(
+            records.iter().map(FileRecordData::data_length).sum(),
+            MAX_DATA_LENGTH,
+        )?;
+        Ok(Self { records })
+    }
+}
+
+impl Serialize for WriteFileRecordRequest {
+    fn serialize(&self, cursor: &mut WriteCursor) -> Result<(), RequestError> {
+        let length: usize = self.records.iter().map(FileRecordData::data_length).sum();
+        if length > MAX_DATA_LENGTH {
+            return Err(InvalidRequest::FileRecordsTooLarge(length, MAX_DATA_LENGTH).into());
+        }
+        cursor.write_u8(length as u8)?;
+        for record in self.records.iter() {
+            cursor.write_u8(REFERENCE_TYPE)?;
+            cursor.write_u16_be(record.file_number)?;
+            cursor.write_u16_be(record.record_number)?;
+            cursor.write_u16_be(record.values.len() as u16)?;
+            write_values(cursor, &record.values)?;
+        }
+        Ok(())
+    }
+}
+
+impl Loggable for WriteFileRecordRequest {
+    fn log(
+        &self,
+        _payload: &[u8],
+        level: PduDecodeLevel,
+        f: &mut std::fmt::Formatter,
+    ) -> std::fmt::Result {
+        if level.data_headers() {
+            write!(f, "{}", FileRecordDataDisplay::new(level, &self.records))?;
+        }
+        Ok(())
+    }
+}
+
+// sub-requests of a `read file record` request received by the server
+pub(crate) struct ReadFileRecordSubRequests {
+    pub(crate) records: Vec<FileRecord>,
+    valid_references: bool,
+}
+
+impl ReadFileRecordSubRequests {
+    pub(crate) fn parse(cursor: &mut ReadCursor) -> Result<Self, RequestError> {
+        read_byte_count(cursor)?;
+        let mut valid_references = true;
+        let mut records = Vec::new();
+        while !cursor.is_empty() {
+            valid_references &= cursor.read_u8()? == REFERENCE_TYPE;
+            records.push(FileRecord::new(
+                cursor.read_u16_be()?,
+                cursor.read_u16_be()?,
+                cursor.read_u16_be()?,
+            ));
+        }
+        Ok(Self {
+            records,
+            valid_references,
+        })
+    }
+
+    // the sub-requests are checked by the server, not the parser, to return the specified exceptions
+    pub(crate) fn validate(&self) -> Result<(), ExceptionCode> {
+        if self.records.is_empty()
+            || self.records.len() * SUB_REQUEST_HEADER_LENGTH > MAX_READ_REQUEST_LENGTH
+        {
+            return Err(ExceptionCode::IllegalDataValue);
+        }
+        if !self.valid_references
+            || self
+                .records
+                .iter()
+                .any(|x| x.record_number > MAX_FILE_RECORD_NUMBER)
+        {
+            return Err(ExceptionCode::IllegalDataAddress);
+        }
+        if self.records.iter().map(read_response_length).sum::<usize>() > MAX_DATA_LENGTH {
+            return Err(ExceptionCode::IllegalDataValue);
+        }
+        Ok(())
+    }
+}
+
+// sub-requests of a `write file record` request received by the server, or the echo received by the client
+pub(crate) struct WriteFileRecordSubRequests {
+    pub(crate) request: WriteFileRecordRequest,
+    valid_references: bool,
+}
+
+impl WriteFileRecordSubRequests {
+    pub(crate) fn parse(cursor: &mut ReadCursor) -> Result<Self, RequestError> {
+        read_byte_count(cursor)?;
+        let mut valid_references = true;
+        let mut records = Vec::new();
+        while !cursor.is_empty() {
+            valid_references &= cursor.read_u8()? == REFERENCE_TYPE;
+            let file_number = cursor.read_u16_be()?;
+            let record_number = cursor.read_u16_be()?;
+            let count = cursor.read_u16_be()? as usize;
+            records.push(FileRecordData::new(
+                file_number,
+                record_number,
+                read_values(cursor, count)?,
+            ));
+        }
+        Ok(Self {
+            request: WriteFileRecordRequest { records },
+            valid_references,
+        })
+    }
+
+    pub(crate) fn validate(&self) -> Result<(), ExceptionCode> {
+        if self.request.records.is_empty() {
+            return Err(ExceptionCode::IllegalDataValue);
+        }
+        if !self.valid_references
+            || self
+                .request
+                .records
+                .iter()
+                .any(|x| x.record_number > MAX_FILE_RECORD_NUMBER)
+        {
+            return Err(ExceptionCode::IllegalDataAddress);
+        }
+        Ok(())
+    }
+}
+
+// records read by the server, in the order they were requested
+pub(crate) struct ReadFileRecordResponse {
+    pub(crate) records: Vec<FileRecordData>,
+}
+
+impl Serialize for ReadFileRecordResponse {
+    fn serialize(&self, cursor: &mut WriteCursor) -> Result<(), RequestError> {
+        let length: usize = self
+            .records
+            .iter()
+            .map(|x| SUB_RESPONSE_HEADER_LENGTH + 2 * x.values.len())
+            .sum();
+        if length > MAX_DATA_LENGTH {
+            return Err(InvalidRequest::FileRecordsTooLarge(length, MAX_DATA_LENGTH).into());
+        }
+        cursor.write_u8(length as u8)?;
+        for record in self.records.iter() {
+            cursor.write_u8((1 + 2 * record.values.len()) as u8)?;
+            cursor.write_u8(REFERENCE_TYPE)?;
+            write_values(cursor, &record.values)?;
+        }
+        Ok(())
+    }
+}
+
+impl Loggable for ReadFileRecordResponse {
+    fn log(
+        &self,
+        _payload: &[u8],
+        level: PduDecodeLevel,
+        f: &mut std::fmt::Formatter,
+    ) -> std::fmt::Result {
+        if level.data_headers() {
+            write!(f, "{}", FileRecordDataDisplay::new(level, &self.records))?;
+        }
+        Ok(())
+    }
+}
+
+pub(crate) struct FileRecordDataDisplay<'a> {
+    level: PduDecodeLevel,
+    records: &'a [FileRecordData],
+}
+
+impl<'a> FileRecordDataDisplay<'a> {
+    pub(crate) fn new(level: PduDecodeLevel, records: &'a [FileRecordData]) -> Self {
+        Self { level, records }
+    }
+}
+
+impl std::fmt::Display for FileRecordDataDisplay<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        for (i, record) in self.records.iter().enumerate() {
+            if i > 0 {
+                f.write_str("\n")?;
+            }
+            write!(f, "{}", record)?;
+            if self.level.data_values() {
+                for (offset, value) in record.values.iter().enumerate() {
+                    write!(
+                        f,
+                        "\nrecord: {:#06X} value: {:#06X}",
+                        record.record_number as usize + offset,
+                        value
+                    )?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+pub(crate) struct FileRecordDisplay<'a> {
+    records: &'a [FileRecord],
+}
+
+impl<'a> FileRecordDisplay<'a> {
+    pub(crate) fn new(records: &'a [FileRecord]) -> Self {
+        Self { records }
+    }
+}
+
+impl std::fmt::Display for FileRecordDisplay<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        for (i, record) in self.records.iter().enumerate() {
+            if i > 0 {
+                f.write_str("\n")?;
+            }
+            write!(f, "{}", record)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn serialize<T: Serialize>(value: &T) -> Vec<u8> {
+        let mut buffer = [0u8; 256];
+        let mut cursor = WriteCursor::new(&mut buffer);
+        value.serialize(&mut cursor).unwrap();
+        let length = cursor.position();
+        buffer[..length].to_vec()
+    }
+
+    #[test]
+    fn serializes_read_request() {
+        let request =
+            ReadFileRecordRequest::new(vec![FileRecord::new(4, 1, 2), FileRecord::new(3, 9, 2)])
+                .unwrap();
+        assert_eq!(
+            serialize(&request),
+            vec![
+                0x0E, 0x06, 0x00, 0x04, 0x00, 0x01, 0x00, 0x02, 0x06, 0x00, 0x03, 0x00, 0x09, 0x00,
+                0x02
+            ]
+        );
+    }
+
+    #[test]
+    fn parses_read_response() {
+        let request =
+            ReadFileRecordRequest::new(vec![FileRecord::new(4, 1, 2), FileRecord::new(3, 9, 2)])
+                .unwrap();
+        let bytes = [
+            0x0C, 0x05, 0x06, 0x0D, 0xFE, 0x00, 0x20, 0x05, 0x06, 0x33, 0xCD, 0x00, 0x40,
+        ];
+        assert_eq!(
+            request.parse_response(&mut ReadCursor::new(&bytes)),
+            Ok(vec![
+                FileRecordData::new(4, 1, vec![0x0DFE, 0x0020]),
+                FileRecordData::new(3, 9, vec![0x33CD, 0x0040])
+            ])
+        );
+    }
+
+    #[test]
+    fn rejects_read_response_with_different_record_length() {
+        let request = ReadFileRecordRequest::new(vec![FileRecord::new(4, 1, 2)]).unwrap();
+        let bytes = [0x04, 0x03, 0x06, 0x0D, 0xFE];
+        assert_eq!(
+            request.parse_response(&mut ReadCursor::new(&bytes)),
+            Err(AduParseError::ReplyEchoMismatch.into())
+        );
+    }
+
+    #[test]
+    fn rejects_record_number_above_maximum() {
+        assert_eq!(
+            ReadFileRecordRequest::new(vec![FileRecord::new(1, 0x2710, 1)]).unwrap_err(),
+            InvalidRequest::FileRecordNumberTooLarge(0x2710)
+        );
+    }
+
+    #[test]
+    fn rejects_read_that_does_not_fit_in_response() {
+        assert_eq!(
+            ReadFileRecordRequest::new(vec![FileRecord::new(1, 0, 125)]).unwrap_err(),
+            InvalidRequest::FileRecordsTooLarge(252, MAX_DATA_LENGTH)
+        );
+    }
+
+    #[test]
+    fn rejects_empty_requests() {
+        assert_eq!(
+            ReadFileRecordRequest::new(Vec::new()).unwrap_err(),
+            InvalidRequest::NoFileRecords
+        );
+        assert_eq!(
+            WriteFileRecordRequest::new(Vec::new()).unwrap_err(),
+            InvalidRequest::NoFileRecords
+        );
+    }
+
+    #[test]
+    fn write_request_round_trips() {
+        let request = WriteFileRecordRequest::new(vec![FileRecordData::new(
+            4,
+            7,
+            vec![0x06AF, 0x04BE, 0x100D],
+        )])
+        .unwrap();
+        let bytes = serialize(&request);
+        assert_eq!(
+            bytes,
+            vec![
+                0x0D, 0x06, 0x00, 0x04, 0x00, 0x07, 0x00, 0x03, 0x06, 0xAF, 0x04, 0xBE, 0x10, 0x0D
+            ]
+        );
+        let parsed = WriteFileRecordSubRequests::parse(&mut ReadCursor::new(&bytes)).unwrap();
+        assert_eq!(parsed.request, request);
+        assert_eq!(parsed.validate(), Ok(()));
+    }
+
+    #[test]
+    fn server_rejects_unknown_reference_type() {
+        let bytes = [0x07, 0x05, 0x00, 0x04, 0x00, 0x01, 0x00, 0x02];
+        let request = ReadFileRecordSubRequests::parse(&mut ReadCursor::new(&bytes)).unwrap();
+        assert_eq!(request.validate(), Err(ExceptionCode::IllegalDataAddress));
+    }
+}
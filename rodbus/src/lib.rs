@@ -13,6 +13,10 @@
 //! # Supported modes
 //!
 //! * TCP client and server
+//! * TLS client and server (Modbus/TCP Security) using [Rustls](https://docs.rs/rustls)
+//!   with role-based authorization on the server
+//! * Modbus RTU over TCP client and server
+//! * Modbus RTU and ASCII client and server over serial
 //!
 //! # Supported Functions
 //!
@@ -22,14 +26,28 @@
 //! * Read Input Registers
 //! * Write Single Coil
 //! * Write Single Register
+//! * Read Exception Status
+//! * Diagnostics
+//! * Get Comm Event Counter
+//! * Get Comm Event Log
 //! * Write Multiple Coils
 //! * Write Multiple Registers
+//! * Report Server ID
+//! * Read File Record
+//! * Write File Record
+//! * Mask Write Register
+//! * Read/Write Multiple Registers
+//! * Read FIFO Queue
+//! * Read Device Identification
+//! * User-defined function codes
+//!
+//! # Optional features
+//!
+//! * `profile` - devices described as named tags loaded from TOML or CSV
 //!
 //! # Future support
 //!
-//! * TLS Client / TLS Server + Modbus X.509 extensions using [Rustls](https://docs.rs/rustls)
 //! * Additional function code support
-//! * Modbus RTU over serial
 //!
 //! # Example Client
 //!
@@ -137,6 +155,7 @@
 //!}
 //!```
 
+#![cfg_attr(docsrs, feature(doc_cfg))]
 #![deny(
     dead_code,
     arithmetic_overflow,
@@ -188,26 +207,43 @@ pub const VERSION: &str = env!("CARGO_PKG_VERSION");
 
 /// Client API
 pub mod client;
+/// Encoding and decoding of values stored in one or more registers
+pub mod codec;
 /// Public constant values related to the Modbus specification
 pub mod constants;
 
 /// Error types associated with making requests
 pub mod error;
+/// Devices described as named tags loaded from TOML or CSV
+#[cfg(feature = "profile")]
+#[cfg_attr(docsrs, doc(cfg(feature = "profile")))]
+pub mod profile;
 /// Server API
 pub mod server;
 
 // modules that are re-exported
+pub(crate) mod custom;
 pub(crate) mod decode;
+pub(crate) mod device_id;
+pub(crate) mod diagnostics;
 pub(crate) mod exception;
+pub(crate) mod file_record;
 pub(crate) mod types;
 
 // re-exports
+pub use crate::common::function::FunctionCode;
 pub use crate::decode::*;
+pub use crate::device_id::{DeviceIdentification, ReadDeviceIdCode};
+pub use crate::diagnostics::{CommEvent, CommEventCounter, CommEventLog, DiagnosticSubFunction};
 pub use crate::exception::*;
+pub use crate::file_record::{FileRecord, FileRecordData};
+pub use crate::serial::settings::*;
+pub use crate::tcp::tls::*;
 pub use crate::types::*;
 pub use error::RequestError;
 
 // internal modules
 mod common;
+mod serial;
 mod tcp;
 mod tokio;
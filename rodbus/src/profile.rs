@@ -0,0 +1,803 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::client::{BatchOptions, BatchValues, ReadBatch};
+use crate::codec::{
+    decode_bcd, decode_string, encode_bcd, encode_string, ByteOrder, RegisterValue,
+};
+use crate::error::{CodecError, InvalidRequest, ProfileError};
+use crate::exception::ExceptionCode;
+use crate::server::{RequestHandler, WriteCoils, WriteRegisters};
+use crate::types::{AddressRange, Indexed, Table};
+
+/// Type of the value of a tag and how it is stored in its table
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DataType {
+    /// a coil or discrete input
+    Bool,
+    /// a single bit of a register, where 0 is the least significant bit
+    Bit(u8),
+    /// unsigned 16-bit integer
+    U16,
+    /// signed 16-bit integer
+    I16,
+    /// unsigned 32-bit integer stored in two registers
+    U32,
+    /// signed 32-bit integer stored in two registers
+    I32,
+    /// single precision floating point value stored in two registers
+    F32,
+    /// unsigned 64-bit integer stored in four registers
+    U64,
+    /// signed 64-bit integer stored in four registers
+    I64,
+    /// double precision floating point value stored in four registers
+    F64,
+    /// binary-coded decimal value stored in the specified number of registers
+    Bcd(u16),
+    /// ASCII string stored in the specified number of registers
+    String(u16),
+}
+
+/// Operations that clients may perform on a tag
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Access {
+    /// the tag may only be read
+    Read,
+    /// the tag may be read and written
+    ReadWrite,
+}
+
+/// Named value of a device
+#[derive(Clone, Debug, PartialEq)]
+pub struct Tag {
+    /// unique name of the tag
+    pub name: String,
+    /// table in which the tag is stored
+    pub table: Table,
+    /// address of the first coil, input or register of the tag
+    pub address: u16,
+    /// type of the value
+    pub data_type: DataType,
+    /// order of the bytes of values stored in more than one register
+    pub order: ByteOrder,
+    /// the engineering value of numeric tags is `raw * scale + offset`
+    pub scale: f64,
+    /// the engineering value of numeric tags is `raw * scale + offset`
+    pub offset: f64,
+    /// engineering units, for display purposes
+    pub units: Option<String>,
+    /// operations that clients may perform on the tag
+    pub access: Access,
+}
+
+/// Value of a tag in engineering units
+#[derive(Clone, Debug, PartialEq)]
+pub enum TagValue {
+    /// value of a [`DataType::Bool`] or [`DataType::Bit`] tag
+    Bool(bool),
+    /// scaled value of a numeric or BCD tag
+    Number(f64),
+    /// value of a [`DataType::String`] tag
+    Text(String),
+}
+
+/// Collection of named tags that describes a device
+///
+/// Profiles are usually loaded from a TOML file with a `[[tag]]` table per tag, or a CSV file
+/// with a header row and a row per tag. Both use the same fields:
+///
+/// * `name` - unique name of the tag
+/// * `table` - `coils`, `discrete_inputs`, `holding_registers` or `input_registers`
+/// * `address` - address of the first coil, input or register of the tag
+/// * `type` - `bool`, `u16`, `i16`, `u32`, `i32`, `f32`, `u64`, `i64`, `f64`, `bcd` or `string`
+/// * `order` - `abcd`, `cdab`, `badc` or `dcba`, defaults to `abcd`
+/// * `count` - number of registers of `bcd` and `string` tags
+/// * `bit` - bit of the register of `bool` tags stored in registers
+/// * `scale` and `offset` - the engineering value is `raw * scale + offset`, default to 1 and 0
+/// * `units` - engineering units
+/// * `access` - `read` or `read_write`, defaults to `read`
+///
+/// ```toml
+/// [[tag]]
+/// name = "flow"
+/// table = "input_registers"
+/// address = 10
+/// type = "f32"
+/// order = "cdab"
+/// units = "m3/h"
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct DeviceProfile {
+    tags: Vec<Tag>,
+    names: BTreeMap<String, usize>,
+}
+
+// how the value of a tag is written
+pub(crate) enum TagWrite {
+    Coil(bool),
+    RegisterBit(u8, bool),
+    Registers(Vec<u16>),
+}
+
+#[derive(serde::Deserialize)]
+struct ProfileFile {
+    #[serde(default)]
+    tag: Vec<TagRecord>,
+}
+
+#[derive(serde::Deserialize)]
+struct TagRecord {
+    name: String,
+    table: String,
+    address: u16,
+    #[serde(rename = "type")]
+    data_type: String,
+    order: Option<String>,
+    count: Option<u16>,
+    bit: Option<u8>,
+    scale: Option<f64>,
+    offset: Option<f64>,
+    units: Option<String>,
+    access: Option<String>,
+}
+
+impl DataType {
+    /// Number of coils, inputs or registers used to store the value
+    pub fn count(self) -> u16 {
+        match self {
+            DataType::Bool | DataType::Bit(_) | DataType::U16 | DataType::I16 => 1,
+            DataType::U32 | DataType::I32 | DataType::F32 => 2,
+            DataType::U64 | DataType::I64 | DataType::F64 => 4,
+            DataType::Bcd(count) | DataType::String(count) => count,
+        }
+    }
+}
+
+impl Tag {
+    /// Create a read-only tag without scaling that uses the [`ByteOrder::Abcd`] order
+    pub fn new(name: &str, table: Table, address: u16, data_type: DataType) -> Self {
+        Self {
+            name: name.to_string(),
+            table,
+            address,
+            data_type,
+            order: ByteOrder::Abcd,
+            scale: 1.0,
+            offset: 0.0,
+            units: None,
+            access: Access::Read,
+        }
+    }
+
+    /// Range of the table that stores the tag
+    pub fn range(&self) -> AddressRange {
+        AddressRange {
+            start: self.address,
+            count: self.data_type.count(),
+        }
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        match (self.table.is_bits(), self.data_type) {
+            (true, DataType::Bool) => {}
+            (true, _) => return Err(format!("{} can only contain bool tags", self.table)),
+            (false, DataType::Bool) => {
+                return Err("bool tags in registers require a bit".to_string())
+            }
+            (false, DataType::Bit(bit)) if bit > 15 => {
+                return Err(format!("bit {} is outside of the register", bit))
+            }
+            (false, DataType::Bcd(count)) if count == 0 || count > 4 => {
+                return Err("bcd tags require a count between 1 and 4".to_string())
+            }
+            (false, DataType::String(count))
+                if count == 0 || count > crate::constants::limits::MAX_READ_REGISTERS_COUNT =>
+            {
+                return Err(format!(
+                    "string tags require a count between 1 and {}",
+                    crate::constants::limits::MAX_READ_REGISTERS_COUNT
+                ))
+            }
+            (false, _) => {}
+        }
+        if let Err(err) = AddressRange::try_from(self.address, self.data_type.count()) {
+            return Err(err.to_string());
+        }
+        if self.access == Access::ReadWrite
+            && !matches!(self.table, Table::Coils | Table::HoldingRegisters)
+        {
+            return Err(format!("{} can't be written", self.table));
+        }
+        if !self.scale.is_finite() || self.scale == 0.0 || !self.offset.is_finite() {
+            return Err("scale must be finite and non-zero, and offset must be finite".to_string());
+        }
+        Ok(())
+    }
+
+    fn scaled(&self, raw: f64) -> TagValue {
+        TagValue::Number(raw * self.scale + self.offset)
+    }
+
+    // decode the value from the registers of the tag
+    pub(crate) fn decode(&self, registers: &[u16]) -> Result<TagValue, CodecError> {
+        let order = self.order;
+        let value = match self.data_type {
+            DataType::Bool => return Err(CodecError::WrongRegisterCount(registers.len(), 0)),
+            DataType::Bit(bit) => {
+                let register = u16::from_registers(registers, ByteOrder::Abcd)?;
+                TagValue::Bool(register & (1 << bit) != 0)
+            }
+            DataType::U16 => self.scaled(u16::from_registers(registers, order)? as f64),
+            DataType::I16 => self.scaled(i16::from_registers(registers, order)? as f64),
+            DataType::U32 => self.scaled(u32::from_registers(registers, order)? as f64),
+            DataType::I32 => self.scaled(i32::from_registers(registers, order)? as f64),
+            DataType::F32 => self.scaled(f32::from_registers(registers, order)? as f64),
+            DataType::U64 => self.scaled(u64::from_registers(registers, order)? as f64),
+            DataType::I64 => self.scaled(i64::from_registers(registers, order)? as f64),
+            DataType::F64 => self.scaled(f64::from_registers(registers, order)?),
+            DataType::Bcd(_) => self.scaled(decode_bcd(registers, order)? as f64),
+            DataType::String(_) => TagValue::Text(decode_string(registers, order)),
+        };
+        Ok(value)
+    }
+
+    // encode an engineering value into what is written to the table
+    pub(crate) fn encode(&self, value: &TagValue) -> Result<TagWrite, InvalidRequest> {
+        let order = self.order;
+        let write = match (self.data_type, value) {
+            (DataType::Bool, TagValue::Bool(x)) => TagWrite::Coil(*x),
+            (DataType::Bit(bit), TagValue::Bool(x)) => TagWrite::RegisterBit(bit, *x),
+            (DataType::String(count), TagValue::Text(x)) => {
+                TagWrite::Registers(encode_string(x, count, order)?)
+            }
+            (DataType::F32, TagValue::Number(x)) => {
+                TagWrite::Registers((self.raw(*x)? as f32).to_registers(order))
+            }
+            (DataType::F64, TagValue::Number(x)) => {
+                TagWrite::Registers(self.raw(*x)?.to_registers(order))
+            }
+            (data_type, TagValue::Number(x)) => {
+                let raw = self.raw(*x)?.round();
+                let out_of_range = |min: f64, max: f64| -> Result<(), CodecError> {
+                    if raw < min || raw > max {
+                        return Err(CodecError::ValueOutOfRange(*x));
+                    }
+                    Ok(())
+                };
+                let registers = match data_type {
+                    DataType::U16 => {
+                        out_of_range(0.0, u16::MAX as f64)?;
+                        (raw as u16).to_registers(order)
+                    }
+                    DataType::I16 => {
+                        out_of_range(i16::MIN as f64, i16::MAX as f64)?;
+                        (raw as i16).to_registers(order)
+                    }
+                    DataType::U32 => {
+                        out_of_range(0.0, u32::MAX as f64)?;
+                        (raw as u32).to_registers(order)
+                    }
+                    DataType::I32 => {
+                        out_of_range(i32::MIN as f64, i32::MAX as f64)?;
+                        (raw as i32).to_registers(order)
+                    }
+                    DataType::U64 => {
+                        out_of_range(0.0, u64::MAX as f64)?;
+                        (raw as u64).to_registers(order)
+                    }
+                    DataType::I64 => {
+                        out_of_range(i64::MIN as f64, i64::MAX as f64)?;
+                        (raw as i64).to_registers(order)
+                    }
+                    DataType::Bcd(count) => {
+                        out_of_range(0.0, u64::MAX as f64)?;
+                        encode_bcd(raw as u64, count, order)?
+                    }
+                    _ => return Err(InvalidRequest::WrongTagValueType),
+                };
+                TagWrite::Registers(registers)
+            }
+            _ => return Err(InvalidRequest::WrongTagValueType),
+        };
+        Ok(write)
+    }
+
+    // raw value of an engineering value
+    fn raw(&self, value: f64) -> Result<f64, CodecError> {
+        let raw = (value - self.offset) / self.scale;
+        if !raw.is_finite() {
+            return Err(CodecError::ValueOutOfRange(value));
+        }
+        Ok(raw)
+    }
+}
+
+impl DeviceProfile {
+    /// Create a profile from a list of tags
+    pub fn new(tags: Vec<Tag>) -> Result<Self, ProfileError> {
+        let mut names = BTreeMap::new();
+        for (index, tag) in tags.iter().enumerate() {
+            tag.validate()
+                .map_err(|reason| ProfileError::InvalidTag(tag.name.clone(), reason))?;
+            if names.insert(tag.name.clone(), index).is_some() {
+                return Err(ProfileError::DuplicateTag(tag.name.clone()));
+            }
+        }
+        Ok(Self { tags, names })
+    }
+
+    /// Load a profile from TOML text with a `[[tag]]` table per tag
+    pub fn from_toml(text: &str) -> Result<Self, ProfileError> {
+        let file: ProfileFile =
+            toml::from_str(text).map_err(|err| ProfileError::Parse(err.to_string()))?;
+        Self::from_records(file.tag)
+    }
+
+    /// Load a profile from CSV text with a header row that names the fields
+    pub fn from_csv(text: &str) -> Result<Self, ProfileError> {
+        let mut reader = csv::ReaderBuilder::new()
+            .trim(csv::Trim::All)
+            .from_reader(text.as_bytes());
+        let records = reader
+            .deserialize()
+            .collect::<Result<Vec<TagRecord>, csv::Error>>()
+            .map_err(|err| ProfileError::Parse(err.to_string()))?;
+        Self::from_records(records)
+    }
+
+    /// Tag with the specified name
+    pub fn get(&self, name: &str) -> Option<&Tag> {
+        self.names.get(name).and_then(|x| self.tags.get(*x))
+    }
+
+    /// All of the tags in the order they were defined
+    pub fn tags(&self) -> impl Iterator<Item = &Tag> {
+        self.tags.iter()
+    }
+
+    /// Batch that reads every tag of the profile
+    pub fn read_batch(&self, options: BatchOptions) -> ReadBatch {
+        let mut batch = ReadBatch::new(options);
+        for tag in self.tags.iter() {
+            batch.add(tag.table, tag.range());
+        }
+        batch
+    }
+
+    // decode the value of a tag from the result of a batch read
+    pub(crate) fn decode(tag: &Tag, values: &BatchValues) -> Result<TagValue, CodecError> {
+        let range = tag.range();
+        let registers = match tag.table {
+            Table::Coils => return decode_bit(&values.coils, range.start),
+            Table::DiscreteInputs => return decode_bit(&values.discrete_inputs, range.start),
+            Table::HoldingRegisters => collect_registers(&values.holding_registers, range),
+            Table::InputRegisters => collect_registers(&values.input_registers, range),
+        };
+        tag.decode(&registers)
+    }
+
+    fn from_records(records: Vec<TagRecord>) -> Result<Self, ProfileError> {
+        let tags = records
+            .into_iter()
+            .map(|x| {
+                let name = x.name.clone();
+                x.into_tag()
+                    .map_err(|reason| ProfileError::InvalidTag(name, reason))
+            })
+            .collect::<Result<Vec<Tag>, ProfileError>>()?;
+        Self::new(tags)
+    }
+}
+
+fn decode_bit(values: &BTreeMap<u16, bool>, address: u16) -> Result<TagValue, CodecError> {
+    match values.get(&address) {
+        Some(x) => Ok(TagValue::Bool(*x)),
+        None => Err(CodecError::WrongRegisterCount(0, 1)),
+    }
+}
+
+fn collect_registers(values: &BTreeMap<u16, u16>, range: AddressRange) -> Vec<u16> {
+    range
+        .iter()
+        .map_while(|address| values.get(&address).copied())
+        .collect()
+}
+
+impl TagRecord {
+    fn into_tag(self) -> Result<Tag, String> {
+        let table = match self.table.as_str() {
+            "coils" => Table::Coils,
+            "discrete_inputs" => Table::DiscreteInputs,
+            "holding_registers" => Table::HoldingRegisters,
+            "input_registers" => Table::InputRegisters,
+            x => return Err(format!("unknown table: {}", x)),
+        };
+        let count = || {
+            self.count
+                .ok_or_else(|| format!("{} tags require a count", self.data_type))
+        };
+        let data_type = match (self.data_type.as_str(), self.bit) {
+            ("bool", None) => DataType::Bool,
+            ("bool", Some(bit)) => DataType::Bit(bit),
+            (_, Some(_)) => return Err("only bool tags have a bit".to_string()),
+            ("u16", None) => DataType::U16,
+            ("i16", None) => DataType::I16,
+            ("u32", None) => DataType::U32,
+            ("i32", None) => DataType::I32,
+            ("f32", None) => DataType::F32,
+            ("u64", None) => DataType::U64,
+            ("i64", None) => DataType::I64,
+            ("f64", None) => DataType::F64,
+            ("bcd", None) => DataType::Bcd(count()?),
+            ("string", None) => DataType::String(count()?),
+            (x, None) => return Err(format!("unknown type: {}", x)),
+        };
+        let order = match self.order.as_deref() {
+            None | Some("abcd") => ByteOrder::Abcd,
+            Some("cdab") => ByteOrder::Cdab,
+            Some("badc") => ByteOrder::Badc,
+            Some("dcba") => ByteOrder::Dcba,
+            Some(x) => return Err(format!("unknown order: {}", x)),
+        };
+        let access = match self.access.as_deref() {
+            None | Some("read") => Access::Read,
+            Some("read_write") => Access::ReadWrite,
+            Some(x) => return Err(format!("unknown access: {}", x)),
+        };
+        Ok(Tag {
+            name: self.name,
+            table,
+            address: self.address,
+            data_type,
+            order,
+            scale: self.scale.unwrap_or(1.0),
+            offset: self.offset.unwrap_or(0.0),
+            units: self.units.filter(|x| !x.is_empty()),
+            access,
+        })
+    }
+}
+
+/// [`RequestHandler`] that simulates the device described by a [`DeviceProfile`]
+///
+/// Every coil, input and register of the tags is initially zero. Reads of other addresses
+/// fail with [`ExceptionCode::IllegalDataAddress`], as do writes of tags that can only be read.
+#[derive(Debug)]
+pub struct ProfileHandler {
+    profile: DeviceProfile,
+    values: BatchValues,
+    writable_coils: BTreeSet<u16>,
+    writable_registers: BTreeSet<u16>,
+}
+
+impl ProfileHandler {
+    /// Create a handler for the tags of a profile
+    pub fn new(profile: DeviceProfile) -> Self {
+        let mut values = BatchValues::default();
+        let mut writable_coils = BTreeSet::new();
+        let mut writable_registers = BTreeSet::new();
+        for tag in profile.tags() {
+            for address in tag.range().iter() {
+                match tag.table {
+                    Table::Coils => {
+                        values.coils.insert(address, false);
+                    }
+                    Table::DiscreteInputs => {
+                        values.discrete_inputs.insert(address, false);
+                    }
+                    Table::HoldingRegisters => {
+                        values.holding_registers.insert(address, 0);
+                    }
+                    Table::InputRegisters => {
+                        values.input_registers.insert(address, 0);
+                    }
+                }
+                if tag.access == Access::ReadWrite {
+                    match tag.table {
+                        Table::Coils => writable_coils.insert(address),
+                        _ => writable_registers.insert(address),
+                    };
+                }
+            }
+        }
+        Self {
+            profile,
+            values,
+            writable_coils,
+            writable_registers,
+        }
+    }
+
+    /// Current value of a tag
+    pub fn get_tag(&self, name: &str) -> Option<TagValue> {
+        let tag = self.profile.get(name)?;
+        DeviceProfile::decode(tag, &self.values).ok()
+    }
+
+    /// Set the value of a tag, regardless of its access
+    pub fn set_tag(&mut self, name: &str, value: &TagValue) -> Result<(), InvalidRequest> {
+        let tag = self.profile.get(name).ok_or(InvalidRequest::UnknownTag)?;
+        let address = tag.address;
+        let write = tag.encode(value)?;
+        let table = tag.table;
+        match write {
+            TagWrite::Coil(x) => {
+                let map = match table {
+                    Table::Coils => &mut self.values.coils,
+                    _ => &mut self.values.discrete_inputs,
+                };
+                map.insert(address, x);
+            }
+            TagWrite::RegisterBit(bit, x) => {
+                let map = self.registers_mut(table);
+                let register = map.entry(address).or_insert(0);
+                *register = (*register & !(1 << bit)) | ((x as u16) << bit);
+            }
+            TagWrite::Registers(registers) => {
+                let map = self.registers_mut(table);
+                for (address, x) in tag_addresses(address, &registers) {
+                    map.insert(address, x);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn registers_mut(&mut self, table: Table) -> &mut BTreeMap<u16, u16> {
+        match table {
+            Table::InputRegisters => &mut self.values.input_registers,
+            _ => &mut self.values.holding_registers,
+        }
+    }
+}
+
+fn tag_addresses(address: u16, registers: &[u16]) -> impl Iterator<Item = (u16, u16)> + '_ {
+    (address..=u16::MAX).zip(registers.iter().copied())
+}
+
+fn read<T: Copy>(values: &BTreeMap<u16, T>, address: u16) -> Result<T, ExceptionCode> {
+    values
+        .get(&address)
+        .copied()
+        .ok_or(ExceptionCode::IllegalDataAddress)
+}
+
+impl RequestHandler for ProfileHandler {
+    fn read_coil(&self, address: u16) -> Result<bool, ExceptionCode> {
+        read(&self.values.coils, address)
+    }
+
+    fn read_discrete_input(&self, address: u16) -> Result<bool, ExceptionCode> {
+        read(&self.values.discrete_inputs, address)
+    }
+
+    fn read_holding_register(&self, address: u16) -> Result<u16, ExceptionCode> {
+        read(&self.values.holding_registers, address)
+    }
+
+    fn read_input_register(&self, address: u16) -> Result<u16, ExceptionCode> {
+        read(&self.values.input_registers, address)
+    }
+
+    fn write_single_coil(&mut self, value: Indexed<bool>) -> Result<(), ExceptionCode> {
+        if !self.writable_coils.contains(&value.index) {
+            return Err(ExceptionCode::IllegalDataAddress);
+        }
+        self.values.coils.insert(value.index, value.value);
+        Ok(())
+    }
+
+    fn write_single_register(&mut self, value: Indexed<u16>) -> Result<(), ExceptionCode> {
+        if !self.writable_registers.contains(&value.index) {
+            return Err(ExceptionCode::IllegalDataAddress);
+        }
+        self.values
+            .holding_registers
+            .insert(value.index, value.value);
+        Ok(())
+    }
+
+    fn write_multiple_coils(&mut self, values: WriteCoils) -> Result<(), ExceptionCode> {
+        if !values
+            .iterator
+            .clone()
+            .all(|x| self.writable_coils.contains(&x.index))
+        {
+            return Err(ExceptionCode::IllegalDataAddress);
+        }
+        for x in values.iterator {
+            self.values.coils.insert(x.index, x.value);
+        }
+        Ok(())
+    }
+
+    fn write_multiple_registers(&mut self, values: WriteRegisters) -> Result<(), ExceptionCode> {
+        if !values
+            .iterator
+            .clone()
+            .all(|x| self.writable_registers.contains(&x.index))
+        {
+            return Err(ExceptionCode::IllegalDataAddress);
+        }
+        for x in values.iterator {
+            self.values.holding_registers.insert(x.index, x.value);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TOML: &str = r#"
+[[tag]]
+name = "flow"
+table = "input_registers"
+address = 10
+type = "f32"
+order = "cdab"
+scale = 0.5
+units = "m3/h"
+
+[[tag]]
+name = "setpoint"
+table = "holding_registers"
+address = 0
+type = "i16"
+scale = 0.1
+offset = -10.0
+access = "read_write"
+
+[[tag]]
+name = "running"
+table = "coils"
+address = 3
+type = "bool"
+access = "read_write"
+
+[[tag]]
+name = "alarm"
+table = "holding_registers"
+address = 1
+type = "bool"
+bit = 4
+access = "read_write"
+
+[[tag]]
+name = "model"
+table = "holding_registers"
+address = 2
+type = "string"
+count = 3
+"#;
+
+    const CSV: &str = "name, table, address, type, order, count, bit, scale, offset, units, access
+flow, input_registers, 10, f32, cdab, , , 0.5, , m3/h,
+serial, input_registers, 20, bcd, , 2, , , , ,
+";
+
+    #[test]
+    fn loads_profile_from_toml() {
+        let profile = DeviceProfile::from_toml(TOML).unwrap();
+        assert_eq!(profile.tags().count(), 5);
+        let flow = profile.get("flow").unwrap();
+        assert_eq!(flow.table, Table::InputRegisters);
+        assert_eq!(flow.data_type, DataType::F32);
+        assert_eq!(flow.order, ByteOrder::Cdab);
+        assert_eq!(flow.units.as_deref(), Some("m3/h"));
+        assert_eq!(flow.access, Access::Read);
+        assert_eq!(profile.get("alarm").unwrap().data_type, DataType::Bit(4));
+        assert!(profile.get("pressure").is_none());
+    }
+
+    #[test]
+    fn loads_profile_from_csv() {
+        let profile = DeviceProfile::from_csv(CSV).unwrap();
+        let mut expected = Tag::new("flow", Table::InputRegisters, 10, DataType::F32);
+        expected.order = ByteOrder::Cdab;
+        expected.scale = 0.5;
+        expected.units = Some("m3/h".to_string());
+        assert_eq!(profile.get("flow"), Some(&expected));
+        assert_eq!(profile.get("serial").unwrap().data_type, DataType::Bcd(2));
+    }
+
+    #[test]
+    fn rejects_invalid_tags() {
+        let invalid = |tag: Tag| match DeviceProfile::new(vec![tag]) {
+            Err(ProfileError::InvalidTag(_, _)) => {}
+            x => panic!("unexpected result: {:?}", x),
+        };
+        invalid(Tag::new("a", Table::Coils, 0, DataType::U16));
+        invalid(Tag::new("a", Table::HoldingRegisters, 0, DataType::Bool));
+        invalid(Tag::new("a", Table::HoldingRegisters, 0, DataType::Bit(16)));
+        invalid(Tag::new(
+            "a",
+            Table::HoldingRegisters,
+            0xFFFF,
+            DataType::U32,
+        ));
+        invalid(Tag::new("a", Table::HoldingRegisters, 0, DataType::Bcd(5)));
+        let mut tag = Tag::new("a", Table::InputRegisters, 0, DataType::U16);
+        tag.access = Access::ReadWrite;
+        invalid(tag);
+
+        assert_eq!(
+            DeviceProfile::new(vec![
+                Tag::new("a", Table::Coils, 0, DataType::Bool),
+                Tag::new("a", Table::Coils, 1, DataType::Bool),
+            ]),
+            Err(ProfileError::DuplicateTag("a".to_string()))
+        );
+        assert!(matches!(
+            DeviceProfile::from_toml(
+                "[[tag]]\nname = \"a\"\ntable = \"registers\"\naddress = 0\ntype = \"u16\""
+            ),
+            Err(ProfileError::InvalidTag(_, _))
+        ));
+        assert!(matches!(
+            DeviceProfile::from_toml("[[tag]]\nname = 1"),
+            Err(ProfileError::Parse(_))
+        ));
+    }
+
+    #[test]
+    fn decodes_and_encodes_engineering_values() {
+        let profile = DeviceProfile::from_toml(TOML).unwrap();
+        let setpoint = profile.get("setpoint").unwrap();
+        assert_eq!(setpoint.decode(&[0xFFFF]), Ok(TagValue::Number(-10.1)));
+        match setpoint.encode(&TagValue::Number(12.5)).unwrap() {
+            TagWrite::Registers(x) => assert_eq!(x, vec![225]),
+            _ => panic!("unexpected write"),
+        }
+        assert_eq!(
+            setpoint.encode(&TagValue::Number(5000.0)).err(),
+            Some(InvalidRequest::BadValue(CodecError::ValueOutOfRange(
+                5000.0
+            )))
+        );
+        assert_eq!(
+            setpoint.encode(&TagValue::Bool(true)).err(),
+            Some(InvalidRequest::WrongTagValueType)
+        );
+
+        let flow = profile.get("flow").unwrap();
+        assert_eq!(flow.decode(&[0x0000, 0x4120]), Ok(TagValue::Number(5.0)));
+    }
+
+    #[test]
+    fn handler_simulates_the_profile() {
+        let profile = DeviceProfile::from_toml(TOML).unwrap();
+        let mut handler = ProfileHandler::new(profile);
+
+        handler.set_tag("flow", &TagValue::Number(5.0)).unwrap();
+        assert_eq!(handler.read_input_register(10), Ok(0x0000));
+        assert_eq!(handler.read_input_register(11), Ok(0x4120));
+        assert_eq!(handler.get_tag("flow"), Some(TagValue::Number(5.0)));
+
+        handler.set_tag("alarm", &TagValue::Bool(true)).unwrap();
+        assert_eq!(handler.read_holding_register(1), Ok(0x0010));
+        handler
+            .set_tag("model", &TagValue::Text("X1".to_string()))
+            .unwrap();
+        assert_eq!(
+            handler.get_tag("model"),
+            Some(TagValue::Text("X1".to_string()))
+        );
+
+        assert_eq!(
+            handler.read_holding_register(5),
+            Err(ExceptionCode::IllegalDataAddress)
+        );
+        assert_eq!(
+            handler.write_single_register(Indexed::new(2, 0)),
+            Err(ExceptionCode::IllegalDataAddress)
+        );
+        assert_eq!(handler.write_single_register(Indexed::new(0, 225)), Ok(()));
+        assert_eq!(handler.get_tag("setpoint"), Some(TagValue::Number(12.5)));
+        assert_eq!(
+            handler.set_tag("pressure", &TagValue::Number(1.0)),
+            Err(InvalidRequest::UnknownTag)
+        );
+    }
+}
@@ -0,0 +1,481 @@
+use crate::common::buffer::ReadBuffer;
+use crate::common::cursor::WriteCursor;
+use crate::common::frame::{Frame, FrameFormatter, FrameHeader, FrameParser};
+use crate::common::traits::Serialize;
+use crate::decode::AduDecodeLevel;
+use crate::error::{FrameParseError, InternalError, RequestError};
+use crate::types::UnitId;
+
+pub(crate) mod constants {
+    pub(crate) const START: u8 = b':';
+    pub(crate) const CR: u8 = b'\r';
+    pub(crate) const LF: u8 = b'\n';
+    pub(crate) const HEADER_LENGTH: usize = 1;
+    pub(crate) const FUNCTION_CODE_LENGTH: usize = 1;
+    pub(crate) const LRC_LENGTH: usize = 1;
+    /// unit id, ADU, and LRC before they are hex encoded
+    pub(crate) const MAX_BINARY_LENGTH: usize =
+        HEADER_LENGTH + crate::common::frame::constants::MAX_ADU_LENGTH + LRC_LENGTH;
+    /// start character, every byte as two hex characters, CR + LF
+    pub(crate) const MAX_FRAME_LENGTH: usize = 1 + 2 * MAX_BINARY_LENGTH + 2;
+}
+
+/// LRC as specified in the Modbus over serial line specification (two's complement of the sum of all bytes)
+pub(crate) fn calc_lrc(data: &[u8]) -> u8 {
+    data.iter()
+        .fold(0u8, |sum, byte| sum.wrapping_add(*byte))
+        .wrapping_neg()
+}
+
+fn encode_nibble(value: u8) -> u8 {
+    match value & 0x0F {
+        x @ 0..=9 => b'0' + x,
+        x => b'A' + (x - 10),
+    }
+}
+
+fn decode_nibble(value: u8) -> Result<u8, FrameParseError> {
+    match value {
+        b'0'..=b'9' => Ok(value - b'0'),
+        b'A'..=b'F' => Ok(value - b'A' + 10),
+        b'a'..=b'f' => Ok(value - b'a' + 10),
+        _ => Err(FrameParseError::InvalidAsciiCharacter(value)),
+    }
+}
+
+/// ASCII frames are delimited by a start character and CR + LF, so unlike RTU,
+/// the same parser is used for both requests and responses
+pub(crate) struct AsciiParser {
+    decode: AduDecodeLevel,
+}
+
+pub(crate) struct AsciiFormatter {
+    binary: [u8; constants::MAX_BINARY_LENGTH],
+    buffer: [u8; constants::MAX_FRAME_LENGTH],
+    decode: AduDecodeLevel,
+}
+
+impl AsciiParser {
+    pub(crate) fn new(decode: AduDecodeLevel) -> Self {
+        Self { decode }
+    }
+
+    // decode everything between the start character and the LF
+    fn parse_body(&self, body: &[u8]) -> Result<Frame, RequestError> {
+        let hex = match body.split_last() {
+            Some((&constants::CR, hex)) => hex,
+            Some((other, _)) => return Err(FrameParseError::InvalidAsciiCharacter(*other).into()),
+            None => return Err(FrameParseError::InvalidAsciiLength(0).into()),
+        };
+
+        const MIN_BINARY_LENGTH: usize =
+            constants::HEADER_LENGTH + constants::FUNCTION_CODE_LENGTH + constants::LRC_LENGTH;
+
+        if hex.len() % 2 != 0 || hex.len() < 2 * MIN_BINARY_LENGTH {
+            return Err(FrameParseError::InvalidAsciiLength(hex.len()).into());
+        }
+
+        let mut binary = [0u8; constants::MAX_BINARY_LENGTH];
+        let length = hex.len() / 2;
+        if length > binary.len() {
+            return Err(FrameParseError::FrameLengthTooBig(
+                body.len() + 2,
+                constants::MAX_FRAME_LENGTH,
+            )
+            .into());
+        }
+
+        for (dest, pair) in binary.iter_mut().zip(hex.chunks(2)) {
+            if let [high, low] = pair {
+                *dest = (decode_nibble(*high)? << 4) | decode_nibble(*low)?;
+            }
+        }
+
+        let (adu, received_lrc) = match binary[..length].split_last() {
+            Some((lrc, adu)) => (adu, *lrc),
+            None => return Err(InternalError::InsufficientBytesForRead(1, 0).into()),
+        };
+
+        let expected_lrc = calc_lrc(adu);
+        if received_lrc != expected_lrc {
+            return Err(FrameParseError::LrcValidationFailure(received_lrc, expected_lrc).into());
+        }
+
+        let (unit_id, pdu) = match adu.split_first() {
+            Some((unit_id, pdu)) => (UnitId::new(*unit_id), pdu),
+            None => return Err(InternalError::InsufficientBytesForRead(1, 0).into()),
+        };
+
+        let mut frame = Frame::new(FrameHeader::new_without_tx_id(unit_id));
+        frame.set(pdu);
+
+        if self.decode.enabled() {
+            tracing::info!(
+                "ASCII RX - {}",
+                AsciiDisplay::new(self.decode, unit_id, frame.payload(), received_lrc)
+            );
+        }
+
+        Ok(frame)
+    }
+}
+
+impl AsciiFormatter {
+    pub(crate) fn new(decode: AduDecodeLevel) -> Self {
+        Self {
+            binary: [0; constants::MAX_BINARY_LENGTH],
+            buffer: [0; constants::MAX_FRAME_LENGTH],
+            decode,
+        }
+    }
+}
+
+impl FrameParser for AsciiParser {
+    fn max_frame_size(&self) -> usize {
+        constants::MAX_FRAME_LENGTH
+    }
+
+    fn parse(&mut self, cursor: &mut ReadBuffer) -> Result<Option<Frame>, RequestError> {
+        let data = cursor.peek(cursor.len())?;
+
+        // anything preceding the start character is discarded
+        let start = match data.iter().position(|x| *x == constants::START) {
+            Some(x) => x,
+            None => {
+                cursor.read(data.len())?;
+                return Ok(None);
+            }
+        };
+
+        // a start character before the end of the frame restarts the frame
+        let end = data
+            .iter()
+            .skip(start + 1)
+            .position(|x| *x == constants::START || *x == constants::LF)
+            .map(|x| x + start + 1);
+
+        match end {
+            None => {
+                let length = data.len() - start;
+                if length >= constants::MAX_FRAME_LENGTH {
+                    cursor.read(data.len())?;
+                    return Err(FrameParseError::FrameLengthTooBig(
+                        length + 1,
+                        constants::MAX_FRAME_LENGTH,
+                    )
+                    .into());
+                }
+                cursor.read(start)?;
+                Ok(None)
+            }
+            Some(end) => {
+                if data.get(end) == Some(&constants::START) {
+                    cursor.read(end)?;
+                    return self.parse(cursor);
+                }
+
+                // the bytes are consumed whether or not the frame is valid
+                let frame = cursor.read(end + 1)?;
+                match frame.get(start + 1..end) {
+                    Some(body) => self.parse_body(body).map(Some),
+                    None => Err(InternalError::BadSeekOperation.into()),
+                }
+            }
+        }
+    }
+
+    fn reset(&mut self) {}
+}
+
+impl FrameFormatter for AsciiFormatter {
+    fn format_impl(
+        &mut self,
+        header: FrameHeader,
+        msg: &dyn Serialize,
+    ) -> Result<usize, RequestError> {
+        let end = {
+            let mut cursor = WriteCursor::new(self.binary.as_mut());
+            cursor.write_u8(header.unit_id.value)?;
+            msg.serialize(&mut cursor)?;
+            cursor.position()
+        };
+
+        let lrc = calc_lrc(&self.binary[..end]);
+        {
+            let mut cursor = WriteCursor::new(self.binary.as_mut());
+            cursor.seek_from_start(end)?;
+            cursor.write_u8(lrc)?;
+        }
+
+        let total_length = {
+            let mut cursor = WriteCursor::new(self.buffer.as_mut());
+            cursor.write_u8(constants::START)?;
+            for byte in self.binary[..end + constants::LRC_LENGTH].iter() {
+                cursor.write_u8(encode_nibble(byte >> 4))?;
+                cursor.write_u8(encode_nibble(*byte))?;
+            }
+            cursor.write_u8(constants::CR)?;
+            cursor.write_u8(constants::LF)?;
+            cursor.position()
+        };
+
+        // Logging
+        if self.decode.enabled() {
+            tracing::info!(
+                "ASCII TX - {}",
+                AsciiDisplay::new(
+                    self.decode,
+                    header.unit_id,
+                    &self.binary[constants::HEADER_LENGTH..end],
+                    lrc
+                )
+            );
+        }
+
+        Ok(total_length)
+    }
+
+    fn get_full_buffer_impl(&self, size: usize) -> Option<&[u8]> {
+        self.buffer.get(..size)
+    }
+
+    fn get_payload_impl(&self, size: usize) -> Option<&[u8]> {
+        // remove the start character and CR + LF, then each byte is two characters
+        let binary_length = size.checked_sub(3)? / 2;
+        self.binary
+            .get(constants::HEADER_LENGTH..binary_length.checked_sub(constants::LRC_LENGTH)?)
+    }
+}
+
+struct AsciiDisplay<'a> {
+    level: AduDecodeLevel,
+    unit_id: UnitId,
+    data: &'a [u8],
+    lrc: u8,
+}
+
+impl<'a> AsciiDisplay<'a> {
+    fn new(level: AduDecodeLevel, unit_id: UnitId, data: &'a [u8], lrc: u8) -> Self {
+        AsciiDisplay {
+            level,
+            unit_id,
+            data,
+            lrc,
+        }
+    }
+}
+
+impl<'a> std::fmt::Display for AsciiDisplay<'a> {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(
+            f,
+            "unit: {} lrc: {:#04X} (len = {})",
+            self.unit_id,
+            self.lrc,
+            self.data.len()
+        )?;
+        if self.level.payload_enabled() {
+            crate::common::phys::format_bytes(f, self.data)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::task::Poll;
+
+    use crate::common::phys::PhysLayer;
+    use crate::decode::PhysDecodeLevel;
+    use crate::tokio::test::*;
+
+    use crate::common::frame::FramedReader;
+    use crate::error::*;
+
+    use super::*;
+
+    //                                   |unit| fc |start|count|lrc|
+    const READ_REGISTERS_REQUEST: &[u8] = b":01030000000AF2\r\n";
+
+    //                                    |unit| fc |cnt|value|value|lrc|
+    const READ_REGISTERS_RESPONSE: &[u8] = b":01030400060005ED\r\n";
+
+    struct MockMessage<'a> {
+        data: &'a [u8],
+    }
+
+    impl<'a> Serialize for MockMessage<'a> {
+        fn serialize(&self, cursor: &mut WriteCursor) -> Result<(), RequestError> {
+            for byte in self.data {
+                cursor.write_u8(*byte)?;
+            }
+            Ok(())
+        }
+    }
+
+    fn test_parse(input: &[u8], split_at: usize) -> Frame {
+        let (f1, f2) = input.split_at(split_at);
+        let (io, mut io_handle) = io::mock();
+        let mut reader = FramedReader::new(AsciiParser::new(AduDecodeLevel::Nothing));
+        let mut layer = PhysLayer::new_mock(io, PhysDecodeLevel::Nothing);
+        let mut task = spawn(reader.next_frame(&mut layer));
+
+        assert!(task.poll().is_pending());
+        io_handle.read(f1);
+        if !f2.is_empty() {
+            assert!(task.poll().is_pending());
+            io_handle.read(f2);
+        }
+        if let Poll::Ready(frame) = task.poll() {
+            frame.unwrap()
+        } else {
+            panic!("Task not ready");
+        }
+    }
+
+    fn test_error(input: &[u8]) -> RequestError {
+        let (io, mut io_handle) = io::mock();
+        let mut reader = FramedReader::new(AsciiParser::new(AduDecodeLevel::Nothing));
+        let mut layer = PhysLayer::new_mock(io, PhysDecodeLevel::Nothing);
+        let mut task = spawn(reader.next_frame(&mut layer));
+
+        io_handle.read(input);
+        if let Poll::Ready(frame) = task.poll() {
+            frame.err().unwrap()
+        } else {
+            panic!("Task not ready");
+        }
+    }
+
+    #[test]
+    fn calculates_modbus_lrc() {
+        assert_eq!(calc_lrc(&[0x01, 0x03, 0x00, 0x00, 0x00, 0x0A]), 0xF2);
+        assert_eq!(calc_lrc(&[0xFF, 0x01]), 0x00);
+    }
+
+    #[test]
+    fn correctly_formats_frame() {
+        let mut formatter = AsciiFormatter::new(AduDecodeLevel::Nothing);
+        let msg = MockMessage {
+            data: &[0x03, 0x00, 0x00, 0x00, 0x0A],
+        };
+        let header = FrameHeader::new_without_tx_id(UnitId::new(1));
+        let size = formatter.format_impl(header, &msg).unwrap();
+
+        assert_eq!(
+            formatter.get_full_buffer_impl(size).unwrap(),
+            READ_REGISTERS_REQUEST
+        );
+        assert_eq!(
+            formatter.get_payload_impl(size).unwrap(),
+            &[0x03, 0x00, 0x00, 0x00, 0x0A]
+        );
+    }
+
+    #[test]
+    fn can_parse_frame_split_at_any_position() {
+        for split_at in 1..READ_REGISTERS_REQUEST.len() {
+            let frame = test_parse(READ_REGISTERS_REQUEST, split_at);
+            assert_eq!(frame.header.unit_id, UnitId::new(1));
+            assert!(frame.header.tx_id.is_none());
+            assert_eq!(frame.payload(), &[0x03, 0x00, 0x00, 0x00, 0x0A]);
+        }
+    }
+
+    #[test]
+    fn can_parse_response() {
+        let frame = test_parse(READ_REGISTERS_RESPONSE, 9);
+        assert_eq!(frame.payload(), &[0x03, 0x04, 0x00, 0x06, 0x00, 0x05]);
+    }
+
+    #[test]
+    fn can_parse_lower_case_hex() {
+        let frame = test_parse(b":010304CAFE00012F\r\n", 5);
+        assert_eq!(frame.payload(), &[0x03, 0x04, 0xCA, 0xFE, 0x00, 0x01]);
+        let frame = test_parse(b":010304cafe00012f\r\n", 5);
+        assert_eq!(frame.payload(), &[0x03, 0x04, 0xCA, 0xFE, 0x00, 0x01]);
+    }
+
+    #[test]
+    fn discards_bytes_before_start_character() {
+        let mut input = b"\x00\x01garbage\r\n".to_vec();
+        input.extend_from_slice(READ_REGISTERS_REQUEST);
+        let frame = test_parse(&input, 4);
+        assert_eq!(frame.payload(), &[0x03, 0x00, 0x00, 0x00, 0x0A]);
+    }
+
+    #[test]
+    fn start_character_restarts_frame() {
+        let mut input = b":0103".to_vec();
+        input.extend_from_slice(READ_REGISTERS_REQUEST);
+        let frame = test_parse(&input, 3);
+        assert_eq!(frame.payload(), &[0x03, 0x00, 0x00, 0x00, 0x0A]);
+    }
+
+    #[test]
+    fn can_parse_maximum_size_frame() {
+        // 1 byte function code + 1 byte count + 251 bytes = 253 byte ADU
+        let mut msg = vec![0x03, 0xFB];
+        msg.extend_from_slice(&[0xCC; 0xFB]);
+
+        let mut formatter = AsciiFormatter::new(AduDecodeLevel::Nothing);
+        let header = FrameHeader::new_without_tx_id(UnitId::new(1));
+        let size = formatter
+            .format_impl(header, &MockMessage { data: &msg })
+            .unwrap();
+        assert_eq!(size, constants::MAX_FRAME_LENGTH);
+
+        let input = formatter.get_full_buffer_impl(size).unwrap().to_vec();
+        let frame = test_parse(&input, 100);
+        assert_eq!(frame.payload(), msg.as_slice());
+    }
+
+    #[test]
+    fn errors_when_frame_length_too_big() {
+        let mut input = vec![b':'];
+        input.extend_from_slice(&[b'0'; constants::MAX_FRAME_LENGTH - 1]);
+        assert_eq!(
+            test_error(&input),
+            RequestError::BadFrame(FrameParseError::FrameLengthTooBig(
+                constants::MAX_FRAME_LENGTH + 1,
+                constants::MAX_FRAME_LENGTH,
+            ))
+        );
+    }
+
+    #[test]
+    fn errors_on_bad_lrc() {
+        assert_eq!(
+            test_error(b":01030000000AF3\r\n"),
+            RequestError::BadFrame(FrameParseError::LrcValidationFailure(0xF3, 0xF2))
+        );
+    }
+
+    #[test]
+    fn errors_on_invalid_hex_character() {
+        assert_eq!(
+            test_error(b":0103000G000AF2\r\n"),
+            RequestError::BadFrame(FrameParseError::InvalidAsciiCharacter(b'G'))
+        );
+    }
+
+    #[test]
+    fn errors_on_missing_carriage_return() {
+        assert_eq!(
+            test_error(b":01030000000AF2\n"),
+            RequestError::BadFrame(FrameParseError::InvalidAsciiCharacter(b'2'))
+        );
+    }
+
+    #[test]
+    fn errors_on_invalid_number_of_characters() {
+        assert_eq!(
+            test_error(b":01030000000AF\r\n"),
+            RequestError::BadFrame(FrameParseError::InvalidAsciiLength(13))
+        );
+        assert_eq!(
+            test_error(b":01FE\r\n"),
+            RequestError::BadFrame(FrameParseError::InvalidAsciiLength(4))
+        );
+    }
+}
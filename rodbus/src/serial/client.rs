@@ -0,0 +1,113 @@
+use crate::common::frame::{FrameFormatter, FrameParser};
+use crate::common::phys::PhysLayer;
+use crate::decode::DecodeLevel;
+use crate::serial::settings::SerialSettings;
+use crate::tokio::sync::watch;
+
+use crate::client::channel::ChannelState;
+use crate::client::task::{ClientLoop, SessionError};
+
+/// Channel task that is generic over the serial framing (RTU or ASCII)
+pub(crate) struct SerialChannelTask<F, P>
+where
+    F: FrameFormatter,
+    P: FrameParser,
+{
+    path: String,
+    serial_settings: SerialSettings,
+    client_loop: ClientLoop<F, P>,
+    decode: DecodeLevel,
+    state: watch::Sender<ChannelState>,
+}
+
+impl<F, P> SerialChannelTask<F, P>
+where
+    F: FrameFormatter,
+    P: FrameParser,
+{
+    pub(crate) fn new(
+        path: &str,
+        serial_settings: SerialSettings,
+        client_loop: ClientLoop<F, P>,
+        decode: DecodeLevel,
+        state: watch::Sender<ChannelState>,
+    ) -> Self {
+        Self {
+            path: path.to_string(),
+            serial_settings,
+            client_loop,
+            decode,
+            state,
+        }
+    }
+
+    pub(crate) async fn run(&mut self) {
+        self.run_until_shutdown().await;
+        self.set_state(ChannelState::Disabled);
+    }
+
+    async fn run_until_shutdown(&mut self) {
+        loop {
+            let result = if self.client_loop.is_enabled() {
+                self.run_port().await
+            } else {
+                self.set_state(ChannelState::Disabled);
+                self.client_loop.wait_until_enabled().await
+            };
+
+            if let Err(SessionError::Shutdown) = result {
+                // the mpsc was closed, end the task
+                return;
+            }
+        }
+    }
+
+    // try to open the port and run a session, then wait before re-opening it
+    async fn run_port(&mut self) -> Result<(), SessionError> {
+        self.set_state(ChannelState::Connecting);
+        match self.serial_settings.open(&self.path) {
+            Err(e) => {
+                tracing::warn!("error opening serial port: {}", e);
+            }
+            Ok(serial) => {
+                self.client_loop.reset_delay();
+                let mut phys = PhysLayer::new_serial(serial, self.decode.physical);
+                tracing::info!("serial port open");
+                self.set_state(ChannelState::Connected);
+                match self.run_session(&mut phys).await {
+                    // the port is closed when the channel is disabled
+                    SessionError::Disabled => {
+                        tracing::info!("serial port closed");
+                        return Err(SessionError::Disabled);
+                    }
+                    SessionError::Shutdown => return Err(SessionError::Shutdown),
+                    _ => tracing::warn!("serial port closed"),
+                }
+            }
+        }
+
+        // wait before re-opening the port
+        let delay = self.client_loop.next_delay();
+        self.set_state(ChannelState::WaitingToRetry(delay));
+        self.client_loop.fail_requests_for(delay).await
+    }
+
+    fn set_state(&self, state: ChannelState) {
+        // only fails once every receiver is dropped, in which case nobody is interested
+        let _ = self.state.send(state);
+    }
+
+    async fn run_session(&mut self, phys: &mut PhysLayer) -> SessionError {
+        loop {
+            match self.client_loop.run(phys).await {
+                // a corrupted frame on a serial line doesn't require re-opening the port,
+                // just discard whatever was received and keep going
+                SessionError::BadFrame => {
+                    tracing::warn!("discarding received data after framing error");
+                    self.client_loop.reset();
+                }
+                err => return err,
+            }
+        }
+    }
+}
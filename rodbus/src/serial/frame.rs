@@ -0,0 +1,898 @@
+use std::time::Duration;
+
+use crate::common::buffer::ReadBuffer;
+use crate::common::cursor::WriteCursor;
+use crate::common::frame::{Frame, FrameFormatter, FrameHeader, FrameParser};
+use crate::common::function::FunctionCode;
+use crate::common::traits::Serialize;
+use crate::decode::AduDecodeLevel;
+use crate::error::{FrameParseError, InternalError, RequestError};
+use crate::types::UnitId;
+
+pub(crate) mod constants {
+    pub(crate) const HEADER_LENGTH: usize = 1;
+    pub(crate) const FUNCTION_CODE_LENGTH: usize = 1;
+    pub(crate) const CRC_LENGTH: usize = 2;
+    pub(crate) const MAX_FRAME_LENGTH: usize =
+        HEADER_LENGTH + crate::common::frame::constants::MAX_ADU_LENGTH + CRC_LENGTH;
+}
+
+/// CRC-16 as specified in the Modbus over serial line specification (polynomial 0xA001, initial value 0xFFFF)
+pub(crate) fn calc_crc(data: &[u8]) -> u16 {
+    let mut crc: u16 = 0xFFFF;
+    for byte in data {
+        crc ^= *byte as u16;
+        for _ in 0..8 {
+            if crc & 0x0001 != 0 {
+                crc = (crc >> 1) ^ 0xA001;
+            } else {
+                crc >>= 1;
+            }
+        }
+    }
+    crc
+}
+
+/// RTU frames carry no length field, so the parser needs to know if it's
+/// parsing requests or responses to determine the length from the function code
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) enum ParserType {
+    Request,
+    Response,
+}
+
+#[derive(Clone, Copy)]
+enum ParseState {
+    Start,
+    // length of the data following the function code
+    ReadFullBody(UnitId, usize),
+    // offset of the byte count field following the function code
+    ReadToOffsetForLength(UnitId, usize),
+    // offset of the 16-bit byte count field following the function code
+    ReadToOffsetForWordLength(UnitId, usize),
+    // the length is unknown, the frame ends when the line goes silent
+    ReadUntilSilence(UnitId),
+    // the length is determined by walking the objects of a device identification response
+    ReadDeviceIdObjects(UnitId),
+}
+
+#[derive(Clone, Copy)]
+enum LengthMode {
+    // the length of the data is always the same
+    Fixed(usize),
+    // the length of the data is specified by a byte count at the given offset
+    Offset(usize),
+    // the length of the data is specified by a 16-bit byte count at the given offset
+    WordOffset(usize),
+    // the length of the data is the sum of the lengths of the device identification objects
+    DeviceIdObjects,
+    // the length cannot be determined
+    Unknown,
+}
+
+pub(crate) struct RtuParser {
+    state: ParseState,
+    parser_type: ParserType,
+    // inter-frame silence (t3.5), only meaningful on a serial line
+    silence: Option<Duration>,
+    decode: AduDecodeLevel,
+}
+
+pub(crate) struct RtuFormatter {
+    buffer: [u8; constants::MAX_FRAME_LENGTH],
+    decode: AduDecodeLevel,
+}
+
+impl RtuFormatter {
+    pub(crate) fn new(decode: AduDecodeLevel) -> Self {
+        Self {
+            buffer: [0; constants::MAX_FRAME_LENGTH],
+            decode,
+        }
+    }
+}
+
+impl RtuParser {
+    pub(crate) fn new_request_parser(silence: Option<Duration>, decode: AduDecodeLevel) -> Self {
+        Self {
+            state: ParseState::Start,
+            parser_type: ParserType::Request,
+            silence,
+            decode,
+        }
+    }
+
+    pub(crate) fn new_response_parser(silence: Option<Duration>, decode: AduDecodeLevel) -> Self {
+        Self {
+            state: ParseState::Start,
+            parser_type: ParserType::Response,
+            silence,
+            decode,
+        }
+    }
+
+    fn length_mode(&self, function_code: u8) -> LengthMode {
+        let function = match FunctionCode::get(function_code) {
+            Some(x) => x,
+            None => {
+                // exception responses only contain the exception code
+                if self.parser_type == ParserType::Response && function_code & 0x80 != 0 {
+                    return LengthMode::Fixed(1);
+                }
+                return LengthMode::Unknown;
+            }
+        };
+
+        match self.parser_type {
+            ParserType::Request => match function {
+                FunctionCode::ReadCoils
+                | FunctionCode::ReadDiscreteInputs
+                | FunctionCode::ReadHoldingRegisters
+                | FunctionCode::ReadInputRegisters
+                | FunctionCode::WriteSingleCoil
+                | FunctionCode::WriteSingleRegister => LengthMode::Fixed(4),
+                // every supported sub-function carries a single data word
+                FunctionCode::Diagnostics => LengthMode::Fixed(4),
+                FunctionCode::ReadExceptionStatus
+                | FunctionCode::GetCommEventCounter
+                | FunctionCode::GetCommEventLog
+                | FunctionCode::ReportServerId => LengthMode::Fixed(0),
+                FunctionCode::WriteMultipleCoils | FunctionCode::WriteMultipleRegisters => {
+                    LengthMode::Offset(4)
+                }
+                FunctionCode::ReadFileRecord | FunctionCode::WriteFileRecord => {
+                    LengthMode::Offset(0)
+                }
+                FunctionCode::MaskWriteRegister => LengthMode::Fixed(6),
+                FunctionCode::ReadWriteMultipleRegisters => LengthMode::Offset(8),
+                FunctionCode::ReadFifoQueue => LengthMode::Fixed(2),
+                FunctionCode::ReadDeviceIdentification => LengthMode::Fixed(3),
+                // the layout of user-defined functions is unknown
+                FunctionCode::Custom(_) => LengthMode::Unknown,
+            },
+            ParserType::Response => match function {
+                FunctionCode::ReadCoils
+                | FunctionCode::ReadDiscreteInputs
+                | FunctionCode::ReadHoldingRegisters
+                | FunctionCode::ReadInputRegisters
+                | FunctionCode::GetCommEventLog
+                | FunctionCode::ReportServerId
+                | FunctionCode::ReadFileRecord
+                | FunctionCode::WriteFileRecord
+                | FunctionCode::ReadWriteMultipleRegisters => LengthMode::Offset(0),
+                FunctionCode::WriteSingleCoil
+                | FunctionCode::WriteSingleRegister
+                | FunctionCode::WriteMultipleCoils
+                | FunctionCode::WriteMultipleRegisters
+                | FunctionCode::Diagnostics
+                | FunctionCode::GetCommEventCounter => LengthMode::Fixed(4),
+                FunctionCode::ReadExceptionStatus => LengthMode::Fixed(1),
+                FunctionCode::MaskWriteRegister => LengthMode::Fixed(6),
+                FunctionCode::ReadFifoQueue => LengthMode::WordOffset(0),
+                FunctionCode::ReadDeviceIdentification => LengthMode::DeviceIdObjects,
+                FunctionCode::Custom(_) => LengthMode::Unknown,
+            },
+        }
+    }
+
+    fn can_frame(&self, function: FunctionCode) -> bool {
+        self.silence.is_some()
+            || !matches!(self.length_mode(function.get_value()), LengthMode::Unknown)
+    }
+
+    // requests to user-defined functions are valid, so they are answered even if they can't be framed
+    fn can_discard_request(&self, function_code: u8) -> bool {
+        self.parser_type == ParserType::Request && FunctionCode::get(function_code).is_some()
+    }
+
+    // the end of the request can't be found, so the bytes received so far are assumed to hold the
+    // whole request and only the function code is kept to answer it with an exception
+    fn discard_request(cursor: &mut ReadBuffer, unit_id: UnitId, function_code: u8) -> Frame {
+        cursor.reset();
+        let mut frame = Frame::new(FrameHeader::new_without_tx_id(unit_id));
+        frame.set(&[function_code]);
+        frame
+    }
+
+    fn parse_body(
+        &self,
+        cursor: &mut ReadBuffer,
+        unit_id: UnitId,
+        frame_length: usize,
+    ) -> Result<Frame, RequestError> {
+        let data = cursor.read(frame_length)?;
+        let (adu, crc) = data.split_at(frame_length - constants::CRC_LENGTH);
+
+        let received_crc = match crc {
+            [low, high] => u16::from_le_bytes([*low, *high]),
+            _ => {
+                return Err(InternalError::InsufficientBytesForRead(
+                    constants::CRC_LENGTH,
+                    crc.len(),
+                )
+                .into())
+            }
+        };
+
+        let expected_crc = calc_crc(adu);
+        if received_crc != expected_crc {
+            return Err(FrameParseError::CrcValidationFailure(received_crc, expected_crc).into());
+        }
+
+        let mut frame = Frame::new(FrameHeader::new_without_tx_id(unit_id));
+        if let Some(pdu) = adu.get(constants::HEADER_LENGTH..) {
+            frame.set(pdu);
+        }
+
+        if self.decode.enabled() {
+            tracing::info!(
+                "RTU RX - {}",
+                RtuDisplay::new(self.decode, unit_id, frame.payload(), received_crc)
+            );
+        }
+
+        Ok(frame)
+    }
+}
+
+// length of the data of a device identification response, if enough bytes are present to determine it
+fn device_id_objects_length(data: &[u8]) -> Option<usize> {
+    // MEI type, device id code, conformity level, more follows, next object id, and object count
+    const OBJECTS_OFFSET: usize = 6;
+    const MAX_LENGTH: usize = constants::MAX_FRAME_LENGTH
+        - constants::HEADER_LENGTH
+        - constants::FUNCTION_CODE_LENGTH
+        - constants::CRC_LENGTH;
+
+    let count = *data.get(OBJECTS_OFFSET - 1)?;
+    let mut length = OBJECTS_OFFSET;
+    for _ in 0..count {
+        // the frame is too big, stop walking so that the error is reported
+        if length >= MAX_LENGTH {
+            return Some(length + 2);
+        }
+        // each object is an id and a length followed by the value
+        length += 2 + *data.get(length + 1)? as usize;
+    }
+    Some(length)
+}
+
+impl FrameParser for RtuParser {
+    fn max_frame_size(&self) -> usize {
+        constants::MAX_FRAME_LENGTH
+    }
+
+    fn parse(&mut self, cursor: &mut ReadBuffer) -> Result<Option<Frame>, RequestError> {
+        const FIXED_LENGTH: usize = constants::HEADER_LENGTH + constants::FUNCTION_CODE_LENGTH;
+
+        match self.state {
+            ParseState::Start => {
+                let (unit_id, function_code) = match cursor.peek(FIXED_LENGTH) {
+                    Ok([unit_id, function_code]) => (UnitId::new(*unit_id), *function_code),
+                    _ => return Ok(None),
+                };
+
+                self.state = match self.length_mode(function_code) {
+                    LengthMode::Fixed(length) => ParseState::ReadFullBody(unit_id, length),
+                    LengthMode::Offset(offset) => {
+                        ParseState::ReadToOffsetForLength(unit_id, offset)
+                    }
+                    LengthMode::WordOffset(offset) => {
+                        ParseState::ReadToOffsetForWordLength(unit_id, offset)
+                    }
+                    LengthMode::DeviceIdObjects => ParseState::ReadDeviceIdObjects(unit_id),
+                    LengthMode::Unknown => match self.silence {
+                        Some(_) => ParseState::ReadUntilSilence(unit_id),
+                        None if self.can_discard_request(function_code) => {
+                            return Ok(Some(Self::discard_request(cursor, unit_id, function_code)));
+                        }
+                        None => {
+                            return Err(FrameParseError::UnknownFunctionCode(function_code).into())
+                        }
+                    },
+                };
+
+                self.parse(cursor)
+            }
+            ParseState::ReadToOffsetForLength(unit_id, offset) => {
+                let byte_count = match cursor.peek(FIXED_LENGTH + offset + 1) {
+                    Ok(data) => match data.last() {
+                        Some(count) => *count as usize,
+                        None => return Ok(None),
+                    },
+                    Err(_) => return Ok(None),
+                };
+
+                self.state = ParseState::ReadFullBody(unit_id, offset + 1 + byte_count);
+                self.parse(cursor)
+            }
+            ParseState::ReadToOffsetForWordLength(unit_id, offset) => {
+                let byte_count = match cursor.peek(FIXED_LENGTH + offset + 2) {
+                    Ok([.., high, low]) => u16::from_be_bytes([*high, *low]) as usize,
+                    _ => return Ok(None),
+                };
+
+                self.state = ParseState::ReadFullBody(unit_id, offset + 2 + byte_count);
+                self.parse(cursor)
+            }
+            ParseState::ReadDeviceIdObjects(unit_id) => {
+                let data = match cursor.peek(cursor.len()) {
+                    Ok(data) => data.get(FIXED_LENGTH..).unwrap_or(&[]),
+                    Err(_) => return Ok(None),
+                };
+
+                let length = match device_id_objects_length(data) {
+                    Some(length) => length,
+                    None => return Ok(None),
+                };
+
+                self.state = ParseState::ReadFullBody(unit_id, length);
+                self.parse(cursor)
+            }
+            ParseState::ReadFullBody(unit_id, length) => {
+                let frame_length = FIXED_LENGTH + length + constants::CRC_LENGTH;
+
+                if frame_length > constants::MAX_FRAME_LENGTH {
+                    self.state = ParseState::Start;
+                    return Err(FrameParseError::FrameLengthTooBig(
+                        frame_length,
+                        constants::MAX_FRAME_LENGTH,
+                    )
+                    .into());
+                }
+
+                if cursor.len() < frame_length {
+                    return Ok(None);
+                }
+
+                // the bytes are consumed whether or not the CRC is valid
+                self.state = ParseState::Start;
+                let frame = self.parse_body(cursor, unit_id, frame_length)?;
+                Ok(Some(frame))
+            }
+            ParseState::ReadUntilSilence(unit_id) => {
+                // no more bytes can be buffered, so this has to be the end of the frame
+                if cursor.len() < constants::MAX_FRAME_LENGTH {
+                    return Ok(None);
+                }
+
+                self.state = ParseState::Start;
+                let frame = self.parse_body(cursor, unit_id, constants::MAX_FRAME_LENGTH)?;
+                Ok(Some(frame))
+            }
+        }
+    }
+
+    fn reset(&mut self) {
+        self.state = ParseState::Start;
+    }
+
+    fn silence_timeout(&self) -> Option<Duration> {
+        self.silence
+    }
+
+    fn can_frame_request(&self, function: FunctionCode) -> bool {
+        self.can_frame(function)
+    }
+
+    fn can_frame_response(&self, function: FunctionCode) -> bool {
+        self.can_frame(function)
+    }
+
+    fn is_delimited_by_silence(&self) -> bool {
+        match self.parser_type {
+            // servers use the silence to resynchronize with the frames sent to other devices
+            ParserType::Request => self.silence.is_some(),
+            // serial adapters can leave gaps longer than t3.5 within a response, so silence only
+            // ends a response whose length can't be determined from its contents
+            ParserType::Response => matches!(self.state, ParseState::ReadUntilSilence(_)),
+        }
+    }
+
+    fn on_silence(&mut self, cursor: &mut ReadBuffer) -> Result<Option<Frame>, RequestError> {
+        match self.state {
+            ParseState::ReadUntilSilence(unit_id) => {
+                self.state = ParseState::Start;
+                let frame_length = cursor.len();
+                if frame_length
+                    < constants::HEADER_LENGTH
+                        + constants::FUNCTION_CODE_LENGTH
+                        + constants::CRC_LENGTH
+                {
+                    return Ok(None);
+                }
+                let frame = self.parse_body(cursor, unit_id, frame_length)?;
+                Ok(Some(frame))
+            }
+            _ => Ok(None),
+        }
+    }
+}
+
+impl FrameFormatter for RtuFormatter {
+    fn format_impl(
+        &mut self,
+        header: FrameHeader,
+        msg: &dyn Serialize,
+    ) -> Result<usize, RequestError> {
+        let end = {
+            let mut cursor = WriteCursor::new(self.buffer.as_mut());
+            cursor.write_u8(header.unit_id.value)?;
+            msg.serialize(&mut cursor)?;
+            cursor.position()
+        };
+
+        // the CRC is transmitted low byte first
+        let crc = calc_crc(&self.buffer[..end]);
+        let total_length = {
+            let mut cursor = WriteCursor::new(self.buffer.as_mut());
+            cursor.seek_from_start(end)?;
+            for byte in crc.to_le_bytes().iter() {
+                cursor.write_u8(*byte)?;
+            }
+            cursor.position()
+        };
+
+        // Logging
+        if self.decode.enabled() {
+            tracing::info!(
+                "RTU TX - {}",
+                RtuDisplay::new(
+                    self.decode,
+                    header.unit_id,
+                    &self.buffer[constants::HEADER_LENGTH..end],
+                    crc
+                )
+            );
+        }
+
+        Ok(total_length)
+    }
+
+    fn get_full_buffer_impl(&self, size: usize) -> Option<&[u8]> {
+        self.buffer.get(..size)
+    }
+
+    fn get_payload_impl(&self, size: usize) -> Option<&[u8]> {
+        self.buffer
+            .get(constants::HEADER_LENGTH..size.checked_sub(constants::CRC_LENGTH)?)
+    }
+}
+
+struct RtuDisplay<'a> {
+    level: AduDecodeLevel,
+    unit_id: UnitId,
+    data: &'a [u8],
+    crc: u16,
+}
+
+impl<'a> RtuDisplay<'a> {
+    fn new(level: AduDecodeLevel, unit_id: UnitId, data: &'a [u8], crc: u16) -> Self {
+        RtuDisplay {
+            level,
+            unit_id,
+            data,
+            crc,
+        }
+    }
+}
+
+impl<'a> std::fmt::Display for RtuDisplay<'a> {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(
+            f,
+            "unit: {} crc: {:#06X} (len = {})",
+            self.unit_id,
+            self.crc,
+            self.data.len()
+        )?;
+        if self.level.payload_enabled() {
+            crate::common::phys::format_bytes(f, self.data)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::task::Poll;
+
+    use crate::common::phys::PhysLayer;
+    use crate::decode::PhysDecodeLevel;
+    use crate::tokio::test::*;
+
+    use crate::common::frame::{FramedReader, TxId};
+    use crate::error::*;
+
+    use super::*;
+
+    //                                 | unit |  fc  |   start   |   count   |    crc    |
+    const READ_REGISTERS_REQUEST: &[u8] = &[0x01, 0x03, 0x00, 0x00, 0x00, 0x0A, 0xC5, 0xCD];
+
+    //                                   | unit |  fc  | count |   value   |   value   |    crc    |
+    const READ_REGISTERS_RESPONSE: &[u8] = &[0x01, 0x03, 0x04, 0x00, 0x06, 0x00, 0x05, 0xDA, 0x31];
+
+    //                                  | unit |  fc  |  address  |   value   |    crc    |
+    const WRITE_REGISTER_RESPONSE: &[u8] = &[0x01, 0x06, 0x00, 0x01, 0x00, 0x03, 0x98, 0x0B];
+
+    //                                   | unit |  fc  | start |  count  | bytes | values |   crc   |
+    const WRITE_REGISTERS_REQUEST: &[u8] = &[
+        0x01, 0x10, 0x00, 0x01, 0x00, 0x02, 0x04, 0x00, 0x0A, 0x01, 0x02, 0x92, 0x30,
+    ];
+
+    //                             | unit |  fc  | code |    crc    |
+    const EXCEPTION_RESPONSE: &[u8] = &[0x01, 0x83, 0x02, 0xC0, 0xF1];
+
+    struct MockMessage<'a> {
+        data: &'a [u8],
+    }
+
+    impl<'a> Serialize for MockMessage<'a> {
+        fn serialize(&self, cursor: &mut WriteCursor) -> Result<(), RequestError> {
+            for byte in self.data {
+                cursor.write_u8(*byte)?;
+            }
+            Ok(())
+        }
+    }
+
+    fn with_crc(data: &[u8]) -> Vec<u8> {
+        let mut ret = data.to_vec();
+        ret.extend_from_slice(&calc_crc(data).to_le_bytes());
+        ret
+    }
+
+    fn test_parse(parser: RtuParser, input: &[u8], split_at: usize) -> Frame {
+        let (f1, f2) = input.split_at(split_at);
+        let (io, mut io_handle) = io::mock();
+        let mut reader = FramedReader::new(parser);
+        let mut layer = PhysLayer::new_mock(io, PhysDecodeLevel::Nothing);
+        let mut task = spawn(reader.next_frame(&mut layer));
+
+        assert!(task.poll().is_pending());
+        io_handle.read(f1);
+        if !f2.is_empty() {
+            assert!(task.poll().is_pending());
+            io_handle.read(f2);
+        }
+        if let Poll::Ready(frame) = task.poll() {
+            frame.unwrap()
+        } else {
+            panic!("Task not ready");
+        }
+    }
+
+    fn test_error(parser: RtuParser, input: &[u8]) -> RequestError {
+        let (io, mut io_handle) = io::mock();
+        let mut reader = FramedReader::new(parser);
+        let mut layer = PhysLayer::new_mock(io, PhysDecodeLevel::Nothing);
+        let mut task = spawn(reader.next_frame(&mut layer));
+
+        io_handle.read(input);
+        if let Poll::Ready(frame) = task.poll() {
+            frame.err().unwrap()
+        } else {
+            panic!("Task not ready");
+        }
+    }
+
+    fn request_parser() -> RtuParser {
+        RtuParser::new_request_parser(None, AduDecodeLevel::Nothing)
+    }
+
+    fn response_parser() -> RtuParser {
+        RtuParser::new_response_parser(None, AduDecodeLevel::Nothing)
+    }
+
+    fn serial_request_parser() -> RtuParser {
+        RtuParser::new_request_parser(Some(SILENCE), AduDecodeLevel::Nothing)
+    }
+
+    fn serial_response_parser() -> RtuParser {
+        RtuParser::new_response_parser(Some(SILENCE), AduDecodeLevel::Nothing)
+    }
+
+    #[test]
+    fn calculates_modbus_crc() {
+        assert_eq!(calc_crc(b"123456789"), 0x4B37);
+        assert_eq!(calc_crc(&[0x01, 0x03, 0x00, 0x00, 0x00, 0x0A]), 0xCDC5);
+    }
+
+    #[test]
+    fn correctly_formats_frame() {
+        let mut formatter = RtuFormatter::new(AduDecodeLevel::Nothing);
+        let msg = MockMessage {
+            data: &READ_REGISTERS_REQUEST[1..6],
+        };
+        let header = FrameHeader::new(UnitId::new(1), TxId::new(7));
+        let size = formatter.format_impl(header, &msg).unwrap();
+
+        assert_eq!(
+            formatter.get_full_buffer_impl(size).unwrap(),
+            READ_REGISTERS_REQUEST
+        );
+        assert_eq!(
+            formatter.get_payload_impl(size).unwrap(),
+            &READ_REGISTERS_REQUEST[1..6]
+        );
+    }
+
+    const SILENCE: Duration = Duration::from_millis(2);
+
+    #[test]
+    fn can_parse_fixed_length_request() {
+        for split_at in 1..READ_REGISTERS_REQUEST.len() {
+            let frame = test_parse(request_parser(), READ_REGISTERS_REQUEST, split_at);
+            assert_eq!(frame.header.unit_id, UnitId::new(1));
+            assert_eq!(frame.payload(), &READ_REGISTERS_REQUEST[1..6]);
+        }
+    }
+
+    #[test]
+    fn can_parse_request_with_byte_count() {
+        for split_at in 1..WRITE_REGISTERS_REQUEST.len() {
+            let frame = test_parse(request_parser(), WRITE_REGISTERS_REQUEST, split_at);
+            assert_eq!(frame.payload(), &WRITE_REGISTERS_REQUEST[1..11]);
+        }
+    }
+
+    #[test]
+    fn can_parse_read_write_registers_request() {
+        //                     | unit |  fc  | read start | read count | write start | write count | bytes | values |
+        let request = with_crc(&[
+            0x01, 0x17, 0x00, 0x03, 0x00, 0x06, 0x00, 0x0E, 0x00, 0x01, 0x02, 0x00, 0xFF,
+        ]);
+        for split_at in 1..request.len() {
+            let frame = test_parse(request_parser(), &request, split_at);
+            assert_eq!(frame.payload(), &request[1..13]);
+        }
+    }
+
+    #[test]
+    fn can_parse_mask_write_register_response() {
+        //                      | unit |  fc  |  address  | and mask  |  or mask  |
+        let response = with_crc(&[0x01, 0x16, 0x00, 0x04, 0x00, 0xF2, 0x00, 0x25]);
+        for split_at in 1..response.len() {
+            let frame = test_parse(response_parser(), &response, split_at);
+            assert_eq!(frame.payload(), &response[1..8]);
+        }
+    }
+
+    #[test]
+    fn can_parse_device_identification_response() {
+        //                      | unit |  fc  | mei  | code | conf | more | next | count | id  | len |   value   | id  | len | value |
+        let response = with_crc(&[
+            0x01, 0x2B, 0x0E, 0x01, 0x81, 0x00, 0x00, 0x02, 0x00, 0x02, 0x41, 0x42, 0x01, 0x01,
+            0x43,
+        ]);
+        for split_at in 1..response.len() {
+            let frame = test_parse(response_parser(), &response, split_at);
+            assert_eq!(frame.payload(), &response[1..15]);
+        }
+    }
+
+    #[test]
+    fn can_parse_get_comm_event_log_response() {
+        //                      | unit |  fc  | count |  status   |  events   | messages  | event |
+        let response = with_crc(&[0x01, 0x0C, 0x07, 0x00, 0x00, 0x01, 0x08, 0x01, 0x21, 0x20]);
+        for split_at in 1..response.len() {
+            let frame = test_parse(response_parser(), &response, split_at);
+            assert_eq!(frame.payload(), &response[1..10]);
+        }
+    }
+
+    #[test]
+    fn can_parse_read_file_record_response() {
+        //                      | unit |  fc  | byte count | length | ref  |   value   |   value   |
+        let response = with_crc(&[0x01, 0x14, 0x06, 0x05, 0x06, 0x0D, 0xFE, 0x00, 0x20]);
+        for split_at in 1..response.len() {
+            let frame = test_parse(response_parser(), &response, split_at);
+            assert_eq!(frame.payload(), &response[1..9]);
+        }
+    }
+
+    #[test]
+    fn can_parse_read_fifo_queue_response() {
+        //                      | unit |  fc  | byte count | fifo count |   value   |   value   |
+        let response = with_crc(&[0x01, 0x18, 0x00, 0x06, 0x00, 0x02, 0x01, 0xB8, 0x12, 0x84]);
+        for split_at in 1..response.len() {
+            let frame = test_parse(response_parser(), &response, split_at);
+            assert_eq!(frame.payload(), &response[1..10]);
+        }
+    }
+
+    #[test]
+    fn can_parse_fixed_length_response() {
+        for split_at in 1..WRITE_REGISTER_RESPONSE.len() {
+            let frame = test_parse(response_parser(), WRITE_REGISTER_RESPONSE, split_at);
+            assert_eq!(frame.header.unit_id, UnitId::new(1));
+            assert!(frame.header.tx_id.is_none());
+            assert_eq!(frame.payload(), &WRITE_REGISTER_RESPONSE[1..6]);
+        }
+    }
+
+    #[test]
+    fn can_parse_response_with_byte_count() {
+        for split_at in 1..READ_REGISTERS_RESPONSE.len() {
+            let frame = test_parse(response_parser(), READ_REGISTERS_RESPONSE, split_at);
+            assert_eq!(frame.payload(), &READ_REGISTERS_RESPONSE[1..7]);
+        }
+    }
+
+    #[test]
+    fn can_parse_exception_response() {
+        let frame = test_parse(response_parser(), EXCEPTION_RESPONSE, 2);
+        assert_eq!(frame.payload(), &[0x83, 0x02]);
+    }
+
+    #[test]
+    fn can_parse_maximum_size_frame() {
+        // 1 byte function code + 1 byte count + 251 bytes = 253 byte ADU
+        let mut data = vec![0x01, 0x03, 0xFB];
+        data.extend_from_slice(&[0xCC; 0xFB]);
+        let input = with_crc(&data);
+
+        let frame = test_parse(response_parser(), &input, 3);
+        assert_eq!(frame.payload(), &data[1..]);
+    }
+
+    #[test]
+    fn errors_when_frame_length_too_big() {
+        let input = &[0x01, 0x03, 0xFC];
+        assert_eq!(
+            test_error(response_parser(), input),
+            RequestError::BadFrame(FrameParseError::FrameLengthTooBig(
+                constants::MAX_FRAME_LENGTH + 1,
+                constants::MAX_FRAME_LENGTH,
+            ))
+        );
+    }
+
+    #[test]
+    fn errors_on_bad_crc() {
+        let mut input = WRITE_REGISTER_RESPONSE.to_vec();
+        input[7] = 0xFF;
+        assert_eq!(
+            test_error(response_parser(), &input),
+            RequestError::BadFrame(FrameParseError::CrcValidationFailure(0xFF98, 0x0B98))
+        );
+    }
+
+    #[test]
+    fn errors_on_unknown_function_code() {
+        let input = &[0x01, 0x45, 0x00];
+        assert_eq!(
+            test_error(response_parser(), input),
+            RequestError::BadFrame(FrameParseError::UnknownFunctionCode(0x45))
+        );
+    }
+
+    #[test]
+    fn exception_function_codes_are_not_valid_requests() {
+        let input = &[0x01, 0x83, 0x02];
+        assert_eq!(
+            test_error(request_parser(), input),
+            RequestError::BadFrame(FrameParseError::UnknownFunctionCode(0x83))
+        );
+    }
+
+    #[test]
+    fn keeps_only_the_function_code_of_user_defined_requests_without_silence() {
+        let custom = FunctionCode::get(0x41).unwrap();
+        assert!(!request_parser().can_frame_request(custom));
+        assert!(serial_request_parser().can_frame_request(custom));
+
+        let input = with_crc(&[0x01, 0x41, 0x01, 0x02]);
+        let frame = test_parse(request_parser(), &input, input.len());
+        assert_eq!(frame.header.unit_id, UnitId::new(1));
+        assert_eq!(frame.payload(), &[0x41]);
+    }
+
+    #[test]
+    fn discards_incomplete_frame_after_silence() {
+        let (io, mut io_handle) = io::mock();
+        let mut reader = FramedReader::new(serial_request_parser());
+        let mut layer = PhysLayer::new_mock(io, PhysDecodeLevel::Nothing);
+        let mut task = spawn(reader.next_frame(&mut layer));
+
+        assert!(task.poll().is_pending());
+        io_handle.read(&READ_REGISTERS_REQUEST[0..4]);
+        assert!(task.poll().is_pending());
+        crate::tokio::time::advance(SILENCE);
+        assert!(task.poll().is_pending());
+        io_handle.read(READ_REGISTERS_REQUEST);
+        if let Poll::Ready(frame) = task.poll() {
+            assert_eq!(frame.unwrap().payload(), &READ_REGISTERS_REQUEST[1..6]);
+        } else {
+            panic!("Task not ready");
+        }
+    }
+
+    #[test]
+    fn responses_to_user_defined_functions_require_silence() {
+        let custom = FunctionCode::get(0x41).unwrap();
+        assert!(!response_parser().can_frame_response(custom));
+        assert!(serial_response_parser().can_frame_response(custom));
+        assert!(response_parser().can_frame_response(FunctionCode::ReadHoldingRegisters));
+    }
+
+    #[test]
+    fn gaps_within_response_of_known_length_are_not_silence() {
+        let (io, mut io_handle) = io::mock();
+        let mut reader = FramedReader::new(serial_response_parser());
+        let mut layer = PhysLayer::new_mock(io, PhysDecodeLevel::Nothing);
+        let mut task = spawn(reader.next_frame(&mut layer));
+
+        assert!(task.poll().is_pending());
+        io_handle.read(&READ_REGISTERS_RESPONSE[0..4]);
+        assert!(task.poll().is_pending());
+        crate::tokio::time::advance(SILENCE * 10);
+        assert!(task.poll().is_pending());
+        io_handle.read(&READ_REGISTERS_RESPONSE[4..]);
+        if let Poll::Ready(frame) = task.poll() {
+            assert_eq!(frame.unwrap().payload(), &READ_REGISTERS_RESPONSE[1..7]);
+        } else {
+            panic!("Task not ready");
+        }
+    }
+
+    #[test]
+    fn silence_completes_response_with_unknown_function_code() {
+        let input = with_crc(&[0x01, 0x45, 0xCA, 0xFE]);
+
+        let (io, mut io_handle) = io::mock();
+        let mut reader = FramedReader::new(serial_response_parser());
+        let mut layer = PhysLayer::new_mock(io, PhysDecodeLevel::Nothing);
+        let mut task = spawn(reader.next_frame(&mut layer));
+
+        io_handle.read(&input);
+        assert!(task.poll().is_pending());
+        crate::tokio::time::advance(SILENCE);
+        if let Poll::Ready(frame) = task.poll() {
+            assert_eq!(frame.unwrap().payload(), &[0x45, 0xCA, 0xFE]);
+        } else {
+            panic!("Task not ready");
+        }
+    }
+
+    #[test]
+    fn silence_completes_frame_with_unknown_function_code() {
+        let input = with_crc(&[0x01, 0x45, 0xCA, 0xFE]);
+
+        let (io, mut io_handle) = io::mock();
+        let mut reader = FramedReader::new(serial_request_parser());
+        let mut layer = PhysLayer::new_mock(io, PhysDecodeLevel::Nothing);
+        let mut task = spawn(reader.next_frame(&mut layer));
+
+        io_handle.read(&input);
+        assert!(task.poll().is_pending());
+        crate::tokio::time::advance(SILENCE);
+        if let Poll::Ready(frame) = task.poll() {
+            assert_eq!(frame.unwrap().payload(), &[0x45, 0xCA, 0xFE]);
+        } else {
+            panic!("Task not ready");
+        }
+    }
+
+    #[test]
+    fn discards_bytes_until_silence_after_reset() {
+        let (io, mut io_handle) = io::mock();
+        let mut reader = FramedReader::new(serial_request_parser());
+        let mut layer = PhysLayer::new_mock(io, PhysDecodeLevel::Nothing);
+
+        reader.reset();
+        let mut task = spawn(reader.next_frame(&mut layer));
+
+        // the tail end of a corrupted frame looks like a valid request
+        io_handle.read(READ_REGISTERS_REQUEST);
+        assert!(task.poll().is_pending());
+        crate::tokio::time::advance(SILENCE);
+        assert!(task.poll().is_pending());
+        io_handle.read(WRITE_REGISTERS_REQUEST);
+        if let Poll::Ready(frame) = task.poll() {
+            assert_eq!(frame.unwrap().payload(), &WRITE_REGISTERS_REQUEST[1..11]);
+        } else {
+            panic!("Task not ready");
+        }
+    }
+}
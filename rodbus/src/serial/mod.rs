@@ -0,0 +1,5 @@
+pub(crate) mod ascii;
+pub(crate) mod client;
+pub(crate) mod frame;
+pub(crate) mod server;
+pub(crate) mod settings;
@@ -0,0 +1,91 @@
+use crate::client::ReconnectStrategy;
+use crate::common::frame::{FrameFormatter, FrameParser};
+use crate::common::phys::PhysLayer;
+use crate::decode::DecodeLevel;
+use crate::error::RequestError;
+use crate::serial::settings::SerialSettings;
+use crate::server::handler::RequestHandler;
+use crate::server::task::SessionTask;
+
+/// Server task that is generic over the serial framing (RTU or ASCII)
+pub(crate) struct SerialServerTask<T, F, P>
+where
+    T: RequestHandler,
+    F: FrameFormatter,
+    P: FrameParser,
+{
+    path: String,
+    serial_settings: SerialSettings,
+    retry: Box<dyn ReconnectStrategy + Send>,
+    session: SessionTask<T, F, P>,
+    decode: DecodeLevel,
+}
+
+impl<T, F, P> SerialServerTask<T, F, P>
+where
+    T: RequestHandler,
+    F: FrameFormatter,
+    P: FrameParser,
+{
+    pub(crate) fn new(
+        path: &str,
+        serial_settings: SerialSettings,
+        retry: Box<dyn ReconnectStrategy + Send>,
+        session: SessionTask<T, F, P>,
+        decode: DecodeLevel,
+    ) -> Self {
+        Self {
+            path: path.to_string(),
+            serial_settings,
+            retry,
+            session,
+            decode,
+        }
+    }
+
+    pub(crate) async fn run(&mut self) {
+        loop {
+            match self.serial_settings.open(&self.path) {
+                Err(err) => {
+                    tracing::warn!("error opening serial port: {}", err);
+                }
+                Ok(serial) => {
+                    self.retry.reset();
+                    let mut phys = PhysLayer::new_serial(serial, self.decode.physical);
+                    tracing::info!("serial port open");
+                    match self.run_session(&mut phys).await {
+                        RequestError::Shutdown => {
+                            tracing::info!("server shutdown");
+                            return;
+                        }
+                        err => tracing::warn!("serial port closed: {}", err),
+                    }
+                }
+            }
+
+            // wait before re-opening the port
+            let delay = self.retry.next_delay();
+            if self.session.sleep_for(delay).await.is_err() {
+                tracing::info!("server shutdown");
+                return;
+            }
+        }
+    }
+
+    async fn run_session(&mut self, phys: &mut PhysLayer) -> RequestError {
+        loop {
+            if let Err(err) = self.session.run(phys).await {
+                match err {
+                    // a corrupted frame on a serial line doesn't require re-opening the port,
+                    // just discard whatever was received and keep going
+                    RequestError::BadFrame(err) => {
+                        tracing::warn!("discarding received data after framing error: {}", err);
+                        self.session.on_bad_frame(err);
+                        self.session.reset();
+                    }
+                    err => return err,
+                }
+            }
+        }
+    }
+}
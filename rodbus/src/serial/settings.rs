@@ -0,0 +1,208 @@
+use std::time::Duration;
+
+/// Serial port settings
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct SerialSettings {
+    /// Baud rate of the port
+    pub baud_rate: u32,
+    /// Number of data bits
+    pub data_bits: DataBits,
+    /// Types of flow control
+    pub flow_control: FlowControl,
+    /// Number of stop bits
+    pub stop_bits: StopBits,
+    /// Parity setting
+    pub parity: Parity,
+    // set with `with_inter_frame_delay`, otherwise derived from the baud rate
+    inter_frame_delay: Option<Duration>,
+}
+
+/// Number of bits per character
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum DataBits {
+    /// 5 bits per character
+    Five,
+    /// 6 bits per character
+    Six,
+    /// 7 bits per character
+    Seven,
+    /// 8 bits per character
+    Eight,
+}
+
+/// Flow control modes
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum FlowControl {
+    /// No flow control
+    None,
+    /// Flow control using XON/XOFF bytes
+    Software,
+    /// Flow control using RTS/CTS signals
+    Hardware,
+}
+
+/// Number of stop bits
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum StopBits {
+    /// One stop bit
+    One,
+    /// Two stop bits
+    Two,
+}
+
+/// Parity checking modes
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Parity {
+    /// No parity bit
+    None,
+    /// Parity bit sets odd number of 1 bits
+    Odd,
+    /// Parity bit sets even number of 1 bits
+    Even,
+}
+
+impl SerialSettings {
+    /// create a `SerialSettings` from the port parameters, the inter-frame delay is derived
+    /// from the baud rate unless it is set with [`SerialSettings::with_inter_frame_delay`]
+    pub fn new(
+        baud_rate: u32,
+        data_bits: DataBits,
+        flow_control: FlowControl,
+        stop_bits: StopBits,
+        parity: Parity,
+    ) -> Self {
+        Self {
+            baud_rate,
+            data_bits,
+            flow_control,
+            stop_bits,
+            parity,
+            inter_frame_delay: None,
+        }
+    }
+
+    /// Use a longer silent interval between frames than the one derived from the baud rate,
+    /// e.g. for a USB adapter that adds gaps within a frame
+    ///
+    /// Servers use the silence to find the start of the next request. Clients only use it to
+    /// find the end of responses whose length can't be determined from their contents.
+    pub fn with_inter_frame_delay(mut self, delay: Duration) -> Self {
+        self.inter_frame_delay = Some(delay);
+        self
+    }
+
+    /// The minimum silent interval between two frames, which defaults to t3.5
+    ///
+    /// Above 19200 bps, the specification recommends a fixed value of 1.75 ms
+    pub(crate) fn effective_inter_frame_delay(&self) -> Duration {
+        const FIXED_DELAY: Duration = Duration::from_micros(1750);
+        // 11 bits per character (start, 8 data bits, parity or stop, stop)
+        const BITS_PER_CHARACTER: u64 = 11;
+
+        if let Some(delay) = self.inter_frame_delay {
+            return delay;
+        }
+
+        if self.baud_rate == 0 || self.baud_rate > 19200 {
+            return FIXED_DELAY;
+        }
+
+        // 3.5 characters expressed in microseconds
+        Duration::from_micros((35 * BITS_PER_CHARACTER * 1_000_000) / (10 * self.baud_rate as u64))
+    }
+
+    pub(crate) fn open(&self, path: &str) -> tokio_serial::Result<tokio_serial::SerialStream> {
+        let builder = tokio_serial::new(path, self.baud_rate)
+            .data_bits(self.data_bits.into())
+            .flow_control(self.flow_control.into())
+            .stop_bits(self.stop_bits.into())
+            .parity(self.parity.into());
+        tokio_serial::SerialStream::open(&builder)
+    }
+}
+
+impl Default for SerialSettings {
+    fn default() -> Self {
+        Self {
+            baud_rate: 9600,
+            data_bits: DataBits::Eight,
+            flow_control: FlowControl::None,
+            stop_bits: StopBits::One,
+            parity: Parity::None,
+            inter_frame_delay: None,
+        }
+    }
+}
+
+impl From<DataBits> for tokio_serial::DataBits {
+    fn from(from: DataBits) -> Self {
+        match from {
+            DataBits::Five => tokio_serial::DataBits::Five,
+            DataBits::Six => tokio_serial::DataBits::Six,
+            DataBits::Seven => tokio_serial::DataBits::Seven,
+            DataBits::Eight => tokio_serial::DataBits::Eight,
+        }
+    }
+}
+
+impl From<FlowControl> for tokio_serial::FlowControl {
+    fn from(from: FlowControl) -> Self {
+        match from {
+            FlowControl::None => tokio_serial::FlowControl::None,
+            FlowControl::Software => tokio_serial::FlowControl::Software,
+            FlowControl::Hardware => tokio_serial::FlowControl::Hardware,
+        }
+    }
+}
+
+impl From<StopBits> for tokio_serial::StopBits {
+    fn from(from: StopBits) -> Self {
+        match from {
+            StopBits::One => tokio_serial::StopBits::One,
+            StopBits::Two => tokio_serial::StopBits::Two,
+        }
+    }
+}
+
+impl From<Parity> for tokio_serial::Parity {
+    fn from(from: Parity) -> Self {
+        match from {
+            Parity::None => tokio_serial::Parity::None,
+            Parity::Odd => tokio_serial::Parity::Odd,
+            Parity::Even => tokio_serial::Parity::Even,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn inter_frame_delay_depends_on_baud_rate_up_to_19200() {
+        let mut settings = SerialSettings::default();
+        assert_eq!(
+            settings.effective_inter_frame_delay(),
+            Duration::from_micros(4010)
+        );
+        settings.baud_rate = 19200;
+        assert_eq!(
+            settings.effective_inter_frame_delay(),
+            Duration::from_micros(2005)
+        );
+        settings.baud_rate = 115200;
+        assert_eq!(
+            settings.effective_inter_frame_delay(),
+            Duration::from_micros(1750)
+        );
+    }
+
+    #[test]
+    fn inter_frame_delay_can_be_configured() {
+        let settings = SerialSettings::default().with_inter_frame_delay(Duration::from_millis(20));
+        assert_eq!(
+            settings.effective_inter_frame_delay(),
+            Duration::from_millis(20)
+        );
+    }
+}
@@ -0,0 +1,64 @@
+use crate::common::function::FunctionCode;
+use crate::types::UnitId;
+
+/// Result of an authorization check
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Authorization {
+    /// The request is processed by the [`RequestHandler`](crate::server::RequestHandler)
+    Allow,
+    /// The request is rejected with [`ExceptionCode::IllegalFunction`](crate::ExceptionCode::IllegalFunction)
+    Deny,
+}
+
+/// Trait implemented by the user to authorize requests received by a TLS server
+///
+/// The role is extracted from the Modbus Role extension (OID 1.3.6.1.4.1.50316.802.1)
+/// of the client certificate when the session is established. Each request is checked
+/// before it is passed to the [`RequestHandler`](crate::server::RequestHandler).
+pub trait AuthorizationHandler: Send + Sync + 'static {
+    /// Authorize a request with the specified function code and unit id from a client with `role`
+    fn is_authorized(&self, unit_id: UnitId, function: FunctionCode, role: &str) -> Authorization;
+}
+
+/// [`AuthorizationHandler`] that allows read requests from any role and denies all writes
+#[derive(Copy, Clone, Debug, Default)]
+pub struct ReadOnlyAuthorizationHandler;
+
+impl ReadOnlyAuthorizationHandler {
+    /// Create a read-only authorization handler
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl AuthorizationHandler for ReadOnlyAuthorizationHandler {
+    fn is_authorized(
+        &self,
+        _unit_id: UnitId,
+        function: FunctionCode,
+        _role: &str,
+    ) -> Authorization {
+        match function {
+            FunctionCode::ReadCoils
+            | FunctionCode::ReadDiscreteInputs
+            | FunctionCode::ReadHoldingRegisters
+            | FunctionCode::ReadInputRegisters
+            | FunctionCode::ReadExceptionStatus
+            | FunctionCode::GetCommEventCounter
+            | FunctionCode::GetCommEventLog
+            | FunctionCode::ReportServerId
+            | FunctionCode::ReadFileRecord
+            | FunctionCode::ReadFifoQueue
+            | FunctionCode::ReadDeviceIdentification => Authorization::Allow,
+            FunctionCode::WriteSingleCoil
+            | FunctionCode::WriteSingleRegister
+            | FunctionCode::Diagnostics
+            | FunctionCode::WriteMultipleCoils
+            | FunctionCode::WriteMultipleRegisters
+            | FunctionCode::WriteFileRecord
+            | FunctionCode::MaskWriteRegister
+            | FunctionCode::ReadWriteMultipleRegisters
+            | FunctionCode::Custom(_) => Authorization::Deny,
+        }
+    }
+}
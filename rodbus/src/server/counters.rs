@@ -0,0 +1,372 @@
+use std::collections::VecDeque;
+
+use crate::common::function::FunctionCode;
+use crate::constants::limits::MAX_COMM_EVENTS;
+use crate::diagnostics::{
+    CommEvent, CommEventCounter, CommEventLog, Diagnostic, DiagnosticSubFunction, STATUS_NOT_BUSY,
+};
+use crate::exception::ExceptionCode;
+
+// exception codes with dedicated counters
+const EXCEPTION_SERVER_DEVICE_BUSY: u8 = 0x06;
+const EXCEPTION_NEGATIVE_ACKNOWLEDGE: u8 = 0x07;
+
+/// Counters, event log, and listen only mode of a server session
+///
+/// Maintained by the session as frames are received and replies are sent, and reported
+/// to clients with the `diagnostics`, `get comm event counter`, and `get comm event log`
+/// requests. The counters are 16-bit and wrap around.
+#[derive(Debug, Default)]
+pub(crate) struct DiagnosticCounters {
+    bus_message: u16,
+    bus_communication_error: u16,
+    bus_exception_error: u16,
+    server_message: u16,
+    server_no_response: u16,
+    server_nak: u16,
+    server_busy: u16,
+    comm_event: u16,
+    listen_only: bool,
+    // most recent event first
+    events: VecDeque<CommEvent>,
+}
+
+impl DiagnosticCounters {
+    pub(crate) fn new() -> Self {
+        Self::default()
+    }
+
+    pub(crate) fn listen_only(&self) -> bool {
+        self.listen_only
+    }
+
+    pub(crate) fn comm_event_counter(&self) -> CommEventCounter {
+        CommEventCounter {
+            status: STATUS_NOT_BUSY,
+            event_count: self.comm_event,
+        }
+    }
+
+    pub(crate) fn comm_event_log(&self) -> CommEventLog {
+        CommEventLog {
+            status: STATUS_NOT_BUSY,
+            event_count: self.comm_event,
+            message_count: self.bus_message,
+            events: self.events.iter().copied().collect(),
+        }
+    }
+
+    // perform a diagnostics sub-function, returning the response data
+    pub(crate) fn diagnostics(&mut self, request: Diagnostic) -> Result<Diagnostic, ExceptionCode> {
+        let sub_function = DiagnosticSubFunction::get(request.sub_function)
+            .ok_or(ExceptionCode::IllegalFunction)?;
+
+        let data = match sub_function {
+            DiagnosticSubFunction::ReturnQueryData => request.data,
+            DiagnosticSubFunction::RestartCommunications => {
+                match request.data {
+                    0x0000 => self.restart(false),
+                    0xFF00 => self.restart(true),
+                    _ => return Err(ExceptionCode::IllegalDataValue),
+                }
+                request.data
+            }
+            // the ASCII delimiter is fixed
+            DiagnosticSubFunction::ChangeAsciiInputDelimiter => {
+                return Err(ExceptionCode::IllegalFunction)
+            }
+            _ if request.data != 0 => return Err(ExceptionCode::IllegalDataValue),
+            // no device specific conditions are reported in the diagnostic register
+            DiagnosticSubFunction::ReturnDiagnosticRegister => 0,
+            DiagnosticSubFunction::ForceListenOnlyMode => {
+                self.enter_listen_only_mode();
+                0
+            }
+            DiagnosticSubFunction::ClearCountersAndDiagnosticRegister => {
+                self.clear();
+                0
+            }
+            DiagnosticSubFunction::ReturnBusMessageCount => self.bus_message,
+            DiagnosticSubFunction::ReturnBusCommunicationErrorCount => self.bus_communication_error,
+            DiagnosticSubFunction::ReturnBusExceptionErrorCount => self.bus_exception_error,
+            DiagnosticSubFunction::ReturnServerMessageCount => self.server_message,
+            DiagnosticSubFunction::ReturnServerNoResponseCount => self.server_no_response,
+            DiagnosticSubFunction::ReturnServerNakCount => self.server_nak,
+            DiagnosticSubFunction::ReturnServerBusyCount => self.server_busy,
+            // character overruns cannot be detected above the serial port driver
+            DiagnosticSubFunction::ReturnBusCharacterOverrunCount
+            | DiagnosticSubFunction::ClearOverrunCounterAndFlag => 0,
+        };
+
+        Ok(Diagnostic::new(request.sub_function, data))
+    }
+
+    // a frame that failed validation was detected on the bus
+    pub(crate) fn on_bad_frame(&mut self, communication_error: bool) {
+        self.bus_message = self.bus_message.wrapping_add(1);
+        if communication_error {
+            self.bus_communication_error = self.bus_communication_error.wrapping_add(1);
+        }
+        self.push_event(CommEvent::Receive {
+            communication_error,
+            character_overrun: false,
+            listen_only: self.listen_only,
+            broadcast: false,
+        });
+    }
+
+    // a valid frame was detected on the bus, addressed to this server or not
+    pub(crate) fn on_bus_message(&mut self) {
+        self.bus_message = self.bus_message.wrapping_add(1);
+    }
+
+    // a valid frame addressed to this server was received
+    pub(crate) fn on_server_message(&mut self, broadcast: bool) {
+        self.server_message = self.server_message.wrapping_add(1);
+        self.push_event(CommEvent::Receive {
+            communication_error: false,
+            character_overrun: false,
+            listen_only: self.listen_only,
+            broadcast,
+        });
+    }
+
+    // a message addressed to this server was processed without sending a reply
+    pub(crate) fn on_no_response(&mut self) {
+        self.server_no_response = self.server_no_response.wrapping_add(1);
+    }
+
+    // a reply was sent, with the exception code if it was an exception response
+    pub(crate) fn on_reply(&mut self, function: Option<FunctionCode>, exception: Option<u8>) {
+        match exception {
+            Some(code) => {
+                self.bus_exception_error = self.bus_exception_error.wrapping_add(1);
+                match code {
+                    EXCEPTION_SERVER_DEVICE_BUSY => {
+                        self.server_busy = self.server_busy.wrapping_add(1)
+                    }
+                    EXCEPTION_NEGATIVE_ACKNOWLEDGE => {
+                        self.server_nak = self.server_nak.wrapping_add(1)
+                    }
+                    _ => {}
+                }
+            }
+            None => {
+                // fetching the event counter or log doesn't count as an event
+                if !matches!(
+                    function,
+                    Some(FunctionCode::GetCommEventCounter) | Some(FunctionCode::GetCommEventLog)
+                ) {
+                    self.comm_event = self.comm_event.wrapping_add(1);
+                }
+            }
+        }
+        self.push_event(CommEvent::send(exception, self.listen_only));
+    }
+
+    pub(crate) fn enter_listen_only_mode(&mut self) {
+        if !self.listen_only {
+            self.listen_only = true;
+            self.push_event(CommEvent::EnteredListenOnlyMode);
+        }
+    }
+
+    // restart communications, optionally clearing the event log
+    pub(crate) fn restart(&mut self, clear_log: bool) {
+        self.clear();
+        self.listen_only = false;
+        if clear_log {
+            self.events.clear();
+        }
+        self.push_event(CommEvent::CommunicationRestart);
+    }
+
+    // clear all of the counters, but not the event log
+    pub(crate) fn clear(&mut self) {
+        *self = Self {
+            listen_only: self.listen_only,
+            events: std::mem::take(&mut self.events),
+            ..Self::default()
+        };
+    }
+
+    fn push_event(&mut self, event: CommEvent) {
+        if self.events.len() == MAX_COMM_EVENTS {
+            self.events.pop_back();
+        }
+        self.events.push_front(event);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn counts_messages_and_exceptions() {
+        let mut counters = DiagnosticCounters::new();
+        counters.on_bus_message();
+        counters.on_bus_message();
+        counters.on_server_message(false);
+        counters.on_reply(Some(FunctionCode::ReadCoils), None);
+        counters.on_bus_message();
+        counters.on_server_message(false);
+        counters.on_reply(
+            Some(FunctionCode::ReadCoils),
+            Some(EXCEPTION_SERVER_DEVICE_BUSY),
+        );
+        counters.on_bad_frame(true);
+
+        assert_eq!(counters.bus_message, 4);
+        assert_eq!(counters.bus_communication_error, 1);
+        assert_eq!(counters.server_message, 2);
+        assert_eq!(counters.bus_exception_error, 1);
+        assert_eq!(counters.server_busy, 1);
+        assert_eq!(counters.server_nak, 0);
+        assert_eq!(counters.comm_event_counter().event_count, 1);
+        assert_eq!(counters.comm_event_log().events.len(), 5);
+        assert_eq!(counters.comm_event_log().events[0], CommEvent::from(0x82));
+    }
+
+    #[test]
+    fn fetching_the_event_counter_is_not_an_event() {
+        let mut counters = DiagnosticCounters::new();
+        counters.on_reply(Some(FunctionCode::GetCommEventCounter), None);
+        counters.on_reply(Some(FunctionCode::GetCommEventLog), None);
+        assert_eq!(counters.comm_event_counter().event_count, 0);
+    }
+
+    #[test]
+    fn event_log_keeps_most_recent_events() {
+        let mut counters = DiagnosticCounters::new();
+        for _ in 0..MAX_COMM_EVENTS {
+            counters.on_server_message(false);
+        }
+        counters.enter_listen_only_mode();
+        let events = counters.comm_event_log().events;
+        assert_eq!(events.len(), MAX_COMM_EVENTS);
+        assert_eq!(events[0], CommEvent::EnteredListenOnlyMode);
+    }
+
+    fn diagnostic(
+        counters: &mut DiagnosticCounters,
+        sub_function: DiagnosticSubFunction,
+        data: u16,
+    ) -> Result<u16, ExceptionCode> {
+        counters
+            .diagnostics(Diagnostic::new(sub_function.get_value(), data))
+            .map(|x| x.data)
+    }
+
+    #[test]
+    fn returns_counters_by_sub_function() {
+        let mut counters = DiagnosticCounters::new();
+        counters.on_bus_message();
+        counters.on_server_message(false);
+        counters.on_reply(None, Some(0x02));
+        counters.on_no_response();
+
+        assert_eq!(
+            diagnostic(
+                &mut counters,
+                DiagnosticSubFunction::ReturnQueryData,
+                0xA537
+            ),
+            Ok(0xA537)
+        );
+        assert_eq!(
+            diagnostic(
+                &mut counters,
+                DiagnosticSubFunction::ReturnBusMessageCount,
+                0
+            ),
+            Ok(1)
+        );
+        assert_eq!(
+            diagnostic(
+                &mut counters,
+                DiagnosticSubFunction::ReturnBusExceptionErrorCount,
+                0
+            ),
+            Ok(1)
+        );
+        assert_eq!(
+            diagnostic(
+                &mut counters,
+                DiagnosticSubFunction::ReturnServerNoResponseCount,
+                0
+            ),
+            Ok(1)
+        );
+        assert_eq!(
+            diagnostic(
+                &mut counters,
+                DiagnosticSubFunction::ClearCountersAndDiagnosticRegister,
+                0
+            ),
+            Ok(0)
+        );
+        assert_eq!(
+            diagnostic(
+                &mut counters,
+                DiagnosticSubFunction::ReturnServerMessageCount,
+                0
+            ),
+            Ok(0)
+        );
+    }
+
+    #[test]
+    fn rejects_bad_sub_functions_and_data() {
+        let mut counters = DiagnosticCounters::new();
+        assert_eq!(
+            counters.diagnostics(Diagnostic::new(0x13, 0)),
+            Err(ExceptionCode::IllegalFunction)
+        );
+        assert_eq!(
+            diagnostic(
+                &mut counters,
+                DiagnosticSubFunction::ChangeAsciiInputDelimiter,
+                0x0A00
+            ),
+            Err(ExceptionCode::IllegalFunction)
+        );
+        assert_eq!(
+            diagnostic(
+                &mut counters,
+                DiagnosticSubFunction::ReturnBusMessageCount,
+                1
+            ),
+            Err(ExceptionCode::IllegalDataValue)
+        );
+        assert_eq!(
+            diagnostic(
+                &mut counters,
+                DiagnosticSubFunction::RestartCommunications,
+                0x1234
+            ),
+            Err(ExceptionCode::IllegalDataValue)
+        );
+    }
+
+    #[test]
+    fn restart_clears_counters_and_listen_only_mode() {
+        let mut counters = DiagnosticCounters::new();
+        counters.on_bus_message();
+        counters.on_server_message(false);
+        counters.enter_listen_only_mode();
+        assert!(counters.listen_only());
+
+        counters.restart(false);
+        assert!(!counters.listen_only());
+        assert_eq!(counters.bus_message, 0);
+        assert_eq!(counters.server_message, 0);
+        assert_eq!(counters.comm_event_log().events.len(), 3);
+
+        counters.restart(true);
+        assert_eq!(
+            counters.comm_event_log().events,
+            vec![CommEvent::CommunicationRestart]
+        );
+    }
+}
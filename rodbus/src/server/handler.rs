@@ -1,7 +1,9 @@
 use std::collections::BTreeMap;
 use std::sync::{Arc, Mutex};
 
+use crate::device_id::DeviceIdentification;
 use crate::exception::ExceptionCode;
+use crate::file_record::{FileRecord, FileRecordData};
 use crate::server::{WriteCoils, WriteRegisters};
 use crate::types::*;
 
@@ -63,6 +65,84 @@ pub trait RequestHandler: Send + 'static {
         Err(ExceptionCode::IllegalFunction)
     }
 
+    /// Read a group of records from a file or return an ExceptionCode
+    ///
+    /// Called once for each sub-request of a `read file record` request. Exactly
+    /// `record.record_length` values must be returned, otherwise the file is considered
+    /// inconsistent and [`ExceptionCode::MemoryParityError`] is returned to the client.
+    fn read_file_record(&self, _record: FileRecord) -> Result<Vec<u16>, ExceptionCode> {
+        Err(ExceptionCode::IllegalFunction)
+    }
+
+    /// Write a group of records to a file or return an ExceptionCode
+    ///
+    /// Called once for each sub-request of a `write file record` request, in order. Processing
+    /// stops at the first error, so the preceding groups have already been written.
+    fn write_file_record(&mut self, _record: &FileRecordData) -> Result<(), ExceptionCode> {
+        Err(ExceptionCode::IllegalFunction)
+    }
+
+    /// Modify individual bits of a holding register
+    ///
+    /// The default implementation reads the register using [`RequestHandler::read_holding_register`],
+    /// applies the masks, and writes the result using [`RequestHandler::write_single_register`].
+    /// The handler lock is held for the whole operation, so no other request can modify the
+    /// register in between.
+    fn mask_write_register(&mut self, value: MaskWriteRegister) -> Result<(), ExceptionCode> {
+        let current = self.read_holding_register(value.address)?;
+        self.write_single_register(Indexed::new(value.address, value.apply(current)))
+    }
+
+    /// Perform only the write of a `read/write multiple registers` request
+    ///
+    /// This must not read anything: if the write succeeds, the requested range is then read
+    /// by the library using [`RequestHandler::read_holding_register`]. Both steps are performed
+    /// without releasing the handler lock, so no other request can be processed in between.
+    fn write_for_read_write_multiple(
+        &mut self,
+        _values: WriteRegisters,
+    ) -> Result<(), ExceptionCode> {
+        Err(ExceptionCode::IllegalFunction)
+    }
+
+    /// Read the contents of the FIFO queue at the pointer address or return an ExceptionCode
+    ///
+    /// Queues with more than [`MAX_FIFO_COUNT`](crate::constants::limits::MAX_FIFO_COUNT)
+    /// values are answered with [`ExceptionCode::IllegalDataValue`]
+    fn read_fifo_queue(&self, _pointer_address: u16) -> Result<Vec<u16>, ExceptionCode> {
+        Err(ExceptionCode::IllegalFunction)
+    }
+
+    /// Read the eight exception status outputs of the device or return an ExceptionCode
+    ///
+    /// The meaning of each bit is device specific
+    fn read_exception_status(&self) -> Result<u8, ExceptionCode> {
+        Err(ExceptionCode::IllegalFunction)
+    }
+
+    /// Report the device specific server id or return an ExceptionCode
+    ///
+    /// The data usually consists of the server id, the run indicator status (`0x00` = OFF,
+    /// `0xFF` = ON), and any additional data. Data longer than
+    /// [`MAX_SERVER_ID_LENGTH`](crate::constants::limits::MAX_SERVER_ID_LENGTH) is answered
+    /// with [`ExceptionCode::ServerDeviceFailure`]
+    fn report_server_id(&self) -> Result<Vec<u8>, ExceptionCode> {
+        Err(ExceptionCode::IllegalFunction)
+    }
+
+    /// Process a request with a user-defined function code (65-72 or 100-110) or return an ExceptionCode
+    ///
+    /// `data` is everything following the function code in the request, and the returned bytes
+    /// are sent back following the same function code. Responses longer than
+    /// [`MAX_CUSTOM_FUNCTION_DATA_LENGTH`](crate::constants::limits::MAX_CUSTOM_FUNCTION_DATA_LENGTH)
+    /// are answered with [`ExceptionCode::ServerDeviceFailure`]
+    ///
+    /// RTU over TCP servers have no inter-frame silence to find the end of such requests, so
+    /// they are answered with [`ExceptionCode::IllegalFunction`] without calling this method
+    fn custom_function(&mut self, _function: u8, _data: &[u8]) -> Result<Vec<u8>, ExceptionCode> {
+        Err(ExceptionCode::IllegalFunction)
+    }
+
     /// Helper function to convert an Option<T> to Result<T, ExceptionCode::IllegalDataAddress>
     ///
     /// This is useful when looking up requested values in a map where the value may not be present
@@ -84,6 +164,7 @@ type ServerHandlerType<T> = Arc<Mutex<Box<T>>>;
 #[derive(Debug, Default)]
 pub struct ServerHandlerMap<T: RequestHandler> {
     handlers: BTreeMap<UnitId, ServerHandlerType<T>>,
+    identifications: BTreeMap<UnitId, Arc<DeviceIdentification>>,
 }
 
 // this couldn't be derived automatically
@@ -95,6 +176,7 @@ where
     fn clone(&self) -> Self {
         ServerHandlerMap {
             handlers: self.handlers.clone(),
+            identifications: self.identifications.clone(),
         }
     }
 }
@@ -107,6 +189,7 @@ where
     pub fn new() -> Self {
         Self {
             handlers: BTreeMap::new(),
+            identifications: BTreeMap::new(),
         }
     }
 
@@ -114,7 +197,10 @@ where
     pub fn single(id: UnitId, handler: ServerHandlerType<T>) -> Self {
         let mut map: BTreeMap<UnitId, ServerHandlerType<T>> = BTreeMap::new();
         map.insert(id, handler);
-        Self { handlers: map }
+        Self {
+            handlers: map,
+            identifications: BTreeMap::new(),
+        }
     }
 
     /// Retrieve a mutable reference to a [`RequestHandler`]
@@ -130,6 +216,26 @@ where
     ) -> Option<ServerHandlerType<T>> {
         self.handlers.insert(id, server)
     }
+
+    /// Set the objects returned by `read device identification` requests for a unit id
+    ///
+    /// Requests are only answered for unit ids that also have a [`RequestHandler`]. Unit ids
+    /// without identification objects respond with [`ExceptionCode::IllegalFunction`].
+    pub fn set_device_identification(&mut self, id: UnitId, identification: DeviceIdentification) {
+        self.identifications.insert(id, Arc::new(identification));
+    }
+
+    pub(crate) fn handlers(&self) -> impl Iterator<Item = &ServerHandlerType<T>> {
+        self.handlers.values()
+    }
+
+    pub(crate) fn get_with_identification(
+        &mut self,
+        id: UnitId,
+    ) -> Option<(&mut ServerHandlerType<T>, Option<&DeviceIdentification>)> {
+        let handler = self.handlers.get_mut(&id)?;
+        Some((handler, self.identifications.get(&id).map(|x| x.as_ref())))
+    }
 }
 
 #[cfg(test)]
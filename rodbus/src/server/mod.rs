@@ -1,12 +1,22 @@
 use std::net::SocketAddr;
+use std::sync::Arc;
 
 use tracing::Instrument;
 
+use crate::client::ReconnectStrategy;
 use crate::decode::DecodeLevel;
-use crate::tcp::server::ServerTask;
+use crate::serial::ascii::{AsciiFormatter, AsciiParser};
+use crate::serial::frame::{RtuFormatter, RtuParser};
+use crate::serial::server::SerialServerTask;
+use crate::serial::settings::SerialSettings;
+use crate::server::task::SessionTask;
+use crate::tcp::server::{FrameType, ServerTask, TcpServerConnectionHandler};
+use crate::tcp::tls::TlsServerConfig;
 use crate::tokio;
 
 /// server handling
+pub(crate) mod authorization;
+pub(crate) mod counters;
 pub(crate) mod handler;
 pub(crate) mod request;
 pub(crate) mod response;
@@ -14,6 +24,7 @@ pub(crate) mod task;
 pub(crate) mod types;
 
 // re-export to the public API
+pub use authorization::*;
 pub use handler::*;
 pub use types::*;
 
@@ -92,6 +103,148 @@ pub async fn create_tcp_server_task<T: RequestHandler>(
     ))
 }
 
+/// Spawns a TLS server task (Modbus/TCP Security) onto the runtime. This method can only
+/// be called from within the runtime context. Use [`create_tls_server_task`]
+/// and then spawn it manually if using outside the Tokio runtime.
+///
+/// Clients must authenticate with a certificate that contains the Modbus Role extension.
+/// Each request is checked by the [`AuthorizationHandler`] using this role before it is
+/// passed to the [`RequestHandler`]. Denied requests receive an
+/// [`ExceptionCode::IllegalFunction`](crate::ExceptionCode::IllegalFunction) response.
+///
+/// Each incoming connection will spawn a new task to handle it.
+///
+/// * `max_sessions` - Maximum number of concurrent sessions
+/// * `addr` - A socket address to bound to
+/// * `handlers` - A map of handlers keyed by a unit id
+/// * `auth_handler` - Authorizes requests based on the role of the client
+/// * `tls_config` - TLS configuration
+/// * `decode` - Decode log level
+pub async fn spawn_tls_server_task<T: RequestHandler>(
+    max_sessions: usize,
+    addr: SocketAddr,
+    handlers: ServerHandlerMap<T>,
+    auth_handler: Arc<dyn AuthorizationHandler>,
+    tls_config: TlsServerConfig,
+    decode: DecodeLevel,
+) -> Result<ServerHandle, tokio::io::Error> {
+    let listener = tokio::net::TcpListener::bind(addr).await?;
+
+    let (tx, rx) = tokio::sync::mpsc::channel(1);
+    tokio::spawn(create_tls_server_task_impl(
+        rx,
+        max_sessions,
+        addr,
+        listener,
+        handlers,
+        TcpServerConnectionHandler::Tls(tls_config, auth_handler),
+        decode,
+    ));
+
+    Ok(ServerHandle::new(tx))
+}
+
+/// Creates a TLS server task (Modbus/TCP Security) that can then be spawned onto the runtime
+/// manually. Most users will prefer [`spawn_tls_server_task`] unless they are using the library
+/// from outside the Tokio runtime and need to spawn it using a Runtime handle instead of the
+/// `tokio::spawn` function.
+///
+/// Each incoming connection will spawn a new task to handle it.
+///
+/// * `rx` - Receiver used to shutdown the task
+/// * `max_sessions` - Maximum number of concurrent sessions
+/// * `addr` - A socket address to bound to
+/// * `handlers` - A map of handlers keyed by a unit id
+/// * `auth_handler` - Authorizes requests based on the role of the client
+/// * `tls_config` - TLS configuration
+/// * `decode` - Decode log level
+pub async fn create_tls_server_task<T: RequestHandler>(
+    rx: tokio::sync::mpsc::Receiver<()>,
+    max_sessions: usize,
+    addr: SocketAddr,
+    handlers: ServerHandlerMap<T>,
+    auth_handler: Arc<dyn AuthorizationHandler>,
+    tls_config: TlsServerConfig,
+    decode: DecodeLevel,
+) -> Result<impl std::future::Future<Output = ()>, tokio::io::Error> {
+    let listener = tokio::net::TcpListener::bind(addr).await?;
+    Ok(create_tls_server_task_impl(
+        rx,
+        max_sessions,
+        addr,
+        listener,
+        handlers,
+        TcpServerConnectionHandler::Tls(tls_config, auth_handler),
+        decode,
+    ))
+}
+
+/// Spawns an RTU over TCP server task onto the runtime. The server accepts TCP connections
+/// carrying raw Modbus RTU frames (unit id, PDU, and CRC without an MBAP header), such as
+/// those from a serial-to-Ethernet converter. This method can only be called from within the
+/// runtime context. Use [`create_rtu_over_tcp_server_task`] and then spawn it manually if using
+/// outside the Tokio runtime.
+///
+/// Each incoming connection will spawn a new task to handle it.
+///
+/// The end of a request to a user-defined function code can't be found without inter-frame
+/// silence, so the bytes received with it are discarded and the request is answered with an
+/// `IllegalFunction` exception.
+///
+/// * `max_sessions` - Maximum number of concurrent sessions
+/// * `addr` - A socket address to bound to
+/// * `handlers` - A map of handlers keyed by a unit id
+/// * `decode` - Decode log level
+pub async fn spawn_rtu_over_tcp_server_task<T: RequestHandler>(
+    max_sessions: usize,
+    addr: SocketAddr,
+    handlers: ServerHandlerMap<T>,
+    decode: DecodeLevel,
+) -> Result<ServerHandle, tokio::io::Error> {
+    let listener = tokio::net::TcpListener::bind(addr).await?;
+
+    let (tx, rx) = tokio::sync::mpsc::channel(1);
+    tokio::spawn(create_rtu_over_tcp_server_task_impl(
+        rx,
+        max_sessions,
+        addr,
+        listener,
+        handlers,
+        decode,
+    ));
+
+    Ok(ServerHandle::new(tx))
+}
+
+/// Creates an RTU over TCP server task that can then be spawned onto the runtime manually.
+/// Most users will prefer [`spawn_rtu_over_tcp_server_task`] unless they are using the library
+/// from outside the Tokio runtime and need to spawn it using a Runtime handle instead of the
+/// `tokio::spawn` function.
+///
+/// Each incoming connection will spawn a new task to handle it.
+///
+/// * `max_sessions` - Maximum number of concurrent sessions
+/// * `addr` - A socket address to bound to
+/// * `handlers` - A map of handlers keyed by a unit id
+/// * `decode` - Decode log level
+pub async fn create_rtu_over_tcp_server_task<T: RequestHandler>(
+    rx: tokio::sync::mpsc::Receiver<()>,
+    max_sessions: usize,
+    addr: SocketAddr,
+    handlers: ServerHandlerMap<T>,
+    decode: DecodeLevel,
+) -> Result<impl std::future::Future<Output = ()>, tokio::io::Error> {
+    let listener = tokio::net::TcpListener::bind(addr).await?;
+    Ok(create_rtu_over_tcp_server_task_impl(
+        rx,
+        max_sessions,
+        addr,
+        listener,
+        handlers,
+        decode,
+    ))
+}
+
 async fn create_tcp_server_task_impl<T: RequestHandler>(
     rx: tokio::sync::mpsc::Receiver<()>,
     max_sessions: usize,
@@ -100,8 +253,201 @@ async fn create_tcp_server_task_impl<T: RequestHandler>(
     handlers: ServerHandlerMap<T>,
     decode: DecodeLevel,
 ) {
-    ServerTask::new(max_sessions, listener, handlers, decode)
-        .run(rx)
-        .instrument(tracing::info_span!("Modbus-Server-TCP", "listen" = ?addr))
-        .await;
+    ServerTask::new(
+        max_sessions,
+        listener,
+        handlers,
+        FrameType::Mbap,
+        TcpServerConnectionHandler::Tcp,
+        decode,
+    )
+    .run(rx)
+    .instrument(tracing::info_span!("Modbus-Server-TCP", "listen" = ?addr))
+    .await;
+}
+
+async fn create_tls_server_task_impl<T: RequestHandler>(
+    rx: tokio::sync::mpsc::Receiver<()>,
+    max_sessions: usize,
+    addr: SocketAddr,
+    listener: tokio::net::TcpListener,
+    handlers: ServerHandlerMap<T>,
+    connection_handler: TcpServerConnectionHandler,
+    decode: DecodeLevel,
+) {
+    ServerTask::new(
+        max_sessions,
+        listener,
+        handlers,
+        FrameType::Mbap,
+        connection_handler,
+        decode,
+    )
+    .run(rx)
+    .instrument(tracing::info_span!("Modbus-Server-TLS", "listen" = ?addr))
+    .await;
+}
+
+async fn create_rtu_over_tcp_server_task_impl<T: RequestHandler>(
+    rx: tokio::sync::mpsc::Receiver<()>,
+    max_sessions: usize,
+    addr: SocketAddr,
+    listener: tokio::net::TcpListener,
+    handlers: ServerHandlerMap<T>,
+    decode: DecodeLevel,
+) {
+    ServerTask::new(
+        max_sessions,
+        listener,
+        handlers,
+        FrameType::Rtu,
+        TcpServerConnectionHandler::Tcp,
+        decode,
+    )
+    .run(rx)
+    .instrument(tracing::info_span!("Modbus-Server-RTU-over-TCP", "listen" = ?addr))
+    .await;
+}
+
+/// Spawns a Modbus RTU server task onto the runtime that serves requests received on a serial
+/// port. This method can only be called from within the runtime context. Use
+/// [`create_rtu_server_task`] and then spawn it manually if using outside the Tokio runtime.
+///
+/// The server uses the provided [`ReconnectStrategy`] to pause between failed attempts
+/// to open the serial port
+///
+/// * `path` - Path to the serial device, e.g. `/dev/ttyUSB0` or `COM1`
+/// * `serial_settings` - Serial port settings
+/// * `retry` - A boxed trait object that controls when opening the port is retried on failure
+/// * `handlers` - A map of handlers keyed by a unit id
+/// * `decode` - Decode log level
+pub fn spawn_rtu_server_task<T: RequestHandler>(
+    path: &str,
+    serial_settings: SerialSettings,
+    retry: Box<dyn ReconnectStrategy + Send>,
+    handlers: ServerHandlerMap<T>,
+    decode: DecodeLevel,
+) -> ServerHandle {
+    let (tx, rx) = tokio::sync::mpsc::channel(1);
+    tokio::spawn(create_rtu_server_task(
+        rx,
+        path,
+        serial_settings,
+        retry,
+        handlers,
+        decode,
+    ));
+    ServerHandle::new(tx)
+}
+
+/// Creates a Modbus RTU server task that can then be spawned onto the runtime manually.
+/// Most users will prefer [`spawn_rtu_server_task`] unless they are using the library from
+/// outside the Tokio runtime and need to spawn it using a Runtime handle instead of the
+/// `tokio::spawn` function.
+///
+/// The server uses the provided [`ReconnectStrategy`] to pause between failed attempts
+/// to open the serial port
+///
+/// * `rx` - Receiver used to shutdown the task
+/// * `path` - Path to the serial device, e.g. `/dev/ttyUSB0` or `COM1`
+/// * `serial_settings` - Serial port settings
+/// * `retry` - A boxed trait object that controls when opening the port is retried on failure
+/// * `handlers` - A map of handlers keyed by a unit id
+/// * `decode` - Decode log level
+pub fn create_rtu_server_task<T: RequestHandler>(
+    rx: tokio::sync::mpsc::Receiver<()>,
+    path: &str,
+    serial_settings: SerialSettings,
+    retry: Box<dyn ReconnectStrategy + Send>,
+    handlers: ServerHandlerMap<T>,
+    decode: DecodeLevel,
+) -> impl std::future::Future<Output = ()> {
+    let path = path.to_string();
+    async move {
+        let session = SessionTask::new(
+            handlers,
+            RtuFormatter::new(decode.adu),
+            RtuParser::new_request_parser(
+                Some(serial_settings.effective_inter_frame_delay()),
+                decode.adu,
+            ),
+            rx,
+            decode.pdu,
+        )
+        .with_broadcast();
+        SerialServerTask::new(&path, serial_settings, retry, session, decode)
+            .run()
+            .instrument(tracing::info_span!("Modbus-Server-RTU", port = ?path))
+            .await
+    }
+}
+
+/// Spawns a Modbus ASCII server task onto the runtime that serves requests received on a serial
+/// port. This method can only be called from within the runtime context. Use
+/// [`create_ascii_server_task`] and then spawn it manually if using outside the Tokio runtime.
+///
+/// The server uses the provided [`ReconnectStrategy`] to pause between failed attempts
+/// to open the serial port
+///
+/// * `path` - Path to the serial device, e.g. `/dev/ttyUSB0` or `COM1`
+/// * `serial_settings` - Serial port settings
+/// * `retry` - A boxed trait object that controls when opening the port is retried on failure
+/// * `handlers` - A map of handlers keyed by a unit id
+/// * `decode` - Decode log level
+pub fn spawn_ascii_server_task<T: RequestHandler>(
+    path: &str,
+    serial_settings: SerialSettings,
+    retry: Box<dyn ReconnectStrategy + Send>,
+    handlers: ServerHandlerMap<T>,
+    decode: DecodeLevel,
+) -> ServerHandle {
+    let (tx, rx) = tokio::sync::mpsc::channel(1);
+    tokio::spawn(create_ascii_server_task(
+        rx,
+        path,
+        serial_settings,
+        retry,
+        handlers,
+        decode,
+    ));
+    ServerHandle::new(tx)
+}
+
+/// Creates a Modbus ASCII server task that can then be spawned onto the runtime manually.
+/// Most users will prefer [`spawn_ascii_server_task`] unless they are using the library from
+/// outside the Tokio runtime and need to spawn it using a Runtime handle instead of the
+/// `tokio::spawn` function.
+///
+/// The server uses the provided [`ReconnectStrategy`] to pause between failed attempts
+/// to open the serial port
+///
+/// * `rx` - Receiver used to shutdown the task
+/// * `path` - Path to the serial device, e.g. `/dev/ttyUSB0` or `COM1`
+/// * `serial_settings` - Serial port settings
+/// * `retry` - A boxed trait object that controls when opening the port is retried on failure
+/// * `handlers` - A map of handlers keyed by a unit id
+/// * `decode` - Decode log level
+pub fn create_ascii_server_task<T: RequestHandler>(
+    rx: tokio::sync::mpsc::Receiver<()>,
+    path: &str,
+    serial_settings: SerialSettings,
+    retry: Box<dyn ReconnectStrategy + Send>,
+    handlers: ServerHandlerMap<T>,
+    decode: DecodeLevel,
+) -> impl std::future::Future<Output = ()> {
+    let path = path.to_string();
+    async move {
+        let session = SessionTask::new(
+            handlers,
+            AsciiFormatter::new(decode.adu),
+            AsciiParser::new(decode.adu),
+            rx,
+            decode.pdu,
+        )
+        .with_broadcast();
+        SerialServerTask::new(&path, serial_settings, retry, session, decode)
+            .run()
+            .instrument(tracing::info_span!("Modbus-Server-ASCII", port = ?path))
+            .await
+    }
 }
@@ -2,9 +2,17 @@ use crate::common::cursor::ReadCursor;
 use crate::common::frame::{FrameFormatter, FrameHeader};
 use crate::common::function::FunctionCode;
 use crate::common::traits::{Loggable, Parse, Serialize};
+use crate::custom::{CustomFunctionData, CustomFunctionDataDisplay};
 use crate::decode::PduDecodeLevel;
+use crate::device_id::{DeviceIdResponse, DeviceIdentification, ReadDeviceIdRequest};
+use crate::diagnostics::{Diagnostic, DiagnosticSubFunction, ExceptionStatus, ServerId};
 use crate::error::RequestError;
 use crate::exception::ExceptionCode;
+use crate::file_record::{
+    FileRecordData, FileRecordDataDisplay, FileRecordDisplay, ReadFileRecordResponse,
+    ReadFileRecordSubRequests, WriteFileRecordSubRequests,
+};
+use crate::server::counters::DiagnosticCounters;
 use crate::server::handler::RequestHandler;
 use crate::server::response::{BitWriter, RegisterWriter};
 use crate::server::*;
@@ -17,8 +25,20 @@ pub(crate) enum Request<'a> {
     ReadInputRegisters(ReadRegistersRange),
     WriteSingleCoil(Indexed<bool>),
     WriteSingleRegister(Indexed<u16>),
+    ReadExceptionStatus,
+    Diagnostics(Diagnostic),
+    GetCommEventCounter,
+    GetCommEventLog,
     WriteMultipleCoils(WriteCoils<'a>),
     WriteMultipleRegisters(WriteRegisters<'a>),
+    ReportServerId,
+    ReadFileRecord(ReadFileRecordSubRequests),
+    WriteFileRecord(WriteFileRecordSubRequests),
+    MaskWriteRegister(MaskWriteRegister),
+    ReadWriteMultipleRegisters(ReadRegistersRange, WriteRegisters<'a>),
+    ReadFifoQueue(u16),
+    ReadDeviceIdentification(ReadDeviceIdRequest),
+    CustomFunction(u8, &'a [u8]),
 }
 
 impl<'a> Request<'a> {
@@ -30,8 +50,56 @@ impl<'a> Request<'a> {
             Request::ReadInputRegisters(_) => FunctionCode::ReadInputRegisters,
             Request::WriteSingleCoil(_) => FunctionCode::WriteSingleCoil,
             Request::WriteSingleRegister(_) => FunctionCode::WriteSingleRegister,
+            Request::ReadExceptionStatus => FunctionCode::ReadExceptionStatus,
+            Request::Diagnostics(_) => FunctionCode::Diagnostics,
+            Request::GetCommEventCounter => FunctionCode::GetCommEventCounter,
+            Request::GetCommEventLog => FunctionCode::GetCommEventLog,
             Request::WriteMultipleCoils(_) => FunctionCode::WriteMultipleCoils,
             Request::WriteMultipleRegisters(_) => FunctionCode::WriteMultipleRegisters,
+            Request::ReportServerId => FunctionCode::ReportServerId,
+            Request::ReadFileRecord(_) => FunctionCode::ReadFileRecord,
+            Request::WriteFileRecord(_) => FunctionCode::WriteFileRecord,
+            Request::MaskWriteRegister(_) => FunctionCode::MaskWriteRegister,
+            Request::ReadWriteMultipleRegisters(_, _) => FunctionCode::ReadWriteMultipleRegisters,
+            Request::ReadFifoQueue(_) => FunctionCode::ReadFifoQueue,
+            Request::ReadDeviceIdentification(_) => FunctionCode::ReadDeviceIdentification,
+            Request::CustomFunction(function, _) => FunctionCode::Custom(*function),
+        }
+    }
+
+    // the only request that is processed while the server is in listen only mode
+    pub(crate) fn is_restart_communications(&self) -> bool {
+        match self {
+            Request::Diagnostics(request) => {
+                request.sub_function == DiagnosticSubFunction::RestartCommunications.get_value()
+            }
+            _ => false,
+        }
+    }
+
+    // the requests that are performed without a reply when sent to the broadcast unit id
+    pub(crate) fn is_broadcast_write(&self) -> bool {
+        matches!(
+            self,
+            Request::WriteSingleCoil(_)
+                | Request::WriteSingleRegister(_)
+                | Request::WriteMultipleCoils(_)
+                | Request::WriteMultipleRegisters(_)
+                | Request::MaskWriteRegister(_)
+        )
+    }
+
+    pub(crate) fn execute_broadcast<T>(&self, handler: &mut T) -> Result<(), ExceptionCode>
+    where
+        T: RequestHandler,
+    {
+        match self {
+            Request::WriteSingleCoil(request) => handler.write_single_coil(*request),
+            Request::WriteSingleRegister(request) => handler.write_single_register(*request),
+            Request::WriteMultipleCoils(items) => handler.write_multiple_coils(*items),
+            Request::WriteMultipleRegisters(items) => handler.write_multiple_registers(*items),
+            Request::MaskWriteRegister(request) => handler.mask_write_register(*request),
+            _ => Err(ExceptionCode::IllegalFunction),
         }
     }
 
@@ -39,6 +107,8 @@ impl<'a> Request<'a> {
         self,
         header: FrameHeader,
         handler: &mut T,
+        identification: Option<&DeviceIdentification>,
+        counters: &mut DiagnosticCounters,
         writer: &'b mut F,
         level: PduDecodeLevel,
     ) -> Result<&'b [u8], RequestError>
@@ -97,6 +167,28 @@ impl<'a> Request<'a> {
                 handler.write_single_register(request).map(|_| request),
                 level,
             ),
+            Request::ReadExceptionStatus => serialize_result(
+                function,
+                header,
+                writer,
+                handler
+                    .read_exception_status()
+                    .map(|value| ExceptionStatus { value }),
+                level,
+            ),
+            Request::Diagnostics(request) => serialize_result(
+                function,
+                header,
+                writer,
+                counters.diagnostics(request),
+                level,
+            ),
+            Request::GetCommEventCounter => {
+                writer.format(header, function, &counters.comm_event_counter(), level)
+            }
+            Request::GetCommEventLog => {
+                writer.format(header, function, &counters.comm_event_log(), level)
+            }
             Request::WriteMultipleCoils(items) => serialize_result(
                 function,
                 header,
@@ -111,6 +203,101 @@ impl<'a> Request<'a> {
                 handler.write_multiple_registers(items).map(|_| items.range),
                 level,
             ),
+            Request::ReportServerId => {
+                let result = handler.report_server_id().and_then(|data| {
+                    if data.len() > crate::constants::limits::MAX_SERVER_ID_LENGTH {
+                        tracing::warn!("handler returned a server id of {} bytes", data.len());
+                        return Err(ExceptionCode::ServerDeviceFailure);
+                    }
+                    Ok(ServerId { data })
+                });
+                serialize_result(function, header, writer, result, level)
+            }
+            Request::ReadFileRecord(request) => {
+                let result = request.validate().and_then(|_| {
+                    let records = request
+                        .records
+                        .iter()
+                        .map(|record| {
+                            let values = handler.read_file_record(*record)?;
+                            if values.len() != record.record_length as usize {
+                                tracing::warn!(
+                                    "handler returned {} values for {}",
+                                    values.len(),
+                                    record
+                                );
+                                return Err(ExceptionCode::MemoryParityError);
+                            }
+                            Ok(FileRecordData::new(
+                                record.file_number,
+                                record.record_number,
+                                values,
+                            ))
+                        })
+                        .collect::<Result<Vec<_>, _>>()?;
+                    Ok(ReadFileRecordResponse { records })
+                });
+                serialize_result(function, header, writer, result, level)
+            }
+            Request::WriteFileRecord(request) => {
+                let result = request.validate().and_then(|_| {
+                    for record in request.request.records.iter() {
+                        handler.write_file_record(record)?;
+                    }
+                    Ok(request.request)
+                });
+                serialize_result(function, header, writer, result, level)
+            }
+            Request::MaskWriteRegister(request) => serialize_result(
+                function,
+                header,
+                writer,
+                handler.mask_write_register(request).map(|_| request),
+                level,
+            ),
+            Request::ReadWriteMultipleRegisters(range, items) => {
+                // the write is applied before the read, both under the same handler lock
+                match handler.write_for_read_write_multiple(items) {
+                    Ok(()) => {
+                        let registers = RegisterWriter::new(range, |index| {
+                            handler.read_holding_register(index)
+                        });
+                        writer.format(header, function, &registers, level)
+                    }
+                    Err(ex) => writer.exception(header, function, ex, level),
+                }
+            }
+            Request::ReadFifoQueue(address) => {
+                let result = handler.read_fifo_queue(address).and_then(|values| {
+                    if values.len() > crate::constants::limits::MAX_FIFO_COUNT as usize {
+                        Err(ExceptionCode::IllegalDataValue)
+                    } else {
+                        Ok(FifoQueue::new(values))
+                    }
+                });
+                serialize_result(function, header, writer, result, level)
+            }
+            Request::ReadDeviceIdentification(request) => {
+                let result = match identification {
+                    Some(identification) => DeviceIdResponse::build(identification, request),
+                    None => Err(ExceptionCode::IllegalFunction),
+                };
+                serialize_result(function, header, writer, result, level)
+            }
+            Request::CustomFunction(code, data) => {
+                let result = handler.custom_function(code, data).and_then(|data| {
+                    if data.len() > crate::constants::limits::MAX_CUSTOM_FUNCTION_DATA_LENGTH {
+                        tracing::warn!(
+                            "handler returned {} bytes for custom function {:#04X}",
+                            data.len(),
+                            code
+                        );
+                        return Err(ExceptionCode::ServerDeviceFailure);
+                    }
+                    Ok(CustomFunctionData { data })
+                });
+                serialize_result(function, header, writer, result, level)
+            }
         }
     }
 
@@ -170,6 +357,62 @@ impl<'a> Request<'a> {
                     RegisterIterator::parse_all(range, cursor)?,
                 )))
             }
+            FunctionCode::ReadExceptionStatus => {
+                cursor.expect_empty()?;
+                Ok(Request::ReadExceptionStatus)
+            }
+            FunctionCode::Diagnostics => Ok(Request::Diagnostics(Diagnostic::parse(cursor)?)),
+            FunctionCode::GetCommEventCounter => {
+                cursor.expect_empty()?;
+                Ok(Request::GetCommEventCounter)
+            }
+            FunctionCode::GetCommEventLog => {
+                cursor.expect_empty()?;
+                Ok(Request::GetCommEventLog)
+            }
+            FunctionCode::ReportServerId => {
+                cursor.expect_empty()?;
+                Ok(Request::ReportServerId)
+            }
+            FunctionCode::ReadFileRecord => Ok(Request::ReadFileRecord(
+                ReadFileRecordSubRequests::parse(cursor)?,
+            )),
+            FunctionCode::WriteFileRecord => Ok(Request::WriteFileRecord(
+                WriteFileRecordSubRequests::parse(cursor)?,
+            )),
+            FunctionCode::MaskWriteRegister => {
+                let x = Request::MaskWriteRegister(MaskWriteRegister::parse(cursor)?);
+                cursor.expect_empty()?;
+                Ok(x)
+            }
+            FunctionCode::ReadWriteMultipleRegisters => {
+                let read_range = AddressRange::parse(cursor)?.of_read_registers()?;
+                let write_range = AddressRange::parse(cursor)?
+                    .limited_count(crate::constants::limits::MAX_READ_WRITE_WRITE_COUNT)?;
+                // don't care about the count, validated b/c all bytes are consumed
+                cursor.read_u8()?;
+                Ok(Request::ReadWriteMultipleRegisters(
+                    read_range,
+                    WriteRegisters::new(
+                        write_range,
+                        RegisterIterator::parse_all(write_range, cursor)?,
+                    ),
+                ))
+            }
+            FunctionCode::ReadFifoQueue => {
+                let x = Request::ReadFifoQueue(cursor.read_u16_be()?);
+                cursor.expect_empty()?;
+                Ok(x)
+            }
+            FunctionCode::ReadDeviceIdentification => {
+                let x = Request::ReadDeviceIdentification(ReadDeviceIdRequest::parse(cursor)?);
+                cursor.expect_empty()?;
+                Ok(x)
+            }
+            FunctionCode::Custom(code) => Ok(Request::CustomFunction(
+                code,
+                cursor.read_bytes(cursor.len())?,
+            )),
         }
     }
 }
@@ -223,6 +466,43 @@ impl std::fmt::Display for RequestDisplay<'_, '_> {
                         RegisterIteratorDisplay::new(self.level, &items.iterator)
                     )?;
                 }
+                Request::ReadExceptionStatus
+                | Request::GetCommEventCounter
+                | Request::GetCommEventLog
+                | Request::ReportServerId => {}
+                Request::Diagnostics(request) => {
+                    write!(f, " {}", request)?;
+                }
+                Request::ReadFileRecord(request) => {
+                    write!(f, " {}", FileRecordDisplay::new(&request.records))?;
+                }
+                Request::WriteFileRecord(request) => {
+                    write!(
+                        f,
+                        " {}",
+                        FileRecordDataDisplay::new(self.level, &request.request.records)
+                    )?;
+                }
+                Request::MaskWriteRegister(request) => {
+                    write!(f, " {}", request)?;
+                }
+                Request::ReadWriteMultipleRegisters(range, items) => {
+                    write!(
+                        f,
+                        " read: {} write: {}",
+                        range.get(),
+                        RegisterIteratorDisplay::new(self.level, &items.iterator)
+                    )?;
+                }
+                Request::ReadFifoQueue(address) => {
+                    write!(f, " pointer: {:#06X}", address)?;
+                }
+                Request::ReadDeviceIdentification(request) => {
+                    write!(f, " {}", request)?;
+                }
+                Request::CustomFunction(_, data) => {
+                    write!(f, " {}", CustomFunctionDataDisplay::new(self.level, data))?;
+                }
             }
         }
 
@@ -356,4 +636,240 @@ mod tests {
             )
         }
     }
+
+    mod mask_write_register {
+        use crate::common::cursor::ReadCursor;
+
+        use super::super::*;
+        use crate::error::AduParseError;
+
+        #[test]
+        fn can_parse_mask_write_register() {
+            let mut cursor = ReadCursor::new(&[0x00, 0x04, 0x00, 0xF2, 0x00, 0x25]);
+            match Request::parse(FunctionCode::MaskWriteRegister, &mut cursor).unwrap() {
+                Request::MaskWriteRegister(request) => {
+                    assert_eq!(request, MaskWriteRegister::new(4, 0x00F2, 0x0025))
+                }
+                _ => panic!("bad match"),
+            }
+        }
+
+        #[test]
+        fn fails_when_too_many_bytes_present() {
+            let mut cursor = ReadCursor::new(&[0x00, 0x04, 0x00, 0xF2, 0x00, 0x25, 0xFF]);
+            let err = Request::parse(FunctionCode::MaskWriteRegister, &mut cursor)
+                .err()
+                .unwrap();
+            assert_eq!(err, AduParseError::TrailingBytes(1).into());
+        }
+    }
+
+    mod read_write_registers {
+        use crate::common::cursor::ReadCursor;
+
+        use super::super::*;
+        use crate::error::{AduParseError, InvalidRange};
+        use crate::types::Indexed;
+
+        #[test]
+        fn can_parse_read_write_registers() {
+            let mut cursor = ReadCursor::new(&[
+                0x00, 0x03, 0x00, 0x06, 0x00, 0x0E, 0x00, 0x02, 0x04, 0xCA, 0xFE, 0xBB, 0xDD,
+            ]);
+            let (range, registers) = match Request::parse(
+                FunctionCode::ReadWriteMultipleRegisters,
+                &mut cursor,
+            )
+            .unwrap()
+            {
+                Request::ReadWriteMultipleRegisters(range, write) => (range, write),
+                _ => panic!("bad match"),
+            };
+
+            assert_eq!(range.get(), AddressRange::try_from(3, 6).unwrap());
+            assert_eq!(registers.range, AddressRange::try_from(14, 2).unwrap());
+            assert_eq!(
+                registers.iterator.collect::<Vec<Indexed<u16>>>(),
+                vec![Indexed::new(14, 0xCAFE), Indexed::new(15, 0xBBDD)]
+            )
+        }
+
+        #[test]
+        fn fails_when_write_count_exceeds_limit() {
+            let mut cursor = ReadCursor::new(&[0x00, 0x03, 0x00, 0x06, 0x00, 0x0E, 0x00, 0x7A]);
+            let err = Request::parse(FunctionCode::ReadWriteMultipleRegisters, &mut cursor)
+                .err()
+                .unwrap();
+            assert_eq!(err, InvalidRange::CountTooLargeForType(0x7A, 0x79).into());
+        }
+
+        #[test]
+        fn fails_when_too_many_bytes_present() {
+            let mut cursor = ReadCursor::new(&[
+                0x00, 0x03, 0x00, 0x06, 0x00, 0x0E, 0x00, 0x01, 0x02, 0xFF, 0xFF, 0xFF,
+            ]);
+            let err = Request::parse(FunctionCode::ReadWriteMultipleRegisters, &mut cursor)
+                .err()
+                .unwrap();
+            assert_eq!(err, AduParseError::TrailingBytes(1).into());
+        }
+    }
+
+    mod read_fifo_queue {
+        use crate::common::cursor::ReadCursor;
+
+        use super::super::*;
+        use crate::error::AduParseError;
+
+        #[test]
+        fn can_parse_read_fifo_queue_request() {
+            let mut cursor = ReadCursor::new(&[0x04, 0xDE]);
+            match Request::parse(FunctionCode::ReadFifoQueue, &mut cursor).unwrap() {
+                Request::ReadFifoQueue(address) => assert_eq!(address, 0x04DE),
+                _ => panic!("bad match"),
+            }
+        }
+
+        #[test]
+        fn fails_when_too_many_bytes_present() {
+            let mut cursor = ReadCursor::new(&[0x04, 0xDE, 0x00]);
+            let err = Request::parse(FunctionCode::ReadFifoQueue, &mut cursor)
+                .err()
+                .unwrap();
+            assert_eq!(err, AduParseError::TrailingBytes(1).into());
+        }
+    }
+
+    mod diagnostics {
+        use crate::common::cursor::ReadCursor;
+
+        use super::super::*;
+        use crate::error::AduParseError;
+
+        #[test]
+        fn can_parse_diagnostics_request() {
+            let mut cursor = ReadCursor::new(&[0x00, 0x01, 0xFF, 0x00]);
+            let request = Request::parse(FunctionCode::Diagnostics, &mut cursor).unwrap();
+            assert!(request.is_restart_communications());
+            match request {
+                Request::Diagnostics(request) => {
+                    assert_eq!(request, Diagnostic::new(0x0001, 0xFF00))
+                }
+                _ => panic!("bad match"),
+            }
+        }
+
+        #[test]
+        fn fails_when_status_request_has_data() {
+            let mut cursor = ReadCursor::new(&[0x00]);
+            let err = Request::parse(FunctionCode::GetCommEventLog, &mut cursor)
+                .err()
+                .unwrap();
+            assert_eq!(err, AduParseError::TrailingBytes(1).into());
+        }
+    }
+
+    mod file_record {
+        use crate::common::cursor::ReadCursor;
+
+        use super::super::*;
+        use crate::file_record::FileRecord;
+
+        #[test]
+        fn can_parse_read_file_record_request() {
+            let mut cursor = ReadCursor::new(&[
+                0x0E, 0x06, 0x00, 0x04, 0x00, 0x01, 0x00, 0x02, 0x06, 0x00, 0x03, 0x00, 0x09, 0x00,
+                0x02,
+            ]);
+            match Request::parse(FunctionCode::ReadFileRecord, &mut cursor).unwrap() {
+                Request::ReadFileRecord(request) => {
+                    assert_eq!(
+                        request.records,
+                        vec![FileRecord::new(4, 1, 2), FileRecord::new(3, 9, 2)]
+                    );
+                    assert_eq!(request.validate(), Ok(()));
+                }
+                _ => panic!("bad match"),
+            }
+        }
+
+        #[test]
+        fn can_parse_write_file_record_request() {
+            let mut cursor = ReadCursor::new(&[
+                0x0D, 0x06, 0x00, 0x04, 0x00, 0x07, 0x00, 0x03, 0x06, 0xAF, 0x04, 0xBE, 0x10, 0x0D,
+            ]);
+            match Request::parse(FunctionCode::WriteFileRecord, &mut cursor).unwrap() {
+                Request::WriteFileRecord(request) => {
+                    assert_eq!(
+                        request.request.records,
+                        vec![FileRecordData::new(4, 7, vec![0x06AF, 0x04BE, 0x100D])]
+                    );
+                    assert_eq!(request.validate(), Ok(()));
+                }
+                _ => panic!("bad match"),
+            }
+        }
+
+        #[test]
+        fn fails_when_byte_count_does_not_match() {
+            let mut cursor = ReadCursor::new(&[0x08, 0x06, 0x00, 0x04, 0x00, 0x01, 0x00, 0x02]);
+            assert!(Request::parse(FunctionCode::ReadFileRecord, &mut cursor).is_err());
+        }
+    }
+
+    mod read_device_identification {
+        use crate::common::cursor::ReadCursor;
+
+        use super::super::*;
+        use crate::device_id::ReadDeviceIdCode;
+        use crate::error::AduParseError;
+
+        #[test]
+        fn can_parse_read_device_identification_request() {
+            let mut cursor = ReadCursor::new(&[0x0E, 0x02, 0x04]);
+            match Request::parse(FunctionCode::ReadDeviceIdentification, &mut cursor).unwrap() {
+                Request::ReadDeviceIdentification(request) => assert_eq!(
+                    request,
+                    ReadDeviceIdRequest::new(ReadDeviceIdCode::Regular, 0x04)
+                ),
+                _ => panic!("bad match"),
+            }
+        }
+
+        #[test]
+        fn fails_when_device_id_code_is_unknown() {
+            let mut cursor = ReadCursor::new(&[0x0E, 0x05, 0x00]);
+            let err = Request::parse(FunctionCode::ReadDeviceIdentification, &mut cursor)
+                .err()
+                .unwrap();
+            assert_eq!(err, AduParseError::UnknownReadDeviceIdCode(0x05).into());
+        }
+    }
+
+    mod custom_function {
+        use crate::common::cursor::ReadCursor;
+
+        use super::super::*;
+
+        #[test]
+        fn user_defined_function_codes_are_recognized() {
+            assert_eq!(FunctionCode::get(0x41), Some(FunctionCode::Custom(0x41)));
+            assert_eq!(FunctionCode::get(0x6E), Some(FunctionCode::Custom(0x6E)));
+            assert_eq!(FunctionCode::get(0x49), None);
+            assert_eq!(FunctionCode::get(0x63), None);
+        }
+
+        #[test]
+        fn can_parse_custom_function_request() {
+            let mut cursor = ReadCursor::new(&[0xCA, 0xFE, 0x01]);
+            match Request::parse(FunctionCode::Custom(0x65), &mut cursor).unwrap() {
+                Request::CustomFunction(code, data) => {
+                    assert_eq!(code, 0x65);
+                    assert_eq!(data, &[0xCA, 0xFE, 0x01]);
+                }
+                _ => panic!("bad match"),
+            }
+            assert!(cursor.is_empty());
+        }
+    }
 }
@@ -1,3 +1,6 @@
+use std::sync::Arc;
+use std::time::Duration;
+
 use tracing::Instrument;
 
 use crate::common::phys::PhysLayer;
@@ -5,26 +8,43 @@ use crate::decode::PduDecodeLevel;
 use crate::tokio;
 
 use crate::common::cursor::ReadCursor;
-use crate::common::frame::{Frame, FrameFormatter, FrameHeader, FrameParser, FramedReader};
+use crate::common::frame::{Frame, FrameFormatter, FrameParser, FramedReader};
 use crate::common::function::FunctionCode;
 use crate::error::*;
 use crate::exception::ExceptionCode;
+use crate::server::authorization::{Authorization, AuthorizationHandler};
+use crate::server::counters::DiagnosticCounters;
 use crate::server::handler::{RequestHandler, ServerHandlerMap};
 use crate::server::request::{Request, RequestDisplay};
 use crate::server::response::ErrorResponse;
 
+// role of an authenticated client and the handler used to authorize its requests
+pub(crate) struct SessionAuthorization {
+    handler: Arc<dyn AuthorizationHandler>,
+    role: String,
+}
+
+impl SessionAuthorization {
+    pub(crate) fn new(handler: Arc<dyn AuthorizationHandler>, role: String) -> Self {
+        Self { handler, role }
+    }
+}
+
 pub(crate) struct SessionTask<T, F, P>
 where
     T: RequestHandler,
     F: FrameFormatter,
     P: FrameParser,
 {
-    io: PhysLayer,
     handlers: ServerHandlerMap<T>,
     shutdown: tokio::sync::mpsc::Receiver<()>,
     writer: F,
     reader: FramedReader<P>,
+    auth: Option<SessionAuthorization>,
+    counters: DiagnosticCounters,
     decode: PduDecodeLevel,
+    // writes to the broadcast unit id are only supported on links without transaction ids
+    broadcast: bool,
 }
 
 impl<T, F, P> SessionTask<T, F, P>
@@ -34,7 +54,6 @@ where
     P: FrameParser,
 {
     pub(crate) fn new(
-        io: PhysLayer,
         handlers: ServerHandlerMap<T>,
         formatter: F,
         parser: P,
@@ -42,38 +61,86 @@ where
         decode: PduDecodeLevel,
     ) -> Self {
         Self {
-            io,
             handlers,
             shutdown,
             writer: formatter,
             reader: FramedReader::new(parser),
+            auth: None,
+            counters: DiagnosticCounters::new(),
             decode,
+            broadcast: false,
         }
     }
 
-    async fn reply_with_error(
+    // perform writes to the broadcast unit id on every handler without replying
+    //
+    // broadcasts are not authorized, only RTU framings enable them and those are never used over TLS
+    pub(crate) fn with_broadcast(mut self) -> Self {
+        self.broadcast = true;
+        self
+    }
+
+    // when present, every request is authorized before it is passed to the handler
+    pub(crate) fn with_authorization(mut self, auth: Option<SessionAuthorization>) -> Self {
+        self.auth = auth;
+        self
+    }
+
+    // write a formatted reply of the specified length and record it in the counters
+    async fn write_reply(
         &mut self,
-        header: FrameHeader,
-        err: ErrorResponse,
+        io: &mut PhysLayer,
+        function: Option<FunctionCode>,
+        length: usize,
     ) -> Result<(), RequestError> {
-        let bytes = self.writer.error(header, err)?;
-        self.io.write(bytes).await?;
+        let exception = match self.writer.get_payload_impl(length) {
+            Some([code, exception, ..]) if code & 0x80 != 0 => Some(*exception),
+            _ => None,
+        };
+        self.counters.on_reply(function, exception);
+        io.write(self.writer.get_full_buffer(length)?).await?;
         Ok(())
     }
 
-    pub(crate) async fn run(&mut self) -> Result<(), RequestError> {
+    pub(crate) async fn run(&mut self, io: &mut PhysLayer) -> Result<(), RequestError> {
         loop {
-            self.run_one().await?;
+            self.run_one(io).await?;
+        }
+    }
+
+    // discard any partially received frame
+    pub(crate) fn reset(&mut self) {
+        self.reader.reset();
+    }
+
+    // record a frame that was discarded because it failed validation
+    pub(crate) fn on_bad_frame(&mut self, err: FrameParseError) {
+        self.counters.on_bad_frame(matches!(
+            err,
+            FrameParseError::CrcValidationFailure(_, _)
+                | FrameParseError::LrcValidationFailure(_, _)
+        ));
+    }
+
+    // wait for the specified duration unless the session is shutdown
+    pub(crate) async fn sleep_for(&mut self, duration: Duration) -> Result<(), RequestError> {
+        let deadline = tokio::time::Instant::now() + duration;
+        crate::tokio::select! {
+            _ = tokio::time::sleep_until(deadline) => Ok(()),
+            _ = self.shutdown.recv() => Err(RequestError::Shutdown),
         }
     }
 
-    async fn run_one(&mut self) -> Result<(), RequestError> {
+    async fn run_one(&mut self, io: &mut PhysLayer) -> Result<(), RequestError> {
         crate::tokio::select! {
-            frame = self.reader.next_frame(&mut self.io) => {
+            frame = self.reader.next_frame(io) => {
                 let frame = frame?;
-                let tx_id = frame.header.tx_id;
-                self.handle_frame(frame)
-                    .instrument(tracing::info_span!("Transaction", tx_id=%tx_id))
+                let span = match frame.header.tx_id {
+                    Some(tx_id) => tracing::info_span!("Transaction", tx_id=%tx_id),
+                    None => tracing::info_span!("Transaction"),
+                };
+                self.handle_frame(io, frame)
+                    .instrument(span)
                     .await
             }
             _ = self.shutdown.recv() => {
@@ -82,49 +149,95 @@ where
         }
     }
 
-    async fn handle_frame(&mut self, frame: Frame) -> Result<(), RequestError> {
+    async fn handle_frame(&mut self, io: &mut PhysLayer, frame: Frame) -> Result<(), RequestError> {
         let mut cursor = ReadCursor::new(frame.payload());
+        self.counters.on_bus_message();
+
+        if self.broadcast && frame.header.unit_id.is_broadcast() {
+            self.handle_broadcast(&mut cursor);
+            return Ok(());
+        }
 
         // if no addresses match, then don't respond
-        let handler = match self.handlers.get(frame.header.unit_id) {
-            None => {
-                tracing::warn!(
-                    "received frame for unmapped unit id: {}",
-                    frame.header.unit_id.value
-                );
-                return Ok(());
-            }
-            Some(handler) => handler,
-        };
+        let (handler, identification) =
+            match self.handlers.get_with_identification(frame.header.unit_id) {
+                None => {
+                    tracing::warn!(
+                        "received frame for unmapped unit id: {}",
+                        frame.header.unit_id.value
+                    );
+                    return Ok(());
+                }
+                Some(handler) => handler,
+            };
+
+        self.counters.on_server_message(false);
+        // in listen only mode requests are monitored, but only a restart is processed
+        let listen_only = self.counters.listen_only();
 
         let function = match cursor.read_u8() {
             Err(_) => {
                 tracing::warn!("received an empty frame");
+                self.counters.on_no_response();
                 return Ok(());
             }
             Ok(value) => match FunctionCode::get(value) {
                 Some(x) => x,
                 None => {
                     tracing::warn!("received unknown function code: {}", value);
-                    return self
-                        .reply_with_error(frame.header, ErrorResponse::unknown_function(value))
-                        .await;
+                    if listen_only {
+                        self.counters.on_no_response();
+                        return Ok(());
+                    }
+                    let length = self
+                        .writer
+                        .error(frame.header, ErrorResponse::unknown_function(value))?
+                        .len();
+                    return self.write_reply(io, None, length).await;
                 }
             },
         };
 
+        // the parser discarded the data of a request whose end it couldn't find
+        if !self.reader.can_frame_request(function) {
+            tracing::warn!(
+                "{} requests can't be framed without inter-frame silence",
+                function
+            );
+            if listen_only {
+                self.counters.on_no_response();
+                return Ok(());
+            }
+            let length = self
+                .writer
+                .exception(
+                    frame.header,
+                    function,
+                    ExceptionCode::IllegalFunction,
+                    self.decode,
+                )?
+                .len();
+            return self.write_reply(io, Some(function), length).await;
+        }
+
         let request = match Request::parse(function, &mut cursor) {
             Ok(x) => x,
             Err(err) => {
                 tracing::warn!("error parsing {:?} request: {}", function, err);
-                let reply = self.writer.exception(
-                    frame.header,
-                    function,
-                    ExceptionCode::IllegalDataValue,
-                    self.decode,
-                )?;
-                self.io.write(reply).await?;
-                return Ok(());
+                if listen_only {
+                    self.counters.on_no_response();
+                    return Ok(());
+                }
+                let length = self
+                    .writer
+                    .exception(
+                        frame.header,
+                        function,
+                        ExceptionCode::IllegalDataValue,
+                        self.decode,
+                    )?
+                    .len();
+                return self.write_reply(io, Some(function), length).await;
             }
         };
 
@@ -132,14 +245,108 @@ where
             tracing::info!("PDU RX - {}", RequestDisplay::new(self.decode, &request));
         }
 
+        if listen_only && !request.is_restart_communications() {
+            tracing::info!("listen only mode, not responding to {}", function);
+            self.counters.on_no_response();
+            return Ok(());
+        }
+
+        if let Some(auth) = &self.auth {
+            if auth
+                .handler
+                .is_authorized(frame.header.unit_id, function, &auth.role)
+                == Authorization::Deny
+            {
+                tracing::warn!(
+                    "role \"{}\" is not authorized to perform {} on unit id: {}",
+                    auth.role,
+                    function,
+                    frame.header.unit_id.value
+                );
+                let length = self
+                    .writer
+                    .exception(
+                        frame.header,
+                        function,
+                        ExceptionCode::IllegalFunction,
+                        self.decode,
+                    )?
+                    .len();
+                return self.write_reply(io, Some(function), length).await;
+            }
+        }
+
         // get the reply data (or exception reply)
-        let reply_frame: &[u8] = {
+        let length = {
             let mut lock = handler.lock().unwrap();
-            request.get_reply(frame.header, lock.as_mut(), &mut self.writer, self.decode)?
+            request
+                .get_reply(
+                    frame.header,
+                    lock.as_mut(),
+                    identification,
+                    &mut self.counters,
+                    &mut self.writer,
+                    self.decode,
+                )?
+                .len()
         };
 
+        // entering or leaving listen only mode is never answered
+        if listen_only || self.counters.listen_only() {
+            self.counters.on_no_response();
+            return Ok(());
+        }
+
         // reply with the bytes
-        self.io.write(reply_frame).await?;
-        Ok(())
+        self.write_reply(io, Some(function), length).await
+    }
+
+    fn handle_broadcast(&mut self, cursor: &mut ReadCursor) {
+        // broadcasts are never answered
+        self.counters.on_server_message(true);
+        self.counters.on_no_response();
+
+        let function = match cursor.read_u8().ok().and_then(FunctionCode::get) {
+            Some(x) => x,
+            None => {
+                tracing::warn!("received a broadcast with an unknown function code");
+                return;
+            }
+        };
+
+        let request = match Request::parse(function, cursor) {
+            Ok(x) => x,
+            Err(err) => {
+                tracing::warn!("error parsing broadcast {:?} request: {}", function, err);
+                return;
+            }
+        };
+
+        if self.decode.enabled() {
+            tracing::info!(
+                "PDU RX (broadcast) - {}",
+                RequestDisplay::new(self.decode, &request)
+            );
+        }
+
+        if !request.is_broadcast_write() {
+            tracing::warn!(
+                "ignoring broadcast {}, only writes may be broadcast",
+                function
+            );
+            return;
+        }
+
+        if self.counters.listen_only() {
+            tracing::info!("listen only mode, ignoring broadcast {}", function);
+            return;
+        }
+
+        for handler in self.handlers.handlers() {
+            let mut lock = handler.lock().unwrap();
+            if let Err(ex) = request.execute_broadcast(lock.as_mut()) {
+                tracing::warn!("broadcast {} failed with exception: {:?}", function, ex);
+            }
+        }
     }
 }
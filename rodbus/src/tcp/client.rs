@@ -1,65 +1,160 @@
-use std::net::SocketAddr;
-
+use crate::common::frame::{FrameFormatter, FrameParser};
 use crate::common::phys::PhysLayer;
-use crate::decode::DecodeLevel;
-use crate::tcp::frame::{MbapFormatter, MbapParser};
+use crate::decode::{DecodeLevel, PhysDecodeLevel};
+use crate::tcp::tls::TlsClientConfig;
 use crate::tokio::net::TcpStream;
-use crate::tokio::sync::mpsc::Receiver;
+use crate::tokio::sync::watch;
+use crate::tokio::time::Instant;
 
-use crate::client::channel::ReconnectStrategy;
-use crate::client::message::Request;
+use crate::client::channel::ChannelState;
+use crate::client::endpoint::{Endpoint, EndpointList};
 use crate::client::task::{ClientLoop, SessionError};
 
-pub(crate) struct TcpChannelTask {
-    addr: SocketAddr,
-    connect_retry: Box<dyn ReconnectStrategy + Send>,
-    client_loop: ClientLoop<MbapFormatter, MbapParser>,
+/// Determines how the physical layer is created from a connected socket
+pub(crate) enum TcpTaskConnectionHandler {
+    Tcp,
+    Tls(TlsClientConfig),
+}
+
+impl TcpTaskConnectionHandler {
+    async fn handle(&self, socket: TcpStream, level: PhysDecodeLevel) -> Result<PhysLayer, String> {
+        match self {
+            Self::Tcp => Ok(PhysLayer::new_tcp(socket, level)),
+            Self::Tls(config) => config.handle_connection(socket, level).await,
+        }
+    }
+}
+
+/// Channel task that is generic over the framing used on the stream (MBAP or RTU)
+pub(crate) struct TcpChannelTask<F, P>
+where
+    F: FrameFormatter,
+    P: FrameParser,
+{
+    endpoints: EndpointList,
+    // index of the endpoint used by the next connection attempt
+    current: usize,
+    connection_handler: TcpTaskConnectionHandler,
+    client_loop: ClientLoop<F, P>,
     decode: DecodeLevel,
+    state: watch::Sender<ChannelState>,
+    active: watch::Sender<Option<Endpoint>>,
 }
 
-impl TcpChannelTask {
+impl<F, P> TcpChannelTask<F, P>
+where
+    F: FrameFormatter,
+    P: FrameParser,
+{
     pub(crate) fn new(
-        addr: SocketAddr,
-        rx: Receiver<Request>,
-        connect_retry: Box<dyn ReconnectStrategy + Send>,
+        endpoints: EndpointList,
+        connection_handler: TcpTaskConnectionHandler,
+        client_loop: ClientLoop<F, P>,
         decode: DecodeLevel,
+        state: watch::Sender<ChannelState>,
+        active: watch::Sender<Option<Endpoint>>,
     ) -> Self {
         Self {
-            addr,
-            connect_retry,
-            client_loop: ClientLoop::new(
-                rx,
-                MbapFormatter::new(decode.adu),
-                MbapParser::new(decode.adu),
-                decode.pdu,
-            ),
+            endpoints,
+            current: 0,
+            connection_handler,
+            client_loop,
             decode,
+            state,
+            active,
         }
     }
 
     pub(crate) async fn run(&mut self) {
-        // try to connect
+        self.run_until_shutdown().await;
+        self.set_state(ChannelState::Disabled);
+    }
+
+    async fn run_until_shutdown(&mut self) {
         loop {
-            match TcpStream::connect(self.addr).await {
-                Err(e) => {
-                    tracing::warn!("error connecting: {}", e);
-                    let delay = self.connect_retry.next_delay();
-                    if self.client_loop.fail_requests_for(delay).await.is_err() {
-                        // this occurs when the mpsc is dropped, so the task can exit
-                        return;
-                    }
+            let result = if self.client_loop.is_enabled() {
+                self.run_connection().await
+            } else {
+                self.set_state(ChannelState::Disabled);
+                self.client_loop.wait_until_enabled().await
+            };
+
+            match result {
+                // the mpsc was closed, end the task
+                Err(SessionError::Shutdown) => return,
+                // connect to the new primary endpoint right away
+                Err(SessionError::EndpointChanged(endpoints)) => {
+                    tracing::info!("changing endpoint to: {}", endpoints);
+                    self.endpoints = endpoints;
+                    self.current = 0;
+                    self.client_loop.reset_delay();
                 }
-                Ok(socket) => {
-                    let mut phys = PhysLayer::new_tcp(socket, self.decode.physical);
-                    tracing::info!("connected to: {}", self.addr);
-                    match self.client_loop.run(&mut phys).await {
-                        // the mpsc was closed, end the task
-                        SessionError::Shutdown => return,
-                        // re-establish the connection
-                        SessionError::IoError | SessionError::BadFrame => {}
-                    }
+                // the connection to a backup was closed to try the primary again
+                Err(SessionError::Expired) => {
+                    tracing::info!("failing back to: {}", self.endpoints.get(0));
+                    self.current = 0;
                 }
+                // re-establish the connection, unless the channel is now disabled
+                Ok(())
+                | Err(SessionError::IoError)
+                | Err(SessionError::BadFrame)
+                | Err(SessionError::Disabled) => {}
+            }
+        }
+    }
+
+    // try to connect and run a session, or wait before the next attempt
+    async fn run_connection(&mut self) -> Result<(), SessionError> {
+        let endpoint = self.endpoints.get(self.current).clone();
+        self.set_state(ChannelState::Connecting);
+        match self.connect(&endpoint).await {
+            None => {
+                // the next attempt is made with the next endpoint in the list
+                self.current = self.endpoints.next_index(self.current);
+                let delay = self.client_loop.next_delay();
+                self.set_state(ChannelState::WaitingToRetry(delay));
+                self.client_loop.fail_requests_for(delay).await
+            }
+            Some(mut phys) => {
+                tracing::info!("connected to: {}", endpoint);
+                self.set_state(ChannelState::Connected);
+                let _ = self.active.send(Some(endpoint));
+                // a session with a backup only lasts until it's time to try the primary again
+                let end = match self.endpoints.fail_back() {
+                    Some(period) if self.current != 0 => Some(Instant::now() + period),
+                    _ => None,
+                };
+                let err = self.client_loop.run_until(&mut phys, end).await;
+                let _ = self.active.send(None);
+                Err(err)
+            }
+        }
+    }
+
+    fn set_state(&self, state: ChannelState) {
+        // only fails once every receiver is dropped, in which case nobody is interested
+        let _ = self.state.send(state);
+    }
+
+    async fn connect(&mut self, endpoint: &Endpoint) -> Option<PhysLayer> {
+        let socket = match endpoint.connect(self.endpoints.local_address()).await {
+            Err(e) => {
+                tracing::warn!("error connecting to {}: {}", endpoint, e);
+                return None;
+            }
+            Ok(socket) => socket,
+        };
+
+        match self
+            .connection_handler
+            .handle(socket, self.decode.physical)
+            .await
+        {
+            Err(err) => {
+                tracing::warn!("{}", err);
+                None
             }
+            Ok(phys) => Some(phys),
         }
     }
 }
@@ -125,6 +125,10 @@ impl FrameParser for MbapParser {
             }
         }
     }
+
+    fn reset(&mut self) {
+        self.state = ParseState::Begin;
+    }
 }
 
 impl FrameFormatter for MbapFormatter {
@@ -136,7 +140,7 @@ impl FrameFormatter for MbapFormatter {
         let mut cursor = WriteCursor::new(self.buffer.as_mut());
 
         // Write header
-        cursor.write_u16_be(header.tx_id.to_u16())?;
+        cursor.write_u16_be(header.tx_id.unwrap_or_default().to_u16())?;
         cursor.write_u16_be(0)?;
         cursor.seek_from_current(2)?; // write the length later
         cursor.write_u8(header.unit_id.value)?;
@@ -161,7 +165,7 @@ impl FrameFormatter for MbapFormatter {
         // Logging
         if self.decode.enabled() {
             let header = MbapHeader {
-                tx_id: header.tx_id,
+                tx_id: header.tx_id.unwrap_or_default(),
                 adu_length,
                 unit_id: header.unit_id,
             };
@@ -243,7 +247,7 @@ mod tests {
     }
 
     fn assert_equals_simple_frame(frame: &Frame) {
-        assert_eq!(frame.header.tx_id, TxId::new(0x0007));
+        assert_eq!(frame.header.tx_id, Some(TxId::new(0x0007)));
         assert_eq!(frame.header.unit_id, UnitId::new(0x2A));
         assert_eq!(frame.payload(), &[0x03, 0x04]);
     }
@@ -5,7 +5,11 @@ use tracing::Instrument;
 
 use crate::common::phys::PhysLayer;
 use crate::decode::DecodeLevel;
+use crate::serial::frame::{RtuFormatter, RtuParser};
+use crate::server::authorization::AuthorizationHandler;
+use crate::server::task::{SessionAuthorization, SessionTask};
 use crate::tcp::frame::{MbapFormatter, MbapParser};
+use crate::tcp::tls::TlsServerConfig;
 use crate::tokio;
 use crate::tokio::net::TcpListener;
 use std::net::SocketAddr;
@@ -59,10 +63,52 @@ impl SessionTracker {
     }
 }
 
+/// Framing used on the accepted connections
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub(crate) enum FrameType {
+    /// Modbus TCP with an MBAP header
+    Mbap,
+    /// Raw RTU frames with a CRC, as tunneled by serial-to-Ethernet converters
+    Rtu,
+}
+
+/// How the accepted sockets are turned into a physical layer
+#[derive(Clone)]
+pub(crate) enum TcpServerConnectionHandler {
+    Tcp,
+    Tls(TlsServerConfig, Arc<dyn AuthorizationHandler>),
+}
+
+impl TcpServerConnectionHandler {
+    async fn handle(
+        &self,
+        socket: tokio::net::TcpStream,
+        decode: DecodeLevel,
+    ) -> Result<(PhysLayer, Option<SessionAuthorization>), String> {
+        match self {
+            Self::Tcp => Ok((PhysLayer::new_tcp(socket, decode.physical), None)),
+            Self::Tls(config, handler) => {
+                // a client that never completes the handshake would otherwise hold the task forever
+                let timeout = config.handshake_timeout();
+                let (phys, role) = tokio::select! {
+                    x = config.handle_connection(socket, decode.physical) => x?,
+                    _ = tokio::time::sleep(timeout) => {
+                        return Err(format!("TLS handshake not completed within {:?}", timeout));
+                    }
+                };
+                tracing::info!("client authenticated with role: {}", role);
+                Ok((phys, Some(SessionAuthorization::new(handler.clone(), role))))
+            }
+        }
+    }
+}
+
 pub(crate) struct ServerTask<T: RequestHandler> {
     listener: TcpListener,
     handlers: ServerHandlerMap<T>,
     tracker: SessionTrackerWrapper,
+    frame_type: FrameType,
+    connection_handler: TcpServerConnectionHandler,
     decode: DecodeLevel,
 }
 
@@ -74,12 +120,16 @@ where
         max_sessions: usize,
         listener: TcpListener,
         handlers: ServerHandlerMap<T>,
+        frame_type: FrameType,
+        connection_handler: TcpServerConnectionHandler,
         decode: DecodeLevel,
     ) -> Self {
         Self {
             listener,
             handlers,
             tracker: SessionTracker::wrapped(max_sessions),
+            frame_type,
+            connection_handler,
             decode,
         }
     }
@@ -108,30 +158,68 @@ where
     }
 
     async fn handle(&self, socket: tokio::net::TcpStream, addr: SocketAddr) {
-        let phys = PhysLayer::new_tcp(socket, self.decode.physical);
         let decode = self.decode;
+        let frame_type = self.frame_type;
+        let connection_handler = self.connection_handler.clone();
         let handlers = self.handlers.clone();
         let tracker = self.tracker.clone();
         let (tx, rx) = tokio::sync::mpsc::channel(1);
 
-        let id = self.tracker.lock().unwrap().add(tx);
-
-        tracing::info!("accepted connection {} from: {}", id, addr);
+        tracing::info!("accepted connection from: {}", addr);
         let span = tracing::span::Span::current();
 
         tokio::spawn(async move {
-            crate::server::task::SessionTask::new(
-                phys,
-                handlers,
-                MbapFormatter::new(decode.adu),
-                MbapParser::new(decode.adu),
-                rx,
-                decode.pdu,
-            )
-            .run()
-            .instrument(tracing::info_span!(parent: &span, "Session", "remote" = ?addr))
-            .await
-            .ok();
+            // the TLS handshake is performed here so that it doesn't block the listener
+            let (mut phys, auth) = match connection_handler
+                .handle(socket, decode)
+                .instrument(tracing::info_span!(parent: &span, "Handshake", "remote" = ?addr))
+                .await
+            {
+                Ok(x) => x,
+                Err(err) => {
+                    tracing::warn!("{}", err);
+                    return;
+                }
+            };
+
+            // only connections that complete the handshake count against the maximum sessions
+            let id = tracker.lock().unwrap().add(tx);
+            tracing::info!("started session {} with: {}", id, addr);
+
+            let session = async {
+                match frame_type {
+                    FrameType::Mbap => {
+                        SessionTask::new(
+                            handlers,
+                            MbapFormatter::new(decode.adu),
+                            MbapParser::new(decode.adu),
+                            rx,
+                            decode.pdu,
+                        )
+                        .with_authorization(auth)
+                        .run(&mut phys)
+                        .await
+                    }
+                    FrameType::Rtu => {
+                        // frames are delimited by the TCP stream, so there is no inter-frame silence
+                        SessionTask::new(
+                            handlers,
+                            RtuFormatter::new(decode.adu),
+                            RtuParser::new_request_parser(None, decode.adu),
+                            rx,
+                            decode.pdu,
+                        )
+                        .with_broadcast()
+                        .with_authorization(auth)
+                        .run(&mut phys)
+                        .await
+                    }
+                }
+            };
+            session
+                .instrument(tracing::info_span!(parent: &span, "Session", "remote" = ?addr))
+                .await
+                .ok();
             tracing::info!("shutdown session: {}", id);
             tracker.lock().unwrap().remove(id);
         });
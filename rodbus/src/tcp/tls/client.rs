@@ -0,0 +1,124 @@
+use std::convert::TryFrom;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
+
+use rustls::pki_types::ServerName;
+use rustls::RootCertStore;
+
+use crate::common::phys::PhysLayer;
+use crate::decode::PhysDecodeLevel;
+use crate::tcp::tls::{crypto_provider, load_certs, load_private_key, TlsError};
+use crate::tokio;
+use crate::tokio::net::TcpStream;
+
+/// TLS configuration for a Modbus/TCP Security client
+///
+/// Connection attempts that don't complete the handshake within the handshake timeout fail,
+/// see [`TlsClientConfig::DEFAULT_HANDSHAKE_TIMEOUT`].
+#[derive(Clone, Debug)]
+pub struct TlsClientConfig {
+    server_name: ServerName<'static>,
+    config: Arc<rustls::ClientConfig>,
+    handshake_timeout: Duration,
+}
+
+impl TlsClientConfig {
+    /// Time that the server is given to complete the TLS handshake by default
+    pub const DEFAULT_HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(10);
+
+    /// Create a TLS client configuration from PEM encoded files
+    ///
+    /// * `server_name` - DNS name or IP address verified against the subject alternative names of the server certificate
+    /// * `ca_bundle_path` - Path to the CA certificate(s) used to verify the server certificate
+    /// * `local_cert_path` - Path to the certificate chain presented to the server
+    /// * `private_key_path` - Path to the private key of the local certificate (PKCS#8, PKCS#1, or SEC1)
+    pub fn new(
+        server_name: &str,
+        ca_bundle_path: &Path,
+        local_cert_path: &Path,
+        private_key_path: &Path,
+    ) -> Result<Self, TlsError> {
+        let server_name = ServerName::try_from(server_name.to_string())
+            .map_err(|_| TlsError::InvalidDnsName(server_name.to_string()))?;
+
+        let mut roots = RootCertStore::empty();
+        for cert in load_certs(ca_bundle_path).map_err(TlsError::InvalidPeerCertificate)? {
+            roots
+                .add(cert)
+                .map_err(|err| TlsError::InvalidPeerCertificate(err.to_string()))?;
+        }
+
+        let chain = load_certs(local_cert_path).map_err(TlsError::InvalidLocalCertificate)?;
+        let key = load_private_key(private_key_path).map_err(TlsError::InvalidPrivateKey)?;
+
+        let config = rustls::ClientConfig::builder_with_provider(crypto_provider())
+            .with_safe_default_protocol_versions()
+            .map_err(|err| TlsError::BadConfig(err.to_string()))?
+            .with_root_certificates(roots)
+            .with_client_auth_cert(chain, key)
+            .map_err(|err| TlsError::BadConfig(err.to_string()))?;
+
+        Ok(Self {
+            server_name,
+            config: Arc::new(config),
+            handshake_timeout: Self::DEFAULT_HANDSHAKE_TIMEOUT,
+        })
+    }
+
+    /// Change the time that the server is given to complete the TLS handshake
+    pub fn with_handshake_timeout(mut self, timeout: Duration) -> Self {
+        self.handshake_timeout = timeout;
+        self
+    }
+
+    pub(crate) async fn handle_connection(
+        &self,
+        socket: TcpStream,
+        level: PhysDecodeLevel,
+    ) -> Result<PhysLayer, String> {
+        let connector = tokio_rustls::TlsConnector::from(self.config.clone());
+        // a server that never completes the handshake would otherwise leave the channel connecting forever
+        let result = tokio::time::timeout(
+            self.handshake_timeout,
+            connector.connect(self.server_name.clone(), socket),
+        )
+        .await
+        .map_err(|_| {
+            format!(
+                "TLS handshake not completed within {:?}",
+                self.handshake_timeout
+            )
+        })?;
+        match result {
+            Ok(stream) => Ok(PhysLayer::new_tls(
+                tokio_rustls::TlsStream::from(stream),
+                level,
+            )),
+            Err(err) => Err(format!("TLS handshake failed: {}", err)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rejects_invalid_server_name() {
+        let path = Path::new("does-not-exist.pem");
+        assert_eq!(
+            TlsClientConfig::new("not a name", path, path, path).unwrap_err(),
+            TlsError::InvalidDnsName("not a name".to_string())
+        );
+    }
+
+    #[test]
+    fn rejects_missing_ca_bundle() {
+        let path = Path::new("does-not-exist.pem");
+        assert!(matches!(
+            TlsClientConfig::new("localhost", path, path, path),
+            Err(TlsError::InvalidPeerCertificate(_))
+        ));
+    }
+}
@@ -0,0 +1,68 @@
+use std::path::Path;
+use std::sync::Arc;
+
+use rustls::crypto::CryptoProvider;
+use rustls::pki_types::pem::PemObject;
+use rustls::pki_types::{CertificateDer, PrivateKeyDer};
+
+pub(crate) mod client;
+pub(crate) mod server;
+
+pub use client::*;
+pub use server::*;
+
+/// Errors that can occur when building a TLS configuration
+#[derive(Clone, Debug, PartialEq)]
+pub enum TlsError {
+    /// The certificate(s) used to verify the peer could not be loaded
+    InvalidPeerCertificate(String),
+    /// The local certificate chain could not be loaded
+    InvalidLocalCertificate(String),
+    /// The private key could not be loaded
+    InvalidPrivateKey(String),
+    /// The name is not a valid DNS name or IP address
+    InvalidDnsName(String),
+    /// The certificates and key could not be combined into a valid configuration
+    BadConfig(String),
+}
+
+impl std::error::Error for TlsError {}
+
+impl std::fmt::Display for TlsError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> Result<(), std::fmt::Error> {
+        match self {
+            TlsError::InvalidPeerCertificate(err) => {
+                write!(f, "invalid peer certificate file: {}", err)
+            }
+            TlsError::InvalidLocalCertificate(err) => {
+                write!(f, "invalid local certificate file: {}", err)
+            }
+            TlsError::InvalidPrivateKey(err) => write!(f, "invalid private key file: {}", err),
+            TlsError::InvalidDnsName(name) => write!(f, "invalid DNS name: {}", name),
+            TlsError::BadConfig(err) => write!(f, "bad TLS configuration: {}", err),
+        }
+    }
+}
+
+// always use ring, so that enabling another provider elsewhere in the dependency tree
+// doesn't make the process-wide default ambiguous
+pub(crate) fn crypto_provider() -> Arc<CryptoProvider> {
+    Arc::new(rustls::crypto::ring::default_provider())
+}
+
+pub(crate) fn load_certs(path: &Path) -> Result<Vec<CertificateDer<'static>>, String> {
+    let certs = CertificateDer::pem_file_iter(path)
+        .map_err(|err| err.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|err| err.to_string())?;
+
+    if certs.is_empty() {
+        return Err(format!("no certificates found in {}", path.display()));
+    }
+
+    Ok(certs)
+}
+
+pub(crate) fn load_private_key(path: &Path) -> Result<PrivateKeyDer<'static>, String> {
+    PrivateKeyDer::from_pem_file(path).map_err(|err| err.to_string())
+}
@@ -0,0 +1,179 @@
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
+
+use rustls::server::WebPkiClientVerifier;
+use rustls::RootCertStore;
+use x509_parser::certificate::X509Certificate;
+use x509_parser::der_parser::asn1_rs::{FromDer, Oid, Utf8String};
+
+use crate::common::phys::PhysLayer;
+use crate::decode::PhysDecodeLevel;
+use crate::tcp::tls::{crypto_provider, load_certs, load_private_key, TlsError};
+use crate::tokio::net::TcpStream;
+
+// Modbus Role extension defined by the Modbus/TCP Security specification
+const MODBUS_ROLE_OID: &[u64] = &[1, 3, 6, 1, 4, 1, 50316, 802, 1];
+
+/// TLS configuration for a Modbus/TCP Security server
+///
+/// Clients must present a certificate issued by one of the trusted CAs that contains
+/// the Modbus Role extension, otherwise the connection is closed after the handshake.
+///
+/// Connections that don't complete the handshake within the handshake timeout are closed,
+/// see [`TlsServerConfig::DEFAULT_HANDSHAKE_TIMEOUT`].
+#[derive(Clone, Debug)]
+pub struct TlsServerConfig {
+    config: Arc<rustls::ServerConfig>,
+    handshake_timeout: Duration,
+}
+
+impl TlsServerConfig {
+    /// Time that a client is given to complete the TLS handshake by default
+    pub const DEFAULT_HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(10);
+
+    /// Create a TLS server configuration from PEM encoded files
+    ///
+    /// * `ca_bundle_path` - Path to the CA certificate(s) used to verify client certificates
+    /// * `local_cert_path` - Path to the certificate chain presented to clients
+    /// * `private_key_path` - Path to the private key of the local certificate (PKCS#8, PKCS#1, or SEC1)
+    pub fn new(
+        ca_bundle_path: &Path,
+        local_cert_path: &Path,
+        private_key_path: &Path,
+    ) -> Result<Self, TlsError> {
+        let mut roots = RootCertStore::empty();
+        for cert in load_certs(ca_bundle_path).map_err(TlsError::InvalidPeerCertificate)? {
+            roots
+                .add(cert)
+                .map_err(|err| TlsError::InvalidPeerCertificate(err.to_string()))?;
+        }
+
+        let chain = load_certs(local_cert_path).map_err(TlsError::InvalidLocalCertificate)?;
+        let key = load_private_key(private_key_path).map_err(TlsError::InvalidPrivateKey)?;
+
+        let verifier = WebPkiClientVerifier::builder_with_provider(roots.into(), crypto_provider())
+            .build()
+            .map_err(|err| TlsError::BadConfig(err.to_string()))?;
+
+        let config = rustls::ServerConfig::builder_with_provider(crypto_provider())
+            .with_safe_default_protocol_versions()
+            .map_err(|err| TlsError::BadConfig(err.to_string()))?
+            .with_client_cert_verifier(verifier)
+            .with_single_cert(chain, key)
+            .map_err(|err| TlsError::BadConfig(err.to_string()))?;
+
+        Ok(Self {
+            config: Arc::new(config),
+            handshake_timeout: Self::DEFAULT_HANDSHAKE_TIMEOUT,
+        })
+    }
+
+    /// Change the time that a client is given to complete the TLS handshake
+    pub fn with_handshake_timeout(mut self, timeout: Duration) -> Self {
+        self.handshake_timeout = timeout;
+        self
+    }
+
+    pub(crate) fn handshake_timeout(&self) -> Duration {
+        self.handshake_timeout
+    }
+
+    // perform the handshake and return the physical layer along with the role of the client
+    pub(crate) async fn handle_connection(
+        &self,
+        socket: TcpStream,
+        level: PhysDecodeLevel,
+    ) -> Result<(PhysLayer, String), String> {
+        let acceptor = tokio_rustls::TlsAcceptor::from(self.config.clone());
+        let stream = match acceptor.accept(socket).await {
+            Ok(stream) => stream,
+            Err(err) => return Err(format!("TLS handshake failed: {}", err)),
+        };
+
+        // the verifier requires a client certificate, so the chain is never empty
+        let role = match stream.get_ref().1.peer_certificates() {
+            Some([cert, ..]) => extract_modbus_role(cert)?,
+            _ => return Err("client did not present a certificate".to_string()),
+        };
+
+        Ok((
+            PhysLayer::new_tls(tokio_rustls::TlsStream::from(stream), level),
+            role,
+        ))
+    }
+}
+
+pub(crate) fn extract_modbus_role(cert: &[u8]) -> Result<String, String> {
+    let (_, cert) = X509Certificate::from_der(cert)
+        .map_err(|err| format!("unable to parse client certificate: {}", err))?;
+    let oid = Oid::from(MODBUS_ROLE_OID).map_err(|_| "invalid Modbus Role OID".to_string())?;
+
+    let mut extensions = cert.extensions().iter().filter(|ext| ext.oid == oid);
+    let extension = match (extensions.next(), extensions.next()) {
+        (Some(extension), None) => extension,
+        (None, _) => {
+            return Err("client certificate does not contain the Modbus Role extension".to_string())
+        }
+        (Some(_), Some(_)) => {
+            return Err(
+                "client certificate contains more than one Modbus Role extension".to_string(),
+            )
+        }
+    };
+
+    match Utf8String::from_der(extension.value) {
+        Ok((_, role)) => Ok(role.string()),
+        Err(err) => Err(format!(
+            "Modbus Role extension is not a UTF8String: {}",
+            err
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn certificate(extensions: Vec<rcgen::CustomExtension>) -> Vec<u8> {
+        let key = rcgen::KeyPair::generate().unwrap();
+        let mut params = rcgen::CertificateParams::new(vec!["client".to_string()]).unwrap();
+        params.custom_extensions = extensions;
+        params.self_signed(&key).unwrap().der().to_vec()
+    }
+
+    fn role_extension(content: &[u8]) -> rcgen::CustomExtension {
+        rcgen::CustomExtension::from_oid_content(MODBUS_ROLE_OID, content.to_vec())
+    }
+
+    #[test]
+    fn extracts_role_from_certificate() {
+        //                          | UTF8String | len |  o    p    e    r    a    t    o    r  |
+        let cert = certificate(vec![role_extension(&[
+            0x0C, 0x08, 0x6F, 0x70, 0x65, 0x72, 0x61, 0x74, 0x6F, 0x72,
+        ])]);
+        assert_eq!(extract_modbus_role(&cert), Ok("operator".to_string()));
+    }
+
+    #[test]
+    fn rejects_certificate_without_role() {
+        let cert = certificate(Vec::new());
+        assert!(extract_modbus_role(&cert).is_err());
+    }
+
+    #[test]
+    fn rejects_role_that_is_not_a_utf8_string() {
+        //                                      | INTEGER | len | value |
+        let cert = certificate(vec![role_extension(&[0x02, 0x01, 0x05])]);
+        assert!(extract_modbus_role(&cert).is_err());
+    }
+
+    #[test]
+    fn rejects_multiple_roles() {
+        let cert = certificate(vec![
+            role_extension(&[0x0C, 0x01, 0x61]),
+            role_extension(&[0x0C, 0x01, 0x62]),
+        ]);
+        assert!(extract_modbus_role(&cert).is_err());
+    }
+}
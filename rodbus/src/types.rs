@@ -60,6 +60,32 @@ pub struct Indexed<T> {
     pub value: T,
 }
 
+/// Masks applied to a single holding register by a `mask write register` request
+///
+/// The new value of the register is `(current AND and_mask) OR (or_mask AND NOT and_mask)`
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct MaskWriteRegister {
+    /// address of the register
+    pub address: u16,
+    /// bits set in this mask are kept from the current value
+    pub and_mask: u16,
+    /// bits cleared in the AND mask are taken from this mask
+    pub or_mask: u16,
+}
+
+/// One of the four tables of the Modbus data model
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Table {
+    /// single bit outputs, read with function code 0x01
+    Coils,
+    /// single bit inputs, read with function code 0x02
+    DiscreteInputs,
+    /// 16-bit outputs, read with function code 0x03
+    HoldingRegisters,
+    /// 16-bit inputs, read with function code 0x04
+    InputRegisters,
+}
+
 /// Zero-copy type used to iterate over a collection of bits
 #[derive(Debug, Copy, Clone)]
 pub struct BitIterator<'a> {
@@ -86,6 +112,17 @@ pub(crate) struct RegisterIteratorDisplay<'a, 'b> {
     level: PduDecodeLevel,
 }
 
+/// Values read from a FIFO queue
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct FifoQueue {
+    pub(crate) values: Vec<u16>,
+}
+
+pub(crate) struct FifoQueueDisplay<'a> {
+    queue: &'a FifoQueue,
+    level: PduDecodeLevel,
+}
+
 impl std::fmt::Display for UnitId {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "{:#04X}", self.value)
@@ -164,6 +201,32 @@ impl std::fmt::Display for RegisterIteratorDisplay<'_, '_> {
     }
 }
 
+impl FifoQueue {
+    pub(crate) fn new(values: Vec<u16>) -> Self {
+        Self { values }
+    }
+}
+
+impl<'a> FifoQueueDisplay<'a> {
+    pub(crate) fn new(level: PduDecodeLevel, queue: &'a FifoQueue) -> Self {
+        Self { queue, level }
+    }
+}
+
+impl std::fmt::Display for FifoQueueDisplay<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "count: {}", self.queue.values.len())?;
+
+        if self.level.data_values() {
+            for x in self.queue.values.iter() {
+                write!(f, "\nvalue: {:#06X}", x)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
 impl<'a> Iterator for BitIterator<'a> {
     type Item = Indexed<bool>;
 
@@ -276,6 +339,17 @@ impl AddressRange {
         AddressIterator::new(self.start, self.count)
     }
 
+    /// split into consecutive ranges that each contain at most `max` elements
+    pub(crate) fn chunks(self, max: u16) -> impl Iterator<Item = AddressRange> {
+        let end = self.start as u32 + self.count as u32;
+        (self.start as u32..end)
+            .step_by(max as usize)
+            .map(move |start| AddressRange {
+                start: start as u16,
+                count: (end - start).min(max as u32) as u16,
+            })
+    }
+
     pub(crate) fn of_read_bits(self) -> Result<ReadBitsRange, InvalidRange> {
         Ok(ReadBitsRange {
             inner: self.limited_count(crate::constants::limits::MAX_READ_COILS_COUNT)?,
@@ -288,7 +362,7 @@ impl AddressRange {
         })
     }
 
-    fn limited_count(self, limit: u16) -> Result<Self, InvalidRange> {
+    pub(crate) fn limited_count(self, limit: u16) -> Result<Self, InvalidRange> {
         if self.count > limit {
             return Err(InvalidRange::CountTooLargeForType(self.count, limit));
         }
@@ -296,6 +370,33 @@ impl AddressRange {
     }
 }
 
+impl Table {
+    /// true if the table contains single bit values
+    pub fn is_bits(self) -> bool {
+        matches!(self, Table::Coils | Table::DiscreteInputs)
+    }
+
+    /// the maximum count of values read by a single request
+    pub fn max_read_count(self) -> u16 {
+        if self.is_bits() {
+            crate::constants::limits::MAX_READ_COILS_COUNT
+        } else {
+            crate::constants::limits::MAX_READ_REGISTERS_COUNT
+        }
+    }
+}
+
+impl std::fmt::Display for Table {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Table::Coils => f.write_str("coils"),
+            Table::DiscreteInputs => f.write_str("discrete inputs"),
+            Table::HoldingRegisters => f.write_str("holding registers"),
+            Table::InputRegisters => f.write_str("input registers"),
+        }
+    }
+}
+
 impl std::fmt::Display for AddressRange {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "start: {:#06X} qty: {}", self.start, self.count)
@@ -348,6 +449,32 @@ impl std::fmt::Display for Indexed<u16> {
     }
 }
 
+impl MaskWriteRegister {
+    /// Create a new mask write register request
+    pub fn new(address: u16, and_mask: u16, or_mask: u16) -> Self {
+        Self {
+            address,
+            and_mask,
+            or_mask,
+        }
+    }
+
+    /// Apply the masks to the current value of the register
+    pub fn apply(self, current: u16) -> u16 {
+        (current & self.and_mask) | (self.or_mask & !self.and_mask)
+    }
+}
+
+impl std::fmt::Display for MaskWriteRegister {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "idx: {:#06X} and_mask: {:#06X} or_mask: {:#06X}",
+            self.address, self.and_mask, self.or_mask
+        )
+    }
+}
+
 impl UnitId {
     /// Create a new UnitId
     pub fn new(value: u8) -> Self {
@@ -358,6 +485,18 @@ impl UnitId {
     pub fn default() -> Self {
         Self { value: 0xFF }
     }
+
+    /// Create the UnitId of `0x00` used by broadcast requests on serial and RTU over TCP links
+    pub fn broadcast() -> Self {
+        Self {
+            value: crate::constants::broadcast::UNIT_ID,
+        }
+    }
+
+    /// Returns true if this is the broadcast UnitId of `0x00`
+    pub fn is_broadcast(&self) -> bool {
+        self.value == crate::constants::broadcast::UNIT_ID
+    }
 }
 
 #[cfg(test)]
@@ -389,6 +528,37 @@ mod tests {
         );
     }
 
+    #[test]
+    fn splits_range_into_chunks() {
+        let chunks: Vec<AddressRange> = AddressRange::try_from(10, 300)
+            .unwrap()
+            .chunks(125)
+            .collect();
+        assert_eq!(
+            chunks,
+            vec![
+                AddressRange::try_from(10, 125).unwrap(),
+                AddressRange::try_from(135, 125).unwrap(),
+                AddressRange::try_from(260, 50).unwrap(),
+            ]
+        );
+    }
+
+    #[test]
+    fn splits_range_that_ends_at_the_last_address() {
+        let chunks: Vec<AddressRange> = AddressRange::try_from(0, 0xFFFF)
+            .unwrap()
+            .chunks(2000)
+            .collect();
+        assert_eq!(chunks.len(), 33);
+        assert_eq!(chunks[32], AddressRange::try_from(64000, 1535).unwrap());
+        let chunks: Vec<AddressRange> = AddressRange::try_from(0xFFFF, 1)
+            .unwrap()
+            .chunks(125)
+            .collect();
+        assert_eq!(chunks, vec![AddressRange::try_from(0xFFFF, 1).unwrap()]);
+    }
+
     #[test]
     fn correctly_iterates_over_low_order_bits() {
         let mut cursor = ReadCursor::new(&[0x03]);
@@ -420,4 +590,11 @@ mod tests {
             vec![Indexed::new(1, 0xFFFF), Indexed::new(2, 0x01CC)]
         );
     }
+
+    #[test]
+    fn mask_write_register_applies_masks() {
+        // example from the Modbus application protocol specification
+        let request = MaskWriteRegister::new(4, 0x00F2, 0x0025);
+        assert_eq!(request.apply(0x0012), 0x0017);
+    }
 }
@@ -3,25 +3,29 @@ use std::str::FromStr;
 use std::time::Duration;
 
 use rodbus::client::*;
+use rodbus::error::{AduParseError, InvalidRequest};
 use rodbus::server::*;
 use rodbus::*;
 
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
 use tokio::runtime::Runtime;
 
 struct Handler {
-    pub coils: [bool; 10],
+    pub coils: [bool; 3000],
     pub discrete_inputs: [bool; 10],
-    pub holding_registers: [u16; 10],
+    pub holding_registers: [u16; 1000],
     pub input_registers: [u16; 10],
+    pub file_records: [u16; 10],
 }
 
 impl Handler {
     fn new() -> Self {
         Self {
-            coils: [false; 10],
+            coils: [false; 3000],
             discrete_inputs: [false; 10],
-            holding_registers: [0; 10],
+            holding_registers: [0; 1000],
             input_registers: [0; 10],
+            file_records: [0; 10],
         }
     }
 }
@@ -94,6 +98,57 @@ impl RequestHandler for Handler {
         }
         Ok(())
     }
+
+    fn write_for_read_write_multiple(
+        &mut self,
+        values: WriteRegisters,
+    ) -> Result<(), ExceptionCode> {
+        self.write_multiple_registers(values)
+    }
+
+    fn read_fifo_queue(&self, pointer_address: u16) -> Result<Vec<u16>, ExceptionCode> {
+        match pointer_address {
+            0x04DE => Ok(vec![0x01B8, 0x1284]),
+            // one more value than the specification allows
+            0x04DF => Ok(vec![0; 32]),
+            _ => Err(ExceptionCode::IllegalDataAddress),
+        }
+    }
+
+    fn read_file_record(&self, record: FileRecord) -> Result<Vec<u16>, ExceptionCode> {
+        let start = record.record_number as usize;
+        let end = start + record.record_length as usize;
+        match record.file_number {
+            4 => match self.file_records.get(start..end) {
+                Some(x) => Ok(x.to_vec()),
+                None => Err(ExceptionCode::IllegalDataAddress),
+            },
+            // a corrupted file that returns fewer records than requested
+            5 => Ok(vec![0; record.record_length as usize / 2]),
+            _ => Err(ExceptionCode::IllegalDataAddress),
+        }
+    }
+
+    fn write_file_record(&mut self, record: &FileRecordData) -> Result<(), ExceptionCode> {
+        let start = record.record_number as usize;
+        let end = start + record.values.len();
+        match (record.file_number, self.file_records.get_mut(start..end)) {
+            (4, Some(x)) => {
+                x.copy_from_slice(&record.values);
+                Ok(())
+            }
+            _ => Err(ExceptionCode::IllegalDataAddress),
+        }
+    }
+
+    fn custom_function(&mut self, function: u8, data: &[u8]) -> Result<Vec<u8>, ExceptionCode> {
+        match function {
+            0x41 => Ok(data.iter().rev().copied().collect()),
+            // one byte more than fits in a response
+            0x42 => Ok(vec![0; 253]),
+            _ => Err(ExceptionCode::IllegalFunction),
+        }
+    }
 }
 
 async fn test_requests_and_responses() {
@@ -171,6 +226,19 @@ async fn test_requests_and_responses() {
             .unwrap(),
         vec![Indexed::new(0, 0x0000), Indexed::new(1, 0xABCD)]
     );
+    assert_eq!(
+        channel
+            .read_file_record(
+                params,
+                vec![FileRecord::new(4, 0, 2), FileRecord::new(4, 5, 1)]
+            )
+            .await
+            .unwrap(),
+        vec![
+            FileRecordData::new(4, 0, vec![0, 0]),
+            FileRecordData::new(4, 5, vec![0]),
+        ]
+    );
 
     // write multiple coils and verify that they were written
     assert_eq!(
@@ -217,10 +285,1021 @@ async fn test_requests_and_responses() {
             Indexed::new(2, 0x0506)
         ]
     );
+
+    // write registers and read back an overlapping range in the same request
+    assert_eq!(
+        channel
+            .read_write_multiple_registers(
+                params,
+                ReadWriteMultiple::new(
+                    AddressRange::try_from(1, 3).unwrap(),
+                    2,
+                    vec![0xAAAA, 0xBBBB]
+                )
+                .unwrap()
+            )
+            .await
+            .unwrap(),
+        vec![
+            Indexed::new(1, 0x0304),
+            Indexed::new(2, 0xAAAA),
+            Indexed::new(3, 0xBBBB)
+        ]
+    );
+
+    // clear the low byte and set its lowest bit, keeping the high byte
+    assert_eq!(
+        channel
+            .mask_write_register(params, 2, 0xFF00, 0x0001)
+            .await
+            .unwrap(),
+        MaskWriteRegister::new(2, 0xFF00, 0x0001)
+    );
+    assert_eq!(
+        channel
+            .read_holding_registers(params, AddressRange::try_from(2, 1).unwrap())
+            .await
+            .unwrap(),
+        vec![Indexed::new(2, 0xAA01)]
+    );
+
+    assert_eq!(
+        channel.read_fifo_queue(params, 0x04DE).await.unwrap(),
+        vec![0x01B8, 0x1284]
+    );
+    assert_eq!(
+        channel.read_fifo_queue(params, 0x04DF).await.unwrap_err(),
+        RequestError::Exception(ExceptionCode::IllegalDataValue)
+    );
+
+    channel
+        .write_file_record(
+            params,
+            vec![
+                FileRecordData::new(4, 1, vec![0x06AF, 0x04BE]),
+                FileRecordData::new(4, 7, vec![0x100D]),
+            ],
+        )
+        .await
+        .unwrap();
+    assert_eq!(
+        channel
+            .read_file_record(
+                params,
+                vec![FileRecord::new(4, 0, 3), FileRecord::new(4, 7, 1)]
+            )
+            .await
+            .unwrap(),
+        vec![
+            FileRecordData::new(4, 0, vec![0x0000, 0x06AF, 0x04BE]),
+            FileRecordData::new(4, 7, vec![0x100D]),
+        ]
+    );
+    assert_eq!(
+        channel
+            .read_file_record(params, vec![FileRecord::new(5, 0, 4)])
+            .await
+            .unwrap_err(),
+        RequestError::Exception(ExceptionCode::MemoryParityError)
+    );
+    assert_eq!(
+        channel
+            .write_file_record(params, vec![FileRecordData::new(4, 9, vec![0, 0])])
+            .await
+            .unwrap_err(),
+        RequestError::Exception(ExceptionCode::IllegalDataAddress)
+    );
+
+    assert_eq!(
+        channel
+            .send_custom_function(params, 0x41, vec![0x01, 0x02, 0x03])
+            .await
+            .unwrap(),
+        vec![0x03, 0x02, 0x01]
+    );
+    assert_eq!(
+        channel
+            .send_custom_function(params, 0x42, Vec::new())
+            .await
+            .unwrap_err(),
+        RequestError::Exception(ExceptionCode::ServerDeviceFailure)
+    );
+    assert_eq!(
+        channel
+            .send_custom_function(params, 0x64, Vec::new())
+            .await
+            .unwrap_err(),
+        RequestError::Exception(ExceptionCode::IllegalFunction)
+    );
+    // standard function codes can be sent as raw requests too
+    assert_eq!(
+        channel
+            .send_custom_function(params, 0x03, vec![0x00, 0x02, 0x00, 0x01])
+            .await
+            .unwrap(),
+        vec![0x02, 0xAA, 0x01]
+    );
+    assert_eq!(
+        channel
+            .send_custom_function(params, 0x09, Vec::new())
+            .await
+            .unwrap_err(),
+        RequestError::BadRequest(error::InvalidRequest::UnsupportedFunctionCode(0x09))
+    );
+
+    // multi-register values
+    assert_eq!(
+        channel
+            .write_value(params, 100, 123.456f32, codec::ByteOrder::Cdab)
+            .await
+            .unwrap(),
+        AddressRange::try_from(100, 2).unwrap()
+    );
+    assert_eq!(
+        handler.lock().unwrap().holding_registers[100..102],
+        [0xE979, 0x42F6]
+    );
+    assert_eq!(
+        channel
+            .read_f32(params, 100, codec::ByteOrder::Cdab)
+            .await
+            .unwrap(),
+        123.456
+    );
+    assert_eq!(
+        channel
+            .read_u32(params, 100, codec::ByteOrder::Abcd)
+            .await
+            .unwrap(),
+        0xE979_42F6
+    );
+    channel
+        .write_value(params, 102, -2i64, codec::ByteOrder::Dcba)
+        .await
+        .unwrap();
+    assert_eq!(
+        channel
+            .read_i64(params, 102, codec::ByteOrder::Dcba)
+            .await
+            .unwrap(),
+        -2
+    );
+    let range = AddressRange::try_from(110, 4).unwrap();
+    channel
+        .write_string(params, range, "PUMP-1", codec::ByteOrder::Abcd)
+        .await
+        .unwrap();
+    assert_eq!(
+        channel
+            .read_string(params, range, codec::ByteOrder::Abcd)
+            .await
+            .unwrap(),
+        "PUMP-1"
+    );
+    assert_eq!(
+        channel
+            .write_string(params, range, "PUMP-STATION-1", codec::ByteOrder::Abcd)
+            .await
+            .unwrap_err(),
+        RequestError::BadRequest(error::InvalidRequest::BadValue(
+            error::CodecError::StringTooLong(14, 8)
+        ))
+    );
+
+    // several requests are in flight on the same connection at once
+    let mut pipelined = spawn_tcp_client_task_with_options(
+        addr,
+        10,
+        default_reconnect_strategy(),
+        ClientOptions::default().with_max_in_flight(4),
+        DecodeLevel::default(),
+    );
+    let mut other = pipelined.clone();
+    let (inputs, registers) = tokio::join!(
+        pipelined.read_discrete_inputs(params, AddressRange::try_from(0, 2).unwrap()),
+        other.read_holding_registers(params, AddressRange::try_from(2, 1).unwrap()),
+    );
+    assert_eq!(
+        inputs.unwrap(),
+        vec![Indexed::new(0, true), Indexed::new(1, false)]
+    );
+    assert_eq!(registers.unwrap(), vec![Indexed::new(2, 0xAA01)]);
 }
 
-#[test]
-fn can_read_and_write_values() {
-    let rt = Runtime::new().unwrap();
-    rt.block_on(test_requests_and_responses())
+async fn test_rtu_over_tcp() {
+    let handler = Handler::new().wrap();
+    let addr = SocketAddr::from_str("127.0.0.1:40001").unwrap();
+
+    let _server = spawn_rtu_over_tcp_server_task(
+        2,
+        addr,
+        ServerHandlerMap::single(UnitId::new(1), handler.clone()),
+        DecodeLevel::default(),
+    )
+    .await
+    .unwrap();
+
+    let mut channel = spawn_rtu_over_tcp_client_task(
+        addr,
+        10,
+        default_reconnect_strategy(),
+        DecodeLevel::default(),
+    );
+    let params = RequestParam::new(UnitId::new(0x01), Duration::from_secs(1));
+
+    assert_eq!(
+        channel
+            .write_single_register(params, Indexed::new(1, 0xABCD))
+            .await
+            .unwrap(),
+        Indexed::new(1, 0xABCD)
+    );
+    assert_eq!(
+        channel
+            .read_holding_registers(params, AddressRange::try_from(0, 2).unwrap())
+            .await
+            .unwrap(),
+        vec![Indexed::new(0, 0x0000), Indexed::new(1, 0xABCD)]
+    );
+
+    // the end of a response to a user-defined function can't be found without silence
+    assert_eq!(
+        channel.send_custom_function(params, 0x41, vec![0x01]).await,
+        Err(RequestError::BadRequest(
+            InvalidRequest::UnsupportedByChannel(0x41)
+        ))
+    );
+    // standard function codes have a known response length
+    assert_eq!(
+        channel
+            .send_custom_function(params, 0x03, vec![0x00, 0x01, 0x00, 0x01])
+            .await,
+        Ok(vec![0x02, 0xAB, 0xCD])
+    );
+
+    // the frames on the wire are plain RTU frames without an MBAP header
+    let mut socket = tokio::net::TcpStream::connect(addr).await.unwrap();
+    //                           | unit |  fc  |   start   |   count   |    crc    |
+    let request: &[u8] = &[0x01, 0x03, 0x00, 0x00, 0x00, 0x02, 0xC4, 0x0B];
+    socket.write_all(request).await.unwrap();
+
+    //                            | unit |  fc  | count |   value   |   value   |    crc    |
+    let expected: &[u8] = &[0x01, 0x03, 0x04, 0x00, 0x00, 0xAB, 0xCD, 0x44, 0x96];
+    let mut response = [0u8; 9];
+    socket.read_exact(&mut response).await.unwrap();
+    assert_eq!(&response, expected);
+}
+
+async fn test_read_device_identification() {
+    let handler = Handler::new().wrap();
+    let addr = SocketAddr::from_str("127.0.0.1:40002").unwrap();
+
+    // the extended objects don't fit in a single response
+    let identification = DeviceIdentification::new("ACME", "X-100", "V1.2")
+        .unwrap()
+        .with_object(constants::device_id::PRODUCT_NAME, b"widget")
+        .unwrap()
+        .with_object(0x80, &[0xAA; 200])
+        .unwrap()
+        .with_object(0x81, &[0xBB; 200])
+        .unwrap();
+
+    let mut map = ServerHandlerMap::single(UnitId::new(1), handler.clone());
+    map.set_device_identification(UnitId::new(1), identification);
+    map.add(UnitId::new(2), handler);
+
+    let _server = spawn_rtu_over_tcp_server_task(1, addr, map, DecodeLevel::default())
+        .await
+        .unwrap();
+
+    let mut channel = spawn_rtu_over_tcp_client_task(
+        addr,
+        10,
+        default_reconnect_strategy(),
+        DecodeLevel::default(),
+    );
+    let params = RequestParam::new(UnitId::new(0x01), Duration::from_secs(1));
+
+    let basic = channel
+        .read_device_identification(params, ReadDeviceIdCode::Basic, 0)
+        .await
+        .unwrap();
+    assert_eq!(basic.vendor_name(), Some("ACME"));
+    assert_eq!(basic.product_code(), Some("X-100"));
+    assert_eq!(basic.revision(), Some("V1.2"));
+    assert_eq!(basic.product_name(), None);
+    assert_eq!(basic.conformity_level(), Some(0x83));
+
+    let extended = channel
+        .read_device_identification(params, ReadDeviceIdCode::Extended, 0)
+        .await
+        .unwrap();
+    assert_eq!(extended.product_name(), Some("widget"));
+    assert_eq!(
+        extended.custom_objects().collect::<Vec<_>>(),
+        vec![(0x80, [0xAA; 200].as_ref()), (0x81, [0xBB; 200].as_ref())]
+    );
+
+    let individual = channel
+        .read_device_identification(params, ReadDeviceIdCode::Individual, 0x04)
+        .await
+        .unwrap();
+    assert_eq!(individual.product_name(), Some("widget"));
+    assert_eq!(individual.vendor_name(), None);
+
+    assert_eq!(
+        channel
+            .read_device_identification(params, ReadDeviceIdCode::Individual, 0x05)
+            .await
+            .unwrap_err(),
+        RequestError::Exception(ExceptionCode::IllegalDataAddress)
+    );
+
+    // unit ids without identification objects don't support the function
+    assert_eq!(
+        channel
+            .read_device_identification(
+                RequestParam::new(UnitId::new(0x02), Duration::from_secs(1)),
+                ReadDeviceIdCode::Basic,
+                0
+            )
+            .await
+            .unwrap_err(),
+        RequestError::Exception(ExceptionCode::IllegalFunction)
+    );
+}
+
+async fn test_chunked_requests() {
+    let handler = Handler::new().wrap();
+    let addr = SocketAddr::from_str("127.0.0.1:40003").unwrap();
+
+    let _server = spawn_tcp_server_task(
+        1,
+        addr,
+        ServerHandlerMap::single(UnitId::new(1), handler.clone()),
+        DecodeLevel::default(),
+    )
+    .await
+    .unwrap();
+
+    let mut channel = spawn_tcp_client_task_with_options(
+        addr,
+        10,
+        default_reconnect_strategy(),
+        ClientOptions::default().with_max_in_flight(4),
+        DecodeLevel::default(),
+    );
+    let params = RequestParam::new(UnitId::new(0x01), Duration::from_secs(1));
+
+    let registers: Vec<u16> = (0..1000).collect();
+    assert_eq!(
+        channel
+            .write_multiple_registers_chunked(
+                params,
+                WriteMultiple::from(0, registers.clone()).unwrap()
+            )
+            .await
+            .unwrap(),
+        AddressRange::try_from(0, 1000).unwrap()
+    );
+    assert_eq!(
+        channel
+            .read_holding_registers_chunked(params, AddressRange::try_from(0, 1000).unwrap())
+            .await
+            .unwrap(),
+        registers
+            .iter()
+            .enumerate()
+            .map(|(i, x)| Indexed::new(i as u16, *x))
+            .collect::<Vec<_>>()
+    );
+
+    let coils: Vec<bool> = (0..3000).map(|x| x % 3 == 0).collect();
+    assert_eq!(
+        channel
+            .write_multiple_coils_chunked(params, WriteMultiple::from(0, coils.clone()).unwrap())
+            .await
+            .unwrap(),
+        AddressRange::try_from(0, 3000).unwrap()
+    );
+    assert_eq!(
+        channel
+            .read_coils_chunked(params, AddressRange::try_from(0, 3000).unwrap())
+            .await
+            .unwrap(),
+        coils
+            .iter()
+            .enumerate()
+            .map(|(i, x)| Indexed::new(i as u16, *x))
+            .collect::<Vec<_>>()
+    );
+
+    // scattered values are read with as few requests as possible
+    let mut batch = ReadBatch::new(BatchOptions::new(10));
+    batch.add_address(Table::HoldingRegisters, 5);
+    batch.add_address(Table::HoldingRegisters, 7);
+    batch.add(
+        Table::HoldingRegisters,
+        AddressRange::try_from(500, 3).unwrap(),
+    );
+    batch.add_address(Table::HoldingRegisters, 999);
+    batch.add_address(Table::Coils, 0);
+    batch.add_address(Table::Coils, 2997);
+    assert_eq!(batch.plan().len(), 5);
+    let values = channel.read_batch(params, &batch).await.unwrap();
+    assert_eq!(
+        values.holding_registers.into_iter().collect::<Vec<_>>(),
+        vec![
+            (5, 5),
+            (7, 7),
+            (500, 500),
+            (501, 501),
+            (502, 502),
+            (999, 999)
+        ]
+    );
+    assert_eq!(
+        values.coils.into_iter().collect::<Vec<_>>(),
+        vec![(0, true), (2997, true)]
+    );
+
+    batch.add_address(Table::InputRegisters, 20);
+    assert_eq!(
+        channel.read_batch(params, &batch).await.unwrap_err(),
+        error::BatchError {
+            table: Table::InputRegisters,
+            range: AddressRange::try_from(20, 1).unwrap(),
+            error: RequestError::Exception(ExceptionCode::IllegalDataAddress),
+        }
+    );
+
+    // the second of three requests is the first to go past the end of the registers
+    assert_eq!(
+        channel
+            .read_holding_registers_chunked(params, AddressRange::try_from(800, 300).unwrap())
+            .await
+            .unwrap_err(),
+        error::ChunkError {
+            range: AddressRange::try_from(925, 125).unwrap(),
+            error: RequestError::Exception(ExceptionCode::IllegalDataAddress),
+        }
+    );
+
+    // the writes stop at the first request that fails
+    assert_eq!(
+        channel
+            .write_multiple_registers_chunked(
+                params,
+                WriteMultiple::from(800, vec![0xFFFF; 300]).unwrap()
+            )
+            .await
+            .unwrap_err(),
+        error::ChunkError {
+            range: AddressRange::try_from(923, 123).unwrap(),
+            error: RequestError::Exception(ExceptionCode::IllegalDataAddress),
+        }
+    );
+    assert_eq!(handler.lock().unwrap().holding_registers[922], 0xFFFF);
+}
+
+async fn test_poller() {
+    let handler = Handler::new().wrap();
+    let addr = SocketAddr::from_str("127.0.0.1:40004").unwrap();
+
+    let _server = spawn_tcp_server_task(
+        1,
+        addr,
+        ServerHandlerMap::single(UnitId::new(1), handler.clone()),
+        DecodeLevel::default(),
+    )
+    .await
+    .unwrap();
+
+    let channel = spawn_tcp_client_task(
+        addr,
+        10,
+        default_reconnect_strategy(),
+        DecodeLevel::default(),
+    );
+    let params = RequestParam::new(UnitId::new(0x01), Duration::from_secs(1));
+
+    handler.lock().unwrap().holding_registers[1] = 0x1234;
+
+    let mut poller = Poller::new(channel, Duration::from_millis(5), 16);
+    let id = poller.add(PollDefinition::new(
+        params,
+        Table::HoldingRegisters,
+        AddressRange::try_from(0, 3).unwrap(),
+        Duration::from_millis(20),
+    ));
+    let mut updates = poller.subscribe();
+    tokio::spawn(poller.run());
+
+    // the first read publishes all of the values
+    let update = tokio::time::timeout(Duration::from_secs(5), updates.recv())
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(update.poll, id);
+    assert_eq!(update.unit_id, UnitId::new(1));
+    assert_eq!(update.table, Table::HoldingRegisters);
+    assert_eq!(
+        update.result,
+        Ok(PollValues::Registers(vec![
+            Indexed::new(0, 0x0000),
+            Indexed::new(1, 0x1234),
+            Indexed::new(2, 0x0000)
+        ]))
+    );
+
+    // only the values that change are published after that
+    handler.lock().unwrap().holding_registers[2] = 0xCAFE;
+    let update = tokio::time::timeout(Duration::from_secs(5), updates.recv())
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(
+        update.result,
+        Ok(PollValues::Registers(vec![Indexed::new(2, 0xCAFE)]))
+    );
+}
+
+async fn wait_for_state(
+    state: &mut tokio::sync::watch::Receiver<ChannelState>,
+    expected: ChannelState,
+) {
+    tokio::time::timeout(Duration::from_secs(5), async {
+        while *state.borrow() != expected {
+            state.changed().await.unwrap();
+        }
+    })
+    .await
+    .unwrap();
+}
+
+async fn test_channel_state() {
+    let handler = Handler::new().wrap();
+    let addr = SocketAddr::from_str("127.0.0.1:40006").unwrap();
+
+    let (channel, task) = create_handle_and_task(
+        addr,
+        10,
+        doubling_reconnect_strategy(Duration::from_millis(50), Duration::from_millis(50)),
+        DecodeLevel::default(),
+    );
+    let mut state = channel.state();
+    assert_eq!(*state.borrow(), ChannelState::Disabled);
+    tokio::spawn(task);
+
+    // nothing is listening yet
+    wait_for_state(
+        &mut state,
+        ChannelState::WaitingToRetry(Duration::from_millis(50)),
+    )
+    .await;
+
+    let _server = spawn_tcp_server_task(
+        1,
+        addr,
+        ServerHandlerMap::single(UnitId::new(1), handler),
+        DecodeLevel::default(),
+    )
+    .await
+    .unwrap();
+    wait_for_state(&mut state, ChannelState::Connected).await;
+
+    // dropping the only handle shuts down the task
+    drop(channel);
+    wait_for_state(&mut state, ChannelState::Disabled).await;
+}
+
+async fn test_enable_disable_and_endpoint() {
+    let first = Handler::new().wrap();
+    let second = Handler::new().wrap();
+    first.lock().unwrap().input_registers[0] = 1;
+    second.lock().unwrap().input_registers[0] = 2;
+
+    let first_addr = SocketAddr::from_str("127.0.0.1:40007").unwrap();
+    let second_addr = SocketAddr::from_str("127.0.0.1:40008").unwrap();
+    let unused_addr = SocketAddr::from_str("127.0.0.1:40009").unwrap();
+
+    let _first_server = spawn_tcp_server_task(
+        1,
+        first_addr,
+        ServerHandlerMap::single(UnitId::new(1), first),
+        DecodeLevel::default(),
+    )
+    .await
+    .unwrap();
+    let _second_server = spawn_tcp_server_task(
+        1,
+        second_addr,
+        ServerHandlerMap::single(UnitId::new(1), second),
+        DecodeLevel::default(),
+    )
+    .await
+    .unwrap();
+
+    let mut channel = spawn_tcp_client_task(
+        first_addr,
+        10,
+        default_reconnect_strategy(),
+        DecodeLevel::default(),
+    );
+    let mut state = channel.state();
+    let params = RequestParam::new(UnitId::new(0x01), Duration::from_secs(1));
+    let range = AddressRange::try_from(0, 1).unwrap();
+
+    wait_for_state(&mut state, ChannelState::Connected).await;
+    assert_eq!(
+        channel.read_input_registers(params, range).await.unwrap(),
+        vec![Indexed::new(0, 1)]
+    );
+
+    // a disabled channel closes its connection and fails requests immediately
+    channel.disable().await.unwrap();
+    wait_for_state(&mut state, ChannelState::Disabled).await;
+    assert_eq!(
+        channel.read_input_registers(params, range).await,
+        Err(RequestError::NoConnection)
+    );
+
+    // changing the endpoint while disabled takes effect once enabled
+    channel.set_endpoint(second_addr).await.unwrap();
+    channel.enable().await.unwrap();
+    wait_for_state(&mut state, ChannelState::Connected).await;
+    assert_eq!(
+        channel.read_input_registers(params, range).await.unwrap(),
+        vec![Indexed::new(0, 2)]
+    );
+
+    // the new strategy is used when the next connection attempt fails
+    channel
+        .set_reconnect_strategy(doubling_reconnect_strategy(
+            Duration::from_millis(25),
+            Duration::from_millis(25),
+        ))
+        .await
+        .unwrap();
+    channel.set_endpoint(unused_addr).await.unwrap();
+    wait_for_state(
+        &mut state,
+        ChannelState::WaitingToRetry(Duration::from_millis(25)),
+    )
+    .await;
+}
+
+async fn wait_for_endpoint(
+    endpoint: &mut tokio::sync::watch::Receiver<Option<Endpoint>>,
+    expected: Option<Endpoint>,
+) {
+    tokio::time::timeout(Duration::from_secs(5), async {
+        while *endpoint.borrow() != expected {
+            endpoint.changed().await.unwrap();
+        }
+    })
+    .await
+    .unwrap();
+}
+
+async fn test_endpoint_failover() {
+    let primary = SocketAddr::from_str("127.0.0.1:40010").unwrap();
+    let backup = SocketAddr::from_str("127.0.0.1:40011").unwrap();
+
+    let _backup_server = spawn_tcp_server_task(
+        1,
+        backup,
+        ServerHandlerMap::single(UnitId::new(1), Handler::new().wrap()),
+        DecodeLevel::default(),
+    )
+    .await
+    .unwrap();
+
+    let channel = spawn_tcp_client_task_with_endpoints(
+        EndpointList::new(primary)
+            .with_backup(backup)
+            .with_fail_back(Duration::from_millis(200)),
+        10,
+        doubling_reconnect_strategy(Duration::from_millis(25), Duration::from_millis(25)),
+        ClientOptions::default(),
+        DecodeLevel::default(),
+    );
+    let mut endpoint = channel.active_endpoint();
+
+    // the primary isn't listening, so the channel moves on to the backup
+    wait_for_endpoint(&mut endpoint, Some(Endpoint::Addr(backup))).await;
+
+    // once the primary is available, the channel fails back to it
+    let _primary_server = spawn_tcp_server_task(
+        1,
+        primary,
+        ServerHandlerMap::single(UnitId::new(1), Handler::new().wrap()),
+        DecodeLevel::default(),
+    )
+    .await
+    .unwrap();
+    wait_for_endpoint(&mut endpoint, Some(Endpoint::Addr(primary))).await;
+}
+
+async fn test_host_name_and_local_address() {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:40012")
+        .await
+        .unwrap();
+    let local = SocketAddr::from_str("127.0.0.1:40013").unwrap();
+
+    let _channel = spawn_tcp_client_task_with_endpoints(
+        EndpointList::new("localhost:40012").with_local_address(local),
+        10,
+        default_reconnect_strategy(),
+        ClientOptions::default(),
+        DecodeLevel::default(),
+    );
+
+    let (_socket, peer) = tokio::time::timeout(Duration::from_secs(5), listener.accept())
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(peer, local);
+}
+
+struct BusyHandler {
+    busy_responses: std::sync::atomic::AtomicUsize,
+}
+
+impl RequestHandler for BusyHandler {
+    fn read_holding_register(&self, _address: u16) -> Result<u16, ExceptionCode> {
+        let busy = self
+            .busy_responses
+            .load(std::sync::atomic::Ordering::SeqCst);
+        if busy > 0 {
+            self.busy_responses
+                .store(busy - 1, std::sync::atomic::Ordering::SeqCst);
+            return Err(ExceptionCode::ServerDeviceBusy);
+        }
+        Ok(0x1234)
+    }
+}
+
+async fn test_retry_policy() {
+    let handler = BusyHandler {
+        busy_responses: std::sync::atomic::AtomicUsize::new(2),
+    }
+    .wrap();
+    let addr = SocketAddr::from_str("127.0.0.1:40014").unwrap();
+
+    let _server = spawn_tcp_server_task(
+        1,
+        addr,
+        ServerHandlerMap::single(UnitId::new(1), handler),
+        DecodeLevel::default(),
+    )
+    .await
+    .unwrap();
+
+    let mut channel = spawn_tcp_client_task(
+        addr,
+        10,
+        default_reconnect_strategy(),
+        DecodeLevel::default(),
+    );
+    let params = RequestParam::new(UnitId::new(0x01), Duration::from_secs(1));
+    let range = AddressRange::try_from(0, 1).unwrap();
+
+    // the channel doesn't retry by default
+    assert_eq!(
+        channel.read_holding_registers(params, range).await,
+        Err(RequestError::Exception(ExceptionCode::ServerDeviceBusy))
+    );
+
+    // the policy of the request allows the remaining busy response to be retried
+    let policy = RetryPolicy::new(3, Duration::from_millis(10), Duration::from_millis(50));
+    assert_eq!(
+        channel
+            .read_holding_registers(params.with_retry_policy(policy), range)
+            .await
+            .unwrap(),
+        vec![Indexed::new(0, 0x1234)]
+    );
+}
+
+async fn test_broadcast() {
+    let first = Handler::new().wrap();
+    let second = Handler::new().wrap();
+    let addr = SocketAddr::from_str("127.0.0.1:40015").unwrap();
+
+    let mut map = ServerHandlerMap::single(UnitId::new(1), first);
+    map.add(UnitId::new(2), second);
+
+    let _server = spawn_rtu_over_tcp_server_task(1, addr, map, DecodeLevel::default())
+        .await
+        .unwrap();
+
+    let mut channel = spawn_rtu_over_tcp_client_task(
+        addr,
+        10,
+        default_reconnect_strategy(),
+        DecodeLevel::default(),
+    );
+    channel
+        .set_turnaround_delay(Duration::from_millis(20))
+        .await
+        .unwrap();
+    let broadcast = RequestParam::new(UnitId::broadcast(), Duration::from_millis(100));
+
+    // the write completes without a response
+    assert_eq!(
+        channel
+            .write_single_register(broadcast, Indexed::new(1, 0xABCD))
+            .await
+            .unwrap(),
+        Indexed::new(1, 0xABCD)
+    );
+    assert_eq!(
+        channel
+            .write_multiple_registers(
+                broadcast,
+                WriteMultiple::from(2, vec![0x0102, 0x0304]).unwrap()
+            )
+            .await
+            .unwrap(),
+        AddressRange::try_from(2, 2).unwrap()
+    );
+
+    // every server performed the writes
+    for unit in [1, 2].iter() {
+        let params = RequestParam::new(UnitId::new(*unit), Duration::from_secs(1));
+        assert_eq!(
+            channel
+                .read_holding_registers(params, AddressRange::try_from(1, 3).unwrap())
+                .await
+                .unwrap(),
+            vec![
+                Indexed::new(1, 0xABCD),
+                Indexed::new(2, 0x0102),
+                Indexed::new(3, 0x0304)
+            ]
+        );
+    }
+
+    // reads can't be broadcast, so they are never answered
+    assert_eq!(
+        channel
+            .read_holding_registers(broadcast, AddressRange::try_from(1, 1).unwrap())
+            .await,
+        Err(RequestError::ResponseTimeout)
+    );
+}
+
+async fn test_device_identification_without_progress() {
+    let addr = SocketAddr::from_str("127.0.0.1:40016").unwrap();
+    let listener = tokio::net::TcpListener::bind(addr).await.unwrap();
+
+    // a server that always returns object 0x00 and continues from object 0x01
+    tokio::spawn(async move {
+        let (mut socket, _) = listener.accept().await.unwrap();
+        let mut request = [0u8; 11];
+        while socket.read_exact(&mut request).await.is_ok() {
+            //                           |    tx id     |  proto  |   len   | unit |  fc  | mei  | code | conf | more | next | num  |  id  | len  | value |
+            let response: &[u8] = &[
+                request[0], request[1], 0x00, 0x00, 0x00, 0x0B, 0x01, 0x2B, 0x0E, 0x01, 0x81, 0xFF,
+                0x01, 0x01, 0x00, 0x01, b'A',
+            ];
+            if socket.write_all(response).await.is_err() {
+                return;
+            }
+        }
+    });
+
+    let mut channel = spawn_tcp_client_task(
+        addr,
+        10,
+        default_reconnect_strategy(),
+        DecodeLevel::default(),
+    );
+    let params = RequestParam::new(UnitId::new(0x01), Duration::from_secs(1));
+
+    assert_eq!(
+        channel
+            .read_device_identification(params, ReadDeviceIdCode::Basic, 0)
+            .await,
+        Err(RequestError::BadResponse(
+            AduParseError::DeviceIdNoProgress(0x01)
+        ))
+    );
+}
+
+async fn test_rtu_over_tcp_server_rejects_user_defined_function() {
+    let handler = Handler::new().wrap();
+    let addr = SocketAddr::from_str("127.0.0.1:40018").unwrap();
+
+    let _server = spawn_rtu_over_tcp_server_task(
+        1,
+        addr,
+        ServerHandlerMap::single(UnitId::new(1), handler.clone()),
+        DecodeLevel::default(),
+    )
+    .await
+    .unwrap();
+
+    let mut socket = tokio::net::TcpStream::connect(addr).await.unwrap();
+
+    // the end of a request to a user-defined function can't be found without silence
+    //                           | unit |  fc  |   data    |    crc    |
+    let request: &[u8] = &[0x01, 0x41, 0x01, 0x02, 0xD1, 0x9D];
+    socket.write_all(request).await.unwrap();
+
+    //                            | unit |  fc  |  ex  |    crc    |
+    let expected: &[u8] = &[0x01, 0xC1, 0x01, 0xB0, 0x50];
+    let mut response = [0u8; 5];
+    socket.read_exact(&mut response).await.unwrap();
+    assert_eq!(&response, expected);
+
+    // the session is still open
+    //                           | unit |  fc  |   start   |   count   |    crc    |
+    let request: &[u8] = &[0x01, 0x03, 0x00, 0x00, 0x00, 0x02, 0xC4, 0x0B];
+    socket.write_all(request).await.unwrap();
+
+    //                            | unit |  fc  | count |   value   |   value   |    crc    |
+    let expected: &[u8] = &[0x01, 0x03, 0x04, 0x00, 0x00, 0x00, 0x00, 0xFA, 0x33];
+    let mut response = [0u8; 9];
+    socket.read_exact(&mut response).await.unwrap();
+    assert_eq!(&response, expected);
+}
+
+#[test]
+fn can_read_and_write_values() {
+    let rt = Runtime::new().unwrap();
+    rt.block_on(test_requests_and_responses())
+}
+
+#[test]
+fn can_read_and_write_values_using_rtu_over_tcp() {
+    let rt = Runtime::new().unwrap();
+    rt.block_on(test_rtu_over_tcp())
+}
+
+#[test]
+fn rtu_over_tcp_server_rejects_user_defined_functions_without_closing_the_session() {
+    let rt = Runtime::new().unwrap();
+    rt.block_on(test_rtu_over_tcp_server_rejects_user_defined_function())
+}
+
+#[test]
+fn can_read_device_identification() {
+    let rt = Runtime::new().unwrap();
+    rt.block_on(test_read_device_identification())
+}
+
+#[test]
+fn can_split_large_reads_and_writes() {
+    let rt = Runtime::new().unwrap();
+    rt.block_on(test_chunked_requests())
+}
+
+#[test]
+fn poller_publishes_changed_values() {
+    let rt = Runtime::new().unwrap();
+    rt.block_on(test_poller())
+}
+
+#[test]
+fn channel_reports_connection_state() {
+    let rt = Runtime::new().unwrap();
+    rt.block_on(test_channel_state())
+}
+
+#[test]
+fn channel_can_be_disabled_and_moved_to_another_endpoint() {
+    let rt = Runtime::new().unwrap();
+    rt.block_on(test_enable_disable_and_endpoint())
+}
+
+#[test]
+fn channel_fails_over_to_backup_endpoint() {
+    let rt = Runtime::new().unwrap();
+    rt.block_on(test_endpoint_failover())
+}
+
+#[test]
+fn channel_resolves_host_name_and_binds_local_address() {
+    let rt = Runtime::new().unwrap();
+    rt.block_on(test_host_name_and_local_address())
+}
+
+#[test]
+fn requests_are_retried_according_to_the_retry_policy() {
+    let rt = Runtime::new().unwrap();
+    rt.block_on(test_retry_policy())
+}
+
+#[test]
+fn broadcast_writes_are_performed_by_every_server_without_a_response() {
+    let rt = Runtime::new().unwrap();
+    rt.block_on(test_broadcast())
+}
+
+#[test]
+fn device_identification_fails_when_the_server_does_not_make_progress() {
+    let rt = Runtime::new().unwrap();
+    rt.block_on(test_device_identification_without_progress())
 }
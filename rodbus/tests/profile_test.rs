@@ -0,0 +1,178 @@
+use std::net::SocketAddr;
+use std::str::FromStr;
+use std::time::Duration;
+
+use rodbus::client::*;
+use rodbus::error::{CodecError, InvalidRequest};
+use rodbus::profile::{DeviceProfile, ProfileHandler, TagValue};
+use rodbus::server::*;
+use rodbus::*;
+
+use tokio::runtime::Runtime;
+
+async fn test_device_profile() {
+    let profile = DeviceProfile::from_toml(
+        r#"
+[[tag]]
+name = "flow"
+table = "input_registers"
+address = 0
+type = "f32"
+order = "cdab"
+units = "m3/h"
+
+[[tag]]
+name = "setpoint"
+table = "holding_registers"
+address = 10
+type = "u16"
+scale = 0.1
+access = "read_write"
+
+[[tag]]
+name = "alarm"
+table = "holding_registers"
+address = 11
+type = "bool"
+bit = 3
+access = "read_write"
+
+[[tag]]
+name = "running"
+table = "coils"
+address = 5
+type = "bool"
+access = "read_write"
+"#,
+    )
+    .unwrap();
+
+    let handler = ProfileHandler::new(profile.clone()).wrap();
+    handler
+        .lock()
+        .unwrap()
+        .set_tag("flow", &TagValue::Number(12.5))
+        .unwrap();
+    let addr = SocketAddr::from_str("127.0.0.1:40005").unwrap();
+
+    let _server = spawn_tcp_server_task(
+        1,
+        addr,
+        ServerHandlerMap::single(UnitId::new(1), handler.clone()),
+        DecodeLevel::default(),
+    )
+    .await
+    .unwrap();
+
+    let mut channel = spawn_tcp_client_task(
+        addr,
+        10,
+        default_reconnect_strategy(),
+        DecodeLevel::default(),
+    );
+    let params = RequestParam::new(UnitId::new(0x01), Duration::from_secs(1));
+
+    assert_eq!(
+        channel.read_tag(params, &profile, "flow").await,
+        Ok(TagValue::Number(12.5))
+    );
+
+    channel
+        .write_tag(params, &profile, "setpoint", &TagValue::Number(42.0))
+        .await
+        .unwrap();
+    channel
+        .write_tag(params, &profile, "alarm", &TagValue::Bool(true))
+        .await
+        .unwrap();
+    channel
+        .write_tag(params, &profile, "running", &TagValue::Bool(true))
+        .await
+        .unwrap();
+    assert_eq!(
+        handler.lock().unwrap().get_tag("alarm"),
+        Some(TagValue::Bool(true))
+    );
+    assert_eq!(
+        channel
+            .write_tag(params, &profile, "flow", &TagValue::Number(1.0))
+            .await,
+        Err(RequestError::BadRequest(InvalidRequest::TagNotWritable))
+    );
+
+    let values = channel
+        .read_tags(params, &profile, BatchOptions::new(0))
+        .await
+        .unwrap();
+    assert_eq!(values.len(), 4);
+    assert_eq!(values["flow"], TagValue::Number(12.5));
+    assert_eq!(values["setpoint"], TagValue::Number(42.0));
+    assert_eq!(values["alarm"], TagValue::Bool(true));
+    assert_eq!(values["running"], TagValue::Bool(true));
+}
+
+async fn test_tag_value_that_cannot_be_decoded() {
+    let server_profile = DeviceProfile::from_toml(
+        r#"
+[[tag]]
+name = "counter"
+table = "holding_registers"
+address = 20
+type = "u16"
+"#,
+    )
+    .unwrap();
+    let client_profile = DeviceProfile::from_toml(
+        r#"
+[[tag]]
+name = "counter"
+table = "holding_registers"
+address = 20
+type = "bcd"
+count = 1
+"#,
+    )
+    .unwrap();
+
+    let handler = ProfileHandler::new(server_profile).wrap();
+    handler
+        .lock()
+        .unwrap()
+        .set_tag("counter", &TagValue::Number(0xA4 as f64))
+        .unwrap();
+    let addr = SocketAddr::from_str("127.0.0.1:40017").unwrap();
+
+    let _server = spawn_tcp_server_task(
+        1,
+        addr,
+        ServerHandlerMap::single(UnitId::new(1), handler),
+        DecodeLevel::default(),
+    )
+    .await
+    .unwrap();
+
+    let mut channel = spawn_tcp_client_task(
+        addr,
+        10,
+        default_reconnect_strategy(),
+        DecodeLevel::default(),
+    );
+    let params = RequestParam::new(UnitId::new(0x01), Duration::from_secs(1));
+
+    assert_eq!(
+        channel.read_tag(params, &client_profile, "counter").await,
+        Err(RequestError::BadTagValue(CodecError::InvalidBcdDigit(0xA4)))
+    );
+}
+
+#[test]
+fn device_profile_drives_reads_and_writes() {
+    let rt = Runtime::new().unwrap();
+    rt.block_on(test_device_profile())
+}
+
+#[test]
+fn tag_value_that_cannot_be_decoded_is_reported() {
+    let rt = Runtime::new().unwrap();
+    rt.block_on(test_tag_value_that_cannot_be_decoded())
+}
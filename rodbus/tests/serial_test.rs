@@ -0,0 +1,431 @@
+#![cfg(unix)]
+
+use std::net::SocketAddr;
+use std::str::FromStr;
+use std::time::Duration;
+
+use rodbus::client::*;
+use rodbus::error::InvalidRequest;
+use rodbus::server::*;
+use rodbus::*;
+
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::runtime::Runtime;
+use tokio_serial::{SerialPort, SerialStream};
+
+//                              | unit |  fc  |   start   |   count   |    crc    |
+const READ_REGISTERS_REQUEST: &[u8] = &[0x01, 0x03, 0x00, 0x00, 0x00, 0x02, 0xC4, 0x0B];
+
+//                                      | unit |  fc  | count |   value   |   value   |    crc    |
+const READ_REGISTERS_RESPONSE: &[u8] = &[0x01, 0x03, 0x04, 0xCA, 0xFE, 0x00, 0x01, 0x65, 0xDB];
+
+// same response, but from another device on the bus
+const OTHER_UNIT_RESPONSE: &[u8] = &[0x02, 0x03, 0x04, 0x00, 0x00, 0x00, 0x00, 0xC9, 0x33];
+
+// return bus communication error count
+//                                  | unit |  fc  | sub-func  |   data    |    crc    |
+const BUS_ERROR_COUNT_REQUEST: &[u8] = &[0x01, 0x08, 0x00, 0x0C, 0x00, 0x00, 0x20, 0x08];
+
+//                                   | unit |  fc  | sub-func  |   count   |    crc    |
+const BUS_ERROR_COUNT_RESPONSE: &[u8] = &[0x01, 0x08, 0x00, 0x0C, 0x00, 0x01, 0xE1, 0xC8];
+
+struct Handler {
+    pub holding_registers: [u16; 10],
+}
+
+impl RequestHandler for Handler {
+    fn read_exception_status(&self) -> Result<u8, ExceptionCode> {
+        Ok(0x6D)
+    }
+
+    fn report_server_id(&self) -> Result<Vec<u8>, ExceptionCode> {
+        Ok(vec![0x2A, 0xFF])
+    }
+
+    fn read_holding_register(&self, address: u16) -> Result<u16, ExceptionCode> {
+        match self.holding_registers.get(address as usize) {
+            Some(x) => Ok(*x),
+            None => Err(ExceptionCode::IllegalDataAddress),
+        }
+    }
+
+    fn write_multiple_registers(&mut self, values: WriteRegisters) -> Result<(), ExceptionCode> {
+        for x in values.iterator {
+            match self.holding_registers.get_mut(x.index as usize) {
+                Some(c) => *c = x.value,
+                None => return Err(ExceptionCode::IllegalDataAddress),
+            }
+        }
+        Ok(())
+    }
+}
+
+// returns the master side of a pseudo-terminal and the path of the slave side
+fn pseudo_terminal() -> (SerialStream, String) {
+    let (master, slave) = SerialStream::pair().unwrap();
+    let path = slave.name().unwrap();
+    // release the lock on the slave so that it can be opened by path
+    drop(slave);
+    (master, path)
+}
+
+// connects two pseudo-terminals back to back and returns the paths of both ends
+fn null_modem() -> (String, String) {
+    let (a, path_a) = pseudo_terminal();
+    let (b, path_b) = pseudo_terminal();
+    tokio::spawn(async move {
+        let (mut read_a, mut write_a) = tokio::io::split(a);
+        let (mut read_b, mut write_b) = tokio::io::split(b);
+        tokio::select! {
+            _ = tokio::io::copy(&mut read_a, &mut write_b) => {}
+            _ = tokio::io::copy(&mut read_b, &mut write_a) => {}
+        }
+    });
+    (path_a, path_b)
+}
+
+async fn test_rtu_client() {
+    // the client opens the slave side of a pseudo-terminal while the test acts as the server
+    let (mut master, path) = pseudo_terminal();
+
+    let mut channel = spawn_rtu_client_task(
+        &path,
+        SerialSettings::default(),
+        10,
+        default_reconnect_strategy(),
+        DecodeLevel::default(),
+    );
+    let params = RequestParam::new(UnitId::new(0x01), Duration::from_secs(1));
+
+    let request = tokio::spawn(async move {
+        channel
+            .read_holding_registers(params, AddressRange::try_from(0, 2).unwrap())
+            .await
+    });
+
+    let mut buffer = [0u8; 8];
+    master.read_exact(&mut buffer).await.unwrap();
+    assert_eq!(buffer, READ_REGISTERS_REQUEST);
+
+    master.write_all(OTHER_UNIT_RESPONSE).await.unwrap();
+    master.write_all(READ_REGISTERS_RESPONSE).await.unwrap();
+
+    assert_eq!(
+        request.await.unwrap().unwrap(),
+        vec![Indexed::new(0, 0xCAFE), Indexed::new(1, 0x0001)]
+    );
+}
+
+async fn test_rtu_server() {
+    // the server opens the slave side of a pseudo-terminal while the test acts as the client
+    let (mut master, path) = pseudo_terminal();
+
+    let mut handler = Handler {
+        holding_registers: [0; 10],
+    };
+    handler.holding_registers[1] = 0xABCD;
+
+    let _server = spawn_rtu_server_task(
+        &path,
+        SerialSettings::default(),
+        default_reconnect_strategy(),
+        ServerHandlerMap::single(UnitId::new(1), handler.wrap()),
+        DecodeLevel::default(),
+    );
+
+    // give the server a chance to open the port
+    tokio::time::sleep(Duration::from_millis(100)).await;
+
+    // a corrupted frame is discarded once the line goes silent
+    master
+        .write_all(&READ_REGISTERS_REQUEST[..5])
+        .await
+        .unwrap();
+    tokio::time::sleep(Duration::from_millis(20)).await;
+    master.write_all(READ_REGISTERS_REQUEST).await.unwrap();
+
+    //                  | unit |  fc  | count |   value   |   value   |    crc    |
+    let expected = &[0x01, 0x03, 0x04, 0x00, 0x00, 0xAB, 0xCD, 0x44, 0x96];
+    let mut buffer = [0u8; 9];
+    master.read_exact(&mut buffer).await.unwrap();
+    assert_eq!(&buffer, expected);
+
+    // a frame with a bad CRC is counted as a communication error
+    let mut corrupted = READ_REGISTERS_REQUEST.to_vec();
+    corrupted[7] ^= 0xFF;
+    master.write_all(&corrupted).await.unwrap();
+    tokio::time::sleep(Duration::from_millis(20)).await;
+    master.write_all(BUS_ERROR_COUNT_REQUEST).await.unwrap();
+
+    let mut buffer = [0u8; 8];
+    master.read_exact(&mut buffer).await.unwrap();
+    assert_eq!(buffer, BUS_ERROR_COUNT_RESPONSE);
+}
+
+async fn test_rtu_client_and_server() {
+    let (client_path, server_path) = null_modem();
+
+    let handler = Handler {
+        holding_registers: [0; 10],
+    }
+    .wrap();
+
+    let _server = spawn_rtu_server_task(
+        &server_path,
+        SerialSettings::default(),
+        default_reconnect_strategy(),
+        ServerHandlerMap::single(UnitId::new(1), handler.clone()),
+        DecodeLevel::default(),
+    );
+
+    let mut channel = spawn_rtu_client_task(
+        &client_path,
+        SerialSettings::default(),
+        10,
+        default_reconnect_strategy(),
+        DecodeLevel::default(),
+    );
+    let params = RequestParam::new(UnitId::new(0x01), Duration::from_secs(1));
+
+    assert_eq!(
+        channel
+            .write_multiple_registers(
+                params,
+                WriteMultiple::from(0, vec![0x0102, 0x0304, 0x0506]).unwrap()
+            )
+            .await
+            .unwrap(),
+        AddressRange::try_from(0, 3).unwrap()
+    );
+    assert_eq!(
+        channel
+            .read_holding_registers(params, AddressRange::try_from(0, 3).unwrap())
+            .await
+            .unwrap(),
+        vec![
+            Indexed::new(0, 0x0102),
+            Indexed::new(1, 0x0304),
+            Indexed::new(2, 0x0506)
+        ]
+    );
+    assert_eq!(handler.lock().unwrap().holding_registers[2], 0x0506);
+
+    // no other device is on the line
+    assert_eq!(
+        channel
+            .read_holding_registers(
+                RequestParam::new(UnitId::new(0x02), Duration::from_millis(100)),
+                AddressRange::try_from(0, 3).unwrap()
+            )
+            .await,
+        Err(RequestError::ResponseTimeout)
+    );
+}
+
+async fn test_rtu_diagnostics() {
+    let (client_path, server_path) = null_modem();
+
+    let handler = Handler {
+        holding_registers: [0; 10],
+    }
+    .wrap();
+
+    let _server = spawn_rtu_server_task(
+        &server_path,
+        SerialSettings::default(),
+        default_reconnect_strategy(),
+        ServerHandlerMap::single(UnitId::new(1), handler),
+        DecodeLevel::default(),
+    );
+
+    let mut channel = spawn_rtu_client_task(
+        &client_path,
+        SerialSettings::default(),
+        10,
+        default_reconnect_strategy(),
+        DecodeLevel::default(),
+    );
+    let params = RequestParam::new(UnitId::new(0x01), Duration::from_secs(1));
+    let no_response = RequestParam::new(UnitId::new(0x01), Duration::from_millis(100));
+
+    assert_eq!(channel.read_exception_status(params).await, Ok(0x6D));
+    assert_eq!(channel.report_server_id(params).await, Ok(vec![0x2A, 0xFF]));
+    assert_eq!(
+        channel
+            .read_holding_registers(params, AddressRange::try_from(9, 2).unwrap())
+            .await,
+        Err(RequestError::Exception(ExceptionCode::IllegalDataAddress))
+    );
+    assert_eq!(
+        channel
+            .diagnostics(params, DiagnosticSubFunction::ReturnQueryData, 0xA537)
+            .await,
+        Ok(0xA537)
+    );
+    assert_eq!(
+        channel
+            .diagnostics(params, DiagnosticSubFunction::ReturnBusMessageCount, 0)
+            .await,
+        Ok(5)
+    );
+    assert_eq!(
+        channel
+            .diagnostics(
+                params,
+                DiagnosticSubFunction::ReturnBusExceptionErrorCount,
+                0
+            )
+            .await,
+        Ok(1)
+    );
+    assert_eq!(
+        channel.get_comm_event_counter(params).await,
+        Ok(CommEventCounter {
+            status: 0x0000,
+            event_count: 5
+        })
+    );
+
+    let log = channel.get_comm_event_log(params).await.unwrap();
+    assert_eq!(log.event_count, 5);
+    assert_eq!(log.message_count, 8);
+    // a send and receive event for each of the previous requests plus this one
+    assert_eq!(log.events.len(), 15);
+    assert_eq!(
+        log.events[1],
+        CommEvent::Send {
+            read_exception: false,
+            abort_exception: false,
+            busy_exception: false,
+            nak_exception: false,
+            write_timeout: false,
+            listen_only: false,
+        }
+    );
+
+    // the server doesn't respond in listen only mode until communications are restarted
+    assert_eq!(
+        channel
+            .diagnostics(no_response, DiagnosticSubFunction::ForceListenOnlyMode, 0)
+            .await,
+        Err(RequestError::ResponseTimeout)
+    );
+    assert_eq!(
+        channel.read_exception_status(no_response).await,
+        Err(RequestError::ResponseTimeout)
+    );
+    assert_eq!(
+        channel
+            .diagnostics(no_response, DiagnosticSubFunction::RestartCommunications, 0)
+            .await,
+        Err(RequestError::ResponseTimeout)
+    );
+    assert_eq!(channel.read_exception_status(params).await, Ok(0x6D));
+    assert_eq!(
+        channel
+            .diagnostics(params, DiagnosticSubFunction::ReturnServerMessageCount, 0)
+            .await,
+        Ok(2)
+    );
+}
+
+async fn test_ascii_client_and_server() {
+    let (client_path, server_path) = null_modem();
+
+    let handler = Handler {
+        holding_registers: [0; 10],
+    }
+    .wrap();
+
+    let _server = spawn_ascii_server_task(
+        &server_path,
+        SerialSettings::default(),
+        default_reconnect_strategy(),
+        ServerHandlerMap::single(UnitId::new(1), handler.clone()),
+        DecodeLevel::default(),
+    );
+
+    let mut channel = spawn_ascii_client_task(
+        &client_path,
+        SerialSettings::default(),
+        10,
+        default_reconnect_strategy(),
+        DecodeLevel::default(),
+    );
+    let params = RequestParam::new(UnitId::new(0x01), Duration::from_secs(1));
+
+    assert_eq!(
+        channel
+            .write_multiple_registers(params, WriteMultiple::from(7, vec![0xCAFE]).unwrap())
+            .await
+            .unwrap(),
+        AddressRange::try_from(7, 1).unwrap()
+    );
+    assert_eq!(
+        channel
+            .read_holding_registers(params, AddressRange::try_from(6, 2).unwrap())
+            .await
+            .unwrap(),
+        vec![Indexed::new(6, 0x0000), Indexed::new(7, 0xCAFE)]
+    );
+    assert_eq!(
+        channel
+            .read_holding_registers(params, AddressRange::try_from(9, 2).unwrap())
+            .await,
+        Err(RequestError::Exception(ExceptionCode::IllegalDataAddress))
+    );
+}
+
+async fn test_serial_channel_rejects_endpoint() {
+    let (_master, path) = pseudo_terminal();
+
+    let mut channel = spawn_rtu_client_task(
+        &path,
+        SerialSettings::default(),
+        10,
+        default_reconnect_strategy(),
+        DecodeLevel::default(),
+    );
+
+    let endpoint = SocketAddr::from_str("127.0.0.1:502").unwrap();
+    assert_eq!(
+        channel.set_endpoint(endpoint).await,
+        Err(RequestError::BadRequest(InvalidRequest::UnsupportedSetting))
+    );
+}
+
+#[test]
+fn rtu_client_can_read_registers_over_pseudo_terminal() {
+    let rt = Runtime::new().unwrap();
+    rt.block_on(test_rtu_client())
+}
+
+#[test]
+fn rtu_server_responds_over_pseudo_terminal() {
+    let rt = Runtime::new().unwrap();
+    rt.block_on(test_rtu_server())
+}
+
+#[test]
+fn rtu_client_and_server_communicate_over_null_modem() {
+    let rt = Runtime::new().unwrap();
+    rt.block_on(test_rtu_client_and_server())
+}
+
+#[test]
+fn rtu_diagnostics_are_maintained_by_the_server() {
+    let rt = Runtime::new().unwrap();
+    rt.block_on(test_rtu_diagnostics())
+}
+
+#[test]
+fn ascii_client_and_server_communicate_over_null_modem() {
+    let rt = Runtime::new().unwrap();
+    rt.block_on(test_ascii_client_and_server())
+}
+
+#[test]
+fn serial_channel_rejects_endpoint() {
+    let rt = Runtime::new().unwrap();
+    rt.block_on(test_serial_channel_rejects_endpoint())
+}
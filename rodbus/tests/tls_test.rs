@@ -0,0 +1,480 @@
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::Duration;
+
+use rcgen::{
+    BasicConstraints, Certificate, CertificateParams, CustomExtension, DnType,
+    ExtendedKeyUsagePurpose, IsCa, KeyPair,
+};
+use rodbus::client::*;
+use rodbus::server::*;
+use rodbus::*;
+
+use rustls::pki_types::pem::PemObject;
+use rustls::pki_types::{CertificateDer, PrivateKeyDer};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::runtime::Runtime;
+
+//                              |  tx id  |  proto  |   len   | unit |  fc  |   start   |   count   |
+const READ_REGISTERS_REQUEST: &[u8] = &[
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x06, 0x01, 0x03, 0x00, 0x00, 0x00, 0x02,
+];
+
+//                               |  tx id  |  proto  |   len   | unit |  fc  | count |   value   |   value   |
+const READ_REGISTERS_RESPONSE: &[u8] = &[
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x07, 0x01, 0x03, 0x04, 0xCA, 0xFE, 0x00, 0x01,
+];
+
+const MODBUS_ROLE_OID: &[u64] = &[1, 3, 6, 1, 4, 1, 50316, 802, 1];
+
+struct Handler {
+    holding_registers: [u16; 2],
+}
+
+impl RequestHandler for Handler {
+    fn read_holding_register(&self, address: u16) -> Result<u16, ExceptionCode> {
+        Self::convert(self.holding_registers.get(address as usize))
+    }
+
+    fn write_single_register(&mut self, value: Indexed<u16>) -> Result<(), ExceptionCode> {
+        match self.holding_registers.get_mut(value.index as usize) {
+            Some(x) => {
+                *x = value.value;
+                Ok(())
+            }
+            None => Err(ExceptionCode::IllegalDataAddress),
+        }
+    }
+}
+
+// engineers may write, every other role is read-only
+struct RoleAuthorizationHandler;
+
+impl AuthorizationHandler for RoleAuthorizationHandler {
+    fn is_authorized(&self, unit_id: UnitId, function: FunctionCode, role: &str) -> Authorization {
+        if role == "engineer" {
+            return Authorization::Allow;
+        }
+        ReadOnlyAuthorizationHandler::new().is_authorized(unit_id, function, role)
+    }
+}
+
+struct CertificateAuthority {
+    cert: Certificate,
+    key: KeyPair,
+}
+
+impl CertificateAuthority {
+    fn new(name: &str) -> Self {
+        let key = KeyPair::generate().unwrap();
+        let mut params = CertificateParams::new(Vec::new()).unwrap();
+        params.is_ca = IsCa::Ca(BasicConstraints::Unconstrained);
+        params.distinguished_name.push(DnType::CommonName, name);
+        let cert = params.self_signed(&key).unwrap();
+        Self { cert, key }
+    }
+
+    // returns the PEM encoded certificate and private key
+    fn issue(
+        &self,
+        name: &str,
+        usage: ExtendedKeyUsagePurpose,
+        role: Option<&str>,
+    ) -> (String, String) {
+        let key = KeyPair::generate().unwrap();
+        let mut params = CertificateParams::new(vec![name.to_string()]).unwrap();
+        params.distinguished_name.push(DnType::CommonName, name);
+        params.extended_key_usages = vec![usage];
+        if let Some(role) = role {
+            // the role is a DER encoded UTF8String
+            let mut content = vec![0x0C, role.len() as u8];
+            content.extend_from_slice(role.as_bytes());
+            params.custom_extensions =
+                vec![CustomExtension::from_oid_content(MODBUS_ROLE_OID, content)];
+        }
+        let cert = params.signed_by(&key, &self.cert, &self.key).unwrap();
+        (cert.pem(), key.serialize_pem())
+    }
+}
+
+// the certificates and keys for both ends of a connection, written to temporary files
+struct Credentials {
+    ca: PathBuf,
+    server_cert: PathBuf,
+    server_key: PathBuf,
+    client_cert: PathBuf,
+    client_key: PathBuf,
+}
+
+impl Credentials {
+    fn generate(test_name: &str, client_role: Option<&str>) -> Self {
+        let ca = CertificateAuthority::new("rodbus test CA");
+        let (server_cert, server_key) =
+            ca.issue("localhost", ExtendedKeyUsagePurpose::ServerAuth, None);
+        let (client_cert, client_key) =
+            ca.issue("client", ExtendedKeyUsagePurpose::ClientAuth, client_role);
+
+        let write = |name: &str, contents: &str| -> PathBuf {
+            let path = std::env::temp_dir().join(format!(
+                "rodbus-{}-{}-{}.pem",
+                test_name,
+                std::process::id(),
+                name
+            ));
+            std::fs::write(&path, contents).unwrap();
+            path
+        };
+
+        Self {
+            ca: write("ca", &ca.cert.pem()),
+            server_cert: write("server-cert", &server_cert),
+            server_key: write("server-key", &server_key),
+            client_cert: write("client-cert", &client_cert),
+            client_key: write("client-key", &client_key),
+        }
+    }
+
+    fn client_config(&self, server_name: &str) -> TlsClientConfig {
+        TlsClientConfig::new(server_name, &self.ca, &self.client_cert, &self.client_key).unwrap()
+    }
+
+    fn server_config(&self) -> TlsServerConfig {
+        TlsServerConfig::new(&self.ca, &self.server_cert, &self.server_key).unwrap()
+    }
+
+    // a plain rustls server that requires a client certificate issued by the CA
+    fn server_acceptor(&self) -> tokio_rustls::TlsAcceptor {
+        let provider = Arc::new(rustls::crypto::ring::default_provider());
+        let mut roots = rustls::RootCertStore::empty();
+        for cert in CertificateDer::pem_file_iter(&self.ca).unwrap() {
+            roots.add(cert.unwrap()).unwrap();
+        }
+        let verifier = rustls::server::WebPkiClientVerifier::builder_with_provider(
+            roots.into(),
+            provider.clone(),
+        )
+        .build()
+        .unwrap();
+        let chain = CertificateDer::pem_file_iter(&self.server_cert)
+            .unwrap()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        let key = PrivateKeyDer::from_pem_file(&self.server_key).unwrap();
+        let config = rustls::ServerConfig::builder_with_provider(provider)
+            .with_safe_default_protocol_versions()
+            .unwrap()
+            .with_client_cert_verifier(verifier)
+            .with_single_cert(chain, key)
+            .unwrap();
+        tokio_rustls::TlsAcceptor::from(Arc::new(config))
+    }
+}
+
+impl Drop for Credentials {
+    fn drop(&mut self) {
+        for path in [
+            &self.ca,
+            &self.server_cert,
+            &self.server_key,
+            &self.client_cert,
+            &self.client_key,
+        ]
+        .iter()
+        {
+            std::fs::remove_file(path).ok();
+        }
+    }
+}
+
+async fn test_tls_client_can_read_registers() {
+    let credentials = Credentials::generate("tls-client", None);
+    let addr = SocketAddr::from_str("127.0.0.1:40802").unwrap();
+
+    let listener = tokio::net::TcpListener::bind(addr).await.unwrap();
+    let acceptor = credentials.server_acceptor();
+    let server = tokio::spawn(async move {
+        let (socket, _) = listener.accept().await.unwrap();
+        let mut stream = acceptor.accept(socket).await.unwrap();
+        let mut request = [0u8; 12];
+        stream.read_exact(&mut request).await.unwrap();
+        assert_eq!(request, READ_REGISTERS_REQUEST);
+        stream.write_all(READ_REGISTERS_RESPONSE).await.unwrap();
+        stream.flush().await.unwrap();
+        // keep the connection open until the client is done
+        stream.read_to_end(&mut Vec::new()).await.ok();
+    });
+
+    let mut channel = spawn_tls_client_task(
+        addr,
+        10,
+        default_reconnect_strategy(),
+        credentials.client_config("localhost"),
+        DecodeLevel::default(),
+    );
+    let params = RequestParam::new(UnitId::new(0x01), Duration::from_secs(1));
+
+    assert_eq!(
+        channel
+            .read_holding_registers(params, AddressRange::try_from(0, 2).unwrap())
+            .await
+            .unwrap(),
+        vec![Indexed::new(0, 0xCAFE), Indexed::new(1, 0x0001)]
+    );
+
+    drop(channel);
+    server.await.unwrap();
+}
+
+async fn test_tls_client_verifies_server_name() {
+    let credentials = Credentials::generate("tls-server-name", None);
+    let addr = SocketAddr::from_str("127.0.0.1:40803").unwrap();
+
+    let listener = tokio::net::TcpListener::bind(addr).await.unwrap();
+    let acceptor = credentials.server_acceptor();
+    tokio::spawn(async move {
+        loop {
+            let (socket, _) = listener.accept().await.unwrap();
+            // the handshake is aborted by the client
+            assert!(acceptor.accept(socket).await.is_err());
+        }
+    });
+
+    // the server certificate was issued for "localhost"
+    let mut channel = spawn_tls_client_task(
+        addr,
+        10,
+        default_reconnect_strategy(),
+        credentials.client_config("other.host"),
+        DecodeLevel::default(),
+    );
+    let params = RequestParam::new(UnitId::new(0x01), Duration::from_secs(1));
+
+    assert_eq!(
+        channel
+            .read_holding_registers(params, AddressRange::try_from(0, 2).unwrap())
+            .await,
+        Err(RequestError::NoConnection)
+    );
+}
+
+async fn spawn_server(credentials: &Credentials, addr: SocketAddr) -> ServerHandle {
+    let handler = Handler {
+        holding_registers: [0xCAFE, 0x0001],
+    }
+    .wrap();
+
+    spawn_tls_server_task(
+        1,
+        addr,
+        ServerHandlerMap::single(UnitId::new(1), handler),
+        Arc::new(RoleAuthorizationHandler),
+        credentials.server_config(),
+        DecodeLevel::default(),
+    )
+    .await
+    .unwrap()
+}
+
+async fn test_tls_server_authorizes_requests_by_role() {
+    let addr = SocketAddr::from_str("127.0.0.1:40804").unwrap();
+    let params = RequestParam::new(UnitId::new(0x01), Duration::from_secs(1));
+    let range = AddressRange::try_from(0, 2).unwrap();
+
+    let operator = Credentials::generate("tls-operator", Some("operator"));
+    let engineer = Credentials::generate("tls-engineer", Some("engineer"));
+    let _server = spawn_server(&operator, addr).await;
+
+    let mut channel = spawn_tls_client_task(
+        addr,
+        10,
+        default_reconnect_strategy(),
+        operator.client_config("localhost"),
+        DecodeLevel::default(),
+    );
+
+    assert_eq!(
+        channel.read_holding_registers(params, range).await.unwrap(),
+        vec![Indexed::new(0, 0xCAFE), Indexed::new(1, 0x0001)]
+    );
+    assert_eq!(
+        channel
+            .write_single_register(params, Indexed::new(1, 0x0002))
+            .await,
+        Err(RequestError::Exception(ExceptionCode::IllegalFunction))
+    );
+    // the denied request never reached the handler
+    assert_eq!(
+        channel.read_holding_registers(params, range).await.unwrap(),
+        vec![Indexed::new(0, 0xCAFE), Indexed::new(1, 0x0001)]
+    );
+    drop(channel);
+
+    // the engineer certificate is issued by a different CA, so use a second server
+    let addr = SocketAddr::from_str("127.0.0.1:40805").unwrap();
+    let _server = spawn_server(&engineer, addr).await;
+
+    let mut channel = spawn_tls_client_task(
+        addr,
+        10,
+        default_reconnect_strategy(),
+        engineer.client_config("localhost"),
+        DecodeLevel::default(),
+    );
+
+    assert_eq!(
+        channel
+            .write_single_register(params, Indexed::new(1, 0x0002))
+            .await,
+        Ok(Indexed::new(1, 0x0002))
+    );
+    assert_eq!(
+        channel.read_holding_registers(params, range).await.unwrap(),
+        vec![Indexed::new(0, 0xCAFE), Indexed::new(1, 0x0002)]
+    );
+}
+
+async fn test_tls_server_rejects_client_without_role() {
+    let credentials = Credentials::generate("tls-no-role", None);
+    let addr = SocketAddr::from_str("127.0.0.1:40806").unwrap();
+    let _server = spawn_server(&credentials, addr).await;
+
+    let mut channel = spawn_tls_client_task(
+        addr,
+        10,
+        default_reconnect_strategy(),
+        credentials.client_config("localhost"),
+        DecodeLevel::default(),
+    );
+    let params = RequestParam::new(UnitId::new(0x01), Duration::from_secs(1));
+
+    assert!(channel
+        .read_holding_registers(params, AddressRange::try_from(0, 2).unwrap())
+        .await
+        .is_err());
+}
+
+async fn test_tls_server_closes_connection_without_handshake() {
+    let credentials = Credentials::generate("tls-no-handshake", Some("operator"));
+    let addr = SocketAddr::from_str("127.0.0.1:40807").unwrap();
+    let handler = Handler {
+        holding_registers: [0xCAFE, 0x0001],
+    }
+    .wrap();
+
+    let _server = spawn_tls_server_task(
+        1,
+        addr,
+        ServerHandlerMap::single(UnitId::new(1), handler),
+        Arc::new(RoleAuthorizationHandler),
+        credentials
+            .server_config()
+            .with_handshake_timeout(Duration::from_millis(100)),
+        DecodeLevel::default(),
+    )
+    .await
+    .unwrap();
+
+    let mut channel = spawn_tls_client_task(
+        addr,
+        10,
+        default_reconnect_strategy(),
+        credentials.client_config("localhost"),
+        DecodeLevel::default(),
+    );
+    let params = RequestParam::new(UnitId::new(0x01), Duration::from_secs(1));
+    let range = AddressRange::try_from(0, 2).unwrap();
+    assert!(channel.read_holding_registers(params, range).await.is_ok());
+
+    // a peer that never starts the handshake is disconnected once the timeout expires
+    let mut socket = tokio::net::TcpStream::connect(addr).await.unwrap();
+    let mut buffer = [0u8; 16];
+    let result = tokio::time::timeout(Duration::from_secs(5), socket.read(&mut buffer)).await;
+    assert!(matches!(result, Ok(Ok(0)) | Ok(Err(_))));
+
+    // and it never took the place of the authenticated session
+    assert!(channel.read_holding_registers(params, range).await.is_ok());
+}
+
+async fn test_tls_client_gives_up_on_handshake() {
+    let credentials = Credentials::generate("tls-client-no-handshake", None);
+    let addr = SocketAddr::from_str("127.0.0.1:40808").unwrap();
+
+    // the server accepts connections but never answers the handshake
+    let listener = tokio::net::TcpListener::bind(addr).await.unwrap();
+
+    let channel = spawn_tls_client_task(
+        addr,
+        10,
+        default_reconnect_strategy(),
+        credentials
+            .client_config("localhost")
+            .with_handshake_timeout(Duration::from_millis(100)),
+        DecodeLevel::default(),
+    );
+    let mut state = channel.state();
+
+    let (_first, _) = listener.accept().await.unwrap();
+    tokio::time::timeout(Duration::from_secs(5), async {
+        while !matches!(*state.borrow(), ChannelState::WaitingToRetry(_)) {
+            state.changed().await.unwrap();
+        }
+    })
+    .await
+    .unwrap();
+
+    // the next attempt is made with a new connection
+    let second = tokio::time::timeout(Duration::from_secs(5), listener.accept()).await;
+    assert!(matches!(second, Ok(Ok(_))));
+}
+
+#[test]
+fn tls_client_can_read_registers() {
+    let rt = Runtime::new().unwrap();
+    rt.block_on(test_tls_client_can_read_registers())
+}
+
+#[test]
+fn tls_client_verifies_server_name() {
+    let rt = Runtime::new().unwrap();
+    rt.block_on(test_tls_client_verifies_server_name())
+}
+
+#[test]
+fn tls_server_authorizes_requests_by_role() {
+    let rt = Runtime::new().unwrap();
+    rt.block_on(test_tls_server_authorizes_requests_by_role())
+}
+
+#[test]
+fn tls_server_rejects_client_without_role() {
+    let rt = Runtime::new().unwrap();
+    rt.block_on(test_tls_server_rejects_client_without_role())
+}
+
+#[test]
+fn tls_server_closes_connection_without_handshake() {
+    let rt = Runtime::new().unwrap();
+    rt.block_on(test_tls_server_closes_connection_without_handshake())
+}
+
+#[test]
+fn tls_client_gives_up_on_handshake() {
+    let rt = Runtime::new().unwrap();
+    rt.block_on(test_tls_client_gives_up_on_handshake())
+}
+
+#[test]
+fn tls_client_config_rejects_invalid_private_key() {
+    let credentials = Credentials::generate("tls-bad-key", None);
+    assert!(matches!(
+        TlsClientConfig::new(
+            "localhost",
+            &credentials.ca,
+            &credentials.client_cert,
+            &credentials.client_cert
+        ),
+        Err(TlsError::InvalidPrivateKey(_))
+    ));
+}